@@ -0,0 +1,49 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use ezkl::circuit::table::{Range, RangeCheck};
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2curves::bn256::Fr;
+
+const K: usize = 12;
+// comfortably below the usable rows of a 2^K column, so that every chunk fills one column
+const ROWS_PER_COL: i128 = 4000;
+
+fn range(num_cols: i128) -> Range {
+    (0, num_cols * ROWS_PER_COL - 1)
+}
+
+/// Prepares the cells of every column of a range check table, serially and in parallel, for ranges
+/// spanning a single column, 8 and 16. Only the preparation is timed, not its assignment or keygen.
+fn runrangecheck(c: &mut Criterion) {
+    let mut group = c.benchmark_group("range_check_layout");
+
+    for &num_cols in [1, 8, 16].iter() {
+        let mut cs = ConstraintSystem::<Fr>::default();
+        let range_check = RangeCheck::<Fr>::configure(&mut cs, range(num_cols), K, None);
+        let chunks = (0..range_check.num_chunks()).collect::<Vec<_>>();
+        assert!(chunks.len() as i128 >= num_cols);
+
+        group.throughput(Throughput::Elements((num_cols * ROWS_PER_COL) as u64));
+        group.bench_with_input(
+            BenchmarkId::new("prepare_serial", num_cols),
+            &num_cols,
+            |b, &_| {
+                b.iter(|| range_check.prepare_chunks_serial(&chunks));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("prepare_par", num_cols),
+            &num_cols,
+            |b, &_| {
+                b.iter(|| range_check.prepare_chunks_par(&chunks));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().with_plots();
+  targets = runrangecheck
+}
+criterion_main!(benches);
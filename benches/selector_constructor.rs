@@ -0,0 +1,65 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ezkl::circuit::table::SelectorConstructor;
+use halo2_proofs::{
+    plonk::{ConstraintSystem, Expression},
+    poly::Rotation,
+};
+use halo2curves::bn256::Fr;
+
+fn query() -> Expression<Fr> {
+    let mut cs = ConstraintSystem::<Fr>::default();
+    let advice = cs.advice_column();
+    let mut query = None;
+    cs.create_gate("query", |meta| {
+        let q = meta.query_advice(advice, Rotation::cur());
+        query = Some(q.clone());
+        vec![q]
+    });
+    query.unwrap()
+}
+
+fn runselector(c: &mut Criterion) {
+    let mut group = c.benchmark_group("selector_constructor");
+    let expr = query();
+
+    for degree in [2, 16] {
+        let constructor = SelectorConstructor::<Fr>::new(degree);
+
+        group.bench_with_input(BenchmarkId::new("expr_serial", degree), &degree, |b, _| {
+            b.iter(|| {
+                (0..degree)
+                    .map(|i| constructor.get_expr_at_idx_serial(i, expr.clone()))
+                    .collect::<Vec<_>>()
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("expr_par", degree), &degree, |b, _| {
+            b.iter(|| {
+                (0..degree)
+                    .map(|i| constructor.get_expr_at_idx_par(i, expr.clone()))
+                    .collect::<Vec<_>>()
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("val_serial", degree), &degree, |b, _| {
+            b.iter(|| {
+                (0..degree)
+                    .map(|i| constructor.get_selector_val_at_idx_serial(i))
+                    .collect::<Vec<_>>()
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("val_par", degree), &degree, |b, _| {
+            b.iter(|| {
+                (0..degree)
+                    .map(|i| constructor.get_selector_val_at_idx_par(i))
+                    .collect::<Vec<_>>()
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().with_plots();
+  targets = runselector
+}
+criterion_main!(benches);
@@ -1,4 +1,5 @@
 use crate::tensor::TensorType;
+use serde::{Deserialize, Serialize};
 use std::{
     fmt,
     ops::{Add, Mul, Neg, Sub},
@@ -6,7 +7,7 @@ use std::{
 
 #[allow(missing_docs)]
 /// An enum representing the operations that can be used to express more complex operations via accumulation
-#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 pub enum BaseOp {
     Dot,
     DotInit,
@@ -18,23 +19,45 @@ pub enum BaseOp {
     SumInit,
     Sum,
     IsBoolean,
+    /// `output = 1` if `input` is zero and `0` otherwise, with the input's inverse as second input
+    IsZero,
+    /// `output = a * b + c`, over three input columns
+    MultAdd,
+    /// `output = input - input[rotation rows up]` within a column, e.g. a rotation of `1` for adjacent rows
+    RotatedSub(usize),
+    /// `output = a + b` clamped to `[lo, hi]`, with the is-clamped indicator as third input
+    SatAdd {
+        lo: i64,
+        hi: i64,
+    },
+    /// `output = a - b` clamped to `[lo, hi]`, with the is-clamped indicator as third input
+    SatSub {
+        lo: i64,
+        hi: i64,
+    },
 }
 
 /// Matches a [BaseOp] to an operation over inputs
 impl BaseOp {
-    /// forward func
+    /// forward func, over one value per input column (at least two, see [BaseOp::num_inputs]). For
+    /// [BaseOp::SatAdd] and [BaseOp::SatSub] this is the unclamped value.
     pub fn nonaccum_f<
         T: TensorType + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Neg<Output = T>,
     >(
         &self,
-        inputs: (T, T),
+        inputs: &[T],
     ) -> T {
-        let (a, b) = inputs;
+        let input = |i: usize| inputs[i].clone();
         match &self {
-            BaseOp::Add => a + b,
-            BaseOp::Sub => a - b,
-            BaseOp::Mult => a * b,
-            BaseOp::IsBoolean => b,
+            BaseOp::Add => input(0) + input(1),
+            BaseOp::Sub => input(0) - input(1),
+            BaseOp::Mult => input(0) * input(1),
+            BaseOp::IsBoolean => input(1),
+            BaseOp::IsZero => T::one().unwrap() - input(0) * input(1),
+            BaseOp::MultAdd => input(0) * input(1) + input(2),
+            BaseOp::RotatedSub(_) => input(0) - input(1),
+            BaseOp::SatAdd { .. } => input(0) + input(1),
+            BaseOp::SatSub { .. } => input(0) - input(1),
             _ => panic!("nonaccum_f called on accumulating operation"),
         }
     }
@@ -75,10 +98,16 @@ impl BaseOp {
             BaseOp::Sum => "SUM",
             BaseOp::SumInit => "SUMINIT",
             BaseOp::IsBoolean => "ISBOOLEAN",
+            BaseOp::IsZero => "ISZERO",
+            BaseOp::MultAdd => "MULTADD",
+            BaseOp::RotatedSub(_) => "ROTATEDSUB",
+            BaseOp::SatAdd { .. } => "SATADD",
+            BaseOp::SatSub { .. } => "SATSUB",
         }
     }
 
-    /// Returns the range of the query offset for this operation.
+    /// Returns the range of the query offset for this operation. For [BaseOp::RotatedSub] this is the
+    /// window of input rows, from the rotated row up to the current one.
     pub fn query_offset_rng(&self) -> (i32, usize) {
         match self {
             BaseOp::DotInit => (0, 1),
@@ -91,6 +120,11 @@ impl BaseOp {
             BaseOp::Sum => (-1, 2),
             BaseOp::SumInit => (0, 1),
             BaseOp::IsBoolean => (0, 1),
+            BaseOp::IsZero => (0, 1),
+            BaseOp::MultAdd => (0, 1),
+            BaseOp::RotatedSub(rotation) => (-(*rotation as i32), rotation + 1),
+            BaseOp::SatAdd { .. } => (0, 1),
+            BaseOp::SatSub { .. } => (0, 1),
         }
     }
 
@@ -107,6 +141,11 @@ impl BaseOp {
             BaseOp::Sum => 1,
             BaseOp::SumInit => 1,
             BaseOp::IsBoolean => 0,
+            BaseOp::IsZero => 2,
+            BaseOp::MultAdd => 3,
+            BaseOp::RotatedSub(_) => 1,
+            BaseOp::SatAdd { .. } => 3,
+            BaseOp::SatSub { .. } => 3,
         }
     }
 
@@ -123,6 +162,11 @@ impl BaseOp {
             BaseOp::CumProd => 1,
             BaseOp::CumProdInit => 0,
             BaseOp::IsBoolean => 0,
+            BaseOp::IsZero => 0,
+            BaseOp::MultAdd => 0,
+            BaseOp::RotatedSub(_) => 0,
+            BaseOp::SatAdd { .. } => 0,
+            BaseOp::SatSub { .. } => 0,
         }
     }
 }
@@ -3,8 +3,12 @@ use std::str::FromStr;
 use thiserror::Error;
 
 use halo2_proofs::{
-    circuit::Layouter,
-    plonk::{ConstraintSystem, Constraints, Expression, Selector},
+    circuit::{Layouter, SimpleFloorPlanner},
+    dev::MockProver,
+    plonk::{
+        Circuit, Column, ConstraintSystem, Constraints, Error as PlonkError, Expression, Instance,
+        Selector, TableColumn, VirtualCells,
+    },
     poly::Rotation,
 };
 use log::debug;
@@ -21,15 +25,27 @@ use tosubcommand::ToFlags;
 use crate::{
     circuit::{
         ops::base::BaseOp,
-        table::{Range, RangeCheck, Table},
+        table::{
+            evals_cache_file_name, merge_ranges, write_table_dump, AssignmentOrder, BinaryFn,
+            BlindingRowsPad, ClampWarning, MultiRangeCheck, PrecomputedOutputs, Range, RangeCheck,
+            Table, Table2, TableError, TableEvalCache,
+        },
         utils,
     },
-    tensor::{Tensor, TensorType, ValTensor, VarTensor},
+    fieldutils::{felt_to_i128, i128_to_felt},
+    tensor::{Tensor, TensorError, TensorType, ValTensor, VarTensor},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    error::Error,
+    marker::PhantomData,
+    path::Path,
+    sync::Arc,
 };
-use std::{collections::BTreeMap, error::Error, marker::PhantomData};
 
-use super::{lookup::LookupOp, region::RegionCtx, Op};
-use halo2curves::ff::{Field, PrimeField};
+use super::{hybrid::HybridOp, lookup::LookupOp, region::RegionCtx, Op};
+use halo2curves::bn256::Fr as Fp;
+use halo2curves::ff::{Field, FromUniformBytes, PrimeField};
 
 /// circuit related errors.
 #[derive(Debug, Error)]
@@ -40,17 +56,106 @@ pub enum CircuitError {
     /// Error when instantiating lookup tables
     #[error("failed to instantiate lookup tables")]
     LookupInstantiation,
-    /// A lookup table was was already assigned
-    #[error("attempting to initialize an already instantiated lookup table")]
-    TableAlreadyAssigned,
+    /// A lookup table or range check failed to be laid out
+    #[error("{0}")]
+    Table(#[from] TableError),
     /// This operation is unsupported
     #[error("unsupported operation in graph")]
     UnsupportedOp,
     ///
     #[error("invalid einsum expression")]
     InvalidEinsum,
+    /// An op failed to lay out
+    #[error("failed to lay out op {0}: {1}")]
+    OpLayout(String, String),
+    /// A lookup input doesn't encode an integer within the table's range
+    #[error("field element {0} does not encode an integer in the range {1:?}")]
+    NonIntegerFieldElement(String, Range),
+    /// A selector was configured twice for the same op and position
+    #[error("selector already configured at {position}")]
+    SelectorAlreadyConfigured {
+        /// the (op, block, inner column) key of the selector
+        position: String,
+    },
+    /// A claimed output differs from the reference output, reported with [BaseConfig::verbose_checks]
+    #[error("safe mode check failed for {op}: {} element(s) differ, first: {:?}", .diffs.len(), .diffs.first())]
+    SafeModeCheck {
+        /// the op whose output was checked
+        op: String,
+        /// the differing elements
+        diffs: Vec<super::ElementDiff>,
+    },
+    /// A lookup op whose parameters don't describe a meaningful table
+    #[error("invalid lookup op {0}")]
+    InvalidLookupOp(String),
+    /// A lookup was configured over an input column other than the one all lookups share
+    #[error("lookup input differs from the input of previously configured lookups, which all share one input")]
+    InconsistentLookupInput,
+    /// A lookup is configured with an index other than the one shared by previously configured lookups
+    #[error("lookup index differs from the index of previously configured lookups, which all share one index")]
+    InconsistentLookupIndex,
+    /// A `configure_*` method was called before [BaseConfig::configure]
+    #[error("{0} called before configure, the custom gates have not been set up")]
+    ConfigNotInitialized(String),
+    /// A public output was routed to an instance column that was never configured
+    #[error("no instance column at index {index}, only {num_columns} are configured")]
+    MissingInstanceColumn {
+        /// the requested column
+        index: usize,
+        /// the number of configured instance columns
+        num_columns: usize,
+    },
+    /// There is no public SRS for the commitment scheme at the required size
+    #[error("no public {scheme} SRS is available for k={k}, the largest is k={max}")]
+    NoPublicSrs {
+        /// the commitment scheme
+        scheme: crate::Commitments,
+        /// log2 of the required SRS size
+        k: u32,
+        /// log2 of the largest public SRS
+        max: u32,
+    },
+    /// A dynamic lookup was configured over more columns than [BaseConfig::max_dynamic_lookup_arity]
+    #[error("dynamic lookup over {arity} columns exceeds the maximum of {max}")]
+    DynamicLookupArityTooLarge {
+        /// the number of columns of the lookup
+        arity: usize,
+        /// the maximum number of columns
+        max: usize,
+    },
+    /// A lookup table or range check holds more rows than a circuit of the given size can
+    #[error("{component} holds {col_size} rows per column but only {max} are usable at k={k}")]
+    InconsistentLogrows {
+        /// the table or range check
+        component: String,
+        /// the rows in each of its columns
+        col_size: usize,
+        /// the usable rows at `k`
+        max: usize,
+        /// log2 of the circuit size
+        k: u32,
+    },
+    /// The circuit structure rebuilt from a config is malformed, see [BaseConfig::mock_structure_check]
+    #[error("malformed circuit structure: {0}")]
+    StructureCheck(String),
+    /// A range whose lower bound is above its upper bound
+    #[error("inverted range {0:?}, the lower bound is above the upper bound")]
+    InvertedRange(Range),
+    /// An op is laid out at a position no selector was configured for
+    #[error("no selector configured for {op} at block {block}, column {column}")]
+    MissingSelector {
+        /// the op being laid out
+        op: String,
+        /// block of the position
+        block: usize,
+        /// inner column of the position
+        column: usize,
+    },
 }
 
+/// Default for [BaseConfig::max_dynamic_lookup_arity].
+pub const DEFAULT_MAX_DYNAMIC_LOOKUP_ARITY: usize = 16;
+
 #[allow(missing_docs)]
 /// An enum representing activating the sanity checks we can perform on the accumulated arguments
 #[derive(
@@ -60,6 +165,11 @@ pub enum CheckMode {
     #[default]
     SAFE,
     UNSAFE,
+    /// Runs the safe mode checks like [CheckMode::SAFE], but logs mismatches as warnings instead of
+    /// failing the layout.
+    WARN,
+    /// Only runs the safe mode checks on ops laid out with [BaseConfig::layout_output].
+    OUTPUTS_ONLY,
 }
 
 impl std::fmt::Display for CheckMode {
@@ -67,10 +177,26 @@ impl std::fmt::Display for CheckMode {
         match self {
             CheckMode::SAFE => write!(f, "safe"),
             CheckMode::UNSAFE => write!(f, "unsafe"),
+            CheckMode::WARN => write!(f, "warn"),
+            CheckMode::OUTPUTS_ONLY => write!(f, "outputs-only"),
         }
     }
 }
 
+impl CheckMode {
+    /// Whether the check mode changes the constraint system (and therefore the verifying key),
+    /// found by configuring the same [BaseConfig] in this mode and in [CheckMode::UNSAFE] and
+    /// comparing the resulting gates, lookups, selectors, columns and degree.
+    pub fn affects_constraint_system(&self) -> bool {
+        let configured = |check_mode: CheckMode| {
+            let mut cs = ConstraintSystem::<Fp>::default();
+            BaseConfig::<Fp>::with_geometry(&mut cs, 4, 1, 2, check_mode);
+            StructureSummary::of(&cs)
+        };
+        configured(*self) != configured(CheckMode::UNSAFE)
+    }
+}
+
 impl ToFlags for CheckMode {
     /// Convert the struct to a subcommand string
     fn to_flags(&self) -> Vec<String> {
@@ -83,6 +209,8 @@ impl From<String> for CheckMode {
         match value.to_lowercase().as_str() {
             "safe" => CheckMode::SAFE,
             "unsafe" => CheckMode::UNSAFE,
+            "warn" => CheckMode::WARN,
+            "outputs-only" => CheckMode::OUTPUTS_ONLY,
             _ => {
                 log::error!("Invalid value for CheckMode");
                 log::warn!("defaulting to SAFE");
@@ -92,17 +220,57 @@ impl From<String> for CheckMode {
     }
 }
 
+/// How the `val` of a [Tolerance] bounds the difference between a value and its comparator.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub enum ToleranceMode {
+    /// `val` is a percentage of the expected value, e.g. `1.0` accepts a 1% error.
+    #[default]
+    Percentage,
+    /// `val` is an absolute difference in the units of the dequantized values, e.g. `0.5` accepts a
+    /// difference of up to `0.5 * scale` between the quantized values.
+    Absolute,
+}
+
+impl std::fmt::Display for ToleranceMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToleranceMode::Percentage => write!(f, "pct"),
+            ToleranceMode::Absolute => write!(f, "abs"),
+        }
+    }
+}
+
+impl FromStr for ToleranceMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pct" => Ok(ToleranceMode::Percentage),
+            "abs" => Ok(ToleranceMode::Absolute),
+            _ => Err(format!(
+                "Invalid tolerance mode {}, expected \"pct\" or \"abs\".",
+                s
+            )),
+        }
+    }
+}
+
 #[allow(missing_docs)]
 /// An enum representing the tolerance we can accept for the accumulated arguments, either absolute or percentage
 #[derive(Clone, Default, Debug, PartialEq, PartialOrd, Serialize, Deserialize, Copy)]
 pub struct Tolerance {
     pub val: f32,
     pub scale: utils::F32,
+    #[serde(default)]
+    pub mode: ToleranceMode,
 }
 
 impl std::fmt::Display for Tolerance {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:.2}", self.val)
+        match self.mode {
+            ToleranceMode::Percentage => write!(f, "{:.2}", self.val),
+            ToleranceMode::Absolute => write!(f, "{}:{:.2}", self.mode, self.val),
+        }
     }
 }
 
@@ -116,26 +284,55 @@ impl ToFlags for Tolerance {
 impl FromStr for Tolerance {
     type Err = String;
 
+    /// Parses `"pct:1.5"` as a percentage and `"abs:3.0"` as an absolute tolerance. Plain numbers are
+    /// percentages.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Ok(val) = s.parse::<f32>() {
+        let (mode, val) = match s.split_once(':') {
+            Some((mode, val)) => (mode.parse::<ToleranceMode>()?, val),
+            None => (ToleranceMode::Percentage, s),
+        };
+        if let Ok(val) = val.parse::<f32>() {
             Ok(Tolerance {
                 val,
                 scale: utils::F32(1.0),
+                mode,
             })
         } else {
             Err(
-                "Invalid tolerance value provided. It should expressed as a percentage (f32)."
+                "Invalid tolerance value provided. It should expressed as a percentage (f32), optionally prefixed with \"pct:\" or \"abs:\"."
                     .to_string(),
             )
         }
     }
 }
 
+impl Tolerance {
+    /// Returns an error message if `self.scale` isn't the fixed point multiplier for `scale`, the
+    /// (log base 2) scale the compared values were quantized with.
+    pub fn check_scale(&self, scale: crate::Scale) -> Result<(), String> {
+        let expected = 2_f32.powi(scale);
+        if self.scale.0 != expected {
+            return Err(format!(
+                "tolerance scale {} does not match the multiplier {} of the compared values (scale {})",
+                self.scale.0, expected, scale
+            ));
+        }
+        Ok(())
+    }
+
+    /// The largest accepted difference between the quantized values under [ToleranceMode::Absolute],
+    /// i.e. `val` expressed at `scale`.
+    pub fn absolute_bound(&self) -> i128 {
+        (self.val * self.scale.0).round() as i128
+    }
+}
+
 impl From<f32> for Tolerance {
     fn from(value: f32) -> Self {
         Tolerance {
             val: value,
             scale: utils::F32(1.0),
+            mode: ToleranceMode::Percentage,
         }
     }
 }
@@ -147,6 +344,8 @@ impl IntoPy<PyObject> for CheckMode {
         match self {
             CheckMode::SAFE => "safe".to_object(py),
             CheckMode::UNSAFE => "unsafe".to_object(py),
+            CheckMode::WARN => "warn".to_object(py),
+            CheckMode::OUTPUTS_ONLY => "outputs-only".to_object(py),
         }
     }
 }
@@ -160,6 +359,8 @@ impl<'source> FromPyObject<'source> for CheckMode {
         match strval.to_lowercase().as_str() {
             "safe" => Ok(CheckMode::SAFE),
             "unsafe" => Ok(CheckMode::UNSAFE),
+            "warn" => Ok(CheckMode::WARN),
+            "outputs-only" => Ok(CheckMode::OUTPUTS_ONLY),
             _ => Err(PyValueError::new_err("Invalid value for CheckMode")),
         }
     }
@@ -169,7 +370,7 @@ impl<'source> FromPyObject<'source> for CheckMode {
 /// Converts Tolerance into a PyObject (Required for Tolerance to be compatible with Python)
 impl IntoPy<PyObject> for Tolerance {
     fn into_py(self, py: Python) -> PyObject {
-        (self.val, self.scale.0).to_object(py)
+        (self.val, self.scale.0, self.mode.to_string()).to_object(py)
     }
 }
 
@@ -177,10 +378,17 @@ impl IntoPy<PyObject> for Tolerance {
 /// Obtains Tolerance from PyObject (Required for Tolerance to be compatible with Python)
 impl<'source> FromPyObject<'source> for Tolerance {
     fn extract(ob: &'source PyAny) -> PyResult<Self> {
-        if let Ok((val, scale)) = ob.extract::<(f32, f32)>() {
+        if let Ok((val, scale, mode)) = ob.extract::<(f32, f32, String)>() {
+            Ok(Tolerance {
+                val,
+                scale: utils::F32(scale),
+                mode: mode.parse().map_err(PyValueError::new_err)?,
+            })
+        } else if let Ok((val, scale)) = ob.extract::<(f32, f32)>() {
             Ok(Tolerance {
                 val,
                 scale: utils::F32(scale),
+                mode: ToleranceMode::Percentage,
             })
         } else {
             Err(PyValueError::new_err("Invalid tolerance value provided. "))
@@ -199,23 +407,22 @@ pub struct DynamicLookups {
     pub inputs: Vec<VarTensor>,
     /// tables
     pub tables: Vec<VarTensor>,
+    /// Flags if the dynamic lookups have been previously laid out.
+    pub is_assigned: bool,
 }
 
 impl DynamicLookups {
-    /// Returns a new [DynamicLookups] with no inputs, no selectors, and no tables.
-    pub fn dummy(col_size: usize, num_inner_cols: usize) -> Self {
+    /// Returns a new [DynamicLookups] with no selectors, and dummy inputs and tables `width` columns wide.
+    pub fn dummy(col_size: usize, num_inner_cols: usize, width: usize) -> Self {
         let dummy_var = VarTensor::dummy(col_size, num_inner_cols);
         let single_col_dummy_var = VarTensor::dummy(col_size, 1);
 
         Self {
             lookup_selectors: BTreeMap::new(),
             table_selectors: vec![],
-            inputs: vec![dummy_var.clone(), dummy_var.clone(), dummy_var.clone()],
-            tables: vec![
-                single_col_dummy_var.clone(),
-                single_col_dummy_var.clone(),
-                single_col_dummy_var.clone(),
-            ],
+            inputs: vec![dummy_var; width],
+            tables: vec![single_col_dummy_var; width],
+            is_assigned: false,
         }
     }
 }
@@ -231,19 +438,22 @@ pub struct Shuffles {
     pub inputs: Vec<VarTensor>,
     /// tables
     pub references: Vec<VarTensor>,
+    /// Flags if the shuffles have been previously laid out.
+    pub is_assigned: bool,
 }
 
 impl Shuffles {
-    /// Returns a new [DynamicLookups] with no inputs, no selectors, and no tables.
-    pub fn dummy(col_size: usize, num_inner_cols: usize) -> Self {
+    /// Returns a new [Shuffles] with no selectors, and dummy inputs and references `width` columns wide.
+    pub fn dummy(col_size: usize, num_inner_cols: usize, width: usize) -> Self {
         let dummy_var = VarTensor::dummy(col_size, num_inner_cols);
         let single_col_dummy_var = VarTensor::dummy(col_size, 1);
 
         Self {
             input_selectors: BTreeMap::new(),
             reference_selectors: vec![],
-            inputs: vec![dummy_var.clone(), dummy_var.clone()],
-            references: vec![single_col_dummy_var.clone(), single_col_dummy_var.clone()],
+            inputs: vec![dummy_var; width],
+            references: vec![single_col_dummy_var; width],
+            is_assigned: false,
         }
     }
 }
@@ -255,6 +465,17 @@ pub struct StaticLookups<F: PrimeField + TensorType + PartialOrd> {
     pub selectors: BTreeMap<(LookupOp, usize, usize), Selector>,
     /// Selectors for the dynamic lookup tables
     pub tables: BTreeMap<LookupOp, Table<F>>,
+    /// Ops looked up against another op's table, whose outputs they match up to a power of two.
+    /// Maps each op to the op owning the table and the shift `k` such that `op(x) = base(x) * 2^k`.
+    pub scaled: BTreeMap<LookupOp, (LookupOp, i32)>,
+    /// Ops whose table is laid out over a wider table's range so they can share its input columns.
+    /// Maps each op to the narrower range it was configured for, which its inputs are range checked against.
+    pub nested: BTreeMap<LookupOp, Range>,
+    /// Ops looked up on a weighted sum of input columns rather than on a single column.
+    /// Maps each op to its input columns and their weights.
+    pub linear: BTreeMap<LookupOp, Vec<(VarTensor, i64)>>,
+    /// [Selector]s for the lookups in `linear`, keyed like `selectors`.
+    pub linear_selectors: BTreeMap<(LookupOp, usize, usize), Selector>,
     ///
     pub index: VarTensor,
     ///
@@ -271,11 +492,47 @@ impl<F: PrimeField + TensorType + PartialOrd> StaticLookups<F> {
         Self {
             selectors: BTreeMap::new(),
             tables: BTreeMap::new(),
+            scaled: BTreeMap::new(),
+            nested: BTreeMap::new(),
+            linear: BTreeMap::new(),
+            linear_selectors: BTreeMap::new(),
             index: dummy_var.clone(),
             output: dummy_var.clone(),
             input: dummy_var,
         }
     }
+
+    /// The table backing `nl`, following power-of-two scaled aliases.
+    pub fn table_for(&self, nl: &LookupOp) -> Option<&Table<F>> {
+        self.tables.get(nl).or_else(|| {
+            self.scaled
+                .get(nl)
+                .and_then(|(base, _)| self.tables.get(base))
+        })
+    }
+
+    /// The `(block, inner_col)` pairs of the input [VarTensor] that `nl` was configured over.
+    pub fn configured_blocks(&self, nl: &LookupOp) -> Vec<(usize, usize)> {
+        self.selectors
+            .keys()
+            .filter(|(op, _, _)| op == nl)
+            .map(|(_, x, y)| (*x, *y))
+            .collect()
+    }
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> StaticLookups<F> {
+    /// Evaluates `nl` at `x`, through its table when the outputs are [Table::precomputed] rather
+    /// than computed by the op.
+    pub fn eval(&self, nl: &LookupOp, x: F) -> Result<F, TensorError> {
+        let input = Tensor::from(vec![x].into_iter());
+        match self.table_for(nl) {
+            Some(table) if table.precomputed.is_some() => Ok(table
+                .eval(&input)
+                .map_err(|_| TensorError::TableLookupError)?[0]),
+            _ => Ok(Op::<F>::f(nl, &[input])?.output[0]),
+        }
+    }
 }
 
 /// A struct representing the selectors for custom gates
@@ -287,6 +544,9 @@ pub struct CustomGates {
     pub output: VarTensor,
     /// selector
     pub selectors: BTreeMap<(BaseOp, usize, usize), Selector>,
+    /// element-wise ops with a single selector per block, keyed on inner column 0, whose gate
+    /// constrains every inner column of a row, see [SelectorStrategy::Shared]
+    pub shared_ops: BTreeSet<BaseOp>,
 }
 
 impl CustomGates {
@@ -297,8 +557,36 @@ impl CustomGates {
             inputs: vec![dummy_var.clone(), dummy_var.clone()],
             output: dummy_var,
             selectors: BTreeMap::new(),
+            shared_ops: BTreeSet::new(),
         }
     }
+
+    /// Whether the selector of `op` is shared by the inner columns of a block.
+    pub fn is_shared(&self, op: &BaseOp) -> bool {
+        self.shared_ops.contains(op)
+    }
+
+    /// The selector enabling `op` at inner column `y` of block `x`.
+    pub fn selector(&self, op: &BaseOp, x: usize, y: usize) -> Option<&Selector> {
+        let y = if self.is_shared(op) { 0 } else { y };
+        self.selectors.get(&(op.clone(), x, y))
+    }
+
+    /// Same as [CustomGates::selector] but errors with [CircuitError::MissingSelector] if `op` has no
+    /// selector at the position, e.g. as its gate was never configured.
+    pub fn required_selector(
+        &self,
+        op: &BaseOp,
+        x: usize,
+        y: usize,
+    ) -> Result<&Selector, CircuitError> {
+        self.selector(op, x, y)
+            .ok_or_else(|| CircuitError::MissingSelector {
+                op: op.as_str().to_string(),
+                block: x,
+                column: y,
+            })
+    }
 }
 
 /// A struct representing the selectors for the range checks
@@ -308,6 +596,14 @@ pub struct RangeChecks<F: PrimeField + TensorType + PartialOrd> {
     pub selectors: BTreeMap<(Range, usize, usize), Selector>,
     /// Selectors for the dynamic lookup tables
     pub ranges: BTreeMap<Range, RangeCheck<F>>,
+    /// Ranges checked against an enclosing range's table rather than their own. Maps each such range
+    /// to the enclosing range, see [BaseConfig::configure_range_check].
+    pub subsets: BTreeMap<Range, Range>,
+    /// Range checks against a union of ranges, keyed on the merged ranges, see
+    /// [BaseConfig::configure_multi_range_check].
+    pub multi: BTreeMap<Vec<Range>, MultiRangeCheck<F>>,
+    /// [Selector]s for the range checks in `multi`, keyed like `selectors`.
+    pub multi_selectors: BTreeMap<(Vec<Range>, usize, usize), Selector>,
     ///
     pub index: VarTensor,
     ///
@@ -321,12 +617,204 @@ impl<F: PrimeField + TensorType + PartialOrd> RangeChecks<F> {
         Self {
             selectors: BTreeMap::new(),
             ranges: BTreeMap::new(),
+            subsets: BTreeMap::new(),
+            multi: BTreeMap::new(),
+            multi_selectors: BTreeMap::new(),
             index: dummy_var.clone(),
             input: dummy_var,
         }
     }
 }
 
+/// A struct representing the selectors for range checks against a witnessed bound
+#[derive(Clone, Debug, Default)]
+pub struct DynamicRangeChecks {
+    /// [Selector]s enforcing `diff = bound - input - 1`, one per block and inner column.
+    pub selectors: BTreeMap<(usize, usize), Selector>,
+    /// the checked input, the witnessed bound and their difference
+    pub inputs: Vec<VarTensor>,
+}
+
+impl DynamicRangeChecks {
+    /// Returns a new [DynamicRangeChecks] with no inputs and no selectors.
+    pub fn dummy(col_size: usize, num_inner_cols: usize) -> Self {
+        let dummy_var = VarTensor::dummy(col_size, num_inner_cols);
+        Self {
+            selectors: BTreeMap::new(),
+            inputs: vec![dummy_var.clone(), dummy_var.clone(), dummy_var],
+        }
+    }
+}
+
+/// A struct representing the selectors for the lookup tables of functions of two inputs
+#[derive(Clone, Debug, Default)]
+pub struct BinaryLookups<F: PrimeField + TensorType + PartialOrd> {
+    /// [Selector]s for the lookups, keyed on the table's name and the block and inner column.
+    pub selectors: BTreeMap<(String, usize, usize), Selector>,
+    /// Tables, keyed on the name they were configured with.
+    pub tables: BTreeMap<String, Table2<F>>,
+    /// the two looked up inputs
+    pub inputs: Vec<VarTensor>,
+    ///
+    pub output: VarTensor,
+    ///
+    pub index: VarTensor,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd> BinaryLookups<F> {
+    /// Returns a new [BinaryLookups] with no inputs, no selectors, and no tables.
+    pub fn dummy(col_size: usize, num_inner_cols: usize) -> Self {
+        let dummy_var = VarTensor::dummy(col_size, num_inner_cols);
+        Self {
+            selectors: BTreeMap::new(),
+            tables: BTreeMap::new(),
+            inputs: vec![dummy_var.clone(), dummy_var.clone()],
+            output: dummy_var.clone(),
+            index: dummy_var,
+        }
+    }
+}
+
+/// Inserts a selector, erroring rather than overwriting one already configured at `key`, which would
+/// orphan the constraints built on the earlier selector.
+fn insert_selector<K: Ord + std::fmt::Debug>(
+    selectors: &mut BTreeMap<K, Selector>,
+    key: K,
+    selector: Selector,
+) -> Result<(), CircuitError> {
+    if selectors.contains_key(&key) {
+        return Err(CircuitError::SelectorAlreadyConfigured {
+            position: format!("{:?}", key),
+        });
+    }
+    selectors.insert(key, selector);
+    Ok(())
+}
+
+/// A digest of the structure of a circuit's verifying key, see [BaseConfig::vk_structure_digest].
+pub type VkStructureDigest = [u8; 32];
+
+/// Returns true if every digest matches, i.e. the proofs the digests were taken from were all
+/// generated by structurally identical circuits. Trivially true for fewer than two digests.
+pub fn proofs_same_circuit(digests: &[VkStructureDigest]) -> bool {
+    digests.windows(2).all(|w| w[0] == w[1])
+}
+
+/// A circuit config whose verifying key structure can be digested, so that the digest of a received
+/// verifying key can be taken from the config it deserializes against, see
+/// [crate::pfsys::vk_structure_digest_from_bytes].
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub trait VkStructure {
+    /// A digest of everything structural that shapes the verifying key.
+    fn vk_structure_digest(&self) -> VkStructureDigest;
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> VkStructure for BaseConfig<F> {
+    fn vk_structure_digest(&self) -> VkStructureDigest {
+        BaseConfig::vk_structure_digest(self)
+    }
+}
+
+/// The rows a single op occupies in a [RowPlan].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OpRows {
+    /// name of the op
+    pub name: String,
+    /// first row the op is laid out on
+    pub start_row: usize,
+    /// row the next op will start on
+    pub end_row: usize,
+}
+
+/// The rows each op occupies when laid out in sequence, as reported by [BaseConfig::dry_run].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RowPlan {
+    /// rows for each op, in layout order
+    pub ops: Vec<OpRows>,
+    /// element-wise [BaseOp]s whose gates the ops enable, see [SelectorStrategy::Lazy]
+    pub base_ops: BTreeSet<BaseOp>,
+}
+
+/// How [BaseConfig::configure_with_strategy] allocates the selectors of the [BaseOp] gates, of which
+/// there is one per element-wise op and `(block, inner column)` position, and one per accumulating op
+/// and block.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum SelectorStrategy {
+    /// Allocate selectors for every element-wise op.
+    #[default]
+    Eager,
+    /// Only allocate selectors for the given element-wise and accumulating ops, typically
+    /// [RowPlan::base_ops] from a [BaseConfig::dry_run] of the ops to be laid out. Halo2 fixes the
+    /// selectors before synthesis, so the ops are found by laying them out once beforehand. Laying
+    /// out an op whose gate has no selector fails with [CircuitError::MissingSelector].
+    Lazy(BTreeSet<BaseOp>),
+    /// Allocate a single selector per element-wise op and block, shared by its inner columns: the
+    /// gate constrains every inner column of an enabled row. An op laid out with a shared selector
+    /// starts on a fresh row and pads the rest of its last row with cells satisfying its gate, so
+    /// the selector count no longer grows with the number of inner columns, at the cost of up to a
+    /// row of padding per op.
+    Shared,
+}
+
+impl SelectorStrategy {
+    /// Whether selectors are allocated for `op`.
+    pub fn allocates(&self, op: &BaseOp) -> bool {
+        match self {
+            SelectorStrategy::Eager | SelectorStrategy::Shared => true,
+            SelectorStrategy::Lazy(ops) => ops.contains(op),
+        }
+    }
+
+    /// Whether the element-wise selectors are shared by the inner columns of a block.
+    pub fn shares_inner_cols(&self) -> bool {
+        matches!(self, SelectorStrategy::Shared)
+    }
+}
+
+impl RowPlan {
+    /// Total number of rows used by the plan.
+    pub fn total_rows(&self) -> usize {
+        self.ops.last().map(|op| op.end_row).unwrap_or(0)
+    }
+}
+
+/// The columns and range of a static lookup table, without its op's evaluation or assignment logic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableColumns {
+    /// range covered by the table
+    pub range: Range,
+    /// input columns
+    pub inputs: Vec<TableColumn>,
+    /// output columns
+    pub outputs: Vec<TableColumn>,
+}
+
+/// The structural part of a [BaseConfig]: the columns and selectors that make up the constraint system.
+/// This is all a verifier needs to rebuild the constraint system when reading a verifying key, so it
+/// carries no [Table]s or [RangeCheck]s. Obtained with [BaseConfig::to_verifier_config].
+#[derive(Clone, Debug)]
+pub struct VerifierConfig {
+    /// Custom gates
+    pub custom_gates: CustomGates,
+    /// [Selector]s for the static lookups
+    pub lookup_selectors: BTreeMap<(LookupOp, usize, usize), Selector>,
+    /// [Selector]s for the static lookups on weighted sums of columns
+    pub linear_lookup_selectors: BTreeMap<(LookupOp, usize, usize), Selector>,
+    /// Columns of the static lookup tables
+    pub lookup_tables: BTreeMap<LookupOp, TableColumns>,
+    /// [Selector]s for the range checks
+    pub range_check_selectors: BTreeMap<(Range, usize, usize), Selector>,
+    /// Columns of the range check tables
+    pub range_check_tables: BTreeMap<Range, Vec<TableColumn>>,
+    /// [Selector]s for the dynamic lookup tables
+    pub dynamic_lookups: DynamicLookups,
+    /// [Selector]s for the range checks against a witnessed bound
+    pub dynamic_range_checks: DynamicRangeChecks,
+    /// [Selector]s for the shuffles
+    pub shuffles: Shuffles,
+}
+
 /// Configuration for an accumulated arg.
 #[derive(Clone, Debug, Default)]
 pub struct BaseConfig<F: PrimeField + TensorType + PartialOrd> {
@@ -338,10 +826,36 @@ pub struct BaseConfig<F: PrimeField + TensorType + PartialOrd> {
     pub dynamic_lookups: DynamicLookups,
     /// [Selector]s for the range checks
     pub range_checks: RangeChecks<F>,
+    /// [Selector]s for the range checks against a witnessed bound
+    pub dynamic_range_checks: DynamicRangeChecks,
+    /// [Selector]s and tables for the lookups of functions of two inputs
+    pub binary_lookups: BinaryLookups<F>,
     /// [Selector]s for the shuffles
     pub shuffles: Shuffles,
     /// Activate sanity checks
     pub check_mode: CheckMode,
+    /// Set once [BaseConfig::layout_tables] or [BaseConfig::layout_range_checks] has run
+    pub tables_laid_out: bool,
+    /// Error (rather than warn) on configuration mistakes: a lookup or range check configured after the
+    /// tables were laid out, or a [Tolerance] whose scale doesn't match that of the values it compares
+    pub strict: bool,
+    /// Maximum number of columns, including the index column, a dynamic lookup may be configured over.
+    pub max_dynamic_lookup_arity: usize,
+    /// Report the differing elements when a safe mode check fails, rather than panicking.
+    pub verbose_checks: bool,
+    /// Names of ops, as given by [Op::as_string], whose safe mode check is skipped whatever the
+    /// [CheckMode], for trusted ops whose check is too expensive to run.
+    pub skip_check_ops: BTreeSet<String>,
+    /// Rows reserved at the end of lookup and range check tables, set before configuring them.
+    pub blinding_rows_pad: BlindingRowsPad,
+    /// Look up ops whose outputs are those of an already configured table over the same range scaled
+    /// by a power of two against that table rather than a table of their own. Off by default, as
+    /// spotting them evaluates every candidate table over its range on each [BaseConfig::configure_lookup].
+    pub share_scaled_tables: bool,
+    /// Instance columns public outputs are laid out to, see [BaseConfig::layout_public_outputs].
+    pub instance_columns: Vec<Column<Instance>>,
+    /// Column argmax indicators are echoed into, see [BaseConfig::configure_argmax].
+    pub argmax_output: Option<VarTensor>,
     _marker: PhantomData<F>,
 }
 
@@ -351,10 +865,23 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
         Self {
             custom_gates: CustomGates::dummy(col_size, num_inner_cols),
             static_lookups: StaticLookups::dummy(col_size, num_inner_cols),
-            dynamic_lookups: DynamicLookups::dummy(col_size, num_inner_cols),
-            shuffles: Shuffles::dummy(col_size, num_inner_cols),
+            // two looked up columns and the lookup index
+            dynamic_lookups: DynamicLookups::dummy(col_size, num_inner_cols, 3),
+            // the shuffled column and the shuffle index
+            shuffles: Shuffles::dummy(col_size, num_inner_cols, 2),
             range_checks: RangeChecks::dummy(col_size, num_inner_cols),
+            dynamic_range_checks: DynamicRangeChecks::dummy(col_size, num_inner_cols),
+            binary_lookups: BinaryLookups::dummy(col_size, num_inner_cols),
             check_mode: CheckMode::SAFE,
+            tables_laid_out: false,
+            strict: false,
+            max_dynamic_lookup_arity: DEFAULT_MAX_DYNAMIC_LOOKUP_ARITY,
+            verbose_checks: false,
+            skip_check_ops: BTreeSet::new(),
+            blinding_rows_pad: BlindingRowsPad::default(),
+            share_scaled_tables: false,
+            instance_columns: vec![],
+            argmax_output: None,
             _marker: PhantomData,
         }
     }
@@ -362,89 +889,195 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
     /// Configures [BaseOp]s for a given [ConstraintSystem].
     /// # Arguments
     /// * `meta` - The [ConstraintSystem] to configure the operations in.
-    /// * `inputs` - The explicit inputs to the operations.
+    /// * `inputs` - The explicit inputs to the operations, at least two. Ops taking more inputs, such
+    ///   as [BaseOp::MultAdd], are only configured when enough inputs are given.
     /// * `output` - The variable representing the (currently singular) output of the operations.
     /// * `check_mode` - The variable representing the (currently singular) output of the operations.
     pub fn configure(
         meta: &mut ConstraintSystem<F>,
-        inputs: &[VarTensor; 2],
+        inputs: &[VarTensor],
+        output: &VarTensor,
+        check_mode: CheckMode,
+    ) -> Self {
+        Self::configure_with_strategy(meta, inputs, output, check_mode, &SelectorStrategy::Eager)
+    }
+
+    /// Same as [BaseConfig::configure] but allocates the element-wise gate selectors according to
+    /// `strategy`.
+    pub fn configure_with_strategy(
+        meta: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
         output: &VarTensor,
         check_mode: CheckMode,
+        strategy: &SelectorStrategy,
     ) -> Self {
+        let mut config = Self {
+            custom_gates: CustomGates::default(),
+            static_lookups: StaticLookups::default(),
+            dynamic_lookups: DynamicLookups::default(),
+            shuffles: Shuffles::default(),
+            range_checks: RangeChecks::default(),
+            dynamic_range_checks: DynamicRangeChecks::default(),
+            binary_lookups: BinaryLookups::default(),
+            check_mode,
+            tables_laid_out: false,
+            strict: false,
+            max_dynamic_lookup_arity: DEFAULT_MAX_DYNAMIC_LOOKUP_ARITY,
+            verbose_checks: false,
+            skip_check_ops: BTreeSet::new(),
+            blinding_rows_pad: BlindingRowsPad::default(),
+            share_scaled_tables: false,
+            instance_columns: vec![],
+            argmax_output: None,
+            _marker: PhantomData,
+        };
+        config
+            .configure_custom_gates_with_strategy(meta, inputs, output, strategy)
+            .expect("failed to configure the custom gates");
+        config
+    }
+
+    /// Same as [BaseConfig::configure] but also reports how much each op raised the maximum degree of
+    /// `meta`, as `(gate name, degree increase)` pairs in the order the gates were created, to find
+    /// the op forcing a larger extended domain. Each gate is measured against the degree of `meta`
+    /// before `configure` and of the gates created before it, so the increases add up to the total
+    /// increase in gate degree. Lookups are configured separately; to have them reported as well,
+    /// configure them through [BaseConfigBuilder::configure_with_degree_report].
+    pub fn configure_with_degree_report(
+        meta: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
+        output: &VarTensor,
+        check_mode: CheckMode,
+    ) -> (Self, Vec<(String, usize)>) {
+        let num_gates = meta.gates().len();
+        let mut max_degree = meta.degree();
+        let config = Self::configure(meta, inputs, output, check_mode);
+
+        let mut report: Vec<(String, usize)> = vec![];
+        for gate in &meta.gates()[num_gates..] {
+            let degree = gate
+                .polynomials()
+                .iter()
+                .map(|poly| poly.degree())
+                .max()
+                .unwrap_or(0);
+            let increase = degree.saturating_sub(max_degree);
+            max_degree = max_degree.max(degree);
+            match report
+                .iter_mut()
+                .find(|(name, _)| name.as_str() == gate.name())
+            {
+                Some((_, total)) => *total += increase,
+                None => report.push((gate.name().to_string(), increase)),
+            }
+        }
+        (config, report)
+    }
+
+    /// Configures the [BaseOp] custom gates over `inputs` and `output`. Errors with
+    /// [CircuitError::SelectorAlreadyConfigured] if the gates were already configured, as happens when
+    /// `configure` runs twice on the same config, and with [CircuitError::DimMismatch] if fewer than two
+    /// inputs are given.
+    pub fn configure_custom_gates(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
+        output: &VarTensor,
+    ) -> Result<(), CircuitError> {
+        self.configure_custom_gates_with_strategy(meta, inputs, output, &SelectorStrategy::Eager)
+    }
+
+    /// Same as [BaseConfig::configure_custom_gates] but allocates the element-wise gate selectors
+    /// according to `strategy`.
+    pub fn configure_custom_gates_with_strategy(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
+        output: &VarTensor,
+        strategy: &SelectorStrategy,
+    ) -> Result<(), CircuitError> {
         // setup a selector per base op
         let mut nonaccum_selectors = BTreeMap::new();
         let mut accum_selectors = BTreeMap::new();
 
-        if inputs[0].num_cols() != inputs[1].num_cols() {
+        if inputs.len() < 2 {
+            return Err(CircuitError::DimMismatch(format!(
+                "custom gates need at least 2 inputs, got {}",
+                inputs.len()
+            )));
+        }
+        if inputs
+            .iter()
+            .any(|input| input.num_cols() != inputs[0].num_cols())
+        {
             log::warn!("input shapes do not match");
         }
         if inputs[0].num_cols() != output.num_cols() {
             log::warn!("input and output shapes do not match");
         }
 
+        let nonaccum_ops = [
+            BaseOp::Add,
+            BaseOp::Sub,
+            BaseOp::Mult,
+            BaseOp::IsBoolean,
+            BaseOp::MultAdd,
+        ]
+        .into_iter()
+        // ops taking more inputs than were given are left out, laying them out errors with
+        // CircuitError::MissingSelector
+        .filter(|op| op.num_inputs() <= inputs.len() && strategy.allocates(op))
+        .collect::<Vec<_>>();
+        // shared selectors are keyed on the first inner column
+        let num_selector_cols = if strategy.shares_inner_cols() {
+            1
+        } else {
+            output.num_inner_cols()
+        };
         for i in 0..output.num_blocks() {
-            for j in 0..output.num_inner_cols() {
-                nonaccum_selectors.insert((BaseOp::Add, i, j), meta.selector());
-                nonaccum_selectors.insert((BaseOp::Sub, i, j), meta.selector());
-                nonaccum_selectors.insert((BaseOp::Mult, i, j), meta.selector());
-                nonaccum_selectors.insert((BaseOp::IsBoolean, i, j), meta.selector());
+            for j in 0..num_selector_cols {
+                for op in nonaccum_ops.iter() {
+                    nonaccum_selectors.insert((op.clone(), i, j), meta.selector());
+                }
             }
         }
 
+        let accum_ops = [
+            BaseOp::DotInit,
+            BaseOp::Dot,
+            BaseOp::CumProd,
+            BaseOp::CumProdInit,
+            BaseOp::Sum,
+            BaseOp::SumInit,
+        ]
+        .into_iter()
+        .filter(|op| strategy.allocates(op))
+        .collect::<Vec<_>>();
         for i in 0..output.num_blocks() {
-            accum_selectors.insert((BaseOp::DotInit, i, 0), meta.selector());
-            accum_selectors.insert((BaseOp::Dot, i, 0), meta.selector());
-            accum_selectors.insert((BaseOp::CumProd, i, 0), meta.selector());
-            accum_selectors.insert((BaseOp::CumProdInit, i, 0), meta.selector());
-            accum_selectors.insert((BaseOp::Sum, i, 0), meta.selector());
-            accum_selectors.insert((BaseOp::SumInit, i, 0), meta.selector());
+            for op in accum_ops.iter() {
+                accum_selectors.insert((op.clone(), i, 0), meta.selector());
+            }
         }
 
-        for ((base_op, block_idx, inner_col_idx), selector) in nonaccum_selectors.iter() {
-            meta.create_gate(base_op.as_str(), |meta| {
-                let selector = meta.query_selector(*selector);
-
-                let zero = Expression::<F>::Constant(F::ZERO);
-                let mut qis = vec![zero; 2];
-                for (i, q_i) in qis
-                    .iter_mut()
-                    .enumerate()
-                    .take(2)
-                    .skip(2 - base_op.num_inputs())
-                {
-                    *q_i = inputs[i]
-                        .query_rng(meta, *block_idx, *inner_col_idx, 0, 1)
-                        .expect("non accum: input query failed")[0]
-                        .clone()
-                }
-
-                // Get output expressions for each input channel
-                let (rotation_offset, rng) = base_op.query_offset_rng();
-
-                let constraints = match base_op {
-                    BaseOp::IsBoolean => {
-                        let expected_output: Tensor<Expression<F>> = output
-                            .query_rng(meta, *block_idx, *inner_col_idx, 0, 1)
-                            .expect("non accum: output query failed");
-
-                        let output = expected_output[base_op.constraint_idx()].clone();
-
-                        vec![(output.clone()) * (output.clone() - Expression::Constant(F::from(1)))]
-                    }
-                    _ => {
-                        let expected_output: Tensor<Expression<F>> = output
-                            .query_rng(meta, *block_idx, *inner_col_idx, rotation_offset, rng)
-                            .expect("non accum: output query failed");
-
-                        let res = base_op.nonaccum_f((qis[0].clone(), qis[1].clone()));
-                        vec![expected_output[base_op.constraint_idx()].clone() - res]
-                    }
-                };
-
-                Constraints::with_selector(selector, constraints)
+        // an existing selector means the gates are being configured twice, check before creating any gate
+        if let Some(key) = nonaccum_selectors
+            .keys()
+            .chain(accum_selectors.keys())
+            .find(|key| self.custom_gates.selectors.contains_key(key))
+        {
+            return Err(CircuitError::SelectorAlreadyConfigured {
+                position: format!("{:?}", key),
             });
         }
 
+        Self::create_nonaccum_gates(
+            meta,
+            inputs,
+            output,
+            &nonaccum_selectors,
+            strategy.shares_inner_cols(),
+        );
+
         for ((base_op, block_idx, _), selector) in accum_selectors.iter() {
             meta.create_gate(base_op.as_str(), |meta| {
                 let selector = meta.query_selector(*selector);
@@ -478,380 +1111,1055 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
         }
 
         // selectors is the merger of nonaccum and accum selectors
-        let selectors = nonaccum_selectors
-            .into_iter()
-            .chain(accum_selectors)
-            .collect();
-
-        Self {
-            custom_gates: CustomGates {
-                inputs: inputs.to_vec(),
-                output: output.clone(),
-                selectors,
-            },
-            static_lookups: StaticLookups::default(),
-            dynamic_lookups: DynamicLookups::default(),
-            shuffles: Shuffles::default(),
-            range_checks: RangeChecks::default(),
-            check_mode,
-            _marker: PhantomData,
+        for (key, selector) in nonaccum_selectors.into_iter().chain(accum_selectors) {
+            insert_selector(&mut self.custom_gates.selectors, key, selector)?;
+        }
+        if strategy.shares_inner_cols() {
+            self.custom_gates.shared_ops.extend(nonaccum_ops);
         }
+        self.custom_gates.inputs = inputs.to_vec();
+        self.custom_gates.output = output.clone();
+
+        Ok(())
     }
 
-    /// Configures and creates lookup selectors
-    #[allow(clippy::too_many_arguments)]
-    pub fn configure_lookup(
-        &mut self,
-        cs: &mut ConstraintSystem<F>,
-        input: &VarTensor,
+    /// Creates the gate of each non-accumulating [BaseOp] selector in `selectors`, keyed on the op, block
+    /// and inner column it constrains. `shared` selectors constrain every inner column of their block.
+    fn create_nonaccum_gates(
+        meta: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
         output: &VarTensor,
-        index: &VarTensor,
-        lookup_range: Range,
-        logrows: usize,
-        nl: &LookupOp,
-    ) -> Result<(), Box<dyn Error>>
-    where
-        F: Field,
-    {
-        if !index.is_advice() {
-            return Err("wrong input type for lookup index".into());
-        }
-        if !input.is_advice() {
-            return Err("wrong input type for lookup input".into());
+        selectors: &BTreeMap<(BaseOp, usize, usize), Selector>,
+        shared: bool,
+    ) {
+        for ((base_op, block_idx, inner_col_idx), selector) in selectors.iter() {
+            let inner_cols = if shared {
+                0..output.num_inner_cols()
+            } else {
+                *inner_col_idx..*inner_col_idx + 1
+            };
+            meta.create_gate(base_op.as_str(), |meta| {
+                let selector = meta.query_selector(*selector);
+                let constraints = inner_cols
+                    .clone()
+                    .flat_map(|inner_col_idx| {
+                        Self::nonaccum_constraints(
+                            meta,
+                            inputs,
+                            output,
+                            base_op,
+                            *block_idx,
+                            inner_col_idx,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+
+                Constraints::with_selector(selector, constraints)
+            });
         }
-        if !output.is_advice() {
-            return Err("wrong input type for lookup output".into());
+    }
+
+    /// The constraints of the non-accumulating `base_op` at inner column `inner_col_idx` of block
+    /// `block_idx`.
+    fn nonaccum_constraints(
+        meta: &mut VirtualCells<'_, F>,
+        inputs: &[VarTensor],
+        output: &VarTensor,
+        base_op: &BaseOp,
+        block_idx: usize,
+        inner_col_idx: usize,
+    ) -> Vec<Expression<F>> {
+        // ops with fewer than two inputs query the last of the first two columns
+        let num_qis = base_op.num_inputs().max(2);
+        let zero = Expression::<F>::Constant(F::ZERO);
+        let mut qis = vec![zero; num_qis];
+        for (i, q_i) in qis
+            .iter_mut()
+            .enumerate()
+            .skip(num_qis - base_op.num_inputs())
+        {
+            *q_i = inputs[i]
+                .query_rng(meta, block_idx, inner_col_idx, 0, 1)
+                .expect("non accum: input query failed")[0]
+                .clone()
         }
 
-        // we borrow mutably twice so we need to do this dance
+        // Get output expressions for each input channel
+        let (rotation_offset, rng) = base_op.query_offset_rng();
 
-        let table = if !self.static_lookups.tables.contains_key(nl) {
-            // as all tables have the same input we see if there's another table who's input we can reuse
-            let table = if let Some(table) = self.static_lookups.tables.values().next() {
-                Table::<F>::configure(
-                    cs,
-                    lookup_range,
-                    logrows,
-                    nl,
-                    Some(table.table_inputs.clone()),
-                )
-            } else {
-                Table::<F>::configure(cs, lookup_range, logrows, nl, None)
-            };
-            self.static_lookups.tables.insert(nl.clone(), table.clone());
-            table
-        } else {
-            return Ok(());
-        };
+        match base_op {
+            BaseOp::IsBoolean => {
+                let expected_output: Tensor<Expression<F>> = output
+                    .query_rng(meta, block_idx, inner_col_idx, 0, 1)
+                    .expect("non accum: output query failed");
 
-        for x in 0..input.num_blocks() {
-            for y in 0..input.num_inner_cols() {
-                let len = table.selector_constructor.degree;
+                let output = expected_output[base_op.constraint_idx()].clone();
 
-                let multi_col_selector = cs.complex_selector();
+                vec![(output.clone()) * (output.clone() - Expression::Constant(F::from(1)))]
+            }
+            BaseOp::IsZero => {
+                let expected_output: Tensor<Expression<F>> = output
+                    .query_rng(meta, block_idx, inner_col_idx, 0, 1)
+                    .expect("non accum: output query failed");
 
-                for ((col_idx, input_col), output_col) in table
-                    .table_inputs
-                    .iter()
-                    .enumerate()
-                    .zip(table.table_outputs.iter())
-                {
-                    cs.lookup("", |cs| {
-                        let mut res = vec![];
-                        let sel = cs.query_selector(multi_col_selector);
+                // the standard is-zero gadget: `b` is boolean, `input * b = 0` forces it to
+                // zero for a non zero input, and `b = 1 - input * witness` forces it to one for
+                // a zero input
+                let b = expected_output[base_op.constraint_idx()].clone();
+                let one = Expression::Constant(F::ONE);
+                vec![
+                    b.clone() * (one - b.clone()),
+                    qis[0].clone() * b.clone(),
+                    b - base_op.nonaccum_f(&qis),
+                ]
+            }
+            BaseOp::SatAdd { lo, hi } | BaseOp::SatSub { lo, hi } => {
+                let expected_output: Tensor<Expression<F>> = output
+                    .query_rng(meta, block_idx, inner_col_idx, 0, 1)
+                    .expect("non accum: output query failed");
 
-                        let synthetic_sel = match len {
-                            1 => Expression::Constant(F::from(1)),
-                            _ => match index {
-                                VarTensor::Advice { inner: advices, .. } => {
-                                    cs.query_advice(advices[x][y], Rotation(0))
-                                }
-                                _ => unreachable!(),
-                            },
-                        };
+                // `c` is 0 within the bounds, 1 when clamped to `hi` and -1 when clamped to
+                // `lo`: it must be one of the three, and selects the output accordingly
+                let out = expected_output[base_op.constraint_idx()].clone();
+                let c = qis[2].clone();
+                let one = Expression::Constant(F::ONE);
+                let lo = Expression::Constant(i128_to_felt::<F>(*lo as i128));
+                let hi = Expression::Constant(i128_to_felt::<F>(*hi as i128));
+                let unclamped = base_op.nonaccum_f(&qis);
+                vec![
+                    c.clone() * (c.clone() * c.clone() - one.clone()),
+                    (one.clone() - c.clone() * c.clone()) * (out.clone() - unclamped),
+                    c.clone() * (c.clone() + one.clone()) * (out.clone() - hi),
+                    c.clone() * (c - one) * (out - lo),
+                ]
+            }
+            _ => {
+                let expected_output: Tensor<Expression<F>> = output
+                    .query_rng(meta, block_idx, inner_col_idx, rotation_offset, rng)
+                    .expect("non accum: output query failed");
 
-                        let input_query = match &input {
-                            VarTensor::Advice { inner: advices, .. } => {
-                                cs.query_advice(advices[x][y], Rotation(0))
-                            }
-                            _ => unreachable!(),
-                        };
+                let res = base_op.nonaccum_f(&qis);
+                vec![expected_output[base_op.constraint_idx()].clone() - res]
+            }
+        }
+    }
 
-                        let output_query = match &output {
-                            VarTensor::Advice { inner: advices, .. } => {
-                                cs.query_advice(advices[x][y], Rotation(0))
-                            }
-                            _ => unreachable!(),
-                        };
+    /// Configures the [BaseOp::SatAdd] and [BaseOp::SatSub] gates for the bounds `[lo, hi]`, over the
+    /// custom gate inputs and output. The third input holds the is-clamped indicator `c`: 0 where the
+    /// unclamped value lies within the bounds and the output equals it, 1 where the output is clamped to
+    /// `hi` and -1 where it is clamped to `lo`. The gates alone don't prove that the indicator is truthful,
+    /// `layouts::saturating` also range checks the output against `(lo, hi)` and the overshoot
+    /// `c * (unclamped - output - c)` against `(0, hi - lo)`, which is non negative only if the unclamped
+    /// value lies past the bound it was clamped to. Both range checks have to be configured with
+    /// [BaseConfig::configure_range_check], and operands are expected within `[lo, hi]` with
+    /// `lo <= 0 <= hi` for the overshoot to fit. Needs a third input column to have been passed to
+    /// [BaseConfig::configure]. The gates are of degree 4. They are laid out by
+    /// [crate::circuit::ops::poly::PolyOp::SatAdd] and [crate::circuit::ops::poly::PolyOp::SatSub].
+    ///
+    /// Unlike the other element-wise gates these aren't configured by [BaseConfig::configure]: each
+    /// pair of bounds is a gate of its own, the bounds are only known from the ops to be laid out, and
+    /// the gates need a third input column and range checks most circuits don't have.
+    ///
+    /// Errors with [CircuitError::InvertedRange] if `lo > hi`.
+    pub fn configure_saturating(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+        lo: i64,
+        hi: i64,
+    ) -> Result<(), CircuitError> {
+        if lo > hi {
+            return Err(CircuitError::InvertedRange((lo as i128, hi as i128)));
+        }
+        self.check_initialized("configure_saturating")?;
 
-                        // we index from 1 to avoid the zero element creating soundness issues
-                        // this is 0 if the index is the same as the column index (starting from 1)
+        let inputs = self.custom_gates.inputs.clone();
+        let output = self.custom_gates.output.clone();
+        if inputs.len() < 3 {
+            return Err(CircuitError::DimMismatch(format!(
+                "saturating gates need 3 inputs, got {}",
+                inputs.len()
+            )));
+        }
 
-                        let col_expr = sel.clone()
-                            * table
-                                .selector_constructor
-                                .get_expr_at_idx(col_idx, synthetic_sel);
+        let mut selectors = BTreeMap::new();
+        for base_op in [BaseOp::SatAdd { lo, hi }, BaseOp::SatSub { lo, hi }] {
+            for i in 0..output.num_blocks() {
+                for j in 0..output.num_inner_cols() {
+                    let key = (base_op.clone(), i, j);
+                    if self.custom_gates.selectors.contains_key(&key) {
+                        return Err(CircuitError::SelectorAlreadyConfigured {
+                            position: format!("{:?}", key),
+                        });
+                    }
+                    selectors.insert(key, meta.selector());
+                }
+            }
+        }
 
-                        let multiplier =
-                            table.selector_constructor.get_selector_val_at_idx(col_idx);
+        Self::create_nonaccum_gates(meta, &inputs, &output, &selectors, false);
 
-                        let not_expr = Expression::Constant(multiplier) - col_expr.clone();
+        for (key, selector) in selectors {
+            insert_selector(&mut self.custom_gates.selectors, key, selector)?;
+        }
 
-                        let (default_x, default_y) = table.get_first_element(col_idx);
+        Ok(())
+    }
 
-                        log::trace!("---------------- col {:?} ------------------", col_idx,);
-                        log::trace!("expr: {:?}", col_expr,);
-                        log::trace!("multiplier: {:?}", multiplier);
-                        log::trace!("not_expr: {:?}", not_expr);
-                        log::trace!("default x: {:?}", default_x);
-                        log::trace!("default y: {:?}", default_y);
+    /// Configures the [BaseOp::IsZero] gate over the first two inputs and the output of the custom
+    /// gates, laid out by [crate::circuit::ops::poly::PolyOp::IsZero]. Configured on demand, rather
+    /// than by [BaseConfig::configure], as few circuits need it. Must be called after
+    /// [BaseConfig::configure].
+    pub fn configure_is_zero(
+        &mut self,
+        meta: &mut ConstraintSystem<F>,
+    ) -> Result<(), CircuitError> {
+        self.check_initialized("configure_is_zero")?;
 
-                        res.extend([
-                            (
-                                col_expr.clone() * input_query.clone()
-                                    + not_expr.clone() * Expression::Constant(default_x),
-                                *input_col,
-                            ),
-                            (
-                                col_expr.clone() * output_query.clone()
-                                    + not_expr.clone() * Expression::Constant(default_y),
-                                *output_col,
-                            ),
-                        ]);
+        let inputs = self.custom_gates.inputs.clone();
+        let output = self.custom_gates.output.clone();
 
-                        res
+        let mut selectors = BTreeMap::new();
+        for i in 0..output.num_blocks() {
+            for j in 0..output.num_inner_cols() {
+                let key = (BaseOp::IsZero, i, j);
+                if self.custom_gates.selectors.contains_key(&key) {
+                    return Err(CircuitError::SelectorAlreadyConfigured {
+                        position: format!("{:?}", key),
                     });
                 }
-                self.static_lookups
-                    .selectors
-                    .insert((nl.clone(), x, y), multi_col_selector);
+                selectors.insert(key, meta.selector());
             }
         }
-        // if we haven't previously initialized the input/output, do so now
-        if let VarTensor::Empty = self.static_lookups.input {
-            debug!("assigning lookup input");
-            self.static_lookups.input = input.clone();
-        }
-        if let VarTensor::Empty = self.static_lookups.output {
-            debug!("assigning lookup output");
-            self.static_lookups.output = output.clone();
-        }
-        if let VarTensor::Empty = self.static_lookups.index {
-            debug!("assigning lookup index");
-            self.static_lookups.index = index.clone();
+
+        Self::create_nonaccum_gates(meta, &inputs, &output, &selectors, false);
+
+        for (key, selector) in selectors {
+            insert_selector(&mut self.custom_gates.selectors, key, selector)?;
         }
+
         Ok(())
     }
 
-    /// Configures and creates lookup selectors
-    #[allow(clippy::too_many_arguments)]
-    pub fn configure_dynamic_lookup(
+    /// Configures a [BaseOp::RotatedSub] gate over the first input and the output of the custom gates,
+    /// constraining `output = input - input[rotation rows up]` within each column. With a single inner
+    /// column this relates neighbouring elements, e.g. `rotation = 1` gives an adjacent difference, see
+    /// [crate::circuit::ops::poly::PolyOp::LaggedDiff]. Must be called after [BaseConfig::configure].
+    ///
+    /// Panics if `rotation` is zero.
+    pub fn configure_rotated_sub(
         &mut self,
-        cs: &mut ConstraintSystem<F>,
-        lookups: &[VarTensor; 3],
-        tables: &[VarTensor; 3],
-    ) -> Result<(), Box<dyn Error>>
-    where
-        F: Field,
-    {
-        for l in lookups.iter() {
-            if !l.is_advice() {
-                return Err("wrong input type for dynamic lookup".into());
-            }
-        }
+        meta: &mut ConstraintSystem<F>,
+        rotation: usize,
+    ) -> Result<(), CircuitError> {
+        assert!(rotation > 0, "a rotated sub gate needs a non zero rotation");
+        self.check_initialized("configure_rotated_sub")?;
 
-        for t in tables.iter() {
-            if !t.is_advice() || t.num_blocks() > 1 || t.num_inner_cols() > 1 {
-                return Err("wrong table type for dynamic lookup".into());
+        let base_op = BaseOp::RotatedSub(rotation);
+        let input = self.custom_gates.inputs[0].clone();
+        let output = self.custom_gates.output.clone();
+
+        let mut selectors = BTreeMap::new();
+        for i in 0..output.num_blocks() {
+            for j in 0..output.num_inner_cols() {
+                let key = (base_op.clone(), i, j);
+                if self.custom_gates.selectors.contains_key(&key) {
+                    return Err(CircuitError::SelectorAlreadyConfigured {
+                        position: format!("{:?}", key),
+                    });
+                }
+                selectors.insert(key, meta.selector());
             }
         }
 
-        let one = Expression::Constant(F::ONE);
+        for ((base_op, block_idx, inner_col_idx), selector) in selectors.iter() {
+            meta.create_gate(base_op.as_str(), |meta| {
+                let selector = meta.query_selector(*selector);
 
-        let s_ltable = cs.complex_selector();
+                // the window runs from the rotated row to the current one
+                let (rotation_offset, rng) = base_op.query_offset_rng();
+                let window = input
+                    .query_rng(meta, *block_idx, *inner_col_idx, rotation_offset, rng)
+                    .expect("rotated: input query failed");
+                let expected_output: Tensor<Expression<F>> = output
+                    .query_rng(meta, *block_idx, *inner_col_idx, 0, 1)
+                    .expect("rotated: output query failed");
 
-        for x in 0..lookups[0].num_blocks() {
-            for y in 0..lookups[0].num_inner_cols() {
-                let s_lookup = cs.complex_selector();
+                let res = base_op.nonaccum_f(&[window[rng - 1].clone(), window[0].clone()]);
+                let constraints = vec![expected_output[base_op.constraint_idx()].clone() - res];
 
-                cs.lookup_any("lookup", |cs| {
-                    let s_lookupq = cs.query_selector(s_lookup);
-                    let mut expression = vec![];
-                    let s_ltableq = cs.query_selector(s_ltable);
-                    let mut lookup_queries = vec![one.clone()];
+                Constraints::with_selector(selector, constraints)
+            });
+        }
 
-                    for lookup in lookups {
-                        lookup_queries.push(match lookup {
-                            VarTensor::Advice { inner: advices, .. } => {
-                                cs.query_advice(advices[x][y], Rotation(0))
-                            }
-                            _ => unreachable!(),
-                        });
-                    }
+        for (key, selector) in selectors {
+            insert_selector(&mut self.custom_gates.selectors, key, selector)?;
+        }
 
-                    let mut table_queries = vec![one.clone()];
-                    for table in tables {
-                        table_queries.push(match table {
-                            VarTensor::Advice { inner: advices, .. } => {
-                                cs.query_advice(advices[0][0], Rotation(0))
-                            }
-                            _ => unreachable!(),
-                        });
-                    }
+        Ok(())
+    }
 
-                    let lhs = lookup_queries.into_iter().map(|c| c * s_lookupq.clone());
-                    let rhs = table_queries.into_iter().map(|c| c * s_ltableq.clone());
-                    expression.extend(lhs.zip(rhs));
+    /// Configures [BaseOp]s over freshly allocated inputs and output that all share the same geometry.
+    /// # Arguments
+    /// * `meta` - The [ConstraintSystem] to configure the operations in.
+    /// * `col_size` - The number of usable rows in each column.
+    /// * `num_blocks` - The number of blocks each [VarTensor] spans.
+    /// * `num_inner_cols` - The number of columns in each block.
+    /// * `check_mode` - The variable representing the (currently singular) output of the operations.
+    pub fn with_geometry(
+        meta: &mut ConstraintSystem<F>,
+        col_size: usize,
+        num_blocks: usize,
+        num_inner_cols: usize,
+        check_mode: CheckMode,
+    ) -> Self {
+        let mut var =
+            || VarTensor::new_advice_with_geometry(meta, col_size, num_blocks, num_inner_cols);
+        let inputs = [var(), var()];
+        let output = var();
+        Self::configure(meta, &inputs, &output, check_mode)
+    }
 
-                    expression
-                });
-                self.dynamic_lookups
-                    .lookup_selectors
-                    .entry((x, y))
-                    .or_insert(s_lookup);
-            }
-        }
-        self.dynamic_lookups.table_selectors.push(s_ltable);
+    /// Number of constraints (gate polynomials and lookups) [CheckMode::SAFE] adds over
+    /// [CheckMode::UNSAFE], found by rebuilding the structure of `self` under each mode. Configs
+    /// whose structure can't be rebuilt, see [BaseConfig::mock_structure_check], are compared on
+    /// their base gates alone.
+    pub fn safe_mode_extra_constraints(&self) -> usize {
+        let safe = self.structure_under(CheckMode::SAFE);
+        let unsafe_ = self.structure_under(CheckMode::UNSAFE);
+        safe.num_constraints.saturating_sub(unsafe_.num_constraints)
+    }
 
-        // if we haven't previously initialized the input/output, do so now
-        if self.dynamic_lookups.tables.is_empty() {
-            debug!("assigning dynamic lookup table");
-            self.dynamic_lookups.tables = tables.to_vec();
+    /// Summarizes the constraint system `self` configures when laid out with `check_mode`.
+    fn structure_under(&self, check_mode: CheckMode) -> StructureSummary {
+        let output = &self.custom_gates.output;
+        let mut base_cs = ConstraintSystem::<F>::default();
+        Self::with_geometry(
+            &mut base_cs,
+            output.col_size(),
+            output.num_blocks(),
+            output.num_inner_cols(),
+            check_mode,
+        );
+        // the tables are sized for `k`, which only has to leave room for the columns and the
+        // blinding rows, and be shared by both modes
+        let reserved = base_cs.blinding_factors()
+            + 1
+            + self
+                .blinding_rows_pad
+                .tables
+                .max(self.blinding_rows_pad.range_checks);
+        let k = (output.col_size() + reserved)
+            .next_power_of_two()
+            .trailing_zeros();
+        let mut config = self.clone();
+        config.check_mode = check_mode;
+        let mut cs = ConstraintSystem::<F>::default();
+        match config.rebuild_structure(&mut cs, k) {
+            Ok(_) => StructureSummary::of(&cs),
+            Err(_) => StructureSummary::of(&base_cs),
         }
-        if self.dynamic_lookups.inputs.is_empty() {
-            debug!("assigning dynamic lookup input");
-            self.dynamic_lookups.inputs = lookups.to_vec();
+    }
+
+    /// Flags configuration calls made after the tables were laid out, as the new tables would never get assigned.
+    fn check_not_laid_out(&self, caller: &str) -> Result<(), Box<dyn Error>> {
+        if self.tables_laid_out {
+            let msg = format!(
+                "{} called after layout_tables, the newly configured table will not be assigned",
+                caller
+            );
+            if self.strict {
+                return Err(msg.into());
+            }
+            log::warn!("{}", msg);
         }
+        Ok(())
+    }
 
+    /// Flags configuration calls made on a config whose custom gates were never set up by
+    /// [BaseConfig::configure], e.g. one obtained from `default()`.
+    fn check_initialized(&self, caller: &str) -> Result<(), CircuitError> {
+        if self.custom_gates.inputs.is_empty() {
+            return Err(CircuitError::ConfigNotInitialized(caller.to_string()));
+        }
         Ok(())
     }
 
-    /// Configures and creates lookup selectors
+    /// Configures and creates lookup selectors. All lookups share one `input` and one `index`, those
+    /// of the first lookup configured; passing different ones is an error.
     #[allow(clippy::too_many_arguments)]
-    pub fn configure_shuffles(
+    pub fn configure_lookup(
         &mut self,
         cs: &mut ConstraintSystem<F>,
-        inputs: &[VarTensor; 2],
-        references: &[VarTensor; 2],
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        lookup_range: Range,
+        logrows: usize,
+        nl: &LookupOp,
     ) -> Result<(), Box<dyn Error>>
     where
         F: Field,
     {
-        for l in inputs.iter() {
-            if !l.is_advice() {
-                return Err("wrong input type for dynamic lookup".into());
-            }
-        }
+        self.configure_lookup_with_default_fill(
+            cs,
+            input,
+            output,
+            index,
+            lookup_range,
+            logrows,
+            nl,
+            None,
+        )
+    }
 
-        for t in references.iter() {
-            if !t.is_advice() || t.num_blocks() > 1 || t.num_inner_cols() > 1 {
-                return Err("wrong table type for dynamic lookup".into());
-            }
+    /// Same as [BaseConfig::configure_lookup] but the table outputs come from `precomputed` rather
+    /// than an op, for data-defined mappings such as a learned lookup table or a ROM: the input
+    /// `lookup_range.0 + i` maps to `precomputed[i]`. `precomputed` must hold exactly one output per
+    /// element of `lookup_range`, it is kept by the table. Returns the [LookupOp::Precomputed] keyed on
+    /// the mapping to lay the lookup out with.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_lookup_precomputed(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        lookup_range: Range,
+        logrows: usize,
+        precomputed: Tensor<F>,
+    ) -> Result<LookupOp, Box<dyn Error>>
+    where
+        F: Field,
+    {
+        let range_len = lookup_range.1 - lookup_range.0 + 1;
+        if precomputed.len() as i128 != range_len {
+            return Err(Box::new(CircuitError::DimMismatch(format!(
+                "precomputed lookup has {} outputs for a range of {}",
+                precomputed.len(),
+                range_len
+            ))));
         }
 
-        let one = Expression::Constant(F::ONE);
-
-        let s_reference = cs.complex_selector();
+        let nl = LookupOp::precomputed(lookup_range, &precomputed);
+        let positions = self.unconfigured_positions(input, &nl);
+        if !positions.is_empty() {
+            let precomputed = PrecomputedOutputs {
+                name: Op::<F>::as_string(&nl),
+                range: lookup_range,
+                outputs: Arc::new(precomputed),
+            };
+            self.configure_lookup_inner(
+                cs,
+                input,
+                output,
+                index,
+                lookup_range,
+                logrows,
+                &nl,
+                None,
+                Some(precomputed),
+                positions,
+            )?;
+        }
+        Ok(nl)
+    }
 
-        for x in 0..inputs[0].num_blocks() {
-            for y in 0..inputs[0].num_inner_cols() {
-                let s_input = cs.complex_selector();
+    /// The `(block, inner column)` positions of `input` no lookup of `nl` is configured at yet.
+    fn unconfigured_positions(&self, input: &VarTensor, nl: &LookupOp) -> Vec<(usize, usize)> {
+        (0..input.num_blocks())
+            .flat_map(|x| (0..input.num_inner_cols()).map(move |y| (x, y)))
+            .filter(|(x, y)| {
+                !self
+                    .static_lookups
+                    .selectors
+                    .contains_key(&(nl.clone(), *x, *y))
+            })
+            .collect()
+    }
 
-                cs.lookup_any("lookup", |cs| {
-                    let s_inputq = cs.query_selector(s_input);
-                    let mut expression = vec![];
-                    let s_referenceq = cs.query_selector(s_reference);
-                    let mut input_queries = vec![one.clone()];
+    /// Same as [BaseConfig::configure_lookup] but unselected rows of the table column holding
+    /// `default_fill = (x, y)` look up that row rather than the column's first element. `x` must be
+    /// within `lookup_range` and `y` must be the op's output at `x`. Positions already configured for
+    /// `nl`, e.g. by [BaseConfig::configure_lookup_at_positions], keep their existing default.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_lookup_with_default_fill(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        lookup_range: Range,
+        logrows: usize,
+        nl: &LookupOp,
+        default_fill: Option<(i64, i64)>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        // generated code configures the same op over and over, only the positions not yet configured
+        // (e.g. by a partial configure_lookup_at_positions) are added; a table only configured for a
+        // linear lookup still needs its single column lookups
+        let positions = self.unconfigured_positions(input, nl);
+        if positions.is_empty() {
+            return Ok(());
+        }
+        self.configure_lookup_inner(
+            cs,
+            input,
+            output,
+            index,
+            lookup_range,
+            logrows,
+            nl,
+            default_fill,
+            None,
+            positions,
+        )
+    }
 
-                    for input in inputs {
-                        input_queries.push(match input {
-                            VarTensor::Advice { inner: advices, .. } => {
-                                cs.query_advice(advices[x][y], Rotation(0))
-                            }
-                            _ => unreachable!(),
-                        });
-                    }
+    /// Same as [BaseConfig::configure_lookup] but only creates the lookup at the given
+    /// `(block, inner column)` positions of `input` rather than at all of them, for ops whose inputs
+    /// are laid out in a few scattered blocks of a larger [VarTensor]. Positions already configured
+    /// for `nl` are skipped, so the lookup can be extended to more positions later on.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_lookup_at_positions(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        lookup_range: Range,
+        logrows: usize,
+        nl: &LookupOp,
+        positions: Vec<(usize, usize)>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        for (x, y) in positions.iter() {
+            for var in [input, output, index] {
+                if *x >= var.num_blocks() || *y >= var.num_inner_cols() {
+                    return Err(format!(
+                        "lookup position {:?} is outside of a {} block, {} column lookup variable",
+                        (x, y),
+                        var.num_blocks(),
+                        var.num_inner_cols()
+                    )
+                    .into());
+                }
+            }
+        }
+        let positions = positions
+            .into_iter()
+            .filter(|(x, y)| {
+                !self
+                    .static_lookups
+                    .selectors
+                    .contains_key(&(nl.clone(), *x, *y))
+            })
+            .collect::<Vec<_>>();
+        if positions.is_empty() {
+            return Ok(());
+        }
 
-                    let mut ref_queries = vec![one.clone()];
-                    for reference in references {
-                        ref_queries.push(match reference {
+        self.configure_lookup_inner(
+            cs,
+            input,
+            output,
+            index,
+            lookup_range,
+            logrows,
+            nl,
+            None,
+            None,
+            positions,
+        )
+    }
+
+    /// Same as [BaseConfig::configure_lookup] but the table of `nl` has one column per sub-range of
+    /// `sub_ranges`, see [Table::configure_nonuniform], so that a sparse domain such as `[-4, 4]` and
+    /// `[10, 50]` doesn't take the columns of the range enclosing it. Inputs outside of all sub-ranges
+    /// can't be looked up. The table doesn't share its input columns with other tables.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_lookup_nonuniform(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        sub_ranges: &[Range],
+        logrows: usize,
+        nl: &LookupOp,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        let lookup_range = match self.static_lookups.tables.get(nl) {
+            Some(table) if table.sub_ranges.as_deref() == Some(sub_ranges) => table.range,
+            Some(table) => {
+                return Err(format!(
+                    "{} was already configured over the range {:?}",
+                    Op::<F>::as_string(nl),
+                    table
+                        .sub_ranges
+                        .clone()
+                        .unwrap_or_else(|| vec![table.range])
+                )
+                .into())
+            }
+            None => {
+                nl.validate()?;
+                self.check_initialized("configure_lookup_nonuniform")?;
+                self.check_not_laid_out("configure_lookup_nonuniform")?;
+                let table = Table::<F>::configure_nonuniform(cs, sub_ranges, logrows, nl)?;
+                let range = table.range;
+                self.static_lookups.tables.insert(nl.clone(), table);
+                range
+            }
+        };
+        // the lookups are created against the table configured above
+        self.configure_lookup(cs, input, output, index, lookup_range, logrows, nl)
+    }
+
+    /// Configures the table of `nl`, holding `precomputed` outputs if given, and creates its lookups
+    /// at `positions`.
+    #[allow(clippy::too_many_arguments)]
+    fn configure_lookup_inner(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        lookup_range: Range,
+        logrows: usize,
+        nl: &LookupOp,
+        default_fill: Option<(i64, i64)>,
+        precomputed: Option<PrecomputedOutputs<F>>,
+        positions: Vec<(usize, usize)>,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        nl.validate()?;
+        self.check_initialized("configure_lookup")?;
+        self.check_not_laid_out("configure_lookup")?;
+
+        if lookup_range.1 < lookup_range.0 {
+            return Err(Box::new(TableError::RangeInvalid(lookup_range)));
+        }
+        if !index.is_advice() {
+            return Err("wrong input type for lookup index".into());
+        }
+        if !input.is_advice() {
+            return Err("wrong input type for lookup input".into());
+        }
+        if !output.is_advice() {
+            return Err("wrong input type for lookup output".into());
+        }
+        // the input of the first lookup is kept for all of them, laying out another would be ignored
+        if !matches!(self.static_lookups.input, VarTensor::Empty)
+            && &self.static_lookups.input != input
+        {
+            return Err(Box::new(CircuitError::InconsistentLookupInput));
+        }
+        // likewise for the index, each table builds its synthetic selector from it for its own degree
+        if !matches!(self.static_lookups.index, VarTensor::Empty)
+            && &self.static_lookups.index != index
+        {
+            return Err(Box::new(CircuitError::InconsistentLookupIndex));
+        }
+
+        // an op whose outputs are an existing table's scaled by a power of two is looked up against that table,
+        // with the output rescaled inside the lookup expression
+        let scaled = if self.share_scaled_tables && !self.static_lookups.tables.contains_key(nl) {
+            self.find_power_of_two_table(nl, lookup_range)
+        } else {
+            None
+        };
+        let output_multiplier = scaled.as_ref().map(|(_, k)| {
+            let factor = F::from(1u64 << k.unsigned_abs());
+            if *k > 0 {
+                factor.invert().unwrap()
+            } else {
+                factor
+            }
+        });
+
+        // tables over the same range share their input columns, so we see if there's another table who's input we can reuse
+        let mut nested_range = None;
+        let mut table = if let Some(table) = self.static_lookups.tables.get(nl) {
+            // already configured by a linear lookup
+            if table.range != lookup_range {
+                return Err(format!(
+                    "{} was already configured over the range {:?}",
+                    Op::<F>::as_string(nl),
+                    table.range
+                )
+                .into());
+            }
+            table.clone()
+        } else if let Some((base, _)) = &scaled {
+            debug!(
+                "reusing table for {} to look up {}",
+                Op::<F>::as_string(base),
+                Op::<F>::as_string(nl)
+            );
+            self.static_lookups.tables[base].clone()
+        } else if let Some(table) = self
+            .static_lookups
+            .tables
+            .values()
+            .find(|t| t.range == lookup_range && t.sub_ranges.is_none())
+        {
+            Table::<F>::configure_with_pad(
+                cs,
+                lookup_range,
+                logrows,
+                nl,
+                Some(table.table_inputs.clone()),
+                self.blinding_rows_pad.tables,
+            )
+        } else if let Some(table) = self
+            .static_lookups
+            .tables
+            .values()
+            .filter(|t| {
+                t.sub_ranges.is_none() && t.range.0 <= lookup_range.0 && lookup_range.1 <= t.range.1
+            })
+            .min_by_key(|t| t.range.1 - t.range.0)
+        {
+            // the narrowest table whose range covers ours lends us its input columns, our table is then
+            // evaluated over its wider range and our inputs are range checked against our own range
+            debug!(
+                "laying out {} over the range {:?} of {}",
+                Op::<F>::as_string(nl),
+                table.range,
+                Op::<F>::as_string(&table.nonlinearity)
+            );
+            nested_range = Some(lookup_range);
+            Table::<F>::configure_with_pad(
+                cs,
+                table.range,
+                logrows,
+                nl,
+                Some(table.table_inputs.clone()),
+                self.blinding_rows_pad.tables,
+            )
+        } else {
+            Table::<F>::configure_with_pad(
+                cs,
+                lookup_range,
+                logrows,
+                nl,
+                None,
+                self.blinding_rows_pad.tables,
+            )
+        };
+        // the table must hold its outputs before the lookups below read its first rows
+        if precomputed.is_some() {
+            table.precomputed = precomputed;
+        }
+
+        // the default fill has to be a row of the table, so it only replaces the default of the column holding it
+        let default_fill = match default_fill {
+            Some((x, y)) => {
+                let (x, y) = (x as i128, y as i128);
+                if x < lookup_range.0 || x > lookup_range.1 {
+                    return Err(format!(
+                        "default fill {} is outside the lookup range {:?}",
+                        x, lookup_range
+                    )
+                    .into());
+                }
+                let x = i128_to_felt::<F>(x);
+                let expected = table.eval(&Tensor::from([x].into_iter()))?[0];
+                // a scaled op shares another op's table, its outputs are the table's undone by the
+                // multiplier applied to the output column
+                let expected_output = match output_multiplier {
+                    Some(m) => expected * m.invert().unwrap(),
+                    None => expected,
+                };
+                if i128_to_felt::<F>(y) != expected_output {
+                    return Err(format!(
+                        "default fill output {} does not match the output {} of {}",
+                        y,
+                        felt_to_i128(expected_output),
+                        Op::<F>::as_string(nl)
+                    )
+                    .into());
+                }
+                let col_idx = felt_to_i128(table.get_col_index(x)?) as usize;
+                Some((col_idx, x, expected))
+            }
+            None => None,
+        };
+
+        if table.selector_constructor.degree > 1
+            && (index.num_blocks() < input.num_blocks()
+                || index.num_inner_cols() < input.num_inner_cols())
+        {
+            return Err(format!(
+                "shared lookup index cannot address a degree {} table over the lookup input",
+                table.selector_constructor.degree
+            )
+            .into());
+        }
+
+        if let Some(scaled) = scaled {
+            self.static_lookups.scaled.insert(nl.clone(), scaled);
+        } else {
+            self.static_lookups.tables.insert(nl.clone(), table.clone());
+        }
+        if let Some(range) = nested_range {
+            // the shared table holds the wider range, the op's inputs are held to their own range by a
+            // range check laid out alongside the lookup, see layouts::nonlinearity
+            let (rc_input, rc_index) = match (&self.range_checks.input, &self.range_checks.index) {
+                (VarTensor::Empty, _) | (_, VarTensor::Empty) => (input.clone(), index.clone()),
+                (rc_input, rc_index) => (rc_input.clone(), rc_index.clone()),
+            };
+            self.configure_range_check(cs, &rc_input, &rc_index, range, logrows)?;
+            self.static_lookups.nested.insert(nl.clone(), range);
+        }
+
+        for (x, y) in positions {
+            let len = table.selector_constructor.degree;
+
+            let multi_col_selector = cs.complex_selector();
+
+            for ((col_idx, input_col), output_col) in table
+                .table_inputs
+                .iter()
+                .enumerate()
+                .zip(table.table_outputs.iter())
+            {
+                cs.lookup("", |cs| {
+                    let mut res = vec![];
+                    let sel = cs.query_selector(multi_col_selector);
+
+                    let synthetic_sel = match len {
+                        1 => Expression::Constant(F::from(1)),
+                        _ => match &index {
                             VarTensor::Advice { inner: advices, .. } => {
-                                cs.query_advice(advices[0][0], Rotation(0))
+                                cs.query_advice(advices[x][y], Rotation(0))
                             }
                             _ => unreachable!(),
-                        });
-                    }
+                        },
+                    };
 
-                    let lhs = input_queries.into_iter().map(|c| c * s_inputq.clone());
-                    let rhs = ref_queries.into_iter().map(|c| c * s_referenceq.clone());
-                    expression.extend(lhs.zip(rhs));
+                    let input_query = match &input {
+                        VarTensor::Advice { inner: advices, .. } => {
+                            cs.query_advice(advices[x][y], Rotation(0))
+                        }
+                        _ => unreachable!(),
+                    };
 
-                    expression
+                    let output_query = match &output {
+                        VarTensor::Advice { inner: advices, .. } => {
+                            cs.query_advice(advices[x][y], Rotation(0))
+                        }
+                        _ => unreachable!(),
+                    };
+                    let output_query = match output_multiplier {
+                        Some(m) => output_query * Expression::Constant(m),
+                        None => output_query,
+                    };
+
+                    // we index from 1 to avoid the zero element creating soundness issues
+                    // this is 0 if the index is the same as the column index (starting from 1)
+
+                    let col_expr = sel.clone()
+                        * table
+                            .selector_constructor
+                            .get_expr_at_idx(col_idx, synthetic_sel);
+
+                    let multiplier = table.selector_constructor.get_selector_val_at_idx(col_idx);
+
+                    let not_expr = Expression::Constant(multiplier) - col_expr.clone();
+
+                    let (default_x, default_y) = match default_fill {
+                        Some((fill_col, x, y)) if fill_col == col_idx => (x, y),
+                        _ => table.get_first_element(col_idx),
+                    };
+
+                    log::trace!("---------------- col {:?} ------------------", col_idx,);
+                    log::trace!("expr: {:?}", col_expr,);
+                    log::trace!("multiplier: {:?}", multiplier);
+                    log::trace!("not_expr: {:?}", not_expr);
+                    log::trace!("default x: {:?}", default_x);
+                    log::trace!("default y: {:?}", default_y);
+
+                    res.extend([
+                        (
+                            col_expr.clone() * input_query.clone()
+                                + not_expr.clone() * Expression::Constant(default_x),
+                            *input_col,
+                        ),
+                        (
+                            col_expr.clone() * output_query.clone()
+                                + not_expr.clone() * Expression::Constant(default_y),
+                            *output_col,
+                        ),
+                    ]);
+
+                    res
                 });
-                self.shuffles
-                    .input_selectors
-                    .entry((x, y))
-                    .or_insert(s_input);
             }
+            insert_selector(
+                &mut self.static_lookups.selectors,
+                (nl.clone(), x, y),
+                multi_col_selector,
+            )?;
         }
-        self.shuffles.reference_selectors.push(s_reference);
-
         // if we haven't previously initialized the input/output, do so now
-        if self.shuffles.references.is_empty() {
-            debug!("assigning shuffles reference");
-            self.shuffles.references = references.to_vec();
+        if let VarTensor::Empty = self.static_lookups.input {
+            debug!("assigning lookup input");
+            self.static_lookups.input = input.clone();
         }
-        if self.shuffles.inputs.is_empty() {
-            debug!("assigning shuffles input");
-            self.shuffles.inputs = inputs.to_vec();
+        if let VarTensor::Empty = self.static_lookups.output {
+            debug!("assigning lookup output");
+            self.static_lookups.output = output.clone();
+        }
+        if let VarTensor::Empty = self.static_lookups.index {
+            debug!("assigning lookup index");
+            self.static_lookups.index = index.clone();
         }
-
         Ok(())
     }
 
-    /// Configures and creates lookup selectors
+    /// Configures a lookup of `nl` whose input is the weighted sum of `input_cols`, e.g. `[(a, 2), (b, 1)]`
+    /// looks up `2a + b`. The sum is built directly in the lookup expression so it never needs a column
+    /// of its own. Laid out with [crate::circuit::ops::hybrid::HybridOp::LinearLookup] and the same
+    /// weights, which checks that the sum falls within `lookup_range`.
     #[allow(clippy::too_many_arguments)]
-    pub fn configure_range_check(
+    pub fn configure_lookup_linear(
         &mut self,
         cs: &mut ConstraintSystem<F>,
-        input: &VarTensor,
+        input_cols: &[(VarTensor, i64)],
+        output: &VarTensor,
         index: &VarTensor,
-        range: Range,
+        nl: &LookupOp,
+        lookup_range: Range,
         logrows: usize,
     ) -> Result<(), Box<dyn Error>>
     where
         F: Field,
     {
-        if !input.is_advice() {
-            return Err("wrong input type for lookup input".into());
+        if let Some(configured) = self.static_lookups.linear.get(nl) {
+            if configured != input_cols {
+                return Err(format!(
+                    "{} was already configured as a linear lookup over different inputs",
+                    Op::<F>::as_string(nl)
+                )
+                .into());
+            }
+            return Ok(());
         }
 
-        // we borrow mutably twice so we need to do this dance
+        nl.validate()?;
+        self.check_initialized("configure_lookup_linear")?;
+        self.check_not_laid_out("configure_lookup_linear")?;
 
-        let range_check = if let std::collections::btree_map::Entry::Vacant(e) =
-            self.range_checks.ranges.entry(range)
+        if input_cols.is_empty() {
+            return Err("linear lookup needs at least one input column".into());
+        }
+        if !index.is_advice() {
+            return Err("wrong input type for lookup index".into());
+        }
+        if !output.is_advice() {
+            return Err("wrong input type for lookup output".into());
+        }
+        if !matches!(self.static_lookups.index, VarTensor::Empty)
+            && &self.static_lookups.index != index
         {
-            // as all tables have the same input we see if there's another table who's input we can reuse
-            let range_check = RangeCheck::<F>::configure(cs, range, logrows);
-            e.insert(range_check.clone());
-            range_check
-        } else {
-            return Ok(());
+            return Err(Box::new(CircuitError::InconsistentLookupIndex));
+        }
+        for (input, weight) in input_cols {
+            if !input.is_advice() {
+                return Err("wrong input type for lookup input".into());
+            }
+            if *weight == 0 {
+                return Err("linear lookup weights must be non-zero".into());
+            }
+            if input.num_blocks() != output.num_blocks()
+                || input.num_inner_cols() != output.num_inner_cols()
+            {
+                return Err(
+                    "linear lookup inputs must have the same geometry as the output".into(),
+                );
+            }
+        }
+        if self.static_lookups.scaled.contains_key(nl) {
+            return Err(format!(
+                "{} is looked up against a rescaled table, which linear lookups do not support",
+                Op::<F>::as_string(nl)
+            )
+            .into());
+        }
+
+        let table = match self.static_lookups.tables.get(nl) {
+            Some(table) if table.range == lookup_range => table.clone(),
+            Some(table) => {
+                return Err(format!(
+                    "{} was already configured over the range {:?}",
+                    Op::<F>::as_string(nl),
+                    table.range
+                )
+                .into())
+            }
+            None => {
+                let shared_inputs = self
+                    .static_lookups
+                    .tables
+                    .values()
+                    .find(|t| t.range == lookup_range && t.sub_ranges.is_none())
+                    .map(|t| t.table_inputs.clone());
+                let table = Table::<F>::configure_with_pad(
+                    cs,
+                    lookup_range,
+                    logrows,
+                    nl,
+                    shared_inputs,
+                    self.blinding_rows_pad.tables,
+                );
+                self.static_lookups.tables.insert(nl.clone(), table.clone());
+                table
+            }
         };
 
-        for x in 0..input.num_blocks() {
-            for y in 0..input.num_inner_cols() {
-                let len = range_check.selector_constructor.degree;
+        if table.selector_constructor.degree > 1
+            && (index.num_blocks() < output.num_blocks()
+                || index.num_inner_cols() < output.num_inner_cols())
+        {
+            return Err(format!(
+                "shared lookup index cannot address a degree {} table over the lookup input",
+                table.selector_constructor.degree
+            )
+            .into());
+        }
+
+        let weights = input_cols
+            .iter()
+            .map(|(_, w)| i128_to_felt::<F>(*w as i128))
+            .collect::<Vec<_>>();
+
+        for x in 0..output.num_blocks() {
+            for y in 0..output.num_inner_cols() {
+                let len = table.selector_constructor.degree;
+
                 let multi_col_selector = cs.complex_selector();
 
-                for (col_idx, input_col) in range_check.inputs.iter().enumerate() {
+                for ((col_idx, input_col), output_col) in table
+                    .table_inputs
+                    .iter()
+                    .enumerate()
+                    .zip(table.table_outputs.iter())
+                {
                     cs.lookup("", |cs| {
-                        let mut res = vec![];
                         let sel = cs.query_selector(multi_col_selector);
 
                         let synthetic_sel = match len {
                             1 => Expression::Constant(F::from(1)),
-                            _ => match index {
+                            _ => match &index {
                                 VarTensor::Advice { inner: advices, .. } => {
                                     cs.query_advice(advices[x][y], Rotation(0))
                                 }
@@ -859,53 +2167,938 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
                             },
                         };
 
-                        let input_query = match &input {
+                        // the weighted sum of the inputs stands in for the single input column
+                        let input_query = input_cols
+                            .iter()
+                            .zip(weights.iter())
+                            .map(|((input, _), weight)| match input {
+                                VarTensor::Advice { inner: advices, .. } => {
+                                    cs.query_advice(advices[x][y], Rotation(0))
+                                        * Expression::Constant(*weight)
+                                }
+                                _ => unreachable!(),
+                            })
+                            .reduce(|acc, e| acc + e)
+                            .unwrap();
+
+                        let output_query = match &output {
                             VarTensor::Advice { inner: advices, .. } => {
                                 cs.query_advice(advices[x][y], Rotation(0))
                             }
                             _ => unreachable!(),
                         };
 
-                        let default_x = range_check.get_first_element(col_idx);
-
                         let col_expr = sel.clone()
-                            * range_check
+                            * table
                                 .selector_constructor
                                 .get_expr_at_idx(col_idx, synthetic_sel);
 
-                        let multiplier = range_check
-                            .selector_constructor
-                            .get_selector_val_at_idx(col_idx);
+                        let multiplier =
+                            table.selector_constructor.get_selector_val_at_idx(col_idx);
 
                         let not_expr = Expression::Constant(multiplier) - col_expr.clone();
 
-                        res.extend([(
-                            col_expr.clone() * input_query.clone()
-                                + not_expr.clone() * Expression::Constant(default_x),
-                            *input_col,
-                        )]);
-
-                        log::trace!("---------------- col {:?} ------------------", col_idx,);
-                        log::trace!("expr: {:?}", col_expr,);
-                        log::trace!("multiplier: {:?}", multiplier);
-                        log::trace!("not_expr: {:?}", not_expr);
-                        log::trace!("default x: {:?}", default_x);
+                        let (default_x, default_y) = table.get_first_element(col_idx);
 
-                        res
+                        vec![
+                            (
+                                col_expr.clone() * input_query
+                                    + not_expr.clone() * Expression::Constant(default_x),
+                                *input_col,
+                            ),
+                            (
+                                col_expr * output_query
+                                    + not_expr * Expression::Constant(default_y),
+                                *output_col,
+                            ),
+                        ]
                     });
                 }
-                self.range_checks
-                    .selectors
-                    .insert((range, x, y), multi_col_selector);
+                insert_selector(
+                    &mut self.static_lookups.linear_selectors,
+                    (nl.clone(), x, y),
+                    multi_col_selector,
+                )?;
             }
         }
-        // if we haven't previously initialized the input/output, do so now
-        if let VarTensor::Empty = self.range_checks.input {
-            debug!("assigning range check input");
-            self.range_checks.input = input.clone();
-        }
+        self.static_lookups
+            .linear
+            .insert(nl.clone(), input_cols.to_vec());
 
-        if let VarTensor::Empty = self.range_checks.index {
+        if let VarTensor::Empty = self.static_lookups.output {
+            debug!("assigning lookup output");
+            self.static_lookups.output = output.clone();
+        }
+        if let VarTensor::Empty = self.static_lookups.index {
+            debug!("assigning lookup index");
+            self.static_lookups.index = index.clone();
+        }
+        Ok(())
+    }
+
+    /// Finds an existing table over `range` whose outputs match those of `nl` up to a power of two.
+    /// Returns the op owning the table and the shift `k` such that `nl(x) = base(x) * 2^k`.
+    fn find_power_of_two_table(&self, nl: &LookupOp, range: Range) -> Option<(LookupOp, i32)> {
+        const MAX_SHIFT: u32 = 32;
+
+        let candidates = self
+            .static_lookups
+            .tables
+            .iter()
+            .filter(|(_, t)| t.range == range && t.sub_ranges.is_none())
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return None;
+        }
+
+        let inputs = Tensor::from(range.0..=range.1).map(|x| i128_to_felt::<F>(x));
+        let evals = |op: &LookupOp| -> Option<Vec<i128>> {
+            let res = Op::<F>::f(op, &[inputs.clone()]).ok()?;
+            Some(res.output.iter().map(|v| felt_to_i128(*v)).collect())
+        };
+        let target = evals(nl)?;
+
+        // `larger` must be `smaller` shifted left by some k in 1..=MAX_SHIFT
+        let shift_between = |smaller: &[i128], larger: &[i128]| -> Option<u32> {
+            let i = smaller.iter().position(|v| *v != 0)?;
+            let ratio = larger[i] / smaller[i];
+            if ratio <= 1 || !(ratio as u128).is_power_of_two() || larger[i] % smaller[i] != 0 {
+                return None;
+            }
+            let k = ratio.trailing_zeros();
+            let matches = k <= MAX_SHIFT
+                && smaller
+                    .iter()
+                    .zip(larger)
+                    .all(|(s, l)| s.checked_mul(ratio) == Some(*l));
+            matches.then_some(k)
+        };
+
+        for (op, _) in candidates {
+            let base = match evals(op) {
+                Some(base) => base,
+                None => continue,
+            };
+            if let Some(k) = shift_between(&base, &target) {
+                return Some((op.clone(), k as i32));
+            }
+            if let Some(k) = shift_between(&target, &base) {
+                return Some((op.clone(), -(k as i32)));
+            }
+        }
+        None
+    }
+
+    /// Configures and creates the selectors of a dynamic lookup of the rows of `lookups` into the
+    /// rows of `tables`, which may be any number of columns wide but must be as wide as one another.
+    /// The last column holds the index of each dynamic lookup, see `layouts::dynamic_lookup`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_dynamic_lookup(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        lookups: &[VarTensor],
+        tables: &[VarTensor],
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        self.check_initialized("configure_dynamic_lookup")?;
+        self.check_not_laid_out("configure_dynamic_lookup")?;
+
+        if lookups.is_empty() || lookups.len() != tables.len() {
+            return Err(format!(
+                "dynamic lookup of {} columns into {} table columns",
+                lookups.len(),
+                tables.len()
+            )
+            .into());
+        }
+        if lookups.len() > self.max_dynamic_lookup_arity {
+            return Err(CircuitError::DynamicLookupArityTooLarge {
+                arity: lookups.len(),
+                max: self.max_dynamic_lookup_arity,
+            }
+            .into());
+        }
+
+        for l in lookups.iter() {
+            if !l.is_advice() {
+                return Err("wrong input type for dynamic lookup".into());
+            }
+        }
+
+        for t in tables.iter() {
+            if !t.is_advice() || t.num_blocks() > 1 || t.num_inner_cols() > 1 {
+                return Err("wrong table type for dynamic lookup".into());
+            }
+        }
+
+        let one = Expression::Constant(F::ONE);
+
+        let s_ltable = cs.complex_selector();
+
+        for x in 0..lookups[0].num_blocks() {
+            for y in 0..lookups[0].num_inner_cols() {
+                let s_lookup = cs.complex_selector();
+
+                cs.lookup_any("lookup", |cs| {
+                    let s_lookupq = cs.query_selector(s_lookup);
+                    let mut expression = vec![];
+                    let s_ltableq = cs.query_selector(s_ltable);
+                    let mut lookup_queries = vec![one.clone()];
+
+                    for lookup in lookups {
+                        lookup_queries.push(match lookup {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[x][y], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        });
+                    }
+
+                    let mut table_queries = vec![one.clone()];
+                    for table in tables {
+                        table_queries.push(match table {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[0][0], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        });
+                    }
+
+                    let lhs = lookup_queries.into_iter().map(|c| c * s_lookupq.clone());
+                    let rhs = table_queries.into_iter().map(|c| c * s_ltableq.clone());
+                    expression.extend(lhs.zip(rhs));
+
+                    expression
+                });
+                insert_selector(&mut self.dynamic_lookups.lookup_selectors, (x, y), s_lookup)?;
+            }
+        }
+        self.dynamic_lookups.table_selectors.push(s_ltable);
+
+        // if we haven't previously initialized the input/output, do so now
+        if self.dynamic_lookups.tables.is_empty() {
+            debug!("assigning dynamic lookup table");
+            self.dynamic_lookups.tables = tables.to_vec();
+        }
+        if self.dynamic_lookups.inputs.is_empty() {
+            debug!("assigning dynamic lookup input");
+            self.dynamic_lookups.inputs = lookups.to_vec();
+        }
+
+        Ok(())
+    }
+
+    /// Configures a range check `0 <= input < bound` where `bound` is itself witnessed in `bound_input`.
+    /// The difference `bound - input - 1` is assigned to `diff` and constrained by a gate, and both it
+    /// and `input` are then range checked against a static range covering every admissible bound, see
+    /// [crate::circuit::ops::layouts::dynamic_range_check]. `diff` must not be a column the custom gates
+    /// constrain, as their gates would then apply to the difference too.
+    pub fn configure_dynamic_range_check(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        bound_input: &VarTensor,
+        diff: &VarTensor,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        self.check_initialized("configure_dynamic_range_check")?;
+        self.check_not_laid_out("configure_dynamic_range_check")?;
+
+        let vars = [input.clone(), bound_input.clone(), diff.clone()];
+
+        for v in vars.iter() {
+            if !v.is_advice() {
+                return Err("wrong input type for dynamic range check".into());
+            }
+            if v.num_blocks() != input.num_blocks() || v.num_inner_cols() != input.num_inner_cols()
+            {
+                return Err("dynamic range check inputs must share the same geometry".into());
+            }
+        }
+
+        for x in 0..input.num_blocks() {
+            for y in 0..input.num_inner_cols() {
+                let selector = cs.selector();
+                cs.create_gate("dynamic range check", |cs| {
+                    let selector = cs.query_selector(selector);
+                    let [input, bound, diff] = [0, 1, 2].map(|i| {
+                        vars[i]
+                            .query_rng(cs, x, y, 0, 1)
+                            .expect("dynamic range check: query failed")[0]
+                            .clone()
+                    });
+                    let one = Expression::Constant(F::ONE);
+
+                    Constraints::with_selector(selector, vec![bound - input - one - diff])
+                });
+                insert_selector(&mut self.dynamic_range_checks.selectors, (x, y), selector)?;
+            }
+        }
+
+        if self.dynamic_range_checks.inputs.is_empty() {
+            debug!("assigning dynamic range check inputs");
+            self.dynamic_range_checks.inputs = vars.to_vec();
+        }
+
+        Ok(())
+    }
+
+    /// Configures a lookup of `f(a, b)` for `a` in `ranges[0]` and `b` in `ranges[1]`, against a
+    /// [Table2] holding their cartesian product, under `name`. Laid out with
+    /// [crate::circuit::ops::layouts::binary_lookup] and the same name. All binary lookups share the
+    /// `inputs`, `output` and `index` of the first one configured; configuring `name` again over the
+    /// same ranges is a no-op.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_binary_lookup(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor; 2],
+        output: &VarTensor,
+        index: &VarTensor,
+        name: &str,
+        ranges: [Range; 2],
+        logrows: usize,
+        f: BinaryFn,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        if let Some(table) = self.binary_lookups.tables.get(name) {
+            if table.ranges != ranges {
+                return Err(format!(
+                    "binary lookup {} was already configured over the ranges {:?}",
+                    name, table.ranges
+                )
+                .into());
+            }
+            return Ok(());
+        }
+
+        self.check_initialized("configure_binary_lookup")?;
+        self.check_not_laid_out("configure_binary_lookup")?;
+
+        for var in inputs.iter().chain([output, index]) {
+            if !var.is_advice() {
+                return Err("wrong input type for binary lookup".into());
+            }
+            if var.num_blocks() != output.num_blocks()
+                || var.num_inner_cols() != output.num_inner_cols()
+            {
+                return Err("binary lookup inputs must share the same geometry".into());
+            }
+        }
+        if !self.binary_lookups.inputs.is_empty() && self.binary_lookups.inputs != inputs.to_vec() {
+            return Err(Box::new(CircuitError::InconsistentLookupInput));
+        }
+        if !matches!(self.binary_lookups.index, VarTensor::Empty)
+            && &self.binary_lookups.index != index
+        {
+            return Err(Box::new(CircuitError::InconsistentLookupIndex));
+        }
+
+        let table = Table2::<F>::configure(cs, ranges, logrows, f)?;
+
+        for x in 0..output.num_blocks() {
+            for y in 0..output.num_inner_cols() {
+                let len = table.selector_constructor.degree;
+
+                let multi_col_selector = cs.complex_selector();
+
+                for col_idx in 0..table.table_outputs.len() {
+                    cs.lookup("", |cs| {
+                        let sel = cs.query_selector(multi_col_selector);
+                        let [a, b, output, index] =
+                            [&inputs[0], &inputs[1], output, index].map(|var| match var {
+                                VarTensor::Advice { inner: advices, .. } => {
+                                    cs.query_advice(advices[x][y], Rotation(0))
+                                }
+                                _ => unreachable!(),
+                            });
+
+                        let synthetic_sel = match len {
+                            1 => Expression::Constant(F::from(1)),
+                            _ => index,
+                        };
+
+                        let col_expr = sel
+                            * table
+                                .selector_constructor
+                                .get_expr_at_idx(col_idx, synthetic_sel);
+
+                        let multiplier =
+                            table.selector_constructor.get_selector_val_at_idx(col_idx);
+
+                        let not_expr = Expression::Constant(multiplier) - col_expr.clone();
+
+                        let (default_a, default_b, default_output) =
+                            table.get_first_element(col_idx);
+
+                        vec![
+                            (
+                                col_expr.clone() * a
+                                    + not_expr.clone() * Expression::Constant(default_a),
+                                table.table_inputs_a[col_idx],
+                            ),
+                            (
+                                col_expr.clone() * b
+                                    + not_expr.clone() * Expression::Constant(default_b),
+                                table.table_inputs_b[col_idx],
+                            ),
+                            (
+                                col_expr * output + not_expr * Expression::Constant(default_output),
+                                table.table_outputs[col_idx],
+                            ),
+                        ]
+                    });
+                }
+                insert_selector(
+                    &mut self.binary_lookups.selectors,
+                    (name.to_string(), x, y),
+                    multi_col_selector,
+                )?;
+            }
+        }
+        self.binary_lookups.tables.insert(name.to_string(), table);
+
+        // if we haven't previously initialized the input/output, do so now
+        if self.binary_lookups.inputs.is_empty() {
+            debug!("assigning binary lookup inputs");
+            self.binary_lookups.inputs = inputs.to_vec();
+        }
+        if let VarTensor::Empty = self.binary_lookups.output {
+            debug!("assigning binary lookup output");
+            self.binary_lookups.output = output.clone();
+        }
+        if let VarTensor::Empty = self.binary_lookups.index {
+            debug!("assigning binary lookup index");
+            self.binary_lookups.index = index.clone();
+        }
+        Ok(())
+    }
+
+    /// Configures and creates the selectors of a shuffle of the rows of `inputs` into the rows of
+    /// `references`, which may be any number of columns wide but must be as wide as one another.
+    /// The last column holds the index of each shuffle, see `layouts::shuffles`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_shuffles(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
+        references: &[VarTensor],
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        self.check_initialized("configure_shuffles")?;
+        self.check_not_laid_out("configure_shuffles")?;
+
+        if inputs.is_empty() || inputs.len() != references.len() {
+            return Err(format!(
+                "shuffle of {} columns into {} reference columns",
+                inputs.len(),
+                references.len()
+            )
+            .into());
+        }
+
+        for l in inputs.iter() {
+            if !l.is_advice() {
+                return Err("wrong input type for dynamic lookup".into());
+            }
+        }
+
+        for t in references.iter() {
+            if !t.is_advice() || t.num_blocks() > 1 || t.num_inner_cols() > 1 {
+                return Err("wrong table type for dynamic lookup".into());
+            }
+        }
+
+        let one = Expression::Constant(F::ONE);
+
+        let s_reference = cs.complex_selector();
+
+        for x in 0..inputs[0].num_blocks() {
+            for y in 0..inputs[0].num_inner_cols() {
+                let s_input = cs.complex_selector();
+
+                cs.lookup_any("lookup", |cs| {
+                    let s_inputq = cs.query_selector(s_input);
+                    let mut expression = vec![];
+                    let s_referenceq = cs.query_selector(s_reference);
+                    let mut input_queries = vec![one.clone()];
+
+                    for input in inputs {
+                        input_queries.push(match input {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[x][y], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        });
+                    }
+
+                    let mut ref_queries = vec![one.clone()];
+                    for reference in references {
+                        ref_queries.push(match reference {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[0][0], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        });
+                    }
+
+                    let lhs = input_queries.into_iter().map(|c| c * s_inputq.clone());
+                    let rhs = ref_queries.into_iter().map(|c| c * s_referenceq.clone());
+                    expression.extend(lhs.zip(rhs));
+
+                    expression
+                });
+                insert_selector(&mut self.shuffles.input_selectors, (x, y), s_input)?;
+            }
+        }
+        self.shuffles.reference_selectors.push(s_reference);
+
+        // if we haven't previously initialized the input/output, do so now
+        if self.shuffles.references.is_empty() {
+            debug!("assigning shuffles reference");
+            self.shuffles.references = references.to_vec();
+        }
+        if self.shuffles.inputs.is_empty() {
+            debug!("assigning shuffles input");
+            self.shuffles.inputs = inputs.to_vec();
+        }
+
+        Ok(())
+    }
+
+    /// Configures and creates lookup selectors. A range lying within an already configured range
+    /// whose table fits in a single column reuses that table rather than allocating its own. A
+    /// [RangeCheck] inserted into [RangeChecks::ranges] before its lookups are configured, e.g. with
+    /// defaults set by [RangeCheck::set_default], is configured as is.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_range_check(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        index: &VarTensor,
+        range: Range,
+        logrows: usize,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        self.configure_range_check_with_output(cs, input, index, None, range, logrows)
+    }
+
+    /// Configures a range check on `input` that also proves the checked values are sorted in
+    /// non-decreasing order. Each element is range checked against `range` and each adjacent
+    /// difference `input[i] - input[i-1]` against `(0, range.1 - range.0)`, see
+    /// [crate::circuit::ops::layouts::sorted_range_check], which
+    /// [crate::circuit::ops::hybrid::HybridOp::SortedRangeCheck] lays out. Uses the range check index
+    /// column if one is already configured, else the second custom gate input.
+    pub fn configure_sorted_range_check(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        range: Range,
+        logrows: usize,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        self.check_initialized("configure_sorted_range_check")?;
+        self.check_not_laid_out("configure_sorted_range_check")?;
+
+        if range.1 < range.0 {
+            return Err(format!("invalid range for sorted range check: {:?}", range).into());
+        }
+
+        let index = match &self.range_checks.index {
+            VarTensor::Empty => self.custom_gates.inputs[1].clone(),
+            index => index.clone(),
+        };
+
+        self.configure_range_check(cs, input, &index, range, logrows)?;
+        self.configure_range_check(cs, input, &index, (0, range.1 - range.0), logrows)
+    }
+
+    /// Configures the range checks [crate::circuit::ops::layouts::argmax_indicator] (laid out by
+    /// [crate::circuit::ops::hybrid::HybridOp::ArgmaxIndicator]) needs for vectors whose elements lie
+    /// in `range`: the elements themselves are checked against `range`, and each element's gap to the
+    /// selected maximum against `(0, range.1 - range.0)`, both on `input`. The one-hot indicator is
+    /// echoed into `output`, copy-constrained to equal the constrained indicator, so it lands in a
+    /// known column downstream; `output` is fixed by the first call. Uses the range check index column
+    /// if one is already configured, else the second custom gate input.
+    pub fn configure_argmax(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        range: Range,
+        logrows: usize,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        self.check_initialized("configure_argmax")?;
+        self.check_not_laid_out("configure_argmax")?;
+
+        if range.1 < range.0 {
+            return Err(format!("invalid range for argmax: {:?}", range).into());
+        }
+        if !output.is_advice() {
+            return Err("argmax output must be an advice var".into());
+        }
+        match &self.argmax_output {
+            Some(configured) if configured != output => {
+                return Err("argmax output was already configured over other columns".into())
+            }
+            Some(_) => {}
+            None => self.argmax_output = Some(output.clone()),
+        }
+
+        let index = match &self.range_checks.index {
+            VarTensor::Empty => self.custom_gates.inputs[1].clone(),
+            index => index.clone(),
+        };
+
+        self.configure_range_check(cs, input, &index, range, logrows)?;
+        self.configure_range_check(cs, input, &index, (0, range.1 - range.0), logrows)
+    }
+
+    /// Same as [BaseConfig::configure_range_check] but echoes every checked value into `output`,
+    /// copy-constrained to equal the input, so it is available in a known column downstream. The
+    /// output column is fixed by whichever call first configures `range`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_range_check_with_output(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        index: &VarTensor,
+        output: Option<&VarTensor>,
+        range: Range,
+        logrows: usize,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        // fast path for generated code configuring the same range over and over
+        if self.range_checks.ranges.contains_key(&range)
+            || self.range_checks.subsets.contains_key(&range)
+        {
+            return Ok(());
+        }
+
+        self.check_initialized("configure_range_check")?;
+        self.check_not_laid_out("configure_range_check")?;
+
+        if range.1 < range.0 {
+            return Err(Box::new(TableError::RangeInvalid(range)));
+        }
+        if !input.is_advice() {
+            return Err("wrong input type for lookup input".into());
+        }
+        if let Some(output) = output {
+            if !output.is_advice()
+                || output.num_blocks() != input.num_blocks()
+                || output.num_inner_cols() != input.num_inner_cols()
+            {
+                return Err(
+                    "range check output must be an advice var shaped like the input".into(),
+                );
+            }
+        }
+
+        // a range check inserted into `ranges` before its lookups are configured, e.g. to override its
+        // defaults with [RangeCheck::set_default], is configured as is
+        let registered = self
+            .range_checks
+            .ranges
+            .get(&range)
+            .filter(|_| {
+                !self
+                    .range_checks
+                    .selectors
+                    .keys()
+                    .any(|(r, _, _)| *r == range)
+            })
+            .cloned();
+
+        // a range within an already configured single column range is checked against that column
+        // rather than allocating its own
+        if output.is_none() && registered.is_none() {
+            if let Some(enclosing) = self.enclosing_range_check(range) {
+                return self.configure_subset_range_check(cs, input, index, range, enclosing);
+            }
+        }
+
+        // we borrow mutably twice so we need to do this dance
+
+        let range_check = if let Some(range_check) = registered {
+            range_check
+        } else if let std::collections::btree_map::Entry::Vacant(e) =
+            self.range_checks.ranges.entry(range)
+        {
+            // as all tables have the same input we see if there's another table who's input we can reuse
+            let range_check = RangeCheck::<F>::configure_with_pad(
+                cs,
+                range,
+                logrows,
+                output.cloned(),
+                self.blinding_rows_pad.range_checks,
+            );
+            e.insert(range_check.clone());
+            range_check
+        } else {
+            return Ok(());
+        };
+
+        for x in 0..input.num_blocks() {
+            for y in 0..input.num_inner_cols() {
+                let len = range_check.selector_constructor.degree;
+                let multi_col_selector = cs.complex_selector();
+
+                for (col_idx, input_col) in range_check.inputs.iter().enumerate() {
+                    cs.lookup("", |cs| {
+                        let mut res = vec![];
+                        let sel = cs.query_selector(multi_col_selector);
+
+                        let synthetic_sel = match len {
+                            1 => Expression::Constant(F::from(1)),
+                            _ => match index {
+                                VarTensor::Advice { inner: advices, .. } => {
+                                    cs.query_advice(advices[x][y], Rotation(0))
+                                }
+                                _ => unreachable!(),
+                            },
+                        };
+
+                        let input_query = match &input {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[x][y], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        };
+
+                        let default_x = range_check.get_first_element(col_idx);
+
+                        let col_expr = sel.clone()
+                            * range_check
+                                .selector_constructor
+                                .get_expr_at_idx(col_idx, synthetic_sel);
+
+                        let multiplier = range_check
+                            .selector_constructor
+                            .get_selector_val_at_idx(col_idx);
+
+                        let not_expr = Expression::Constant(multiplier) - col_expr.clone();
+
+                        res.extend([(
+                            col_expr.clone() * input_query.clone()
+                                + not_expr.clone() * Expression::Constant(default_x),
+                            *input_col,
+                        )]);
+
+                        log::trace!("---------------- col {:?} ------------------", col_idx,);
+                        log::trace!("expr: {:?}", col_expr,);
+                        log::trace!("multiplier: {:?}", multiplier);
+                        log::trace!("not_expr: {:?}", not_expr);
+                        log::trace!("default x: {:?}", default_x);
+
+                        res
+                    });
+                }
+                insert_selector(
+                    &mut self.range_checks.selectors,
+                    (range, x, y),
+                    multi_col_selector,
+                )?;
+            }
+        }
+        // if we haven't previously initialized the input/output, do so now
+        if let VarTensor::Empty = self.range_checks.input {
+            debug!("assigning range check input");
+            self.range_checks.input = input.clone();
+        }
+
+        if let VarTensor::Empty = self.range_checks.index {
+            debug!("assigning range check index");
+            self.range_checks.index = index.clone();
+        }
+
+        Ok(())
+    }
+
+    /// Configures a range check against the union of `ranges`, which may overlap, with a
+    /// [MultiRangeCheck] laying out only the integers of the union, e.g. `{-1, 0, 1}` plus a high
+    /// region without the span between them. Laid out with
+    /// [crate::circuit::ops::hybrid::HybridOp::MultiRangeCheck] over the same ranges. Shares the
+    /// input and index columns of the other range checks.
+    pub fn configure_multi_range_check(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        index: &VarTensor,
+        ranges: &[Range],
+        logrows: usize,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        if let Some(range) = ranges.iter().find(|r| r.1 < r.0) {
+            return Err(Box::new(TableError::RangeInvalid(*range)));
+        }
+        let merged = merge_ranges(ranges);
+        if self.range_checks.multi.contains_key(&merged) {
+            return Ok(());
+        }
+
+        self.check_initialized("configure_multi_range_check")?;
+        self.check_not_laid_out("configure_multi_range_check")?;
+
+        if !input.is_advice() {
+            return Err("wrong input type for lookup input".into());
+        }
+
+        let range_check = MultiRangeCheck::<F>::configure(cs, ranges, logrows)?;
+
+        for x in 0..input.num_blocks() {
+            for y in 0..input.num_inner_cols() {
+                let len = range_check.selector_constructor.degree;
+                let multi_col_selector = cs.complex_selector();
+
+                for (col_idx, input_col) in range_check.inputs.iter().enumerate() {
+                    cs.lookup("", |cs| {
+                        let sel = cs.query_selector(multi_col_selector);
+
+                        let synthetic_sel = match len {
+                            1 => Expression::Constant(F::from(1)),
+                            _ => match index {
+                                VarTensor::Advice { inner: advices, .. } => {
+                                    cs.query_advice(advices[x][y], Rotation(0))
+                                }
+                                _ => unreachable!(),
+                            },
+                        };
+
+                        let input_query = match &input {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[x][y], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        };
+
+                        let default_x = range_check.get_first_element(col_idx);
+
+                        let col_expr = sel
+                            * range_check
+                                .selector_constructor
+                                .get_expr_at_idx(col_idx, synthetic_sel);
+
+                        let multiplier = range_check
+                            .selector_constructor
+                            .get_selector_val_at_idx(col_idx);
+
+                        let not_expr = Expression::Constant(multiplier) - col_expr.clone();
+
+                        vec![(
+                            col_expr * input_query + not_expr * Expression::Constant(default_x),
+                            *input_col,
+                        )]
+                    });
+                }
+                insert_selector(
+                    &mut self.range_checks.multi_selectors,
+                    (merged.clone(), x, y),
+                    multi_col_selector,
+                )?;
+            }
+        }
+        self.range_checks.multi.insert(merged, range_check);
+
+        // if we haven't previously initialized the input/output, do so now
+        if let VarTensor::Empty = self.range_checks.input {
+            debug!("assigning range check input");
+            self.range_checks.input = input.clone();
+        }
+
+        if let VarTensor::Empty = self.range_checks.index {
+            debug!("assigning range check index");
+            self.range_checks.index = index.clone();
+        }
+
+        Ok(())
+    }
+
+    /// The narrowest configured range check covering `range` whose table fits in a single column.
+    fn enclosing_range_check(&self, range: Range) -> Option<Range> {
+        self.range_checks
+            .ranges
+            .iter()
+            .filter(|(r, rc)| {
+                r.0 <= range.0 && range.1 <= r.1 && rc.selector_constructor.degree == 1
+            })
+            .min_by_key(|(r, _)| r.1 - r.0)
+            .map(|(r, _)| *r)
+    }
+
+    /// Checks `input` is within `range` using the single table column of the `enclosing` range check.
+    /// With the enclosing range `(lo, hi)`, `x + lo - range.0` and `x + hi - range.1` are both looked
+    /// up, which holds `x` to `[range.0, range.0 + hi - lo]` and `[range.1 - (hi - lo), range.1]`
+    /// respectively, whose intersection is `range`.
+    fn configure_subset_range_check(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        index: &VarTensor,
+        range: Range,
+        enclosing: Range,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        debug!(
+            "checking range {:?} against the table of {:?}",
+            range, enclosing
+        );
+        let range_check = self.range_checks.ranges[&enclosing].clone();
+        let table_col = range_check.inputs[0];
+        let default_x = range_check.get_first_element(0);
+        let shifts = [enclosing.0 - range.0, enclosing.1 - range.1].map(i128_to_felt::<F>);
+
+        for x in 0..input.num_blocks() {
+            for y in 0..input.num_inner_cols() {
+                let selector = cs.complex_selector();
+                for shift in shifts {
+                    cs.lookup("", |cs| {
+                        let sel = cs.query_selector(selector);
+                        let input_query = match &input {
+                            VarTensor::Advice { inner: advices, .. } => {
+                                cs.query_advice(advices[x][y], Rotation(0))
+                            }
+                            _ => unreachable!(),
+                        };
+                        let not_sel = Expression::Constant(F::ONE) - sel.clone();
+                        vec![(
+                            sel * (input_query + Expression::Constant(shift))
+                                + not_sel * Expression::Constant(default_x),
+                            table_col,
+                        )]
+                    });
+                }
+                insert_selector(&mut self.range_checks.selectors, (range, x, y), selector)?;
+            }
+        }
+        self.range_checks.subsets.insert(range, enclosing);
+
+        if let VarTensor::Empty = self.range_checks.input {
+            debug!("assigning range check input");
+            self.range_checks.input = input.clone();
+        }
+        if let VarTensor::Empty = self.range_checks.index {
             debug!("assigning range check index");
             self.range_checks.index = index.clone();
         }
@@ -913,34 +3106,613 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
         Ok(())
     }
 
-    /// layout_tables must be called before layout.
-    pub fn layout_tables(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), Box<dyn Error>> {
-        for (i, table) in self.static_lookups.tables.values_mut().enumerate() {
-            if !table.is_assigned {
-                debug!(
-                    "laying out table for {}",
-                    crate::circuit::ops::Op::<F>::as_string(&table.nonlinearity)
-                );
-                if i == 0 {
-                    table.layout(layouter, false)?;
-                } else {
-                    table.layout(layouter, true)?;
-                }
+    /// The number of distinct [TableColumn]s allocated by lookup tables, including those of binary
+    /// lookups, and range checks, all of which are fixed columns. Tables sharing their input columns count them once.
+    pub fn num_table_columns(&self) -> usize {
+        let lookup_cols = self
+            .static_lookups
+            .tables
+            .values()
+            .flat_map(|t| t.table_inputs.iter().chain(t.table_outputs.iter()));
+        let range_check_cols = self
+            .range_checks
+            .ranges
+            .values()
+            .flat_map(|rc| rc.inputs.iter())
+            .chain(
+                self.range_checks
+                    .multi
+                    .values()
+                    .flat_map(|rc| rc.inputs.iter()),
+            );
+        let binary_lookup_cols = self.binary_lookups.tables.values().flat_map(|t| {
+            t.table_inputs_a
+                .iter()
+                .chain(t.table_inputs_b.iter())
+                .chain(t.table_outputs.iter())
+        });
+
+        lookup_cols
+            .chain(range_check_cols)
+            .chain(binary_lookup_cols)
+            .collect::<HashSet<&TableColumn>>()
+            .len()
+    }
+
+    /// How well lookup tables share their input columns: the number of distinct configured
+    /// [LookupOp]s, including those looked up against another op's table, over the number of
+    /// distinct table input columns. Equals the number of ops when they all share one input column
+    /// and is at most `1.0` when none share. Zero when no lookup is configured.
+    pub fn table_sharing_ratio(&self) -> f64 {
+        let lookups = &self.static_lookups;
+        let num_input_cols = lookups
+            .tables
+            .values()
+            .flat_map(|t| t.table_inputs.iter())
+            .collect::<HashSet<&TableColumn>>()
+            .len();
+        if num_input_cols == 0 {
+            return 0.0;
+        }
+        let num_ops = lookups.tables.len() + lookups.scaled.len();
+        num_ops as f64 / num_input_cols as f64
+    }
+
+    /// Checks that every lookup table and range check fits in a circuit of `2^k` rows, as they may each
+    /// have been configured with their own `logrows`. The usable rows are those left by the blinding
+    /// factors of `cs`, the constraint system the config was built in. Errors with
+    /// [CircuitError::InconsistentLogrows] on the first table or range check with more rows per column
+    /// than are usable.
+    pub fn validate_logrows_consistency(
+        &self,
+        cs: &ConstraintSystem<F>,
+        k: u32,
+    ) -> Result<(), CircuitError> {
+        let max = VarTensor::max_rows(cs, k as usize);
+        let tables = self
+            .static_lookups
+            .tables
+            .iter()
+            .map(|(nl, table)| (format!("table {}", Op::<F>::as_string(nl)), table.col_size));
+        let range_checks = self
+            .range_checks
+            .ranges
+            .values()
+            .map(|rc| (rc.name(), rc.col_size));
+        for (component, col_size) in tables.chain(range_checks) {
+            if col_size > max {
+                return Err(CircuitError::InconsistentLogrows {
+                    component,
+                    col_size,
+                    max,
+                    k,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The [LookupOp]s owning a static lookup table, sorted. Ops looked up against another op's
+    /// table, see [StaticLookups::scaled], are not included.
+    pub fn configured_lookup_ops(&self) -> Vec<LookupOp> {
+        self.static_lookups.tables.keys().cloned().collect()
+    }
+
+    /// The [Range]s of the configured range check tables, sorted. Ranges checked against an
+    /// enclosing range, see [RangeChecks::subsets], are not included.
+    pub fn configured_ranges(&self) -> Vec<Range> {
+        self.range_checks.ranges.keys().copied().collect()
+    }
+
+    /// The integer ranges covered by the static lookup tables and range checks, sorted with overlapping
+    /// and adjacent ranges merged: every input the circuit can look up or range check lies in one of
+    /// them. Tables over a non-uniform domain contribute each of their sub-ranges.
+    pub fn covered_ranges(&self) -> Vec<Range> {
+        let lookup_ranges = self
+            .static_lookups
+            .tables
+            .values()
+            .flat_map(|t| t.sub_ranges.clone().unwrap_or_else(|| vec![t.range]));
+        let range_check_ranges = self.range_checks.ranges.keys().cloned();
+
+        merge_ranges(&lookup_ranges.chain(range_check_ranges).collect::<Vec<_>>())
+    }
+
+    /// The largest degree the synthetic column selectors of the lookup tables and range checks add to
+    /// their lookup expressions, see [crate::circuit::table::SelectorConstructor::expr_degree]. Zero when
+    /// every table fits in a single column.
+    pub fn synthetic_selector_degree(&self) -> usize {
+        let tables = self
+            .static_lookups
+            .tables
+            .values()
+            .map(|t| &t.selector_constructor);
+        let range_checks = self
+            .range_checks
+            .ranges
+            .values()
+            .map(|rc| &rc.selector_constructor);
+        tables
+            .chain(range_checks)
+            .map(|s| s.expr_degree())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The degree of the quotient polynomial halo2 will use for `cs`, i.e. `cs.degree() - 1`, so that the
+    /// extended domain has size `2^k * quotient_poly_degree` (rounded up to a power of two). It only
+    /// depends on the maximum constraint degree, not on the number of constraints. `cs` must be the
+    /// system the config was configured on; selector compression at keygen can raise the degree.
+    pub fn quotient_poly_degree(cs: &ConstraintSystem<F>) -> usize {
+        #[cfg(feature = "mv-lookup")]
+        let cs = cs.clone().chunk_lookups();
+        cs.degree() - 1
+    }
+
+    /// The structural part of this config, see [VerifierConfig].
+    pub fn to_verifier_config(&self) -> VerifierConfig {
+        VerifierConfig {
+            custom_gates: self.custom_gates.clone(),
+            lookup_selectors: self.static_lookups.selectors.clone(),
+            linear_lookup_selectors: self.static_lookups.linear_selectors.clone(),
+            lookup_tables: self
+                .static_lookups
+                .tables
+                .iter()
+                .map(|(nl, t)| {
+                    let columns = TableColumns {
+                        range: t.range,
+                        inputs: t.table_inputs.clone(),
+                        outputs: t.table_outputs.clone(),
+                    };
+                    (nl.clone(), columns)
+                })
+                .collect(),
+            range_check_selectors: self.range_checks.selectors.clone(),
+            range_check_tables: self
+                .range_checks
+                .ranges
+                .iter()
+                .map(|(range, rc)| (*range, rc.inputs.clone()))
+                .collect(),
+            dynamic_lookups: self.dynamic_lookups.clone(),
+            dynamic_range_checks: self.dynamic_range_checks.clone(),
+            shuffles: self.shuffles.clone(),
+        }
+    }
+
+    /// Checks that a circuit of `2^k` rows can be proven with `scheme`: KZG needs a trusted SRS of that
+    /// size, so `k` must have a hash in [crate::srs_sha::PUBLIC_SRS_SHA256_HASHES], whereas IPA
+    /// parameters can always be generated locally.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn provable_with(scheme: crate::Commitments, k: u32) -> Result<(), CircuitError> {
+        match scheme {
+            crate::Commitments::KZG if !crate::srs_sha::contains_k(k) => {
+                Err(CircuitError::NoPublicSrs {
+                    scheme,
+                    k,
+                    max: crate::srs_sha::max_supported_k(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A digest of everything structural that shapes the verifying key: column geometry, the custom gate
+    /// set, lookup and range check arguments, dynamic lookups and shuffles. A verifier can compare it
+    /// against the digest of the circuit it expects before trusting a received verifying key.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub fn vk_structure_digest(&self) -> VkStructureDigest {
+        let geometry = |v: &VarTensor| (v.num_blocks(), v.num_inner_cols(), v.col_size());
+        let geometries = |vs: &[VarTensor]| vs.iter().map(geometry).collect::<Vec<_>>();
+
+        let custom_gates = (
+            geometries(&self.custom_gates.inputs),
+            geometry(&self.custom_gates.output),
+            self.custom_gates.selectors.keys().collect::<Vec<_>>(),
+        );
+        let static_lookups = (
+            self.static_lookups.selectors.keys().collect::<Vec<_>>(),
+            self.static_lookups
+                .tables
+                .iter()
+                .map(|(nl, t)| {
+                    (
+                        nl,
+                        t.range,
+                        &t.sub_ranges,
+                        t.table_inputs.len(),
+                        t.table_outputs.len(),
+                        t.col_size,
+                        t.selector_constructor.degree,
+                    )
+                })
+                .collect::<Vec<_>>(),
+            &self.static_lookups.scaled,
+            self.static_lookups
+                .linear_selectors
+                .keys()
+                .collect::<Vec<_>>(),
+            self.static_lookups
+                .linear
+                .iter()
+                .map(|(nl, inputs)| {
+                    let inputs = inputs
+                        .iter()
+                        .map(|(v, w)| (geometry(v), *w))
+                        .collect::<Vec<_>>();
+                    (nl, inputs)
+                })
+                .collect::<Vec<_>>(),
+            geometry(&self.static_lookups.input),
+            geometry(&self.static_lookups.output),
+            geometry(&self.static_lookups.index),
+        );
+        let range_checks = (
+            self.range_checks.selectors.keys().collect::<Vec<_>>(),
+            self.range_checks
+                .ranges
+                .iter()
+                .map(|(range, rc)| {
+                    (
+                        range,
+                        rc.inputs.len(),
+                        rc.col_size,
+                        rc.output.as_ref().map(geometry),
+                    )
+                })
+                .collect::<Vec<_>>(),
+            &self.range_checks.subsets,
+            self.range_checks.multi_selectors.keys().collect::<Vec<_>>(),
+            self.range_checks
+                .multi
+                .iter()
+                .map(|(ranges, rc)| (ranges, rc.inputs.len(), rc.col_size))
+                .collect::<Vec<_>>(),
+            geometry(&self.range_checks.input),
+            geometry(&self.range_checks.index),
+        );
+        let dynamic_lookups = (
+            self.dynamic_lookups
+                .lookup_selectors
+                .keys()
+                .collect::<Vec<_>>(),
+            self.dynamic_lookups.table_selectors.len(),
+            geometries(&self.dynamic_lookups.inputs),
+            geometries(&self.dynamic_lookups.tables),
+        );
+        let shuffles = (
+            self.shuffles.input_selectors.keys().collect::<Vec<_>>(),
+            self.shuffles.reference_selectors.len(),
+            geometries(&self.shuffles.inputs),
+            geometries(&self.shuffles.references),
+        );
+        let dynamic_range_checks = (
+            self.dynamic_range_checks
+                .selectors
+                .keys()
+                .collect::<Vec<_>>(),
+            geometries(&self.dynamic_range_checks.inputs),
+        );
+        let binary_lookups = (
+            self.binary_lookups.selectors.keys().collect::<Vec<_>>(),
+            self.binary_lookups
+                .tables
+                .iter()
+                .map(|(name, t)| (name, t.ranges, t.table_outputs.len(), t.col_size))
+                .collect::<Vec<_>>(),
+            geometries(&self.binary_lookups.inputs),
+            geometry(&self.binary_lookups.output),
+            geometry(&self.binary_lookups.index),
+        );
+
+        // every collection above is ordered, so the serialization is canonical
+        let structure = bincode::serialize(&(
+            custom_gates,
+            static_lookups,
+            range_checks,
+            dynamic_lookups,
+            shuffles,
+            dynamic_range_checks,
+            binary_lookups,
+        ))
+        .expect("the circuit structure is serializable");
+
+        let mut digest = [0u8; 32];
+        hex::decode_to_slice(sha256::digest(structure), &mut digest)
+            .expect("sha256 digests are 32 bytes of hex");
+        digest
+    }
+
+    /// layout_tables must be called before layout.
+    pub fn layout_tables(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), Box<dyn Error>> {
+        self.layout_tables_with_order(layouter, AssignmentOrder::default())
+    }
+
+    /// Same as [BaseConfig::layout_tables] but assigns the table cells in the given [AssignmentOrder].
+    pub fn layout_tables_with_order(
+        &mut self,
+        layouter: &mut impl Layouter<F>,
+        order: AssignmentOrder,
+    ) -> Result<(), Box<dyn Error>> {
+        // tables sharing input columns only need the inputs assigned once
+        let mut laid_out_inputs = vec![];
+        for table in self.static_lookups.tables.values_mut() {
+            let preassigned_input = laid_out_inputs.contains(&table.table_inputs[0]);
+            if !table.is_assigned {
+                debug!(
+                    "laying out table for {}",
+                    crate::circuit::ops::Op::<F>::as_string(&table.nonlinearity)
+                );
+                table.layout(layouter, preassigned_input, order)?;
+            }
+            if !preassigned_input {
+                laid_out_inputs.push(table.table_inputs[0]);
+            }
+        }
+        for table in self.binary_lookups.tables.values_mut() {
+            if !table.is_assigned {
+                debug!("laying out {}", table.name());
+                table.layout(layouter)?;
+            }
+        }
+        self.tables_laid_out = true;
+        Ok(())
+    }
+
+    /// Shares `cache` with every configured lookup table, so that tables also configured by other
+    /// [BaseConfig]s holding the same cache are only evaluated once. Call this once all lookups are
+    /// configured and before [BaseConfig::layout_tables].
+    pub fn share_table_evals(&mut self, cache: &TableEvalCache<F>) {
+        for table in self.static_lookups.tables.values_mut() {
+            table.eval_cache = Some(cache.clone());
+        }
+    }
+
+    /// Caches the evaluations of every configured lookup table in `dir`, in a file named by
+    /// [evals_cache_file_name], so that later runs reload them rather than recompute them, see
+    /// [Table::evals_file]. Call this once all lookups are configured and before
+    /// [BaseConfig::layout_tables].
+    pub fn cache_table_evals_in(&mut self, dir: &Path) {
+        for table in self.static_lookups.tables.values_mut() {
+            table.evals_file =
+                Some(dir.join(evals_cache_file_name(&table.nonlinearity, table.range)));
+        }
+    }
+
+    /// Has every configured lookup table warn once when more than `warning.fraction` of its outputs
+    /// are clamped, see [ClampWarning]. Call this once all lookups are configured and before
+    /// [BaseConfig::layout_tables].
+    pub fn warn_on_clamped_outputs(&mut self, warning: ClampWarning) {
+        for table in self.static_lookups.tables.values_mut() {
+            table.clamp_warning = Some(warning);
+        }
+    }
+
+    /// Writes the contents of every configured lookup table and range check to `dir` as CSV, one
+    /// `lookup_<op>_<hash>.csv` of `input,output` rows per table and one `range_check_<lo>_<hi>.csv`
+    /// of `value` rows per range check, where `<hash>` is the op's [LookupOp::stable_hash] so that ops
+    /// whose names only differ in characters that aren't kept in file names don't overwrite each
+    /// other. Ops looked up against another op's table have no dump of their own. Compare two dumps
+    /// with [crate::circuit::table::diff_table_dumps].
+    pub fn dump_all_tables(&self, dir: &Path) -> Result<(), Box<dyn Error>> {
+        std::fs::create_dir_all(dir)?;
+        for (nl, table) in self.static_lookups.tables.iter() {
+            let name = Op::<F>::as_string(nl)
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() || c == '-' || c == '.' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>();
+            write_table_dump(
+                &dir.join(format!("lookup_{}_{:016x}.csv", name, nl.stable_hash())),
+                &["input", "output"],
+                table.rows()?.into_iter().map(|(x, y)| vec![x, y]),
+            )?;
+        }
+        for range in self.range_checks.ranges.keys() {
+            write_table_dump(
+                &dir.join(format!("range_check_{}_{}.csv", range.0, range.1)),
+                &["value"],
+                (range.0..=range.1).map(|v| vec![v]),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// layout_range_checks must be called before layout.
+    pub fn layout_range_checks(
+        &mut self,
+        layouter: &mut impl Layouter<F>,
+    ) -> Result<(), Box<dyn Error>> {
+        for range_check in self.range_checks.ranges.values_mut() {
+            if !range_check.is_assigned {
+                debug!("laying out range check for {:?}", range_check.range);
+                range_check.layout(layouter)?;
+            }
+        }
+        for range_check in self.range_checks.multi.values_mut() {
+            if !range_check.is_assigned {
+                debug!("laying out {}", range_check.name());
+                range_check.layout(layouter)?;
+            }
+        }
+        self.tables_laid_out = true;
+        Ok(())
+    }
+
+    /// layout_dynamic_lookups must be called before layout, which otherwise refuses to lay out
+    /// dynamic lookups. Dynamic lookup tables are witnessed inline, alongside their lookups in
+    /// `layouts::dynamic_lookup`, so there is nothing to assign up front: this only checks that the
+    /// configured tables match the configured inputs and marks them as laid out. Unlike
+    /// [BaseConfig::layout_tables] it leaves the config open to further `configure_*` calls.
+    pub fn layout_dynamic_lookups(&mut self) -> Result<(), Box<dyn Error>> {
+        let dynamic_lookups = &mut self.dynamic_lookups;
+        if !dynamic_lookups.is_assigned && !dynamic_lookups.lookup_selectors.is_empty() {
+            debug!("laying out dynamic lookups");
+            if dynamic_lookups.table_selectors.is_empty()
+                || dynamic_lookups.tables.len() != dynamic_lookups.inputs.len()
+            {
+                return Err(format!(
+                    "dynamic lookups have {} inputs but {} tables",
+                    dynamic_lookups.inputs.len(),
+                    dynamic_lookups.tables.len()
+                )
+                .into());
+            }
+        }
+        dynamic_lookups.is_assigned = true;
+        Ok(())
+    }
+
+    /// layout_shuffles must be called before layout, which otherwise refuses to lay out shuffles.
+    /// Shuffle references are witnessed inline, alongside their inputs in `layouts::shuffles`, so
+    /// this only checks that the configured references match the configured inputs and marks them
+    /// as laid out. Unlike [BaseConfig::layout_tables] it leaves the config open to further
+    /// `configure_*` calls.
+    pub fn layout_shuffles(&mut self) -> Result<(), Box<dyn Error>> {
+        let shuffles = &mut self.shuffles;
+        if !shuffles.is_assigned && !shuffles.input_selectors.is_empty() {
+            debug!("laying out shuffles");
+            if shuffles.reference_selectors.is_empty()
+                || shuffles.references.len() != shuffles.inputs.len()
+            {
+                return Err(format!(
+                    "shuffles have {} inputs but {} references",
+                    shuffles.inputs.len(),
+                    shuffles.references.len()
+                )
+                .into());
             }
         }
+        shuffles.is_assigned = true;
         Ok(())
     }
 
-    /// layout_range_checks must be called before layout.
-    pub fn layout_range_checks(
+    /// Switches every configured range check to incremental assignment, so that
+    /// [BaseConfig::layout_range_checks] only assigns the table chunks exercised by inputs laid out
+    /// since the previous call. Useful when inputs arrive in batches and only a narrow part of an
+    /// over-provisioned range is hit early on; call [BaseConfig::layout_range_checks] after each batch
+    /// and [BaseConfig::finish_incremental_range_checks] once the last batch is laid out.
+    /// Unknown inputs, as at keygen, exercise every chunk, so the full tables end up in the keys.
+    pub fn set_incremental_range_checks(&mut self) {
+        for range_check in self.range_checks.ranges.values_mut() {
+            range_check.set_incremental();
+        }
+    }
+
+    /// Assigns the default row of every range check chunk no batch exercised, see
+    /// [RangeCheck::layout_defaults]. Must follow the last [BaseConfig::layout_range_checks] call of
+    /// an incrementally assigned config, or the lookups into the skipped chunks fail.
+    pub fn finish_incremental_range_checks(
         &mut self,
         layouter: &mut impl Layouter<F>,
     ) -> Result<(), Box<dyn Error>> {
         for range_check in self.range_checks.ranges.values_mut() {
-            if !range_check.is_assigned {
-                debug!("laying out range check for {:?}", range_check.range);
-                range_check.layout(layouter)?;
+            range_check.layout_defaults(layouter)?;
+        }
+        Ok(())
+    }
+
+    /// Lays out `ops` in sequence against a dummy region, without assigning anything, and
+    /// records the rows each op lands on.
+    pub fn dry_run(
+        &mut self,
+        ops: &[(Box<dyn Op<F>>, Vec<ValTensor<F>>)],
+    ) -> Result<RowPlan, CircuitError> {
+        let num_inner_cols = self.custom_gates.output.num_inner_cols().max(1);
+        let mut region = RegionCtx::new_dummy(0, num_inner_cols, true);
+
+        let mut plan = RowPlan::default();
+        for (op, values) in ops {
+            let start_row = region.row();
+            self.layout(&mut region, values, op.clone_dyn())
+                .map_err(|e| CircuitError::OpLayout(op.as_string(), e.to_string()))?;
+            plan.ops.push(OpRows {
+                name: op.as_string(),
+                start_row,
+                end_row: region.row(),
+            });
+        }
+        plan.base_ops = region.used_base_ops().into_iter().collect();
+        Ok(plan)
+    }
+
+    /// Checks that the [Tolerance] of a [HybridOp::RangeCheck] is expressed at the scale of the values it
+    /// compares. Mismatches are logged, or returned as errors if `strict` is set.
+    pub fn check_tolerance_scale(
+        &self,
+        op: &dyn Op<F>,
+        values: &[ValTensor<F>],
+    ) -> Result<(), Box<dyn Error>> {
+        let tol = match op.as_any().downcast_ref::<HybridOp>() {
+            Some(HybridOp::RangeCheck(tol)) => tol,
+            _ => return Ok(()),
+        };
+        // a zero tolerance is laid out as an equality constraint, so the scale is unused
+        if tol.val == 0.0 {
+            return Ok(());
+        }
+        if let Some(values) = values.first() {
+            if let Err(msg) = tol.check_scale(values.scale()) {
+                if self.strict {
+                    return Err(msg.into());
+                }
+                log::warn!("{}", msg);
+            }
+        }
+        Ok(())
+    }
+
+    /// Allocates `num_columns` instance columns, with equality enabled, for
+    /// [BaseConfig::layout_public_outputs] to route public outputs to.
+    pub fn configure_instance_columns(&mut self, cs: &mut ConstraintSystem<F>, num_columns: usize) {
+        for _ in 0..num_columns {
+            let col = cs.instance_column();
+            cs.enable_equality(col);
+            self.instance_columns.push(col);
+        }
+    }
+
+    /// Copy-constrains each output to the instance column at the paired index in
+    /// [BaseConfig::instance_columns]. Outputs sharing a column are placed one after the other, in the
+    /// order they are given, starting at the column's first row.
+    pub fn layout_public_outputs(
+        &mut self,
+        region: &mut RegionCtx<F>,
+        outputs: &[(ValTensor<F>, usize)],
+    ) -> Result<(), Box<dyn Error>> {
+        // the shapes of the outputs landing in each column, which fix their offsets within it
+        let mut col_dims: BTreeMap<usize, Vec<Vec<usize>>> = BTreeMap::new();
+        for (output, index) in outputs {
+            if *index >= self.instance_columns.len() {
+                return Err(Box::new(CircuitError::MissingInstanceColumn {
+                    index: *index,
+                    num_columns: self.instance_columns.len(),
+                }));
             }
+            col_dims
+                .entry(*index)
+                .or_default()
+                .push(output.dims().to_vec());
+        }
+
+        let mut next_idx: BTreeMap<usize, usize> = BTreeMap::new();
+        for (output, index) in outputs {
+            let mut instance = ValTensor::new_instance_from_col(
+                col_dims[index].clone(),
+                output.scale(),
+                self.instance_columns[*index],
+            );
+            let idx = next_idx.entry(*index).or_insert(0);
+            instance.set_idx(*idx);
+            *idx += 1;
+
+            super::layouts::enforce_equality(self, region, &[instance, output.clone()])?;
         }
         Ok(())
     }
@@ -955,17 +3727,70 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
         region: &mut RegionCtx<F>,
         values: &[ValTensor<F>],
         op: Box<dyn Op<F>>,
+    ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+        self.layout_checked(region, values, op, false)
+    }
+
+    /// Same as [BaseConfig::layout] but marks `op` as producing a final (public) output of the circuit,
+    /// so that it is also checked under [CheckMode::OUTPUTS_ONLY].
+    pub fn layout_output(
+        &mut self,
+        region: &mut RegionCtx<F>,
+        values: &[ValTensor<F>],
+        op: Box<dyn Op<F>>,
+    ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
+        self.layout_checked(region, values, op, true)
+    }
+
+    fn layout_checked(
+        &mut self,
+        region: &mut RegionCtx<F>,
+        values: &[ValTensor<F>],
+        op: Box<dyn Op<F>>,
+        is_output: bool,
     ) -> Result<Option<ValTensor<F>>, Box<dyn Error>> {
         let res = op.layout(self, region, values)?;
 
-        if matches!(&self.check_mode, CheckMode::SAFE) && !region.is_dummy() {
+        // a precomputed mapping only lives in its table, which the outputs were read from
+        let is_precomputed = matches!(
+            op.as_any().downcast_ref::<LookupOp>(),
+            Some(LookupOp::Precomputed { .. })
+        );
+        let run_checks = match self.check_mode {
+            CheckMode::SAFE | CheckMode::WARN => true,
+            CheckMode::OUTPUTS_ONLY => is_output,
+            CheckMode::UNSAFE => false,
+        } && !self.skip_check_ops.contains(&op.as_string())
+            && !is_precomputed;
+
+        if run_checks && !region.is_dummy() {
+            self.check_tolerance_scale(op.as_ref(), values)?;
             if let Some(claimed_output) = &res {
                 // during key generation this will be unknown vals so we use this as a flag to check
                 let mut is_assigned = !claimed_output.any_unknowns()?;
                 for val in values.iter() {
                     is_assigned = is_assigned && !val.any_unknowns()?;
                 }
-                if is_assigned {
+                if is_assigned && self.check_mode == CheckMode::WARN {
+                    // report the mismatching elements and carry on with the layout
+                    if let Err(diffs) = op.safe_mode_check_verbose(claimed_output, values) {
+                        if diffs.is_empty() {
+                            log::warn!("safe mode check could not evaluate {}", op.as_string());
+                        } else {
+                            log::warn!(
+                                "safe mode check failed for {} at indices {:?}",
+                                op.as_string(),
+                                diffs.iter().map(|d| d.index).collect::<Vec<_>>()
+                            );
+                        }
+                    }
+                } else if is_assigned && self.verbose_checks {
+                    op.safe_mode_check_verbose(claimed_output, values)
+                        .map_err(|diffs| CircuitError::SafeModeCheck {
+                            op: op.as_string(),
+                            diffs,
+                        })?;
+                } else if is_assigned {
                     op.safe_mode_check(claimed_output, values)?;
                 }
             }
@@ -973,3 +3798,701 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfig<F> {
         Ok(res)
     }
 }
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash + FromUniformBytes<64> + Ord>
+    BaseConfig<F>
+{
+    /// Checks that this config is well formed at `2^k` rows, without a witness. The full config is
+    /// rebuilt over fresh columns of the same geometry, with tables sized for `k`: the custom gates
+    /// under the same selector strategy, the rotated sub, is zero and saturating gates, the static,
+    /// linear and binary lookups, the range checks (covering those configured by
+    /// [BaseConfig::configure_argmax] and [BaseConfig::configure_sorted_range_check]), the multi and
+    /// dynamic range checks, the dynamic lookups and the shuffles. [MockProver] is then run twice over
+    /// the rebuilt config: once laying out every table and a region enabling each selector, whose
+    /// witness cells are left unknown, which surfaces layout errors such as selectors or tables that
+    /// don't fit; and once laying out the tables alone, checking that the unselected rows of every
+    /// lookup find their default in its table.
+    ///
+    /// Errors with [CircuitError::MissingSelector] if the rebuilt gates or range checks have a
+    /// selector at a position this config lacks one, and with [CircuitError::StructureCheck] if a
+    /// lookup or range check has selectors but no table, if columns of the config disagree on their
+    /// geometry, if they don't fit in `2^k` rows, if the gates are of too high a degree for the
+    /// field's two-adicity, or if the mock prover fails.
+    pub fn mock_structure_check(&self, k: u32) -> Result<(), CircuitError> {
+        let mut cs = ConstraintSystem::<F>::default();
+        self.rebuild_structure(&mut cs, k)?;
+
+        let max_rows = VarTensor::max_rows(&cs, k as usize);
+        let col_size = self.custom_gates.output.col_size();
+        if col_size > max_rows {
+            return Err(CircuitError::StructureCheck(format!(
+                "columns hold {} rows but only {} are usable at k={}",
+                col_size, max_rows, k
+            )));
+        }
+
+        // the quotient polynomial is evaluated over a domain extended by the degree of the gates
+        let extended_k = k
+            + (cs.degree() as u32 - 1)
+                .next_power_of_two()
+                .trailing_zeros();
+        if extended_k > F::S {
+            return Err(CircuitError::StructureCheck(format!(
+                "gates of degree {} need an extended domain of 2^{} rows, more than the field supports",
+                cs.degree(),
+                extended_k
+            )));
+        }
+
+        for enable_selectors in [true, false] {
+            let circuit = StructureCheckCircuit {
+                config: self.clone(),
+                k,
+                enable_selectors,
+            };
+            let prover = MockProver::run(k, &circuit, vec![])
+                .map_err(|e| CircuitError::StructureCheck(e.to_string()))?;
+            // with the selectors enabled the gates and lookups constrain the unknown witness, so only
+            // the table-only run can be verified
+            if !enable_selectors {
+                prover.verify().map_err(|failures| {
+                    CircuitError::StructureCheck(format!(
+                        "{} failure(s), first: {}",
+                        failures.len(),
+                        failures[0]
+                    ))
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Configures the gates, lookups and arguments checked by [BaseConfig::mock_structure_check] with
+    /// the same shape as those of `self` over fresh columns of `cs`, with tables sized for `k`,
+    /// returning the new config. Columns shared by several parts of `self`, e.g. a custom gate input
+    /// that is also range checked, are shared in the new config too.
+    fn rebuild_structure(
+        &self,
+        cs: &mut ConstraintSystem<F>,
+        k: u32,
+    ) -> Result<Self, CircuitError> {
+        let structure_err = |e: Box<dyn Error>| CircuitError::StructureCheck(e.to_string());
+        let no_table = |what: String| {
+            CircuitError::StructureCheck(format!("{} has selectors but no table", what))
+        };
+        let logrows = k as usize;
+        let output = &self.custom_gates.output;
+        if self.custom_gates.inputs.is_empty() {
+            return Err(CircuitError::ConfigNotInitialized(
+                "mock_structure_check".to_string(),
+            ));
+        }
+        for var in self.custom_gates.inputs.iter() {
+            if var.col_size() != output.col_size()
+                || var.num_blocks() != output.num_blocks()
+                || var.num_inner_cols() != output.num_inner_cols()
+            {
+                return Err(CircuitError::StructureCheck(format!(
+                    "input columns ({} blocks of {} columns of {} rows) don't match the output columns ({} blocks of {} columns of {} rows)",
+                    var.num_blocks(),
+                    var.num_inner_cols(),
+                    var.col_size(),
+                    output.num_blocks(),
+                    output.num_inner_cols(),
+                    output.col_size()
+                )));
+            }
+        }
+
+        let mut fresh_vars: Vec<(VarTensor, VarTensor)> = vec![];
+        let mut fresh = |var: &VarTensor| {
+            if !var.is_advice() {
+                return var.clone();
+            }
+            if let Some((_, new)) = fresh_vars.iter().find(|(old, _)| old == var) {
+                return new.clone();
+            }
+            let new = VarTensor::new_advice_with_geometry(
+                cs,
+                var.col_size(),
+                var.num_blocks(),
+                var.num_inner_cols(),
+            );
+            fresh_vars.push((var.clone(), new.clone()));
+            new
+        };
+        let inputs = self
+            .custom_gates
+            .inputs
+            .iter()
+            .map(&mut fresh)
+            .collect::<Vec<_>>();
+        let output = fresh(output);
+        let lookup_vars = [
+            &self.static_lookups.input,
+            &self.static_lookups.output,
+            &self.static_lookups.index,
+        ]
+        .map(&mut fresh);
+        let linear_inputs = self
+            .static_lookups
+            .linear
+            .iter()
+            .map(|(nl, cols)| {
+                let cols = cols
+                    .iter()
+                    .map(|(var, weight)| (fresh(var), *weight))
+                    .collect::<Vec<_>>();
+                (nl.clone(), cols)
+            })
+            .collect::<Vec<_>>();
+        let range_check_vars = [&self.range_checks.input, &self.range_checks.index].map(&mut fresh);
+        let argmax_output = self.argmax_output.as_ref().map(&mut fresh);
+        let range_check_outputs = self
+            .range_checks
+            .ranges
+            .iter()
+            .filter_map(|(range, rc)| rc.output.as_ref().map(|o| (*range, fresh(o))))
+            .collect::<BTreeMap<_, _>>();
+        let dynamic_range_check_vars = self
+            .dynamic_range_checks
+            .inputs
+            .iter()
+            .map(&mut fresh)
+            .collect::<Vec<_>>();
+        let binary_vars = match self.binary_lookups.inputs.as_slice() {
+            [a, b] => Some(
+                [
+                    a,
+                    b,
+                    &self.binary_lookups.output,
+                    &self.binary_lookups.index,
+                ]
+                .map(&mut fresh),
+            ),
+            _ => None,
+        };
+        let dynamic_lookups = self
+            .dynamic_lookups
+            .inputs
+            .iter()
+            .map(&mut fresh)
+            .collect::<Vec<_>>();
+        let dynamic_tables = self
+            .dynamic_lookups
+            .tables
+            .iter()
+            .map(&mut fresh)
+            .collect::<Vec<_>>();
+        let shuffle_inputs = self
+            .shuffles
+            .inputs
+            .iter()
+            .map(&mut fresh)
+            .collect::<Vec<_>>();
+        let shuffle_references = self
+            .shuffles
+            .references
+            .iter()
+            .map(&mut fresh)
+            .collect::<Vec<_>>();
+
+        let strategy = if self.custom_gates.shared_ops.is_empty() {
+            SelectorStrategy::Lazy(
+                self.custom_gates
+                    .selectors
+                    .keys()
+                    .map(|(op, _, _)| op.clone())
+                    .collect(),
+            )
+        } else {
+            SelectorStrategy::Shared
+        };
+        let mut config =
+            Self::configure_with_strategy(cs, &inputs, &output, self.check_mode, &strategy);
+        config.blinding_rows_pad = self.blinding_rows_pad;
+        config.share_scaled_tables = self.share_scaled_tables;
+        config.max_dynamic_lookup_arity = self.max_dynamic_lookup_arity;
+        config.argmax_output = argmax_output;
+
+        let rotations = self
+            .custom_gates
+            .selectors
+            .keys()
+            .filter_map(|(op, _, _)| match op {
+                BaseOp::RotatedSub(rotation) => Some(*rotation),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        for rotation in rotations {
+            config.configure_rotated_sub(cs, rotation)?;
+        }
+
+        if self
+            .custom_gates
+            .selectors
+            .keys()
+            .any(|(op, _, _)| *op == BaseOp::IsZero)
+        {
+            config.configure_is_zero(cs)?;
+        }
+
+        let saturating_bounds = self
+            .custom_gates
+            .selectors
+            .keys()
+            .filter_map(|(op, _, _)| match op {
+                BaseOp::SatAdd { lo, hi } => Some((*lo, *hi)),
+                _ => None,
+            })
+            .collect::<BTreeSet<_>>();
+        for (lo, hi) in saturating_bounds {
+            config.configure_saturating(cs, lo, hi)?;
+        }
+        check_selectors_cover(
+            &self.custom_gates.selectors,
+            &config.custom_gates.selectors,
+            |(op, x, y)| (op.as_str().to_string(), *x, *y),
+        )?;
+
+        // ops sharing a scaled table are configured after the table they share
+        let [lookup_input, lookup_output, lookup_index] = &lookup_vars;
+        let (scaled_ops, base_ops): (Vec<_>, Vec<_>) = self
+            .static_lookups
+            .selectors
+            .keys()
+            .map(|(op, _, _)| op.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .partition(|op| self.static_lookups.scaled.contains_key(op));
+        for nl in base_ops.iter().chain(scaled_ops.iter()) {
+            let table = self
+                .static_lookups
+                .table_for(nl)
+                .ok_or_else(|| no_table(Op::<F>::as_string(nl)))?;
+            let configured = if let Some(sub_ranges) = &table.sub_ranges {
+                config.configure_lookup_nonuniform(
+                    cs,
+                    lookup_input,
+                    lookup_output,
+                    lookup_index,
+                    sub_ranges,
+                    logrows,
+                    nl,
+                )
+            } else if let Some(precomputed) = &table.precomputed {
+                config
+                    .configure_lookup_precomputed(
+                        cs,
+                        lookup_input,
+                        lookup_output,
+                        lookup_index,
+                        table.range,
+                        logrows,
+                        (*precomputed.outputs).clone(),
+                    )
+                    .map(|_| ())
+            } else {
+                let range = self
+                    .static_lookups
+                    .nested
+                    .get(nl)
+                    .copied()
+                    .unwrap_or(table.range);
+                config.configure_lookup_at_positions(
+                    cs,
+                    lookup_input,
+                    lookup_output,
+                    lookup_index,
+                    range,
+                    logrows,
+                    nl,
+                    self.static_lookups.configured_blocks(nl),
+                )
+            };
+            configured.map_err(structure_err)?;
+        }
+
+        if let Some((nl, _, _)) = self
+            .static_lookups
+            .linear_selectors
+            .keys()
+            .find(|(nl, _, _)| !self.static_lookups.linear.contains_key(nl))
+        {
+            return Err(no_table(format!("linear {}", Op::<F>::as_string(nl))));
+        }
+        for (nl, cols) in linear_inputs.iter() {
+            let table = self
+                .static_lookups
+                .table_for(nl)
+                .ok_or_else(|| no_table(format!("linear {}", Op::<F>::as_string(nl))))?;
+            config
+                .configure_lookup_linear(
+                    cs,
+                    cols,
+                    lookup_output,
+                    lookup_index,
+                    nl,
+                    table.range,
+                    logrows,
+                )
+                .map_err(structure_err)?;
+        }
+
+        // ranges checked against an enclosing range's table are configured after the enclosing range
+        let [range_check_input, range_check_index] = &range_check_vars;
+        let ranges = self
+            .range_checks
+            .selectors
+            .keys()
+            .map(|(range, _, _)| *range)
+            .collect::<BTreeSet<_>>();
+        let (subsets, own_tables): (Vec<_>, Vec<_>) = ranges
+            .into_iter()
+            .partition(|range| self.range_checks.subsets.contains_key(range));
+        for range in own_tables.iter().chain(subsets.iter()) {
+            let table_range = self.range_checks.subsets.get(range).unwrap_or(range);
+            if !self.range_checks.ranges.contains_key(table_range) {
+                return Err(no_table(format!("range check {:?}", range)));
+            }
+            config
+                .configure_range_check_with_output(
+                    cs,
+                    range_check_input,
+                    range_check_index,
+                    range_check_outputs.get(range),
+                    *range,
+                    logrows,
+                )
+                .map_err(structure_err)?;
+        }
+        check_selectors_cover(
+            &self.range_checks.selectors,
+            &config.range_checks.selectors,
+            |(range, x, y)| (format!("range check {:?}", range), *x, *y),
+        )?;
+
+        if let Some((ranges, _, _)) = self
+            .range_checks
+            .multi_selectors
+            .keys()
+            .find(|(ranges, _, _)| !self.range_checks.multi.contains_key(ranges))
+        {
+            return Err(no_table(format!("multi range check {:?}", ranges)));
+        }
+        for ranges in self.range_checks.multi.keys() {
+            config
+                .configure_multi_range_check(
+                    cs,
+                    range_check_input,
+                    range_check_index,
+                    ranges,
+                    logrows,
+                )
+                .map_err(structure_err)?;
+        }
+        check_selectors_cover(
+            &self.range_checks.multi_selectors,
+            &config.range_checks.multi_selectors,
+            |(ranges, x, y)| (format!("multi range check {:?}", ranges), *x, *y),
+        )?;
+
+        if !self.dynamic_range_checks.selectors.is_empty() {
+            config
+                .configure_dynamic_range_check(
+                    cs,
+                    &dynamic_range_check_vars[0],
+                    &dynamic_range_check_vars[1],
+                    &dynamic_range_check_vars[2],
+                )
+                .map_err(structure_err)?;
+        }
+
+        if let Some((name, _, _)) = self
+            .binary_lookups
+            .selectors
+            .keys()
+            .find(|(name, _, _)| !self.binary_lookups.tables.contains_key(name))
+        {
+            return Err(no_table(format!("binary lookup {}", name)));
+        }
+        for (name, table) in self.binary_lookups.tables.iter() {
+            let [binary_a, binary_b, binary_output, binary_index] =
+                binary_vars.as_ref().ok_or_else(|| {
+                    CircuitError::StructureCheck(format!(
+                        "binary lookup {} has a table but no inputs",
+                        name
+                    ))
+                })?;
+            config
+                .configure_binary_lookup(
+                    cs,
+                    &[binary_a.clone(), binary_b.clone()],
+                    binary_output,
+                    binary_index,
+                    name,
+                    table.ranges,
+                    logrows,
+                    table.f,
+                )
+                .map_err(structure_err)?;
+        }
+
+        if !self.dynamic_lookups.lookup_selectors.is_empty() {
+            config
+                .configure_dynamic_lookup(cs, &dynamic_lookups, &dynamic_tables)
+                .map_err(structure_err)?;
+        }
+
+        if !self.shuffles.input_selectors.is_empty() {
+            config
+                .configure_shuffles(cs, &shuffle_inputs, &shuffle_references)
+                .map_err(structure_err)?;
+        }
+
+        Ok(config)
+    }
+
+    /// Every selector of the config, of the custom gates, lookups, range checks, dynamic lookups and
+    /// shuffles alike.
+    fn all_selectors(&self) -> Vec<Selector> {
+        self.custom_gates
+            .selectors
+            .values()
+            .chain(self.static_lookups.selectors.values())
+            .chain(self.static_lookups.linear_selectors.values())
+            .chain(self.range_checks.selectors.values())
+            .chain(self.range_checks.multi_selectors.values())
+            .chain(self.dynamic_range_checks.selectors.values())
+            .chain(self.binary_lookups.selectors.values())
+            .chain(self.dynamic_lookups.lookup_selectors.values())
+            .chain(self.dynamic_lookups.table_selectors.iter())
+            .chain(self.shuffles.input_selectors.values())
+            .chain(self.shuffles.reference_selectors.iter())
+            .copied()
+            .collect()
+    }
+}
+
+/// Errors with [CircuitError::MissingSelector] for the first position `rebuilt` has a selector at but
+/// `original` doesn't, naming it with `describe`, see [BaseConfig::mock_structure_check].
+fn check_selectors_cover<K: Ord>(
+    original: &BTreeMap<K, Selector>,
+    rebuilt: &BTreeMap<K, Selector>,
+    describe: impl Fn(&K) -> (String, usize, usize),
+) -> Result<(), CircuitError> {
+    match rebuilt.keys().find(|key| !original.contains_key(key)) {
+        Some(key) => {
+            let (op, block, column) = describe(key);
+            Err(CircuitError::MissingSelector { op, block, column })
+        }
+        None => Ok(()),
+    }
+}
+
+/// The parts of a [ConstraintSystem] the [CheckMode] could change, see
+/// [CheckMode::affects_constraint_system].
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct StructureSummary {
+    num_constraints: usize,
+    num_selectors: usize,
+    num_advice_columns: usize,
+    num_fixed_columns: usize,
+    degree: usize,
+}
+
+impl StructureSummary {
+    fn of<F: PrimeField>(cs: &ConstraintSystem<F>) -> Self {
+        let gate_constraints = cs
+            .gates()
+            .iter()
+            .map(|g| g.polynomials().len())
+            .sum::<usize>();
+        Self {
+            num_constraints: gate_constraints + cs.lookups().len(),
+            num_selectors: cs.num_selectors(),
+            num_advice_columns: cs.num_advice_columns(),
+            num_fixed_columns: cs.num_fixed_columns(),
+            degree: cs.degree(),
+        }
+    }
+}
+
+/// A circuit holding no witness over a [BaseConfig] rebuilt from `config` at `k`, laying out its tables
+/// and, if `enable_selectors` is set, a region enabling each of its selectors, see
+/// [BaseConfig::mock_structure_check].
+#[derive(Clone, Debug, Default)]
+struct StructureCheckCircuit<F: PrimeField + TensorType + PartialOrd> {
+    config: BaseConfig<F>,
+    k: u32,
+    enable_selectors: bool,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash + FromUniformBytes<64> + Ord>
+    Circuit<F> for StructureCheckCircuit<F>
+{
+    type Config = BaseConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+    type Params = (BaseConfig<F>, u32);
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn params(&self) -> Self::Params {
+        (self.config.clone(), self.k)
+    }
+
+    fn configure_with_params(cs: &mut ConstraintSystem<F>, params: Self::Params) -> Self::Config {
+        // the structure was already rebuilt once by mock_structure_check, which reported any error
+        let (config, k) = params;
+        config
+            .rebuild_structure(cs, k)
+            .expect("failed to rebuild the circuit structure")
+    }
+
+    fn configure(_: &mut ConstraintSystem<F>) -> Self::Config {
+        unimplemented!("you should call configure_with_params instead")
+    }
+
+    fn synthesize(
+        &self,
+        mut config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), PlonkError> {
+        config
+            .layout_tables(&mut layouter)
+            .map_err(|_| PlonkError::Synthesis)?;
+        config
+            .layout_range_checks(&mut layouter)
+            .map_err(|_| PlonkError::Synthesis)?;
+        config
+            .layout_dynamic_lookups()
+            .map_err(|_| PlonkError::Synthesis)?;
+        config
+            .layout_shuffles()
+            .map_err(|_| PlonkError::Synthesis)?;
+        if self.enable_selectors {
+            for (i, selector) in config.all_selectors().iter().enumerate() {
+                layouter.assign_region(
+                    || format!("structure check selector {}", i),
+                    |mut region| selector.enable(&mut region, 0),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Wraps a [BaseConfig] while its lookups are configured, recording for each [LookupOp] whether its
+/// table reused the input columns of a previously configured table or allocated fresh ones, and how
+/// much its lookups raised the degree of the constraint system.
+#[derive(Clone, Debug, Default)]
+pub struct BaseConfigBuilder<F: PrimeField + TensorType + PartialOrd> {
+    config: BaseConfig<F>,
+    column_reuse: BTreeMap<LookupOp, bool>,
+    degree_report: Vec<(String, usize)>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> BaseConfigBuilder<F> {
+    /// Wraps an already configured [BaseConfig], see [BaseConfig::configure].
+    pub fn new(config: BaseConfig<F>) -> Self {
+        Self {
+            config,
+            column_reuse: BTreeMap::new(),
+            degree_report: vec![],
+        }
+    }
+
+    /// Configures the wrapped [BaseConfig] with [BaseConfig::configure_with_degree_report], so the
+    /// [BaseConfigBuilder::degree_report] covers its gates followed by the lookups configured
+    /// through the builder.
+    pub fn configure_with_degree_report(
+        meta: &mut ConstraintSystem<F>,
+        inputs: &[VarTensor],
+        output: &VarTensor,
+        check_mode: CheckMode,
+    ) -> Self {
+        let (config, degree_report) =
+            BaseConfig::configure_with_degree_report(meta, inputs, output, check_mode);
+        Self {
+            config,
+            column_reuse: BTreeMap::new(),
+            degree_report,
+        }
+    }
+
+    /// The wrapped config, for configuring anything other than lookups.
+    pub fn config_mut(&mut self) -> &mut BaseConfig<F> {
+        &mut self.config
+    }
+
+    /// Same as [BaseConfig::configure_lookup], additionally recording whether the table of `nl`
+    /// reused existing input columns, and how much the lookups it created raised the degree of `cs`.
+    /// Ops configured more than once keep their first reuse record.
+    #[allow(clippy::too_many_arguments)]
+    pub fn configure_lookup(
+        &mut self,
+        cs: &mut ConstraintSystem<F>,
+        input: &VarTensor,
+        output: &VarTensor,
+        index: &VarTensor,
+        lookup_range: Range,
+        logrows: usize,
+        nl: &LookupOp,
+    ) -> Result<(), Box<dyn Error>>
+    where
+        F: Field,
+    {
+        let existing_inputs = self
+            .config
+            .static_lookups
+            .tables
+            .values()
+            .flat_map(|t| t.table_inputs.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let degree = cs.degree();
+        self.config
+            .configure_lookup(cs, input, output, index, lookup_range, logrows, nl)?;
+
+        let increase = cs.degree().saturating_sub(degree);
+        let name = Op::<F>::as_string(nl);
+        match self.degree_report.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, total)) => *total += increase,
+            None => self.degree_report.push((name, increase)),
+        }
+
+        if !self.column_reuse.contains_key(nl) {
+            let lookups = &self.config.static_lookups;
+            // ops sharing another op's table look up against that table's columns
+            let owner = lookups.scaled.get(nl).map(|(base, _)| base).unwrap_or(nl);
+            let reused = lookups.tables.get(owner).is_some_and(|t| {
+                t.table_inputs
+                    .iter()
+                    .all(|col| existing_inputs.contains(col))
+            });
+            self.column_reuse.insert(nl.clone(), reused);
+        }
+        Ok(())
+    }
+
+    /// Whether each configured [LookupOp] reused the input columns of a previously configured
+    /// table (`true`) or allocated its own (`false`).
+    pub fn column_reuse_report(&self) -> BTreeMap<LookupOp, bool> {
+        self.column_reuse.clone()
+    }
+
+    /// How much each op raised the degree of the constraint system, as `(name, degree increase)` pairs
+    /// in configuration order: the gates when built with
+    /// [BaseConfigBuilder::configure_with_degree_report], then every lookup configured through the
+    /// builder. The increases add up to the total increase in degree.
+    pub fn degree_report(&self) -> Vec<(String, usize)> {
+        self.degree_report.clone()
+    }
+
+    /// Returns the configured [BaseConfig].
+    pub fn build(self) -> BaseConfig<F> {
+        self.config
+    }
+}
@@ -1,6 +1,6 @@
 use super::*;
 use crate::{
-    circuit::{layouts, utils, Tolerance},
+    circuit::{layouts, utils, CircuitError, Tolerance, ToleranceMode},
     fieldutils::{felt_to_i128, i128_to_felt},
     graph::multiplier_to_scale,
     tensor::{self, Tensor, TensorError, TensorType, ValTensor},
@@ -13,10 +13,14 @@ use serde::{Deserialize, Serialize};
 /// An enum representing the operations that consist of both lookups and arithmetic operations.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum HybridOp {
+    /// Inputs within `epsilon` of zero are floored as in [LookupOp::Recip]. A non zero `epsilon`
+    /// always lays out as a lookup, the range checked integer reciprocal has no floor.
     Recip {
         input_scale: utils::F32,
         output_scale: utils::F32,
         use_range_check_for_int: bool,
+        #[serde(default)]
+        epsilon: i64,
     },
     Div {
         denom: utils::F32,
@@ -28,6 +32,11 @@ pub enum HybridOp {
     ReduceArgMax {
         dim: usize,
     },
+    /// One-hot vector flagging the first maximum of the flattened input, whose elements must lie in
+    /// `range`. Needs [crate::circuit::BaseConfig::configure_argmax] for that range.
+    ArgmaxIndicator {
+        range: crate::circuit::table::Range,
+    },
     SumPool {
         padding: [(usize, usize); 2],
         stride: (usize, usize),
@@ -51,6 +60,31 @@ pub enum HybridOp {
         axes: Vec<usize>,
     },
     RangeCheck(Tolerance),
+    /// Range checks the flattened input against `range` and checks it is sorted in non-decreasing
+    /// order, see [crate::circuit::BaseConfig::configure_sorted_range_check]. Evaluates to the
+    /// flattened input.
+    SortedRangeCheck {
+        range: crate::circuit::table::Range,
+    },
+    /// Lookup of `nl` on the weighted sum of the inputs, `weights[i]` weighing the `i`-th input, see
+    /// [crate::circuit::BaseConfig::configure_lookup_linear] which has to be called with the same
+    /// weights.
+    LinearLookup {
+        nl: LookupOp,
+        weights: Vec<i64>,
+    },
+    /// Checks `0 <= x < bound` for a witnessed `bound` given as the second input, see
+    /// [crate::circuit::BaseConfig::configure_dynamic_range_check]. `range` must start at 0 and cover
+    /// every admissible bound minus one. Evaluates to the checked input.
+    DynamicRangeCheck {
+        range: crate::circuit::table::Range,
+    },
+    /// Range checks the input against the union of `ranges`, see
+    /// [crate::circuit::BaseConfig::configure_multi_range_check] which has to be called with the
+    /// same ranges. Evaluates to the checked input.
+    MultiRangeCheck {
+        ranges: Vec<crate::circuit::table::Range>,
+    },
     Greater,
     GreaterEqual,
     Less,
@@ -77,6 +111,8 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
         match self {
             HybridOp::Greater | HybridOp::Less | HybridOp::Equals => vec![0, 1],
             HybridOp::GreaterEqual | HybridOp::LessEqual => vec![0, 1],
+            HybridOp::DynamicRangeCheck { .. } => vec![0, 1],
+            HybridOp::LinearLookup { weights, .. } => (0..weights.len()).collect(),
             _ => vec![],
         }
     }
@@ -98,13 +134,26 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
             HybridOp::Recip {
                 input_scale,
                 output_scale,
+                epsilon,
                 ..
             } => crate::tensor::ops::nonlinearities::recip(
-                &x,
+                &super::lookup::epsilon_floor(&x, *epsilon),
                 input_scale.0 as f64,
                 output_scale.0 as f64,
             ),
             HybridOp::ReduceArgMax { dim } => tensor::ops::argmax_axes(&x, *dim)?,
+            HybridOp::ArgmaxIndicator { .. } => {
+                // we value the first index in the case of a tie
+                let argmax = x
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(idx, value)| (**value, -(*idx as i64)))
+                    .map(|(idx, _)| idx);
+                let indicator = (0..x.len())
+                    .map(|i| (Some(i) == argmax) as i128)
+                    .collect::<Vec<_>>();
+                Tensor::new(Some(&indicator), &[x.len()])?
+            }
             HybridOp::ReduceArgMin { dim } => tensor::ops::argmin_axes(&x, *dim)?,
             HybridOp::Gather { dim, constant_idx } => {
                 if let Some(idx) = constant_idx {
@@ -143,7 +192,30 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
             ),
             HybridOp::RangeCheck(tol) => {
                 let y = inputs[1].clone().map(|x| felt_to_i128(x));
-                tensor::ops::nonlinearities::range_check_percent(&[x, y], 128, 128, tol.val)
+                match tol.mode {
+                    ToleranceMode::Percentage => {
+                        tensor::ops::nonlinearities::range_check_percent(&[x, y], 128, 128, tol.val)
+                    }
+                    ToleranceMode::Absolute => {
+                        tensor::ops::nonlinearities::range_check_abs(&[x, y], tol.absolute_bound())
+                    }
+                }
+            }
+            HybridOp::DynamicRangeCheck { .. } | HybridOp::MultiRangeCheck { .. } => x,
+            HybridOp::SortedRangeCheck { .. } => {
+                let mut x = x;
+                x.flatten();
+                x
+            }
+            HybridOp::LinearLookup { nl, weights } => {
+                if inputs.len() != weights.len() {
+                    return Err(TensorError::DimMismatch("linear lookup".to_string()));
+                }
+                let mut combination = inputs[0].map(|x| x * i128_to_felt::<F>(weights[0] as i128));
+                for (input, w) in inputs.iter().zip(weights).skip(1) {
+                    combination = (combination + input.map(|x| x * i128_to_felt::<F>(*w as i128)))?;
+                }
+                return Op::<F>::f(nl, &[combination]);
             }
             HybridOp::Greater => {
                 let y = inputs[1].clone().map(|x| felt_to_i128(x));
@@ -179,9 +251,10 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 input_scale,
                 output_scale,
                 use_range_check_for_int,
+                epsilon,
             } => format!(
-                "RECIP (input_scale={}, output_scale={}, use_range_check_for_int={})",
-                input_scale, output_scale, use_range_check_for_int
+                "RECIP (input_scale={}, output_scale={}, use_range_check_for_int={}, epsilon={})",
+                input_scale, output_scale, use_range_check_for_int, epsilon
             ),
             HybridOp::Div {
                 denom,
@@ -201,6 +274,7 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
             ),
             HybridOp::ReduceMax { axes } => format!("REDUCEMAX (axes={:?})", axes),
             HybridOp::ReduceArgMax { dim } => format!("REDUCEARGMAX (dim={})", dim),
+            HybridOp::ArgmaxIndicator { range } => format!("ARGMAXINDICATOR (range={:?})", range),
             HybridOp::MaxPool2d {
                 padding,
                 stride,
@@ -222,6 +296,20 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 )
             }
             HybridOp::RangeCheck(p) => format!("RANGECHECK (tol={:?})", p),
+            HybridOp::DynamicRangeCheck { range } => {
+                format!("DYNAMICRANGECHECK (range={:?})", range)
+            }
+            HybridOp::SortedRangeCheck { range } => {
+                format!("SORTEDRANGECHECK (range={:?})", range)
+            }
+            HybridOp::MultiRangeCheck { ranges } => {
+                format!("MULTIRANGECHECK (ranges={:?})", ranges)
+            }
+            HybridOp::LinearLookup { nl, weights } => format!(
+                "LINEARLOOKUP (nl={}, weights={:?})",
+                Op::<F>::as_string(nl),
+                weights
+            ),
             HybridOp::Greater => "GREATER".into(),
             HybridOp::GreaterEqual => "GREATEREQUAL".into(),
             HybridOp::Less => "LESS".into(),
@@ -262,10 +350,12 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 input_scale,
                 output_scale,
                 use_range_check_for_int,
+                epsilon,
             } => {
                 if input_scale.0.fract() == 0.0
                     && output_scale.0.fract() == 0.0
                     && *use_range_check_for_int
+                    && *epsilon == 0
                 {
                     layouts::recip(
                         config,
@@ -282,6 +372,7 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                         &LookupOp::Recip {
                             input_scale: *input_scale,
                             output_scale: *output_scale,
+                            epsilon: *epsilon,
                         },
                     )?
                 }
@@ -333,6 +424,9 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
             HybridOp::ReduceArgMax { dim } => {
                 layouts::argmax_axes(config, region, values[..].try_into()?, *dim)?
             }
+            HybridOp::ArgmaxIndicator { range } => {
+                layouts::argmax_indicator(config, region, values[..].try_into()?, range)?
+            }
             HybridOp::ReduceMin { axes } => {
                 layouts::min_axes(config, region, values[..].try_into()?, axes)?
             }
@@ -351,13 +445,44 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
                 *output_scale,
                 axes,
             )?,
-            HybridOp::RangeCheck(tol) => layouts::range_check_percent(
-                config,
-                region,
-                values[..].try_into()?,
-                tol.scale,
-                tol.val,
-            )?,
+            HybridOp::RangeCheck(tol) => match tol.mode {
+                ToleranceMode::Percentage => layouts::range_check_percent(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    tol.scale,
+                    tol.val,
+                )?,
+                ToleranceMode::Absolute => layouts::range_check_abs(
+                    config,
+                    region,
+                    values[..].try_into()?,
+                    tol.absolute_bound(),
+                )?,
+            },
+            HybridOp::DynamicRangeCheck { range } => {
+                layouts::dynamic_range_check(config, region, values[..].try_into()?, range)?
+            }
+            HybridOp::SortedRangeCheck { range } => {
+                layouts::sorted_range_check(config, region, values[..].try_into()?, range)?
+            }
+            HybridOp::MultiRangeCheck { ranges } => {
+                layouts::multi_range_check(config, region, values[..].try_into()?, ranges)?
+            }
+            HybridOp::LinearLookup { nl, weights } => {
+                let configured = config
+                    .static_lookups
+                    .linear
+                    .get(nl)
+                    .map(|cols| cols.iter().map(|(_, w)| *w).collect::<Vec<_>>());
+                if configured.as_ref() != Some(weights) {
+                    return Err(Box::new(CircuitError::OpLayout(
+                        Op::<F>::as_string(self),
+                        format!("configured with the weights {:?}", configured),
+                    )));
+                }
+                layouts::linear_nonlinearity(config, region, values, nl)?
+            }
             HybridOp::Greater => layouts::greater(config, region, values[..].try_into()?)?,
             HybridOp::GreaterEqual => {
                 layouts::greater_equal(config, region, values[..].try_into()?)?
@@ -381,11 +506,13 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Hybrid
             | HybridOp::Less { .. }
             | HybridOp::LessEqual { .. }
             | HybridOp::ReduceArgMax { .. }
+            | HybridOp::ArgmaxIndicator { .. }
             | HybridOp::OneHot { .. }
             | HybridOp::ReduceArgMin { .. } => 0,
             HybridOp::Softmax { output_scale, .. } | HybridOp::Recip { output_scale, .. } => {
                 multiplier_to_scale(output_scale.0 as f64)
             }
+            HybridOp::LinearLookup { nl, .. } => Op::<F>::out_scale(nl, in_scales[..1].to_vec())?,
             _ => in_scales[0],
         };
         Ok(scale)
@@ -17,11 +17,11 @@ use maybe_rayon::{
 use self::tensor::{create_constant_tensor, create_zero_tensor};
 
 use super::{
-    chip::{BaseConfig, CircuitError},
+    chip::{BaseConfig, CheckMode, CircuitError},
     region::RegionCtx,
 };
 use crate::{
-    circuit::{ops::base::BaseOp, utils},
+    circuit::{ops::base::BaseOp, table::felt_to_int_in_range, utils},
     fieldutils::{felt_to_i128, i128_to_felt},
     tensor::{
         create_unit_tensor, get_broadcasted_shape,
@@ -302,6 +302,8 @@ pub(crate) fn dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     trace!("assigning output took: {:?}", elapsed);
 
     // enable the selectors
+    region.add_used_base_op(BaseOp::DotInit);
+    region.add_used_base_op(BaseOp::Dot);
     if !region.is_dummy() {
         (0..output_assigned_len)
             .map(|i| {
@@ -314,11 +316,13 @@ pub(crate) fn dot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
                     return Ok(());
                 }
                 let selector = if i == 0 {
-                    config.custom_gates.selectors.get(&(BaseOp::DotInit, x, 0))
+                    config
+                        .custom_gates
+                        .required_selector(&BaseOp::DotInit, x, 0)?
                 } else {
-                    config.custom_gates.selectors.get(&(BaseOp::Dot, x, 0))
+                    config.custom_gates.required_selector(&BaseOp::Dot, x, 0)?
                 };
-                region.enable(selector, z)?;
+                region.enable(Some(selector), z)?;
 
                 Ok(())
             })
@@ -666,8 +670,8 @@ fn select<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     }
     .into();
 
-    let (_, assigned_output) =
-        dynamic_lookup(config, region, &[index, output], &[dim_indices, input])?;
+    let assigned_output =
+        dynamic_lookup(config, region, &[index, output], &[dim_indices, input])?[1].clone();
 
     let end = start.elapsed();
     trace!("select took: {:?}", end);
@@ -726,52 +730,72 @@ fn one_hot<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     Ok(assigned_output)
 }
 
-/// Dynamic lookup
+/// Dynamic lookup of the rows of `lookups` into the rows of `tables`. The dynamic lookups must have
+/// been configured with one column more than there are `lookups` and `tables`, the last column
+/// holding the index telling apart the tables of successive dynamic lookups.
 pub(crate) fn dynamic_lookup<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
-    lookups: &[ValTensor<F>; 2],
-    tables: &[ValTensor<F>; 2],
-) -> Result<(ValTensor<F>, ValTensor<F>), Box<dyn Error>> {
+    lookups: &[ValTensor<F>],
+    tables: &[ValTensor<F>],
+) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
     let start = instant::Instant::now();
+    if !region.is_dummy() && !config.dynamic_lookups.is_assigned {
+        return Err(Box::new(CircuitError::OpLayout(
+            "dynamic_lookup".to_string(),
+            "call layout_dynamic_lookups first".to_string(),
+        )));
+    }
+    let width = config.dynamic_lookups.inputs.len();
+    if lookups.is_empty() || lookups.len() != tables.len() || lookups.len() + 1 != width {
+        return Err(format!(
+            "dynamic lookups configured over {} columns can't look up {} columns into {}",
+            width,
+            lookups.len(),
+            tables.len()
+        )
+        .into());
+    }
+
     // if not all lookups same length err
-    if lookups[0].len() != lookups[1].len() {
+    if lookups.iter().any(|l| l.len() != lookups[0].len()) {
         return Err("lookups must be same length".into());
     }
 
     // if not all inputs same length err
-    if tables[0].len() != tables[1].len() {
+    if tables.iter().any(|t| t.len() != tables[0].len()) {
         return Err("tables must be same length".into());
     }
 
     let dynamic_lookup_index = region.dynamic_lookup_index();
 
-    let (lookup_0, lookup_1) = (lookups[0].clone(), lookups[1].clone());
-    let (table_0, table_1) = (tables[0].clone(), tables[1].clone());
-
-    let table_0 = region.assign_dynamic_lookup(&config.dynamic_lookups.tables[0], &table_0)?;
-    let _table_1 = region.assign_dynamic_lookup(&config.dynamic_lookups.tables[1], &table_1)?;
-    let table_len = table_0.len();
+    let mut table_len = 0;
+    for (table, var) in tables.iter().zip(config.dynamic_lookups.tables.iter()) {
+        table_len = region.assign_dynamic_lookup(var, table)?.len();
+    }
 
     trace!("assigning tables took: {:?}", start.elapsed());
 
     // now create a vartensor of constants for the dynamic lookup index
     let table_index = create_constant_tensor(F::from(dynamic_lookup_index as u64), table_len);
     let _table_index =
-        region.assign_dynamic_lookup(&config.dynamic_lookups.tables[2], &table_index)?;
+        region.assign_dynamic_lookup(&config.dynamic_lookups.tables[width - 1], &table_index)?;
 
     trace!("assigning table index took: {:?}", start.elapsed());
 
-    let lookup_0 = region.assign(&config.dynamic_lookups.inputs[0], &lookup_0)?;
-    let lookup_1 = region.assign(&config.dynamic_lookups.inputs[1], &lookup_1)?;
-    let lookup_len = lookup_0.len();
+    let assigned_lookups = lookups
+        .iter()
+        .zip(config.dynamic_lookups.inputs.iter())
+        .map(|(lookup, var)| region.assign(var, lookup))
+        .collect::<Result<Vec<_>, _>>()?;
+    let lookup_len = assigned_lookups[0].len();
 
     trace!("assigning lookups took: {:?}", start.elapsed());
 
     // now set the lookup index
     let lookup_index = create_constant_tensor(F::from(dynamic_lookup_index as u64), lookup_len);
 
-    let _lookup_index = region.assign(&config.dynamic_lookups.inputs[2], &lookup_index)?;
+    let _lookup_index = region.assign(&config.dynamic_lookups.inputs[width - 1], &lookup_index)?;
 
     trace!("assigning lookup index took: {:?}", start.elapsed());
 
@@ -813,33 +837,58 @@ pub(crate) fn dynamic_lookup<F: PrimeField + TensorType + PartialOrd + std::hash
     let end = start.elapsed();
     trace!("dynamic lookup took: {:?}", end);
 
-    Ok((lookup_0, lookup_1))
+    Ok(assigned_lookups)
 }
 
 /// Shuffle arg
 pub(crate) fn shuffles<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
     region: &mut RegionCtx<F>,
-    input: &[ValTensor<F>; 1],
-    reference: &[ValTensor<F>; 1],
-) -> Result<ValTensor<F>, Box<dyn Error>> {
+    inputs: &[ValTensor<F>],
+    references: &[ValTensor<F>],
+) -> Result<Vec<ValTensor<F>>, Box<dyn Error>> {
+    if !region.is_dummy() && !config.shuffles.is_assigned {
+        return Err(Box::new(CircuitError::OpLayout(
+            "shuffles".to_string(),
+            "call layout_shuffles first".to_string(),
+        )));
+    }
     let shuffle_index = region.shuffle_index();
-    let (input, reference) = (input[0].clone(), reference[0].clone());
+    let width = config.shuffles.inputs.len();
+    if inputs.is_empty() || inputs.len() != references.len() || inputs.len() + 1 != width {
+        return Err(format!(
+            "shuffles configured over {} columns can't shuffle {} columns into {}",
+            width,
+            inputs.len(),
+            references.len()
+        )
+        .into());
+    }
 
-    // assert input and reference are same length
-    if input.len() != reference.len() {
+    // assert inputs and references are same length
+    if inputs
+        .iter()
+        .chain(references.iter())
+        .any(|v| v.len() != references[0].len())
+    {
         return Err("input and reference must be same length".into());
     }
 
-    let reference = region.assign_shuffle(&config.shuffles.references[0], &reference)?;
-    let reference_len = reference.len();
+    let mut reference_len = 0;
+    for (reference, var) in references.iter().zip(config.shuffles.references.iter()) {
+        reference_len = region.assign_shuffle(var, reference)?.len();
+    }
 
     // now create a vartensor of constants for the shuffle index
     let index = create_constant_tensor(F::from(shuffle_index as u64), reference_len);
-    let index = region.assign_shuffle(&config.shuffles.references[1], &index)?;
+    let index = region.assign_shuffle(&config.shuffles.references[width - 1], &index)?;
 
-    let input = region.assign(&config.shuffles.inputs[0], &input)?;
-    region.assign(&config.shuffles.inputs[1], &index)?;
+    let assigned_inputs = inputs
+        .iter()
+        .zip(config.shuffles.inputs.iter())
+        .map(|(input, var)| region.assign(var, input))
+        .collect::<Result<Vec<_>, _>>()?;
+    region.assign(&config.shuffles.inputs[width - 1], &index)?;
 
     if !region.is_dummy() {
         (0..reference_len)
@@ -876,7 +925,7 @@ pub(crate) fn shuffles<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
     region.increment_shuffle_index(1);
     region.increment(reference_len);
 
-    Ok(input)
+    Ok(assigned_inputs)
 }
 
 /// One hot accumulated layout
@@ -1569,6 +1618,8 @@ pub(crate) fn sum<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     )?;
 
     // enable the selectors
+    region.add_used_base_op(BaseOp::SumInit);
+    region.add_used_base_op(BaseOp::Sum);
     if !region.is_dummy() {
         for i in 0..output_assigned_len {
             let (x, _, z) = config
@@ -1580,12 +1631,14 @@ pub(crate) fn sum<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
                 continue;
             }
             let selector = if i == 0 {
-                config.custom_gates.selectors.get(&(BaseOp::SumInit, x, 0))
+                config
+                    .custom_gates
+                    .required_selector(&BaseOp::SumInit, x, 0)?
             } else {
-                config.custom_gates.selectors.get(&(BaseOp::Sum, x, 0))
+                config.custom_gates.required_selector(&BaseOp::Sum, x, 0)?
             };
 
-            region.enable(selector, z)?;
+            region.enable(Some(selector), z)?;
         }
     }
 
@@ -1643,6 +1696,8 @@ pub(crate) fn prod<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     )?;
 
     // enable the selectors
+    region.add_used_base_op(BaseOp::CumProdInit);
+    region.add_used_base_op(BaseOp::CumProd);
     if !region.is_dummy() {
         (0..output_assigned_len)
             .map(|i| {
@@ -1657,13 +1712,14 @@ pub(crate) fn prod<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
                 let selector = if i == 0 {
                     config
                         .custom_gates
-                        .selectors
-                        .get(&(BaseOp::CumProdInit, x, 0))
+                        .required_selector(&BaseOp::CumProdInit, x, 0)?
                 } else {
-                    config.custom_gates.selectors.get(&(BaseOp::CumProd, x, 0))
+                    config
+                        .custom_gates
+                        .required_selector(&BaseOp::CumProd, x, 0)?
                 };
 
-                region.enable(selector, z)?;
+                region.enable(Some(selector), z)?;
                 Ok(())
             })
             .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
@@ -1865,6 +1921,7 @@ pub(crate) fn pairwise<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
         ))));
     }
 
+    align_shared(config, region, &op)?;
     let mut inputs = vec![];
     for (i, input) in [lhs.clone(), rhs.clone()].iter().enumerate() {
         let inp = {
@@ -1904,20 +1961,22 @@ pub(crate) fn pairwise<F: PrimeField + TensorType + PartialOrd + std::hash::Hash
     trace!("pairwise {} calc took {:?}", op.as_str(), elapsed);
 
     // Enable the selectors
+    region.add_used_base_op(op.clone());
     if !region.is_dummy() {
         (0..assigned_len)
             .map(|i| {
                 let (x, y, z) =
                     config.custom_gates.inputs[0].cartesian_coord(region.linear_coord() + i);
-                let selector = config.custom_gates.selectors.get(&(op.clone(), x, y));
+                let selector = config.custom_gates.required_selector(&op, x, y)?;
 
-                region.enable(selector, z)?;
+                region.enable(Some(selector), z)?;
 
                 Ok(())
             })
             .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
     }
     region.increment(assigned_len);
+    pad_shared(config, region, &op)?;
 
     let a_tensor = orig_lhs.get_inner_tensor()?;
     let b_tensor = orig_rhs.get_inner_tensor()?;
@@ -2763,7 +2822,10 @@ pub(crate) fn boolean_identity<F: PrimeField + TensorType + PartialOrd + std::ha
     values: &[ValTensor<F>; 1],
     assign: bool,
 ) -> Result<ValTensor<F>, Box<dyn Error>> {
-    let output = if assign || !values[0].get_const_indices()?.is_empty() {
+    // a shared selector constrains whole rows, so the output is assigned on rows of its own
+    let shared = config.custom_gates.is_shared(&BaseOp::IsBoolean);
+    let output = if assign || shared || !values[0].get_const_indices()?.is_empty() {
+        align_shared(config, region, &BaseOp::IsBoolean)?;
         // get zero constants indices
         let output = region.assign(&config.custom_gates.output, &values[0])?;
         region.increment(output.len());
@@ -2772,6 +2834,7 @@ pub(crate) fn boolean_identity<F: PrimeField + TensorType + PartialOrd + std::ha
         values[0].clone()
     };
     // Enable the selectors
+    region.add_used_base_op(BaseOp::IsBoolean);
     if !region.is_dummy() {
         (0..output.len())
             .map(|j| {
@@ -2780,14 +2843,236 @@ pub(crate) fn boolean_identity<F: PrimeField + TensorType + PartialOrd + std::ha
                 let (x, y, z) = config.custom_gates.output.cartesian_coord(index);
                 let selector = config
                     .custom_gates
-                    .selectors
-                    .get(&(BaseOp::IsBoolean, x, y));
+                    .required_selector(&BaseOp::IsBoolean, x, y)?;
 
-                region.enable(selector, z)?;
+                region.enable(Some(selector), z)?;
                 Ok(())
             })
             .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
     }
+    if shared {
+        pad_shared(config, region, &BaseOp::IsBoolean)?;
+    }
+
+    Ok(output)
+}
+
+/// Moves `region` to the start of a row if the selector of `op` is shared by the inner columns of a
+/// block, see [super::chip::SelectorStrategy::Shared].
+fn align_shared<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    op: &BaseOp,
+) -> Result<(), Box<dyn Error>> {
+    if config.custom_gates.is_shared(op) {
+        region.flush()?;
+    }
+    Ok(())
+}
+
+/// Fills the rest of the current row of `region` with cells satisfying the gate of `op` if its
+/// selector is shared by the inner columns of a block, as the gate then constrains the whole row:
+/// zero inputs, and the output `op` gives for them.
+fn pad_shared<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    op: &BaseOp,
+) -> Result<(), Box<dyn Error>> {
+    let remainder = region.linear_coord() % region.num_inner_cols();
+    if !config.custom_gates.is_shared(op) || remainder == 0 {
+        return Ok(());
+    }
+    let len = region.num_inner_cols() - remainder;
+    let padding =
+        |x: F| -> ValTensor<F> { Tensor::from(vec![Value::known(x); len].into_iter()).into() };
+
+    // ops with fewer than two inputs are queried on the last of the first two columns
+    let num_qis = op.num_inputs().max(2);
+    for input in &config.custom_gates.inputs[num_qis - op.num_inputs()..num_qis] {
+        region.assign(input, &padding(F::ZERO))?;
+    }
+    let output = match op {
+        BaseOp::IsZero => F::ONE,
+        _ => F::ZERO,
+    };
+    region.assign(&config.custom_gates.output, &padding(output))?;
+    region.increment(len);
+    Ok(())
+}
+
+/// Is zero layout, `1` where the input is zero and `0` elsewhere, using the [BaseOp::IsZero] gate with
+/// the input's inverse (zero for a zero input) as the auxiliary witness. The gate has to be configured
+/// with [BaseConfig::configure_is_zero].
+pub fn is_zero<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let inner = values[0].get_inner()?;
+    let inverse = inner.map(|x| x.map(|x| x.invert().unwrap_or(F::ZERO)));
+    let indicator = inner.map(|x| x.map(|x| if x == F::ZERO { F::ONE } else { F::ZERO }));
+
+    align_shared(config, region, &BaseOp::IsZero)?;
+    region.assign(&config.custom_gates.inputs[0], &values[0])?;
+    region.assign(&config.custom_gates.inputs[1], &inverse.into())?;
+    let output = region.assign(&config.custom_gates.output, &indicator.into())?;
+
+    // Enable the selectors
+    region.add_used_base_op(BaseOp::IsZero);
+    if !region.is_dummy() {
+        (0..output.len())
+            .map(|i| {
+                let (x, y, z) = config
+                    .custom_gates
+                    .output
+                    .cartesian_coord(region.linear_coord() + i);
+                let selector = config
+                    .custom_gates
+                    .required_selector(&BaseOp::IsZero, x, y)?;
+
+                region.enable(Some(selector), z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+    region.increment(output.len());
+    pad_shared(config, region, &BaseOp::IsZero)?;
+
+    Ok(output)
+}
+
+/// Multiply-add layout, `a * b + c` element-wise over three inputs of the same shape, using the
+/// [BaseOp::MultAdd] gate. Needs a third input column to have been passed to [BaseConfig::configure].
+pub fn mult_add<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 3],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    if config.custom_gates.inputs.len() < 3 {
+        return Err(Box::new(CircuitError::OpLayout(
+            "mult_add".to_string(),
+            format!(
+                "needs 3 input columns, {} are configured",
+                config.custom_gates.inputs.len()
+            ),
+        )));
+    }
+    if values.iter().any(|v| v.dims() != values[0].dims()) {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "mult_add layout".to_string(),
+        )));
+    }
+
+    align_shared(config, region, &BaseOp::MultAdd)?;
+    let mut inputs = vec![];
+    for (var, value) in config.custom_gates.inputs.iter().zip(values.iter()) {
+        inputs.push(region.assign(var, value)?.get_inner()?);
+    }
+    let product = mult(&inputs[0..2])?;
+    let op_result = add(&[product, inputs[2].clone()])?;
+    let output = region.assign(&config.custom_gates.output, &op_result.into())?;
+
+    // Enable the selectors
+    region.add_used_base_op(BaseOp::MultAdd);
+    if !region.is_dummy() {
+        (0..output.len())
+            .map(|i| {
+                let (x, y, z) = config
+                    .custom_gates
+                    .output
+                    .cartesian_coord(region.linear_coord() + i);
+                let selector = config
+                    .custom_gates
+                    .required_selector(&BaseOp::MultAdd, x, y)?;
+
+                region.enable(Some(selector), z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+    region.increment(output.len());
+    pad_shared(config, region, &BaseOp::MultAdd)?;
+
+    Ok(output)
+}
+
+/// Saturating add or sub layout, `a + b` or `a - b` element-wise clamped to the bounds of `op`, a
+/// [BaseOp::SatAdd] or [BaseOp::SatSub] configured by [BaseConfig::configure_saturating]. The output is
+/// range checked against `(lo, hi)` and the overshoot past the bound each element was clamped to against
+/// `(0, hi - lo)`, both range checks have to be configured.
+pub fn saturating<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    op: BaseOp,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let (lo, hi, unclamped_op) = match op {
+        BaseOp::SatAdd { lo, hi } => (lo as i128, hi as i128, BaseOp::Add),
+        BaseOp::SatSub { lo, hi } => (lo as i128, hi as i128, BaseOp::Sub),
+        _ => return Err(Box::new(CircuitError::UnsupportedOp)),
+    };
+    if config.custom_gates.inputs.len() < 3 {
+        return Err(Box::new(CircuitError::OpLayout(
+            op.as_str().to_string(),
+            format!(
+                "needs 3 input columns, {} are configured",
+                config.custom_gates.inputs.len()
+            ),
+        )));
+    }
+    if values[0].dims() != values[1].dims() {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "saturating layout".to_string(),
+        )));
+    }
+
+    let a = region.assign(&config.custom_gates.inputs[0], &values[0])?;
+    let b = region.assign(&config.custom_gates.inputs[1], &values[1])?;
+    let inner = [a.get_inner()?, b.get_inner()?];
+    let unclamped = match unclamped_op {
+        BaseOp::Add => add(&inner)?,
+        _ => sub(&inner)?,
+    };
+
+    // 0 within the bounds, 1 when clamped to `hi` and -1 when clamped to `lo`
+    let indicator = unclamped.map(|x| {
+        x.map(|x| {
+            let x = felt_to_i128(x);
+            i128_to_felt::<F>((x > hi) as i128 - (x < lo) as i128)
+        })
+    });
+    let clamped = unclamped.map(|x| x.map(|x| i128_to_felt::<F>(felt_to_i128(x).clamp(lo, hi))));
+
+    let indicator = region.assign(&config.custom_gates.inputs[2], &indicator.into())?;
+    let output = region.assign(&config.custom_gates.output, &clamped.into())?;
+
+    // Enable the selectors
+    region.add_used_base_op(op.clone());
+    if !region.is_dummy() {
+        (0..output.len())
+            .map(|i| {
+                let (x, y, z) = config
+                    .custom_gates
+                    .output
+                    .cartesian_coord(region.linear_coord() + i);
+                let selector = config.custom_gates.required_selector(&op, x, y)?;
+
+                region.enable(Some(selector), z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+    region.increment(output.len());
+
+    let output = range_check(config, region, &[output], &(lo, hi))?;
+
+    // the overshoot `c * (unclamped - output - c)` is the distance past the bound minus one when
+    // clamped, and zero otherwise
+    let unclamped = pairwise(config, region, &[a, b], unclamped_op)?;
+    let overshoot = pairwise(config, region, &[unclamped, output.clone()], BaseOp::Sub)?;
+    let overshoot = pairwise(config, region, &[overshoot, indicator.clone()], BaseOp::Sub)?;
+    let overshoot = pairwise(config, region, &[indicator, overshoot], BaseOp::Mult)?;
+    range_check(config, region, &[overshoot], &(0, hi - lo))?;
 
     Ok(output)
 }
@@ -2855,22 +3140,51 @@ pub(crate) fn range_check<F: PrimeField + TensorType + PartialOrd + std::hash::H
 
     let is_dummy = region.is_dummy();
 
+    // a range checked against an enclosing range's table, see [BaseConfig::configure_range_check]
+    let enclosing = config.range_checks.subsets.get(range);
+
+    let table = if !is_dummy {
+        Some(
+            config
+                .range_checks
+                .ranges
+                .get(enclosing.unwrap_or(range))
+                .ok_or(TensorError::TableLookupError)?,
+        )
+    } else {
+        None
+    };
+    // in safe mode, inputs that don't encode an integer within the range are rejected rather than
+    // mapped to a meaningless column
+    let is_safe = matches!(config.check_mode, CheckMode::SAFE);
+
     let table_index: ValTensor<F> = w
         .get_inner_tensor()?
         .par_enum_map(|_, e| {
-            Ok::<ValType<F>, TensorError>(if let Some(f) = e.get_felt_eval() {
-                let col_idx = if !is_dummy {
-                    let table = config
-                        .range_checks
-                        .ranges
-                        .get(range)
-                        .ok_or(TensorError::TableLookupError)?;
-                    table.get_col_index(f)
-                } else {
-                    F::ZERO
+            Ok::<ValType<F>, CircuitError>(if let Some(f) = e.get_felt_eval() {
+                let col_idx = match table {
+                    Some(_) if is_safe && enclosing.is_some() => {
+                        if felt_to_int_in_range(f, *range).is_none() {
+                            return Err(CircuitError::NonIntegerFieldElement(
+                                format!("{:?}", f),
+                                *range,
+                            ));
+                        }
+                        F::ZERO
+                    }
+                    Some(table) if is_safe => table.try_get_col_index(f)?,
+                    Some(table) => table.get_col_index(f),
+                    None => F::ZERO,
                 };
+                if let Some(table) = table {
+                    table.mark_touched(f);
+                }
                 Value::known(col_idx).into()
             } else {
+                // with unknown values, e.g. at keygen, any chunk may be exercised
+                if let Some(table) = table {
+                    table.mark_all_touched();
+                }
                 Value::<F>::unknown().into()
             })
         })?
@@ -2878,6 +3192,19 @@ pub(crate) fn range_check<F: PrimeField + TensorType + PartialOrd + std::hash::H
 
     region.assign(&config.range_checks.index, &table_index)?;
 
+    // echo the checked values into the range check's output column, if it has one
+    let output = match table
+        .filter(|_| enclosing.is_none())
+        .and_then(|t| t.output.as_ref())
+    {
+        Some(output) => {
+            let echoed = region.assign(output, &x)?;
+            region.constrain_equal(&w, &echoed)?;
+            Some(echoed)
+        }
+        None => None,
+    };
+
     if !is_dummy {
         (0..assigned_len)
             .map(|i| {
@@ -2914,9 +3241,148 @@ pub(crate) fn range_check<F: PrimeField + TensorType + PartialOrd + std::hash::H
         region.row()
     );
 
+    Ok(output.unwrap_or(w))
+}
+
+/// layout for a range check against the union of `ranges`, configured with
+/// [BaseConfig::configure_multi_range_check]. Errors if an input isn't covered by the union. Laid out
+/// by [crate::circuit::ops::hybrid::HybridOp::MultiRangeCheck].
+pub fn multi_range_check<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    ranges: &[crate::circuit::table::Range],
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let ranges = crate::circuit::table::merge_ranges(ranges);
+
+    let w = region.assign(&config.range_checks.input, &values[0])?;
+
+    let assigned_len = w.len();
+
+    let is_dummy = region.is_dummy();
+
+    let table = if !is_dummy {
+        Some(
+            config
+                .range_checks
+                .multi
+                .get(&ranges)
+                .ok_or(TensorError::TableLookupError)?,
+        )
+    } else {
+        None
+    };
+
+    let table_index: ValTensor<F> = w
+        .get_inner_tensor()?
+        .par_enum_map(|_, e| {
+            Ok::<ValType<F>, CircuitError>(match (e.get_felt_eval(), table) {
+                (Some(f), Some(table)) => Value::known(table.get_col_index(f)?).into(),
+                (Some(_), None) => Value::known(F::ZERO).into(),
+                (None, _) => Value::<F>::unknown().into(),
+            })
+        })?
+        .into();
+
+    region.assign(&config.range_checks.index, &table_index)?;
+
+    if !is_dummy {
+        (0..assigned_len)
+            .map(|i| {
+                let (x, y, z) = config
+                    .range_checks
+                    .input
+                    .cartesian_coord(region.linear_coord() + i);
+                let selector = config
+                    .range_checks
+                    .multi_selectors
+                    .get(&(ranges.clone(), x, y));
+                region.enable(selector, z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+
+    region.increment(assigned_len);
+
     Ok(w)
 }
 
+/// Range checks `x` against `range` and checks that it is sorted in non-decreasing order, by range
+/// checking every adjacent difference `x[i] - x[i-1]` against `(0, range.1 - range.0)`. Both range
+/// checks have to be configured, see [BaseConfig::configure_sorted_range_check]. Laid out by
+/// [crate::circuit::ops::hybrid::HybridOp::SortedRangeCheck].
+pub fn sorted_range_check<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    range: &crate::circuit::table::Range,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut input = values[0].clone();
+    input.flatten();
+
+    let checked = range_check(config, region, &[input], range)?;
+
+    if checked.len() < 2 {
+        return Ok(checked);
+    }
+
+    let window_a = checked.get_slice(&[0..checked.len() - 1])?;
+    let window_b = checked.get_slice(&[1..checked.len()])?;
+
+    let diff = pairwise(config, region, &[window_b, window_a], BaseOp::Sub)?;
+    range_check(config, region, &[diff], &(0, range.1 - range.0))?;
+
+    Ok(checked)
+}
+
+/// Checks that `0 <= x < bound` for a witnessed `bound`, by constraining `diff = bound - x - 1` and
+/// range checking both `x` and `diff` against the static `range`. `range` must start at 0 and its upper
+/// end must be at least the largest admissible bound minus one; the range check has to be configured
+/// separately with [BaseConfig::configure_range_check]. Laid out by
+/// [crate::circuit::ops::hybrid::HybridOp::DynamicRangeCheck].
+pub fn dynamic_range_check<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    range: &crate::circuit::table::Range,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    if range.0 != 0 {
+        return Err("dynamic range check requires a static range starting at 0".into());
+    }
+    if values[0].len() != values[1].len() {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "dynamic range check layout".to_string(),
+        )));
+    }
+
+    let vars = &config.dynamic_range_checks.inputs;
+
+    let input = region.assign(&vars[0], &values[0])?;
+    let bound = region.assign(&vars[1], &values[1])?;
+
+    let diff = sub(&[bound.get_inner()?, input.get_inner()?])?.map(|v| v - Value::known(F::ONE));
+    let diff = region.assign(&vars[2], &diff.into())?;
+
+    let assigned_len = input.len();
+
+    if !region.is_dummy() {
+        (0..assigned_len)
+            .map(|i| {
+                let (x, y, z) = vars[0].cartesian_coord(region.linear_coord() + i);
+                let selector = config.dynamic_range_checks.selectors.get(&(x, y));
+                region.enable(selector, z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+
+    region.increment(assigned_len);
+
+    range_check(config, region, &[diff], range)?;
+    range_check(config, region, &[input], range)
+}
+
 /// layout for nonlinearity check.
 pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -2937,12 +3403,13 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
 
     let w = region.assign_with_omissions(&config.static_lookups.input, &x, removal_indices_ptr)?;
 
+    let lookups = &config.static_lookups;
     let output = w.get_inner_tensor()?.par_enum_map(|i, e| {
         Ok::<_, TensorError>(if let Some(f) = e.get_felt_eval() {
             if !removal_indices.contains(&i) {
-                Value::known(Op::<F>::f(nl, &[Tensor::from(vec![f].into_iter())])?.output[0]).into()
+                Value::known(lookups.eval(nl, f)?).into()
             } else {
-                ValType::Constant(Op::<F>::f(nl, &[Tensor::from(vec![f].into_iter())])?.output[0])
+                ValType::Constant(lookups.eval(nl, f)?)
             }
         } else {
             Value::<F>::unknown().into()
@@ -2958,19 +3425,40 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
 
     let is_dummy = region.is_dummy();
 
+    let table = if !is_dummy {
+        Some(
+            config
+                .static_lookups
+                .table_for(nl)
+                .ok_or(TensorError::TableLookupError)?,
+        )
+    } else {
+        None
+    };
+    // in safe mode, inputs that don't encode an integer within the table's range are rejected rather
+    // than mapped to a meaningless column
+    let is_safe = matches!(config.check_mode, CheckMode::SAFE);
+    // an op sharing a wider table's inputs must still stay within its own range
+    let nested_range = config.static_lookups.nested.get(nl);
+
     let table_index: ValTensor<F> = w
         .get_inner_tensor()?
         .par_enum_map(|i, e| {
-            Ok::<_, TensorError>(if let Some(f) = e.get_felt_eval() {
-                let col_idx = if !is_dummy {
-                    let table = config
-                        .static_lookups
-                        .tables
-                        .get(nl)
-                        .ok_or(TensorError::TableLookupError)?;
-                    table.get_col_index(f)
-                } else {
-                    F::ZERO
+            Ok::<_, CircuitError>(if let Some(f) = e.get_felt_eval() {
+                let col_idx = match table {
+                    Some(table) if is_safe && !removal_indices.contains(&i) => {
+                        if let Some(range) = nested_range {
+                            if felt_to_int_in_range(f, *range).is_none() {
+                                return Err(CircuitError::NonIntegerFieldElement(
+                                    format!("{:?}", f),
+                                    *range,
+                                ));
+                            }
+                        }
+                        table.try_get_col_index(f)?
+                    }
+                    Some(table) => table.get_col_index(f)?,
+                    None => F::ZERO,
                 };
                 if !removal_indices.contains(&i) {
                     Value::known(col_idx).into()
@@ -2996,8 +3484,16 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
                     .static_lookups
                     .input
                     .cartesian_coord(region.linear_coord() + i);
-                let selector = config.static_lookups.selectors.get(&(nl.clone(), x, y));
-                region.enable(selector, z)?;
+                let selector = config
+                    .static_lookups
+                    .selectors
+                    .get(&(nl.clone(), x, y))
+                    .ok_or_else(|| CircuitError::MissingSelector {
+                        op: <LookupOp as Op<F>>::as_string(nl),
+                        block: x,
+                        column: y,
+                    })?;
+                region.enable(Some(selector), z)?;
                 Ok(())
             })
             .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
@@ -3005,6 +3501,11 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
 
     region.increment(assigned_len);
 
+    // the table covers a wider range than the op was configured for
+    if let Some(range) = nested_range {
+        range_check(config, region, &[w], range)?;
+    }
+
     output.reshape(x.dims())?;
 
     let elapsed = timer.elapsed();
@@ -3019,6 +3520,198 @@ pub(crate) fn nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::
     Ok(output)
 }
 
+/// layout for a lookup of `nl` on the weighted sum of `values`, configured with [BaseConfig::configure_lookup_linear].
+/// Only the inputs are assigned, the sum itself only exists in the lookup expression. Laid out by
+/// [crate::circuit::ops::hybrid::HybridOp::LinearLookup].
+pub fn linear_nonlinearity<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>],
+    nl: &LookupOp,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let inputs = config.static_lookups.linear.get(nl).ok_or_else(|| {
+        format!(
+            "{} is not configured as a linear lookup",
+            <LookupOp as Op<F>>::as_string(nl)
+        )
+    })?;
+    let len = values.first().map(|v| v.len()).unwrap_or(0);
+    if values.len() != inputs.len() || values.iter().any(|v| v.len() != len) {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "linear lookup".to_string(),
+        )));
+    }
+
+    let assigned = inputs
+        .iter()
+        .zip(values)
+        .map(|((var, _), value)| region.assign(var, value))
+        .collect::<Result<Vec<_>, _>>()?;
+    let assigned = assigned
+        .iter()
+        .map(|a| a.get_inner_tensor())
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let weights = inputs
+        .iter()
+        .map(|(_, w)| i128_to_felt::<F>(*w as i128))
+        .collect::<Vec<_>>();
+    let combination = (0..len)
+        .map(|i| {
+            assigned
+                .iter()
+                .zip(weights.iter())
+                .try_fold(F::ZERO, |acc, (input, w)| {
+                    input[i].get_felt_eval().map(|v| acc + v * w)
+                })
+        })
+        .collect::<Vec<_>>();
+
+    // the sum has to fall within the table's range, otherwise the lookup can't be satisfied
+    let table = config.static_lookups.tables.get(nl);
+    if table.is_none() && !region.is_dummy() {
+        return Err(Box::new(TensorError::TableLookupError));
+    }
+
+    let output = combination
+        .iter()
+        .map(|c| {
+            Ok::<_, TensorError>(match c {
+                Some(c) => Value::known(config.static_lookups.eval(nl, *c)?).into(),
+                None => Value::<F>::unknown().into(),
+            })
+        })
+        .collect::<Result<Vec<ValType<F>>, _>>()?;
+    let mut output = region.assign(
+        &config.static_lookups.output,
+        &Tensor::from(output.into_iter()).into(),
+    )?;
+
+    let table_index = combination
+        .iter()
+        .map(|c| {
+            Ok::<_, CircuitError>(match (c, table) {
+                (Some(c), Some(table)) => Value::known(table.try_get_col_index(*c)?).into(),
+                (Some(_), None) => Value::known(F::ZERO).into(),
+                (None, _) => Value::<F>::unknown().into(),
+            })
+        })
+        .collect::<Result<Vec<ValType<F>>, _>>()?;
+    region.assign(
+        &config.static_lookups.index,
+        &Tensor::from(table_index.into_iter()).into(),
+    )?;
+
+    if !region.is_dummy() {
+        (0..len)
+            .map(|i| {
+                let (x, y, z) = config
+                    .static_lookups
+                    .output
+                    .cartesian_coord(region.linear_coord() + i);
+                let selector = config
+                    .static_lookups
+                    .linear_selectors
+                    .get(&(nl.clone(), x, y));
+                region.enable(selector, z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+
+    region.increment(len);
+
+    output.reshape(values[0].dims())?;
+
+    Ok(output)
+}
+
+/// layout for a lookup of the function of two inputs configured under `name` with
+/// [BaseConfig::configure_binary_lookup]. Errors if an input is outside of the range the table covers
+/// for it. Outputs are unknown in a dummy region, which holds no tables.
+pub fn binary_lookup<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    name: &str,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    if values[0].len() != values[1].len() {
+        return Err(Box::new(CircuitError::DimMismatch(
+            "binary lookup layout".to_string(),
+        )));
+    }
+    let table = config.binary_lookups.tables.get(name);
+    if table.is_none() && !region.is_dummy() {
+        return Err(format!("{} is not configured as a binary lookup", name).into());
+    }
+
+    let vars = &config.binary_lookups.inputs;
+    let a = region.assign(&vars[0], &values[0])?;
+    let b = region.assign(&vars[1], &values[1])?;
+    let (a, b) = (a.get_inner_tensor()?, b.get_inner_tensor()?);
+
+    let evals = a
+        .iter()
+        .zip(b.iter())
+        .map(|(a, b)| {
+            Ok::<_, CircuitError>(match (a.get_felt_eval(), b.get_felt_eval(), table) {
+                (Some(a), Some(b), Some(table)) => {
+                    Some((table.eval(a, b)?, table.get_col_index(a, b)?))
+                }
+                _ => None,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let output = evals
+        .iter()
+        .map(|e| match e {
+            Some((output, _)) => Value::known(*output).into(),
+            None => Value::<F>::unknown().into(),
+        })
+        .collect::<Vec<ValType<F>>>();
+    let mut output = region.assign(
+        &config.binary_lookups.output,
+        &Tensor::from(output.into_iter()).into(),
+    )?;
+
+    let table_index = evals
+        .iter()
+        .map(|e| match e {
+            Some((_, index)) => Value::known(*index).into(),
+            None => Value::<F>::unknown().into(),
+        })
+        .collect::<Vec<ValType<F>>>();
+    region.assign(
+        &config.binary_lookups.index,
+        &Tensor::from(table_index.into_iter()).into(),
+    )?;
+
+    let len = evals.len();
+    if !region.is_dummy() {
+        (0..len)
+            .map(|i| {
+                let (x, y, z) = config
+                    .binary_lookups
+                    .output
+                    .cartesian_coord(region.linear_coord() + i);
+                let selector = config
+                    .binary_lookups
+                    .selectors
+                    .get(&(name.to_string(), x, y));
+                region.enable(selector, z)?;
+                Ok(())
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    }
+
+    region.increment(len);
+
+    output.reshape(values[0].dims())?;
+
+    Ok(output)
+}
+
 /// Argmax
 pub(crate) fn argmax<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -3091,6 +3784,199 @@ pub(crate) fn argmin<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     Ok(assigned_argmin)
 }
 
+/// Lays out a one-hot vector whose set bit is at the argmax of `values[0]`, whose elements are range
+/// checked to lie in `range`. Ties go to the first maximal index, as for [argmax]. The output is
+/// constrained to be boolean and sum to one, and with `m` the selected element and `after[i]` the
+/// number of set bits past `i`, each `m - x[i] - after[i]` is range checked against
+/// `(0, range.1 - range.0)`: no element exceeds `m`, and those before the set bit fall strictly short
+/// of it. The range checks have to be configured with [BaseConfig::configure_argmax].
+pub fn argmax_indicator<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    range: &crate::circuit::table::Range,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut input = values[0].clone();
+    input.flatten();
+    let len = input.len();
+    if len == 0 {
+        return Err(Box::new(CircuitError::OpLayout(
+            "argmax_indicator".to_string(),
+            "the input is empty".to_string(),
+        )));
+    }
+
+    // the input is used twice below, so it needs to be assigned once beforehand
+    if !input.all_prev_assigned() {
+        input = region.assign(&config.custom_gates.inputs[0], &input)?;
+        region.increment(input.len());
+    }
+    range_check(config, region, &[input.clone()], range)?;
+
+    // this is safe because we later constrain it
+    let indicator: Tensor<Value<F>> = if !input.any_unknowns()? {
+        let argmax = input
+            .get_int_evals()?
+            .into_iter()
+            .enumerate()
+            // we value the first index in the case of a tie
+            .max_by_key(|(idx, value)| (*value, -(*idx as i64)))
+            .map(|(idx, _)| idx);
+        (0..len)
+            .map(|i| Value::known(if Some(i) == argmax { F::ONE } else { F::ZERO }))
+            .collect()
+    } else {
+        Tensor::new(Some(&vec![Value::<F>::unknown(); len]), &[len])?
+    };
+
+    let assigned_indicator = boolean_identity(config, region, &[indicator.into()], true)?;
+
+    // number of set bits past each index, 1 exactly for the indices before the set bit. This is the
+    // running sum of the indicator taken from the back, so it is witnessed in one pass and pinned
+    // down by `after[len - 1] = 0` and `after[i] - after[i + 1] = indicator[i + 1]`
+    let mut after = vec![Value::known(F::ZERO); len];
+    let bits = assigned_indicator.get_inner()?;
+    for i in (0..len - 1).rev() {
+        after[i] = after[i + 1] + bits[i + 1];
+    }
+    let after = region.assign(
+        &config.custom_gates.inputs[1],
+        &Tensor::new(Some(&after), &[len])?.into(),
+    )?;
+    region.increment(after.len());
+
+    enforce_equality(
+        config,
+        region,
+        &[create_zero_tensor(1), after.get_slice(&[len - 1..len])?],
+    )?;
+    if len > 1 {
+        let steps = pairwise(
+            config,
+            region,
+            &[after.get_slice(&[0..len - 1])?, after.get_slice(&[1..len])?],
+            BaseOp::Sub,
+        )?;
+        enforce_equality(
+            config,
+            region,
+            &[steps, assigned_indicator.get_slice(&[1..len])?],
+        )?;
+    }
+
+    // the indicator sums to one iff its first bit and the bits after it do
+    let total = pairwise(
+        config,
+        region,
+        &[
+            assigned_indicator.get_slice(&[0..1])?,
+            after.get_slice(&[0..1])?,
+        ],
+        BaseOp::Add,
+    )?;
+    enforce_equality(config, region, &[create_unit_tensor(1), total])?;
+
+    let mut selected = dot(config, region, &[assigned_indicator.clone(), input.clone()])?;
+    selected.expand(&[len])?;
+
+    let gap = pairwise(config, region, &[selected, input], BaseOp::Sub)?;
+    let gap = pairwise(config, region, &[gap, after], BaseOp::Sub)?;
+    range_check(config, region, &[gap], &(0, range.1 - range.0))?;
+
+    // echo the indicator into the argmax output column, if one is configured
+    if let Some(output) = &config.argmax_output {
+        let echoed = region.assign(output, &assigned_indicator)?;
+        region.constrain_equal(&assigned_indicator, &echoed)?;
+        region.increment(echoed.len());
+        return Ok(echoed);
+    }
+
+    Ok(assigned_indicator)
+}
+
+/// Lagged difference layout, `output[i] = input[i + lag] - input[i]` over the flattened input, using
+/// the [BaseOp::RotatedSub] gate configured by [BaseConfig::configure_rotated_sub] with a rotation of
+/// `lag / num_inner_cols`. Elements whose lagged neighbour sits in another block's column fall back to a
+/// pairwise subtraction.
+pub fn lagged_diff<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 1],
+    lag: usize,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    let mut input = values[0].clone();
+    input.flatten();
+    let len = input.len();
+
+    let num_inner_cols = config.custom_gates.inputs[0].num_inner_cols();
+    if lag == 0 || lag >= len || lag % num_inner_cols != 0 {
+        return Err(Box::new(CircuitError::OpLayout(
+            "lagged_diff".to_string(),
+            format!(
+                "lag {} must be a non zero multiple of {} below the input length {}",
+                lag, num_inner_cols, len
+            ),
+        )));
+    }
+    let op = BaseOp::RotatedSub(lag / num_inner_cols);
+    if !config
+        .custom_gates
+        .selectors
+        .contains_key(&(op.clone(), 0, 0))
+    {
+        return Err(Box::new(CircuitError::OpLayout(
+            "lagged_diff".to_string(),
+            format!("no {} gate, call configure_rotated_sub first", op),
+        )));
+    }
+
+    // the gate queries the input by rotation, so it needs to sit in the first input column
+    let input = region.assign(&config.custom_gates.inputs[0], &input)?;
+    let inner = input.get_inner()?;
+    let diff = sub(&[
+        inner.get_slice(&[lag..len])?,
+        inner.get_slice(&[0..len - lag])?,
+    ])?;
+
+    // each difference sits level with the later of its two elements
+    region.increment(lag);
+    let mut output = region.assign(&config.custom_gates.output, &diff.into())?;
+
+    let (_, rng) = op.query_offset_rng();
+    let mut crossing = vec![];
+    region.add_used_base_op(op.clone());
+    for i in 0..len - lag {
+        let (x, y, z) = config
+            .custom_gates
+            .output
+            .cartesian_coord(region.linear_coord() + i);
+        // the lagged element is in the previous block's column
+        if z + 1 < rng {
+            crossing.push(i);
+            continue;
+        }
+        if !region.is_dummy() {
+            let selector = config.custom_gates.required_selector(&op, x, y)?;
+            region.enable(Some(selector), z)?;
+        }
+    }
+    region.increment(len - lag);
+
+    if !crossing.is_empty() {
+        let inner = input.get_inner_tensor()?;
+        let later: Tensor<ValType<F>> = crossing.iter().map(|i| inner[i + lag].clone()).collect();
+        let earlier: Tensor<ValType<F>> = crossing.iter().map(|i| inner[*i].clone()).collect();
+        let fallback = pairwise(config, region, &[later.into(), earlier.into()], BaseOp::Sub)?;
+        let fallback = fallback.get_inner_tensor()?;
+        let output = output.get_inner_tensor_mut()?;
+        for (k, i) in crossing.iter().enumerate() {
+            output[*i] = fallback[k].clone();
+        }
+    }
+
+    Ok(output)
+}
+
 /// max layout
 pub(crate) fn max<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
     config: &BaseConfig<F>,
@@ -3289,6 +4175,33 @@ pub(crate) fn softmax<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>
     percent(config, region, &[ex.clone()], input_scale, output_scale)
 }
 
+/// Checks that the expected public output and the actual output value differ by at most `bound`, in
+/// the fixed point representation of the values.
+pub(crate) fn range_check_abs<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>(
+    config: &BaseConfig<F>,
+    region: &mut RegionCtx<F>,
+    values: &[ValTensor<F>; 2],
+    bound: i128,
+) -> Result<ValTensor<F>, Box<dyn Error>> {
+    if bound == 0 {
+        // regular equality constraint
+        return enforce_equality(config, region, values);
+    }
+
+    let mut values = [values[0].clone(), values[1].clone()];
+
+    values[0] = region.assign(&config.custom_gates.inputs[0], &values[0])?;
+    values[1] = region.assign(&config.custom_gates.inputs[1], &values[1])?;
+    let total_assigned = std::cmp::max(values[0].len(), values[1].len());
+    region.increment(total_assigned);
+
+    // Calculate the difference between the expected output and actual output
+    let diff = pairwise(config, region, &values, BaseOp::Sub)?;
+
+    // check that it is within the tolerance range
+    range_check(config, region, &[diff], &(-bound, bound))
+}
+
 /// Checks that the percent error between the expected public output and the actual output value
 /// is within the percent error expressed by the `tol` input, where `tol == 1.0` means the percent
 /// error tolerance is 1 percent.
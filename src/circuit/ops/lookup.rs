@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::error::Error;
 
 use crate::{
-    circuit::{layouts, table::Range, utils},
+    circuit::{layouts, table::Range, utils, CircuitError},
     fieldutils::{felt_to_i128, i128_to_felt},
     graph::multiplier_to_scale,
     tensor::{self, Tensor, TensorError, TensorType},
@@ -23,6 +23,15 @@ pub enum LookupOp {
     Cast {
         scale: utils::F32,
     },
+    /// Quantizes float-domain inputs to the integer domain by dividing them by `scale`. Evaluates
+    /// like [LookupOp::Cast] but has its own table and name, so graph boundaries show as quantization.
+    Quantize {
+        scale: utils::F32,
+    },
+    /// The inverse of [LookupOp::Quantize], multiplying by `scale`.
+    Dequantize {
+        scale: utils::F32,
+    },
     ReLU,
     Max {
         scale: utils::F32,
@@ -47,12 +56,20 @@ pub enum LookupOp {
     Sqrt {
         scale: utils::F32,
     },
+    /// Inputs `x` with `|x| < epsilon` evaluate as `epsilon`, bounding the outputs near zero.
+    /// `epsilon` must be non negative.
     Rsqrt {
         scale: utils::F32,
+        #[serde(default)]
+        epsilon: i64,
     },
+    /// Inputs `x` with `|x| < epsilon` evaluate as `epsilon` with the sign of `x` (`+epsilon` for
+    /// zero), bounding the outputs near zero without flipping their sign.
     Recip {
         input_scale: utils::F32,
         output_scale: utils::F32,
+        #[serde(default)]
+        epsilon: i64,
     },
     LeakyReLU {
         slope: utils::F32,
@@ -126,15 +143,292 @@ pub enum LookupOp {
     HardSwish {
         scale: utils::F32,
     },
+    Piecewise {
+        breakpoints: Vec<i64>,
+        slopes: Vec<i64>,
+        intercept: i64,
+        scale: utils::F32,
+    },
+    RationalScale {
+        op: Box<LookupOp>,
+        num: i64,
+        den: i64,
+    },
+    /// A data-defined mapping, e.g. a learned lookup table or a ROM, see
+    /// [crate::circuit::BaseConfig::configure_lookup_precomputed]. The outputs are held by the op's
+    /// [crate::circuit::table::Table], the op only carries their content hash, see [LookupOp::precomputed],
+    /// so it can't be evaluated without its table.
+    Precomputed {
+        hash: u64,
+    },
+}
+
+/// Replaces the inputs within `epsilon` of zero by `epsilon` with their sign, zero going to
+/// `+epsilon`, see [LookupOp::Recip].
+pub(crate) fn epsilon_floor(x: &Tensor<i128>, epsilon: i64) -> Tensor<i128> {
+    let epsilon = epsilon as i128;
+    x.map(|x| match x {
+        x if x.abs() >= epsilon => x,
+        x if x < 0 => -epsilon,
+        _ => epsilon,
+    })
+}
+
+/// 64 bit FNV-1a, writing integers little endian and `usize`/`isize` as 64 bits so that hashes don't
+/// depend on the platform.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf29ce484222325)
+    }
+}
+
+impl std::hash::Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64)
+    }
+
+    fn write_i16(&mut self, i: i16) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_i32(&mut self, i: i32) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_i64(&mut self, i: i64) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_i128(&mut self, i: i128) {
+        self.write(&i.to_le_bytes())
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_i64(i as i64)
+    }
 }
 
 impl LookupOp {
+    /// A boolean zero indicator, `1` where the input is zero and `0` elsewhere, as a lookup. The
+    /// [LookupOp::KroneckerDelta] table already is one, so this is just another name for it; for a
+    /// zero indicator that needs no table see [crate::circuit::ops::poly::PolyOp::IsZero].
+    pub const IS_ZERO: LookupOp = LookupOp::KroneckerDelta;
+
+    /// A hash of the op that is the same across processes and platforms, for keying caches kept
+    /// outside of the process. It covers the variant and its parameters, floats by their bit
+    /// representation (with all NaNs, and both zeros, alike so that equal ops hash equal).
+    pub fn stable_hash(&self) -> u64 {
+        let mut hasher = StableHasher::default();
+        std::hash::Hash::hash(self, &mut hasher);
+        std::hash::Hasher::finish(&hasher)
+    }
+
+    /// The [LookupOp::Precomputed] of the mapping of each input `range.0 + i` to `outputs[i]`, keyed
+    /// on a hash of `range` and `outputs` so that distinct mappings are distinct ops.
+    pub fn precomputed<F: PrimeField + TensorType>(range: Range, outputs: &Tensor<F>) -> LookupOp {
+        let mut hasher = StableHasher::default();
+        std::hash::Hash::hash(&range, &mut hasher);
+        for y in outputs.iter() {
+            std::hash::Hasher::write(&mut hasher, y.to_repr().as_ref());
+        }
+        LookupOp::Precomputed {
+            hash: std::hash::Hasher::finish(&hasher),
+        }
+    }
+
     /// Returns the range of values that can be represented by the table
     pub fn bit_range(max_len: usize) -> Range {
         let range = (max_len - 1) as f64 / 2_f64;
         let range = range as i128;
         (-range, range)
     }
+
+    /// Checks the op's parameters describe a meaningful table: scales and denominators must be
+    /// positive, other parameters finite, and piecewise definitions consistent.
+    pub fn validate(&self) -> Result<(), CircuitError> {
+        let invalid = |msg: String| {
+            Err(CircuitError::InvalidLookupOp(format!(
+                "{:?}: {}",
+                self, msg
+            )))
+        };
+        let positive = |name: &str, v: &utils::F32| {
+            if v.0.is_finite() && v.0 > 0.0 {
+                Ok(())
+            } else {
+                invalid(format!("{} must be positive, got {}", name, v.0))
+            }
+        };
+        let finite = |name: &str, v: &utils::F32| {
+            if v.0.is_finite() {
+                Ok(())
+            } else {
+                invalid(format!("{} must be finite, got {}", name, v.0))
+            }
+        };
+        let non_negative = |name: &str, v: i64| {
+            if v >= 0 {
+                Ok(())
+            } else {
+                invalid(format!("{} must be non negative, got {}", name, v))
+            }
+        };
+
+        match self {
+            LookupOp::Abs | LookupOp::ReLU | LookupOp::Sign | LookupOp::KroneckerDelta => Ok(()),
+            LookupOp::Div { denom } => positive("denom", denom),
+            LookupOp::Recip {
+                input_scale,
+                output_scale,
+                epsilon,
+            } => {
+                positive("input_scale", input_scale)?;
+                positive("output_scale", output_scale)?;
+                non_negative("epsilon", *epsilon)
+            }
+            LookupOp::Rsqrt { scale, epsilon } => {
+                positive("scale", scale)?;
+                non_negative("epsilon", *epsilon)
+            }
+            LookupOp::LeakyReLU { slope } => finite("slope", slope),
+            LookupOp::GreaterThan { a }
+            | LookupOp::LessThan { a }
+            | LookupOp::GreaterThanEqual { a }
+            | LookupOp::LessThanEqual { a } => finite("a", a),
+            LookupOp::Max { scale, a }
+            | LookupOp::Min { scale, a }
+            | LookupOp::Pow { scale, a } => {
+                positive("scale", scale)?;
+                finite("a", a)
+            }
+            LookupOp::Piecewise {
+                breakpoints,
+                slopes,
+                scale,
+                ..
+            } => {
+                positive("scale", scale)?;
+                if slopes.len() != breakpoints.len() + 1 {
+                    return invalid(format!(
+                        "expected {} slopes for {} breakpoints, got {}",
+                        breakpoints.len() + 1,
+                        breakpoints.len(),
+                        slopes.len()
+                    ));
+                }
+                if breakpoints.windows(2).any(|w| w[0] > w[1]) {
+                    return invalid("breakpoints must be sorted".to_string());
+                }
+                Ok(())
+            }
+            LookupOp::RationalScale { op, num, den } => {
+                if *den == 0 || (*num > 0) != (*den > 0) {
+                    return invalid(format!("scale {}/{} must be positive", num, den));
+                }
+                op.validate()
+            }
+            LookupOp::Precomputed { .. } => Ok(()),
+            LookupOp::Cast { scale }
+            | LookupOp::Quantize { scale }
+            | LookupOp::Dequantize { scale }
+            | LookupOp::Ceil { scale }
+            | LookupOp::Floor { scale }
+            | LookupOp::Round { scale }
+            | LookupOp::RoundHalfToEven { scale }
+            | LookupOp::Sqrt { scale }
+            | LookupOp::Sigmoid { scale }
+            | LookupOp::Ln { scale }
+            | LookupOp::Exp { scale }
+            | LookupOp::Cos { scale }
+            | LookupOp::ACos { scale }
+            | LookupOp::Cosh { scale }
+            | LookupOp::ACosh { scale }
+            | LookupOp::Sin { scale }
+            | LookupOp::ASin { scale }
+            | LookupOp::Sinh { scale }
+            | LookupOp::ASinh { scale }
+            | LookupOp::Tan { scale }
+            | LookupOp::ATan { scale }
+            | LookupOp::Tanh { scale }
+            | LookupOp::ATanh { scale }
+            | LookupOp::Erf { scale }
+            | LookupOp::HardSwish { scale } => positive("scale", scale),
+        }
+    }
+
+    /// Evaluates a scale-parameterised activation at the exact rational scale `num / den`
+    /// rather than at the `f32` scale stored on the op, so quantization happens once at the output.
+    pub fn f_rational(
+        &self,
+        x: &Tensor<i128>,
+        num: i64,
+        den: i64,
+    ) -> Result<Tensor<i128>, TensorError> {
+        if den == 0 {
+            return Err(TensorError::Unsupported);
+        }
+        let scale = num as f64 / den as f64;
+        match self {
+            LookupOp::Ceil { .. } => Ok(tensor::ops::nonlinearities::ceil(x, scale)),
+            LookupOp::Floor { .. } => Ok(tensor::ops::nonlinearities::floor(x, scale)),
+            LookupOp::Round { .. } => Ok(tensor::ops::nonlinearities::round(x, scale)),
+            LookupOp::RoundHalfToEven { .. } => {
+                Ok(tensor::ops::nonlinearities::round_half_to_even(x, scale))
+            }
+            LookupOp::Sigmoid { .. } => Ok(tensor::ops::nonlinearities::sigmoid(x, scale)),
+            LookupOp::Sqrt { .. } => Ok(tensor::ops::nonlinearities::sqrt(x, scale)),
+            LookupOp::Rsqrt { epsilon, .. } => Ok(tensor::ops::nonlinearities::rsqrt(
+                &epsilon_floor(x, *epsilon),
+                scale,
+            )),
+            LookupOp::Erf { .. } => Ok(tensor::ops::nonlinearities::erffunc(x, scale)),
+            LookupOp::Exp { .. } => Ok(tensor::ops::nonlinearities::exp(x, scale)),
+            LookupOp::Ln { .. } => Ok(tensor::ops::nonlinearities::ln(x, scale)),
+            LookupOp::Cos { .. } => Ok(tensor::ops::nonlinearities::cos(x, scale)),
+            LookupOp::ACos { .. } => Ok(tensor::ops::nonlinearities::acos(x, scale)),
+            LookupOp::Cosh { .. } => Ok(tensor::ops::nonlinearities::cosh(x, scale)),
+            LookupOp::ACosh { .. } => Ok(tensor::ops::nonlinearities::acosh(x, scale)),
+            LookupOp::Sin { .. } => Ok(tensor::ops::nonlinearities::sin(x, scale)),
+            LookupOp::ASin { .. } => Ok(tensor::ops::nonlinearities::asin(x, scale)),
+            LookupOp::Sinh { .. } => Ok(tensor::ops::nonlinearities::sinh(x, scale)),
+            LookupOp::ASinh { .. } => Ok(tensor::ops::nonlinearities::asinh(x, scale)),
+            LookupOp::Tan { .. } => Ok(tensor::ops::nonlinearities::tan(x, scale)),
+            LookupOp::ATan { .. } => Ok(tensor::ops::nonlinearities::atan(x, scale)),
+            LookupOp::ATanh { .. } => Ok(tensor::ops::nonlinearities::atanh(x, scale)),
+            LookupOp::Tanh { .. } => Ok(tensor::ops::nonlinearities::tanh(x, scale)),
+            LookupOp::HardSwish { .. } => Ok(tensor::ops::nonlinearities::hardswish(x, scale)),
+            _ => Err(TensorError::Unsupported),
+        }
+    }
 }
 
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for LookupOp {
@@ -193,11 +487,20 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
                 &x,
                 f32::from(*scale).into(),
             )),
+            LookupOp::Quantize { scale } => Ok(tensor::ops::nonlinearities::const_div(
+                &x,
+                f32::from(*scale).into(),
+            )),
+            LookupOp::Dequantize { scale } => Ok(tensor::ops::nonlinearities::const_mult(
+                &x,
+                f32::from(*scale).into(),
+            )),
             LookupOp::Recip {
                 input_scale,
                 output_scale,
+                epsilon,
             } => Ok(tensor::ops::nonlinearities::recip(
-                &x,
+                &epsilon_floor(&x, *epsilon),
                 input_scale.into(),
                 output_scale.into(),
             )),
@@ -210,7 +513,10 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
                 Ok(tensor::ops::nonlinearities::sigmoid(&x, scale.into()))
             }
             LookupOp::Sqrt { scale } => Ok(tensor::ops::nonlinearities::sqrt(&x, scale.into())),
-            LookupOp::Rsqrt { scale } => Ok(tensor::ops::nonlinearities::rsqrt(&x, scale.into())),
+            LookupOp::Rsqrt { scale, epsilon } => Ok(tensor::ops::nonlinearities::rsqrt(
+                &epsilon_floor(&x, *epsilon),
+                scale.into(),
+            )),
             LookupOp::Erf { scale } => Ok(tensor::ops::nonlinearities::erffunc(&x, scale.into())),
             LookupOp::Exp { scale } => Ok(tensor::ops::nonlinearities::exp(&x, scale.into())),
             LookupOp::Ln { scale } => Ok(tensor::ops::nonlinearities::ln(&x, scale.into())),
@@ -229,6 +535,21 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
             LookupOp::HardSwish { scale } => {
                 Ok(tensor::ops::nonlinearities::hardswish(&x, scale.into()))
             }
+            LookupOp::Piecewise {
+                breakpoints,
+                slopes,
+                intercept,
+                scale,
+            } => tensor::ops::nonlinearities::piecewise_linear(
+                &x,
+                breakpoints,
+                slopes,
+                *intercept,
+                scale.into(),
+            ),
+            LookupOp::RationalScale { op, num, den } => op.f_rational(&x, *num, *den),
+            // the mapping only lives in its table
+            LookupOp::Precomputed { .. } => Err(TensorError::TableLookupError),
         }?;
 
         let output = res.map(|x| i128_to_felt(x));
@@ -256,19 +577,33 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
             LookupOp::Recip {
                 input_scale,
                 output_scale,
+                epsilon: 0,
             } => format!(
                 "RECIP(input_scale={}, output_scale={})",
                 input_scale, output_scale
             ),
+            LookupOp::Recip {
+                input_scale,
+                output_scale,
+                epsilon,
+            } => format!(
+                "RECIP(input_scale={}, output_scale={}, epsilon={})",
+                input_scale, output_scale, epsilon
+            ),
             LookupOp::Div { denom, .. } => format!("DIV(denom={})", denom),
             LookupOp::Cast { scale } => format!("CAST(scale={})", scale),
+            LookupOp::Quantize { scale } => format!("QUANTIZE(scale={})", scale),
+            LookupOp::Dequantize { scale } => format!("DEQUANTIZE(scale={})", scale),
             LookupOp::Ln { scale } => format!("LN(scale={})", scale),
             LookupOp::ReLU => "RELU".to_string(),
             LookupOp::LeakyReLU { slope: a } => format!("L_RELU(slope={})", a),
             LookupOp::Sigmoid { scale } => format!("SIGMOID(scale={})", scale),
             LookupOp::Sqrt { scale } => format!("SQRT(scale={})", scale),
             LookupOp::Erf { scale } => format!("ERF(scale={})", scale),
-            LookupOp::Rsqrt { scale } => format!("RSQRT(scale={})", scale),
+            LookupOp::Rsqrt { scale, epsilon: 0 } => format!("RSQRT(scale={})", scale),
+            LookupOp::Rsqrt { scale, epsilon } => {
+                format!("RSQRT(scale={}, epsilon={})", scale, epsilon)
+            }
             LookupOp::Exp { scale } => format!("EXP(scale={})", scale),
             LookupOp::Tan { scale } => format!("TAN(scale={})", scale),
             LookupOp::ATan { scale } => format!("ATAN(scale={})", scale),
@@ -283,6 +618,19 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
             LookupOp::Sinh { scale } => format!("SINH(scale={})", scale),
             LookupOp::ASinh { scale } => format!("ASINH(scale={})", scale),
             LookupOp::HardSwish { scale } => format!("HARDSWISH(scale={})", scale),
+            LookupOp::Piecewise {
+                breakpoints,
+                slopes,
+                intercept,
+                scale,
+            } => format!(
+                "PIECEWISE(breakpoints={:?}, slopes={:?}, intercept={}, scale={})",
+                breakpoints, slopes, intercept, scale
+            ),
+            LookupOp::RationalScale { op, num, den } => {
+                format!("{}(scale={}/{})", Op::<F>::as_string(op.as_ref()), num, den)
+            }
+            LookupOp::Precomputed { hash } => format!("PRECOMPUTED({:016x})", hash),
         }
     }
 
@@ -303,10 +651,14 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
     /// Returns the scale of the output of the operation.
     fn out_scale(&self, inputs_scale: Vec<crate::Scale>) -> Result<crate::Scale, Box<dyn Error>> {
         let scale = match self {
-            LookupOp::Cast { scale } => {
+            LookupOp::Cast { scale } | LookupOp::Quantize { scale } => {
                 let in_scale = inputs_scale[0];
                 in_scale + multiplier_to_scale(1. / scale.0 as f64)
             }
+            LookupOp::Dequantize { scale } => {
+                let in_scale = inputs_scale[0];
+                in_scale + multiplier_to_scale(scale.0 as f64)
+            }
             LookupOp::Recip { output_scale, .. } => multiplier_to_scale(output_scale.into()),
             LookupOp::Sign
             | LookupOp::GreaterThan { .. }
@@ -314,6 +666,7 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Op<F> for Lookup
             | LookupOp::GreaterThanEqual { .. }
             | LookupOp::LessThanEqual { .. }
             | LookupOp::KroneckerDelta => 0,
+            LookupOp::RationalScale { op, .. } => Op::<F>::out_scale(op.as_ref(), inputs_scale)?,
             _ => inputs_scale[0],
         };
         Ok(scale)
@@ -31,6 +31,19 @@ pub struct ForwardResult<F: PrimeField + TensorType + PartialOrd + std::hash::Ha
     pub(crate) output: Tensor<F>,
 }
 
+/// An element of a claimed output that differs from the reference output, see [Op::safe_mode_check_verbose].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementDiff {
+    /// flat index of the element
+    pub index: usize,
+    /// the output laid out in the circuit
+    pub claimed: i128,
+    /// the output of [Op::f]
+    pub expected: i128,
+    /// `claimed - expected`
+    pub diff: i128,
+}
+
 /// A trait representing operations that can be represented as constraints in a circuit.
 pub trait Op<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>:
     std::fmt::Debug + Send + Sync + Any
@@ -98,6 +111,59 @@ pub trait Op<F: PrimeField + TensorType + PartialOrd + std::hash::Hash>:
 
         Ok(())
     }
+
+    /// Like [Op::safe_mode_check] but rather than panicking, returns every element of `claimed_output`
+    /// that differs from the reference output. An empty list means the reference output couldn't be computed.
+    fn safe_mode_check_verbose(
+        &self,
+        claimed_output: &ValTensor<F>,
+        original_values: &[ValTensor<F>],
+    ) -> Result<(), Vec<ElementDiff>> {
+        let evaluate = || -> Result<(Tensor<F>, Tensor<F>), TensorError> {
+            let felt_evals = original_values
+                .iter()
+                .map(|v| v.get_felt_evals().map_err(|_| TensorError::FeltError))
+                .collect::<Result<Vec<_>, _>>()?;
+            let ref_op = self.f(&felt_evals)?.output;
+            let output = claimed_output
+                .get_felt_evals()
+                .map_err(|_| TensorError::FeltError)?;
+            Ok((output, ref_op))
+        };
+        let (output, ref_op) = evaluate().map_err(|e| {
+            log::error!(
+                "safe mode check for {} failed to evaluate: {}",
+                self.as_string(),
+                e
+            );
+            vec![]
+        })?;
+
+        let diffs = output
+            .iter()
+            .zip(ref_op.iter())
+            .enumerate()
+            .filter(|(_, (claimed, expected))| claimed != expected)
+            .map(|(index, (claimed, expected))| {
+                let (claimed, expected) = (
+                    crate::fieldutils::felt_to_i128(*claimed),
+                    crate::fieldutils::felt_to_i128(*expected),
+                );
+                ElementDiff {
+                    index,
+                    claimed,
+                    expected,
+                    diff: claimed - expected,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if diffs.is_empty() && output.len() == ref_op.len() {
+            Ok(())
+        } else {
+            Err(diffs)
+        }
+    }
 }
 
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Clone for Box<dyn Op<F>> {
@@ -83,6 +83,28 @@ pub enum PolyOp {
         scale_factor: Vec<usize>,
     },
     Not,
+    /// `1` where the input is zero and `0` elsewhere, using the [crate::circuit::ops::base::BaseOp::IsZero]
+    /// gate, see [crate::circuit::BaseConfig::configure_is_zero]. Needs no lookup table.
+    IsZero,
+    /// `a * b + c` element-wise, using the [crate::circuit::ops::base::BaseOp::MultAdd] gate which
+    /// needs a third input column to have been passed to [crate::circuit::BaseConfig::configure]
+    MultAdd,
+    /// `a + b` clamped to `[lo, hi]`, see [crate::circuit::BaseConfig::configure_saturating]
+    SatAdd {
+        lo: i64,
+        hi: i64,
+    },
+    /// `a - b` clamped to `[lo, hi]`, see [crate::circuit::BaseConfig::configure_saturating]
+    SatSub {
+        lo: i64,
+        hi: i64,
+    },
+    /// `x[i + lag] - x[i]` over the flattened input, using the
+    /// [crate::circuit::ops::base::BaseOp::RotatedSub] gate configured by
+    /// [crate::circuit::BaseConfig::configure_rotated_sub]
+    LaggedDiff {
+        lag: usize,
+    },
     And,
     Or,
     Xor,
@@ -156,6 +178,11 @@ impl<
             }
             PolyOp::Neg => "NEG".into(),
             PolyOp::Not => "NOT".into(),
+            PolyOp::IsZero => "ISZERO".into(),
+            PolyOp::MultAdd => "MULTADD".into(),
+            PolyOp::SatAdd { lo, hi } => format!("SATADD (lo={}, hi={})", lo, hi),
+            PolyOp::SatSub { lo, hi } => format!("SATSUB (lo={}, hi={})", lo, hi),
+            PolyOp::LaggedDiff { lag } => format!("LAGGEDDIFF (lag={})", lag),
             PolyOp::And => "AND".into(),
             PolyOp::Or => "OR".into(),
             PolyOp::Xor => "XOR".into(),
@@ -183,6 +210,37 @@ impl<
             PolyOp::Or => tensor::ops::or(&inputs[0], &inputs[1]),
             PolyOp::Xor => tensor::ops::xor(&inputs[0], &inputs[1]),
             PolyOp::Not => tensor::ops::not(&inputs[0]),
+            PolyOp::IsZero => Ok(inputs[0].map(|x| if x == F::ZERO { F::ONE } else { F::ZERO })),
+            PolyOp::MultAdd => {
+                if inputs.len() != 3 {
+                    return Err(TensorError::DimMismatch("multadd inputs".to_string()));
+                }
+                let product = tensor::ops::mult(&inputs[0..2])?;
+                tensor::ops::add(&[product, inputs[2].clone()])
+            }
+            PolyOp::SatAdd { lo, hi } | PolyOp::SatSub { lo, hi } => {
+                if inputs.len() != 2 {
+                    return Err(TensorError::DimMismatch("saturating inputs".to_string()));
+                }
+                let unclamped = if matches!(self, PolyOp::SatAdd { .. }) {
+                    tensor::ops::add(&inputs)?
+                } else {
+                    tensor::ops::sub(&inputs)?
+                };
+                let (lo, hi) = (*lo as i128, *hi as i128);
+                Ok(unclamped.map(|x| i128_to_felt(felt_to_i128(x).clamp(lo, hi))))
+            }
+            PolyOp::LaggedDiff { lag } => {
+                let mut x = inputs[0].clone();
+                x.flatten();
+                if *lag == 0 || *lag >= x.len() {
+                    return Err(TensorError::DimMismatch("lagged diff".to_string()));
+                }
+                tensor::ops::sub(&[
+                    x.get_slice(&[*lag..x.len()])?,
+                    x.get_slice(&[0..x.len() - lag])?,
+                ])
+            }
             PolyOp::Downsample {
                 axis,
                 stride,
@@ -324,6 +382,23 @@ impl<
             PolyOp::Or => layouts::or(config, region, values[..].try_into()?)?,
             PolyOp::And => layouts::and(config, region, values[..].try_into()?)?,
             PolyOp::Not => layouts::not(config, region, values[..].try_into()?)?,
+            PolyOp::IsZero => layouts::is_zero(config, region, values[..].try_into()?)?,
+            PolyOp::MultAdd => layouts::mult_add(config, region, values[..].try_into()?)?,
+            PolyOp::LaggedDiff { lag } => {
+                layouts::lagged_diff(config, region, values[..].try_into()?, *lag)?
+            }
+            PolyOp::SatAdd { lo, hi } => layouts::saturating(
+                config,
+                region,
+                values[..].try_into()?,
+                BaseOp::SatAdd { lo: *lo, hi: *hi },
+            )?,
+            PolyOp::SatSub { lo, hi } => layouts::saturating(
+                config,
+                region,
+                values[..].try_into()?,
+                BaseOp::SatSub { lo: *lo, hi: *hi },
+            )?,
             PolyOp::MoveAxis {
                 source,
                 destination,
@@ -433,7 +508,7 @@ impl<
     fn out_scale(&self, in_scales: Vec<crate::Scale>) -> Result<crate::Scale, Box<dyn Error>> {
         let scale = match self {
             PolyOp::MeanOfSquares { .. } => 2 * in_scales[0],
-            PolyOp::Xor | PolyOp::Or | PolyOp::And | PolyOp::Not => 0,
+            PolyOp::Xor | PolyOp::Or | PolyOp::And | PolyOp::Not | PolyOp::IsZero => 0,
             PolyOp::Iff => in_scales[1],
             PolyOp::Einsum { .. } => {
                 let mut scale = in_scales[0];
@@ -471,7 +546,7 @@ impl<
                 scale_a
             }
             PolyOp::Sub => in_scales[0],
-            PolyOp::Mult => {
+            PolyOp::Mult | PolyOp::MultAdd => {
                 let mut scale = in_scales[0];
                 scale += in_scales[1];
                 scale
@@ -485,7 +560,10 @@ impl<
     }
 
     fn requires_homogenous_input_scales(&self) -> Vec<usize> {
-        if matches!(self, PolyOp::Add { .. } | PolyOp::Sub) {
+        if matches!(
+            self,
+            PolyOp::Add { .. } | PolyOp::Sub | PolyOp::SatAdd { .. } | PolyOp::SatSub { .. }
+        ) {
             vec![0, 1]
         } else if matches!(self, PolyOp::Iff) {
             vec![1, 2]
@@ -19,7 +19,7 @@ use std::{
     },
 };
 
-use super::lookup::LookupOp;
+use super::{base::BaseOp, lookup::LookupOp};
 
 /// Constants map
 pub type ConstantsMap<F> = HashMap<F, ValType<F>>;
@@ -133,6 +133,7 @@ pub struct RegionCtx<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Ha
     shuffle_index: ShuffleIndex,
     used_lookups: HashSet<LookupOp>,
     used_range_checks: HashSet<Range>,
+    used_base_ops: HashSet<BaseOp>,
     max_lookup_inputs: i128,
     min_lookup_inputs: i128,
     max_range_size: i128,
@@ -205,6 +206,7 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             shuffle_index: ShuffleIndex::default(),
             used_lookups: HashSet::new(),
             used_range_checks: HashSet::new(),
+            used_base_ops: HashSet::new(),
             max_lookup_inputs: 0,
             min_lookup_inputs: 0,
             max_range_size: 0,
@@ -242,6 +244,7 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             shuffle_index,
             used_lookups: HashSet::new(),
             used_range_checks: HashSet::new(),
+            used_base_ops: HashSet::new(),
             max_lookup_inputs: 0,
             min_lookup_inputs: 0,
             max_range_size: 0,
@@ -264,6 +267,7 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             shuffle_index: ShuffleIndex::default(),
             used_lookups: HashSet::new(),
             used_range_checks: HashSet::new(),
+            used_base_ops: HashSet::new(),
             max_lookup_inputs: 0,
             min_lookup_inputs: 0,
             max_range_size: 0,
@@ -289,6 +293,7 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
             shuffle_index: ShuffleIndex::default(),
             used_lookups: HashSet::new(),
             used_range_checks: HashSet::new(),
+            used_base_ops: HashSet::new(),
             max_lookup_inputs: 0,
             min_lookup_inputs: 0,
             max_range_size: 0,
@@ -347,6 +352,7 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         let min_lookup_inputs = AtomicInt::new(self.min_lookup_inputs());
         let lookups = Arc::new(Mutex::new(self.used_lookups.clone()));
         let range_checks = Arc::new(Mutex::new(self.used_range_checks.clone()));
+        let base_ops = Arc::new(Mutex::new(self.used_base_ops.clone()));
         let dynamic_lookup_index = Arc::new(Mutex::new(self.dynamic_lookup_index.clone()));
         let shuffle_index = Arc::new(Mutex::new(self.shuffle_index.clone()));
         let constants = Arc::new(Mutex::new(self.assigned_constants.clone()));
@@ -381,6 +387,9 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
                 // update the range checks
                 let mut range_checks = range_checks.lock().unwrap();
                 range_checks.extend(local_reg.used_range_checks());
+                // update the base ops
+                let mut base_ops = base_ops.lock().unwrap();
+                base_ops.extend(local_reg.used_base_ops());
                 // update the dynamic lookup index
                 let mut dynamic_lookup_index = dynamic_lookup_index.lock().unwrap();
                 dynamic_lookup_index.update(&local_reg.dynamic_lookup_index);
@@ -429,6 +438,12 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
                     e
                 ))
             })?;
+        self.used_base_ops = Arc::try_unwrap(base_ops)
+            .map_err(|e| RegionError::from(format!("dummy_loop: failed to get base ops: {:?}", e)))?
+            .into_inner()
+            .map_err(|e| {
+                RegionError::from(format!("dummy_loop: failed to get base ops: {:?}", e))
+            })?;
         self.shuffle_index = Arc::try_unwrap(shuffle_index)
             .map_err(|e| {
                 RegionError::from(format!("dummy_loop: failed to get shuffle index: {:?}", e))
@@ -500,6 +515,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         self.update_max_min_lookup_range(range)
     }
 
+    /// add used element-wise [BaseOp], whose gate selectors are enabled by the layout
+    pub fn add_used_base_op(&mut self, op: BaseOp) {
+        self.used_base_ops.insert(op);
+    }
+
     /// Get the offset
     pub fn row(&self) -> usize {
         self.row
@@ -510,6 +530,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         self.linear_coord
     }
 
+    /// Number of inner columns per row
+    pub fn num_inner_cols(&self) -> usize {
+        self.num_inner_cols
+    }
+
     /// Get the total number of constants
     pub fn total_constants(&self) -> usize {
         self.assigned_constants.len()
@@ -545,6 +570,11 @@ impl<'a, F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RegionCtx<'a
         self.used_range_checks.clone()
     }
 
+    /// get used element-wise base ops
+    pub fn used_base_ops(&self) -> HashSet<BaseOp> {
+        self.used_base_ops.clone()
+    }
+
     /// max lookup inputs
     pub fn max_lookup_inputs(&self) -> i128 {
         self.max_lookup_inputs
@@ -1,4 +1,13 @@
-use std::{error::Error, marker::PhantomData};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    error::Error,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use halo2curves::ff::PrimeField;
 
@@ -8,11 +17,13 @@ use halo2_proofs::{
 };
 use log::{debug, warn};
 use maybe_rayon::prelude::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use crate::{
     circuit::CircuitError,
-    fieldutils::i128_to_felt,
-    tensor::{Tensor, TensorType},
+    fieldutils::{felt_to_i128, field_byte_size, i128_to_felt},
+    tensor::{Tensor, TensorType, VarTensor},
 };
 
 use crate::circuit::lookup::LookupOp;
@@ -22,11 +33,75 @@ use super::Op;
 /// The range of the lookup table.
 pub type Range = (i128, i128);
 
-/// The safety factor for the range of the lookup table.
+/// Errors laying out a lookup table or range check.
+#[derive(Debug, Error)]
+pub enum TableError {
+    /// The table was already assigned
+    #[error("attempting to initialize an already instantiated lookup table")]
+    AlreadyAssigned,
+    /// Halo2 failed to assign the table's cells
+    #[error("failed to assign table: {0}")]
+    Assignment(#[from] halo2_proofs::plonk::Error),
+    /// The range's lower end exceeds its upper end
+    #[error("invalid table range: {0:?}")]
+    RangeInvalid(Range),
+    /// The table's op could not be evaluated over its range
+    #[error("failed to evaluate table: {0}")]
+    Evaluation(String),
+}
+
+/// The safety factor for the range of the lookup table. Tables and range checks are configured at exactly
+/// the range they are given; the factor is applied by calibration, through its `lookup_safety_margin`
+/// which defaults to this value. A margin of 1 configures tables at the exact data range, halving them,
+/// at the cost of any input outside of that range failing its lookup, so that witness generation errors
+/// and no valid proof can be produced for it.
 pub const RANGE_MULTIPLIER: i128 = 2;
 /// The safety factor offset for the number of rows in the lookup table.
 pub const RESERVED_BLINDING_ROWS_PAD: usize = 3;
 
+/// Rows reserved at the end of table columns on top of the constraint system's blinding factors, per
+/// kind of table. Each pad is at least 1, as halo2 also reserves the row after the blinding rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlindingRowsPad {
+    /// pad for static lookup tables
+    pub tables: usize,
+    /// pad for range check tables
+    pub range_checks: usize,
+}
+
+impl Default for BlindingRowsPad {
+    fn default() -> Self {
+        BlindingRowsPad {
+            tables: RESERVED_BLINDING_ROWS_PAD,
+            range_checks: RESERVED_BLINDING_ROWS_PAD,
+        }
+    }
+}
+
+/// The integer `input` encodes, if it encodes one within `range`. Field elements beyond the `i128`
+/// range don't survive the round trip through [felt_to_i128] and are rejected.
+pub(crate) fn felt_to_int_in_range<F: PrimeField + PartialOrd>(
+    input: F,
+    range: Range,
+) -> Option<i128> {
+    let int = felt_to_i128(input);
+    (i128_to_felt::<F>(int) == input && range.0 <= int && int <= range.1).then_some(int)
+}
+
+/// The order in which the cells of a lookup table are assigned.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AssignmentOrder {
+    /// Fill each column top to bottom before moving on to the next one.
+    #[default]
+    ColumnMajor,
+    /// Interleave across columns, assigning one row of every column at a time.
+    RowMajor,
+}
+
+/// Largest [SelectorConstructor::degree] whose products are evaluated serially: for the few factors
+/// of small degrees rayon's task overhead outweighs the work.
+pub const SERIAL_SELECTOR_MAX_DEGREE: usize = 4;
+
 #[derive(Debug, Clone)]
 ///
 pub struct SelectorConstructor<F: PrimeField> {
@@ -47,40 +122,221 @@ impl<F: PrimeField> SelectorConstructor<F> {
 
     ///
     pub fn get_expr_at_idx(&self, i: usize, expr: Expression<F>) -> Expression<F> {
+        if self.degree <= SERIAL_SELECTOR_MAX_DEGREE {
+            self.get_expr_at_idx_serial(i, expr)
+        } else {
+            self.get_expr_at_idx_par(i, expr)
+        }
+    }
+
+    /// [SelectorConstructor::get_expr_at_idx] evaluated on the current thread.
+    pub fn get_expr_at_idx_serial(&self, i: usize, expr: Expression<F>) -> Expression<F> {
+        (0..self.degree)
+            .filter(|x| *x != i)
+            .map(|i| Self::expr_factor(i, &expr))
+            .fold(Expression::Constant(F::from(1_u64)), |acc, x| acc * x)
+    }
+
+    /// [SelectorConstructor::get_expr_at_idx] evaluated with a parallel iterator.
+    pub fn get_expr_at_idx_par(&self, i: usize, expr: Expression<F>) -> Expression<F> {
         let indices = 0..self.degree;
         indices
             .into_par_iter()
             .filter(|x| *x != i)
-            .map(|i| {
-                if i == 0 {
-                    expr.clone()
-                } else {
-                    (Expression::Constant(F::from(i as u64))) - expr.clone()
-                }
-            })
+            .map(|i| Self::expr_factor(i, &expr))
             .reduce(|| Expression::Constant(F::from(1_u64)), |acc, x| acc * x)
     }
 
+    fn expr_factor(i: usize, expr: &Expression<F>) -> Expression<F> {
+        if i == 0 {
+            expr.clone()
+        } else {
+            (Expression::Constant(F::from(i as u64))) - expr.clone()
+        }
+    }
+
     ///
     pub fn get_selector_val_at_idx(&self, i: usize) -> F {
+        if self.degree <= SERIAL_SELECTOR_MAX_DEGREE {
+            self.get_selector_val_at_idx_serial(i)
+        } else {
+            self.get_selector_val_at_idx_par(i)
+        }
+    }
+
+    /// [SelectorConstructor::get_selector_val_at_idx] evaluated on the current thread.
+    pub fn get_selector_val_at_idx_serial(&self, i: usize) -> F {
+        (0..self.degree)
+            .filter(|x| *x != i)
+            .map(|x| Self::selector_val_factor(x, i))
+            .product()
+    }
+
+    /// [SelectorConstructor::get_selector_val_at_idx] evaluated with a parallel iterator.
+    pub fn get_selector_val_at_idx_par(&self, i: usize) -> F {
         let indices = 0..self.degree;
         indices
             .into_par_iter()
             .filter(|x| *x != i)
-            .map(|x| {
-                if x == 0 {
-                    F::from(i as u64)
-                } else {
-                    F::from(x as u64) - F::from(i as u64)
-                }
-            })
+            .map(|x| Self::selector_val_factor(x, i))
             .product()
     }
+
+    fn selector_val_factor(x: usize, i: usize) -> F {
+        if x == 0 {
+            F::from(i as u64)
+        } else {
+            F::from(x as u64) - F::from(i as u64)
+        }
+    }
+
+    /// The polynomial degree of [SelectorConstructor::get_expr_at_idx] in its input expression, i.e.
+    /// `degree - 1`: lookups spanning several table columns raise the constraint degree by this much.
+    pub fn expr_degree(&self) -> usize {
+        self.degree.saturating_sub(1)
+    }
+
+    /// The multiplier of every column, i.e. [SelectorConstructor::get_selector_val_at_idx] for each index.
+    pub fn get_selector_vals(&self) -> Vec<F> {
+        (0..self.degree)
+            .map(|i| self.get_selector_val_at_idx(i))
+            .collect()
+    }
+}
+
+/// Warns when a table's op saturates on too many of its inputs, which usually means the op's scale
+/// is too large for the table's range. Outputs are counted once the whole table is evaluated, so a
+/// table logs at most one warning rather than one per saturated element.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClampWarning {
+    /// Outputs whose magnitude reaches this bound count as clamped.
+    pub max_output: i128,
+    /// Fraction of clamped outputs above which the warning is logged.
+    pub fraction: f64,
+}
+
+impl Default for ClampWarning {
+    fn default() -> Self {
+        // float to integer casts saturate at the bounds of i128
+        ClampWarning {
+            max_output: i128::MAX,
+            fraction: 0.1,
+        }
+    }
+}
+
+impl ClampWarning {
+    /// Number of `evals` whose magnitude reaches [ClampWarning::max_output].
+    pub fn count_clamped<F: PrimeField + TensorType + PartialOrd>(
+        &self,
+        evals: &Tensor<F>,
+    ) -> usize {
+        let bound = self.max_output.unsigned_abs();
+        evals
+            .iter()
+            .filter(|y| felt_to_i128(**y).unsigned_abs() >= bound)
+            .count()
+    }
+}
+
+/// Lookup table evaluations keyed by op and range, shared across threads so that tables common to
+/// several circuits are evaluated once. Cloning the cache shares it.
+#[derive(Clone, Debug)]
+pub struct TableEvalCache<F: PrimeField + TensorType> {
+    evals: Arc<Mutex<HashMap<(LookupOp, Range), Arc<Tensor<F>>>>>,
+    num_evaluations: Arc<AtomicUsize>,
+}
+
+impl<F: PrimeField + TensorType> Default for TableEvalCache<F> {
+    fn default() -> Self {
+        Self {
+            evals: Arc::new(Mutex::new(HashMap::new())),
+            num_evaluations: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> TableEvalCache<F> {
+    /// Returns a new, empty [TableEvalCache].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the evaluation of `nl` over `range`, computing it only if no table sharing this cache
+    /// has already done so.
+    pub fn get_or_eval(
+        &self,
+        nl: &LookupOp,
+        range: Range,
+    ) -> Result<Arc<Tensor<F>>, Box<dyn Error>> {
+        // holding the lock while evaluating ensures concurrent callers don't duplicate the work
+        let mut evals = self
+            .evals
+            .lock()
+            .map_err(|_| "table eval cache lock poisoned")?;
+        if let Some(evals) = evals.get(&(nl.clone(), range)) {
+            return Ok(evals.clone());
+        }
+        let inputs = Tensor::from(range.0..=range.1).map(|x| i128_to_felt(x));
+        let output = Arc::new(Op::<F>::f(nl, &[inputs])?.output);
+        self.num_evaluations.fetch_add(1, Ordering::SeqCst);
+        evals.insert((nl.clone(), range), output.clone());
+        Ok(output)
+    }
+
+    /// The number of table evaluations computed through this cache.
+    pub fn num_evaluations(&self) -> usize {
+        self.num_evaluations.load(Ordering::SeqCst)
+    }
+}
+
+/// The name of the file caching the evaluations of `nl` over `range`, keyed on [LookupOp::stable_hash]
+/// so that every process picks the same file.
+pub fn evals_cache_file_name(nl: &LookupOp, range: Range) -> String {
+    format!("{:016x}_{}_{}.evals", nl.stable_hash(), range.0, range.1)
+}
+
+/// Table evaluations as persisted by [Table::serialize_evals], field elements in their canonical representation.
+#[derive(Serialize, Deserialize)]
+struct SerializedEvals {
+    range: Range,
+    nonlinearity: LookupOp,
+    evals: Vec<Vec<u8>>,
+}
+
+fn write_evals<F: PrimeField + TensorType>(
+    path: &Path,
+    range: Range,
+    nl: &LookupOp,
+    evals: &Tensor<F>,
+) -> Result<(), Box<dyn Error>> {
+    let serialized = SerializedEvals {
+        range,
+        nonlinearity: nl.clone(),
+        evals: evals
+            .iter()
+            .map(|e| e.to_repr().as_ref().to_vec())
+            .collect(),
+    };
+    let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    bincode::serialize_into(writer, &serialized)?;
+    Ok(())
+}
+
+/// Table outputs computed outside of the crate, e.g. by a reference implementation, see [Table::from_precomputed].
+#[derive(Clone, Debug)]
+pub struct PrecomputedOutputs<F: PrimeField + TensorType> {
+    /// Name identifying the mapping in the table's regions and cells.
+    pub name: String,
+    /// Inputs the outputs are given for, within the table's range.
+    pub range: Range,
+    /// Output of each element of `range`, in order.
+    pub outputs: Arc<Tensor<F>>,
 }
 
 /// Halo2 lookup table for element wise non-linearities.
 #[derive(Clone, Debug)]
-pub struct Table<F: PrimeField> {
+pub struct Table<F: PrimeField + TensorType> {
     /// Non-linearity to be used in table.
     pub nonlinearity: LookupOp,
     /// Input to table.
@@ -91,34 +347,75 @@ pub struct Table<F: PrimeField> {
     pub table_outputs: Vec<TableColumn>,
     /// selector cn
     pub selector_constructor: SelectorConstructor<F>,
+    /// Multiplier of each column, computed from `selector_constructor` at configure time.
+    pub col_multipliers: Vec<F>,
     /// Flags if table has been previously assigned to.
     pub is_assigned: bool,
     /// Number of bits used in lookup table.
     pub range: Range,
+    /// Per-column sub-ranges for tables covering a non-uniform domain.
+    pub sub_ranges: Option<Vec<Range>>,
+    /// Evaluations shared with other tables, possibly of other circuits.
+    pub eval_cache: Option<TableEvalCache<F>>,
+    /// File the table's evaluations are persisted to and reloaded from across runs, see [Table::serialize_evals].
+    pub evals_file: Option<PathBuf>,
+    /// Warn when too many of the table's outputs are clamped, see [ClampWarning].
+    pub clamp_warning: Option<ClampWarning>,
+    /// Number and fraction of clamped outputs, set when the table's layout logged a [ClampWarning].
+    pub clamped: Option<(usize, f64)>,
+    /// Outputs assigned in place of the evaluations of [Table::nonlinearity], see [Table::from_precomputed].
+    pub precomputed: Option<PrecomputedOutputs<F>>,
     _marker: PhantomData<F>,
 }
 
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
-    /// get column index given input
-    pub fn get_col_index(&self, input: F) -> F {
+    /// get column index given input. Errors when the table covers a non-uniform domain, see
+    /// [Table::configure_nonuniform], and `input` lies outside of all of its sub-ranges.
+    pub fn get_col_index(&self, input: F) -> Result<F, CircuitError> {
+        if let Some(sub_ranges) = &self.sub_ranges {
+            let chunk = sub_ranges
+                .iter()
+                .position(|r| felt_to_int_in_range(input, *r).is_some())
+                .ok_or_else(|| {
+                    CircuitError::NonIntegerFieldElement(format!("{:?}", input), self.range)
+                })?;
+            return Ok(i128_to_felt(chunk as i128));
+        }
         //    range is split up into chunks of size col_size, find the chunk that input is in
         let chunk =
             (crate::fieldutils::felt_to_i128(input) - self.range.0).abs() / (self.col_size as i128);
 
-        i128_to_felt(chunk)
+        Ok(i128_to_felt(chunk))
+    }
+
+    /// Like [Table::get_col_index], but errors rather than returning a meaningless index when `input`
+    /// doesn't encode an integer covered by the table.
+    pub fn try_get_col_index(&self, input: F) -> Result<F, CircuitError> {
+        let covered = match &self.sub_ranges {
+            Some(sub_ranges) => sub_ranges
+                .iter()
+                .any(|r| felt_to_int_in_range(input, *r).is_some()),
+            None => felt_to_int_in_range(input, self.range).is_some(),
+        };
+        if !covered {
+            return Err(CircuitError::NonIntegerFieldElement(
+                format!("{:?}", input),
+                self.range,
+            ));
+        }
+        self.get_col_index(input)
     }
 
     /// get first_element of column
     pub fn get_first_element(&self, chunk: usize) -> (F, F) {
-        let chunk = chunk as i128;
-        // we index from 1 to prevent soundness issues
-        let first_element = i128_to_felt(chunk * (self.col_size as i128) + self.range.0);
-        let op_f = Op::<F>::f(
-            &self.nonlinearity,
-            &[Tensor::from(vec![first_element].into_iter())],
-        )
-        .unwrap();
-        (first_element, op_f.output[0])
+        let first_element = match &self.sub_ranges {
+            Some(sub_ranges) => sub_ranges[chunk].0,
+            // we index from 1 to prevent soundness issues
+            None => chunk as i128 * (self.col_size as i128) + self.range.0,
+        };
+        let input = Tensor::from(vec![i128_to_felt(first_element)].into_iter());
+        let output = self.eval(&input).unwrap();
+        (input[0], output[0])
     }
 
     ///
@@ -130,6 +427,19 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
     pub fn cal_bit_range(bits: usize, reserved_blinding_rows: usize) -> usize {
         2usize.pow(bits as u32) - reserved_blinding_rows
     }
+
+    /// The columns a table over `range` configured at `logrows` takes, counting its input and its
+    /// output columns, see [TableFootprint]. Assumes the minimum number of blinding rows and
+    /// [RESERVED_BLINDING_ROWS_PAD], as [Table::configure] does for a fresh [ConstraintSystem].
+    pub fn layout_footprint(range: Range, logrows: usize) -> TableFootprint {
+        let factors =
+            ConstraintSystem::<F>::default().blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        let col_size = Self::cal_col_size(logrows, factors);
+        TableFootprint::new::<F>(
+            2 * num_cols_required((range.1 - range.0).abs(), col_size),
+            col_size,
+        )
+    }
 }
 
 ///
@@ -138,8 +448,183 @@ pub fn num_cols_required(range_len: i128, col_size: usize) -> usize {
     (range_len / (col_size as i128)) as usize + 1
 }
 
+/// The table columns a [Table] or [RangeCheck] takes, and the cells they hold.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TableFootprint {
+    /// Number of table columns.
+    pub num_cols: usize,
+    /// Number of usable rows in each column.
+    pub col_size: usize,
+    /// Number of usable cells across all columns.
+    pub total_cells: usize,
+    /// Size of the usable cells in bytes, at [field_byte_size] bytes per cell.
+    pub total_bytes: usize,
+}
+
+impl TableFootprint {
+    fn new<F: PrimeField>(num_cols: usize, col_size: usize) -> Self {
+        let total_cells = num_cols * col_size;
+        Self {
+            num_cols,
+            col_size,
+            total_cells,
+            total_bytes: total_cells * field_byte_size::<F>(),
+        }
+    }
+}
+
+/// The smallest `k` such that `2^k - (blinding_factors + RESERVED_BLINDING_ROWS_PAD) >= rows_used`, i.e.
+/// the smallest circuit that fits the rows a dry run of the layout used.
+pub fn min_k_for_rows(rows_used: usize, blinding_factors: usize) -> u32 {
+    let total_rows = rows_used + blinding_factors + RESERVED_BLINDING_ROWS_PAD;
+    total_rows.next_power_of_two().trailing_zeros()
+}
+
+/// The table columns a set of lookups and range checks would take, see [estimate_columns].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnBudget {
+    /// Table columns of each lookup op, counting its input columns and its output columns.
+    pub lookups: Vec<(LookupOp, usize)>,
+    /// Table columns of each range check.
+    pub range_checks: Vec<(Range, usize)>,
+    /// Total table columns, counting the input columns shared by all lookup tables once.
+    pub total: usize,
+}
+
+/// Estimates the table columns that lookups of `ops` over `lookup_range` and range checks over
+/// `ranges` would take at `logrows`, so that `logrows` can be picked before configuring anything.
+/// `blinding_factors` is the [ConstraintSystem::blinding_factors] of the constraint system the tables
+/// will be configured in, which grows with the queries of the rest of the circuit. Assumes the
+/// default [BlindingRowsPad]. Ops that end up
+/// sharing another op's table (e.g. power-of-two rescalings) and ranges checked against an enclosing
+/// range's table are counted in full, so this is an upper bound on what [crate::circuit::BaseConfig::num_table_columns] reports after configuration.
+pub fn estimate_columns(
+    ops: &[LookupOp],
+    lookup_range: Range,
+    ranges: &[Range],
+    logrows: usize,
+    blinding_factors: usize,
+) -> ColumnBudget {
+    let pad = BlindingRowsPad::default();
+    let num_cols = |range: Range, pad: usize| {
+        let col_size = 2usize.pow(logrows as u32) - (blinding_factors + pad.max(1));
+        num_cols_required((range.1 - range.0).abs(), col_size)
+    };
+
+    let lookup_cols = num_cols(lookup_range, pad.tables);
+    let lookups = ops
+        .iter()
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|op| (op, 2 * lookup_cols))
+        .collect::<Vec<_>>();
+    let range_checks = ranges
+        .iter()
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|range| (range, num_cols(range, pad.range_checks)))
+        .collect::<Vec<_>>();
+
+    // every table has its own output columns on top of the shared input columns
+    let lookup_total = match lookups.len() {
+        0 => 0,
+        n => lookup_cols * (n + 1),
+    };
+    let total = lookup_total + range_checks.iter().map(|(_, n)| n).sum::<usize>();
+
+    ColumnBudget {
+        lookups,
+        range_checks,
+        total,
+    }
+}
+
+/// A difference between two directories of table dumps, see [diff_table_dumps].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TableDiff {
+    /// A table dumped only in the first directory.
+    OnlyInFirst(String),
+    /// A table dumped only in the second directory.
+    OnlyInSecond(String),
+    /// A table dumped in both directories whose contents differ.
+    Rows {
+        /// name of the table's dump
+        name: String,
+        /// indices of the differing rows, including rows present in only one of the dumps
+        rows: Vec<usize>,
+    },
+}
+
+/// Writes `rows` to the CSV file at `path` under `header`, see
+/// [crate::circuit::BaseConfig::dump_all_tables].
+pub fn write_table_dump(
+    path: &Path,
+    header: &[&str],
+    rows: impl IntoIterator<Item = Vec<i128>>,
+) -> Result<(), Box<dyn Error>> {
+    let mut wtr = csv::Writer::from_path(path)?;
+    wtr.write_record(header)?;
+    for row in rows {
+        wtr.write_record(row.iter().map(|v| v.to_string()))?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+fn read_table_dumps(dir: &Path) -> Result<HashMap<String, Vec<csv::StringRecord>>, Box<dyn Error>> {
+    let mut dumps = HashMap::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("csv") {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or("invalid table dump name")?
+            .to_string();
+        let records = csv::Reader::from_path(&path)?
+            .records()
+            .collect::<Result<Vec<_>, _>>()?;
+        dumps.insert(name, records);
+    }
+    Ok(dumps)
+}
+
+/// Compares the table dumps written to `dir_a` and `dir_b` by
+/// [crate::circuit::BaseConfig::dump_all_tables], returning the differences sorted by table name.
+pub fn diff_table_dumps(dir_a: &Path, dir_b: &Path) -> Result<Vec<TableDiff>, Box<dyn Error>> {
+    let dumps_a = read_table_dumps(dir_a)?;
+    let dumps_b = read_table_dumps(dir_b)?;
+
+    let names = dumps_a
+        .keys()
+        .chain(dumps_b.keys())
+        .cloned()
+        .collect::<BTreeSet<_>>();
+
+    let mut diffs = vec![];
+    for name in names {
+        match (dumps_a.get(&name), dumps_b.get(&name)) {
+            (Some(a), Some(b)) => {
+                let rows = (0..a.len().max(b.len()))
+                    .filter(|i| a.get(*i) != b.get(*i))
+                    .collect::<Vec<_>>();
+                if !rows.is_empty() {
+                    diffs.push(TableDiff::Rows { name, rows });
+                }
+            }
+            (Some(_), None) => diffs.push(TableDiff::OnlyInFirst(name)),
+            (None, _) => diffs.push(TableDiff::OnlyInSecond(name)),
+        }
+    }
+    Ok(diffs)
+}
+
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
-    /// Configures the table.
+    /// Configures the table. Panics if `range.0 > range.1`.
     pub fn configure(
         cs: &mut ConstraintSystem<F>,
         range: Range,
@@ -147,7 +632,29 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
         nonlinearity: &LookupOp,
         preexisting_inputs: Option<Vec<TableColumn>>,
     ) -> Table<F> {
-        let factors = cs.blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        Self::configure_with_pad(
+            cs,
+            range,
+            logrows,
+            nonlinearity,
+            preexisting_inputs,
+            RESERVED_BLINDING_ROWS_PAD,
+        )
+    }
+
+    /// Same as [Table::configure] but reserves `pad` rows on top of the blinding factors rather than
+    /// [RESERVED_BLINDING_ROWS_PAD], see [BlindingRowsPad].
+    pub fn configure_with_pad(
+        cs: &mut ConstraintSystem<F>,
+        range: Range,
+        logrows: usize,
+        nonlinearity: &LookupOp,
+        preexisting_inputs: Option<Vec<TableColumn>>,
+        pad: usize,
+    ) -> Table<F> {
+        assert!(range.0 <= range.1, "{}", TableError::RangeInvalid(range));
+
+        let factors = cs.blinding_factors() + pad.max(1);
         let col_size = Self::cal_col_size(logrows, factors);
         // number of cols needed to store the range
         let num_cols = num_cols_required((range.1 - range.0).abs(), col_size);
@@ -173,18 +680,122 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
             .map(|_| cs.lookup_table_column())
             .collect::<Vec<_>>();
 
+        let selector_constructor = SelectorConstructor::new(num_cols);
+
         Table {
             nonlinearity: nonlinearity.clone(),
             table_inputs,
             table_outputs,
             is_assigned: false,
-            selector_constructor: SelectorConstructor::new(num_cols),
+            col_multipliers: selector_constructor.get_selector_vals(),
+            selector_constructor,
             col_size,
             range,
+            sub_ranges: None,
+            eval_cache: None,
+            evals_file: None,
+            clamp_warning: None,
+            clamped: None,
+            precomputed: None,
             _marker: PhantomData,
         }
     }
 
+    /// Configures a table whose columns each cover one of `sub_ranges`, so that dense and sparse
+    /// regions of a domain can be packed more tightly than with a single uniform range.
+    pub fn configure_nonuniform(
+        cs: &mut ConstraintSystem<F>,
+        sub_ranges: &[Range],
+        logrows: usize,
+        nonlinearity: &LookupOp,
+    ) -> Result<Table<F>, Box<dyn Error>> {
+        let factors = cs.blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        let col_size = Self::cal_col_size(logrows, factors);
+
+        if sub_ranges.is_empty() {
+            return Err("at least one sub-range is required".into());
+        }
+        for (lo, hi) in sub_ranges {
+            if lo > hi || (hi - lo + 1) as usize > col_size {
+                return Err(format!(
+                    "sub-range {:?} does not fit in a column of size {}",
+                    (lo, hi),
+                    col_size
+                )
+                .into());
+            }
+        }
+
+        debug!("table sub-ranges: {:?}", sub_ranges);
+
+        let table_inputs = sub_ranges
+            .iter()
+            .map(|_| cs.lookup_table_column())
+            .collect::<Vec<_>>();
+        let table_outputs = sub_ranges
+            .iter()
+            .map(|_| cs.lookup_table_column())
+            .collect::<Vec<_>>();
+
+        let range = (
+            sub_ranges.iter().map(|r| r.0).min().unwrap(),
+            sub_ranges.iter().map(|r| r.1).max().unwrap(),
+        );
+
+        let selector_constructor = SelectorConstructor::new(sub_ranges.len());
+
+        Ok(Table {
+            nonlinearity: nonlinearity.clone(),
+            table_inputs,
+            table_outputs,
+            is_assigned: false,
+            col_multipliers: selector_constructor.get_selector_vals(),
+            selector_constructor,
+            col_size,
+            range,
+            sub_ranges: Some(sub_ranges.to_vec()),
+            eval_cache: None,
+            evals_file: None,
+            clamp_warning: None,
+            clamped: None,
+            precomputed: None,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Configures a table over `range` whose outputs are `outputs`, e.g. as computed by an external
+    /// tool, rather than evaluations of a [LookupOp]: the input `range.0 + i` maps to `outputs[i]`.
+    /// [Table::nonlinearity] is then the [LookupOp::Precomputed] keyed on the mapping, see [LookupOp::precomputed].
+    pub fn from_precomputed(
+        cs: &mut ConstraintSystem<F>,
+        range: Range,
+        logrows: usize,
+        name: String,
+        outputs: Tensor<F>,
+    ) -> Result<Table<F>, CircuitError> {
+        if range.0 > range.1 {
+            return Err(TableError::RangeInvalid(range).into());
+        }
+        let range_len = range.1 - range.0 + 1;
+        if outputs.len() as i128 != range_len {
+            return Err(CircuitError::DimMismatch(format!(
+                "precomputed table {} has {} outputs for a range of {}",
+                name,
+                outputs.len(),
+                range_len
+            )));
+        }
+
+        let nonlinearity = LookupOp::precomputed(range, &outputs);
+        let mut table = Self::configure(cs, range, logrows, &nonlinearity, None);
+        table.precomputed = Some(PrecomputedOutputs {
+            name,
+            range,
+            outputs: Arc::new(outputs),
+        });
+        Ok(table)
+    }
+
     /// Take a linear coordinate and output the (column, row) position in the storage block.
     pub fn cartesian_coord(&self, linear_coord: usize) -> (usize, usize) {
         let x = linear_coord / self.col_size;
@@ -192,64 +803,607 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table<F> {
         (x, y)
     }
 
+    /// The number of lookup arguments [crate::circuit::BaseConfig::configure_lookup] adds when
+    /// configuring an op over `input` against this table: one per block, inner column and table column.
+    pub fn num_lookup_arguments_for(&self, input: &VarTensor) -> usize {
+        input.num_blocks() * input.num_inner_cols() * self.table_inputs.len()
+    }
+
+    /// Name used for the table's regions and cells, identifying the op and range it covers,
+    /// e.g. `table:RELU[-128,128]`.
+    pub fn name(&self) -> String {
+        let op = match &self.precomputed {
+            Some(precomputed) => precomputed.name.clone(),
+            None => Op::<F>::as_string(&self.nonlinearity),
+        };
+        format!("table:{}[{},{}]", op, self.range.0, self.range.1)
+    }
+
+    /// Groups the linear offsets of every table row into the `assign_table` calls used for `order`.
+    /// Columns assigned within a single call must have the same length, so under [AssignmentOrder::RowMajor]
+    /// the full columns are interleaved in one call and a trailing partial column gets its own call.
+    pub fn assignment_plan(&self, order: AssignmentOrder) -> Vec<Vec<usize>> {
+        let len = (self.range.1 - self.range.0 + 1) as usize;
+        let col_size = self.col_size;
+        let num_chunks = len.div_ceil(col_size);
+
+        match order {
+            AssignmentOrder::ColumnMajor => (0..num_chunks)
+                .map(|chunk| (chunk * col_size..((chunk + 1) * col_size).min(len)).collect())
+                .collect(),
+            AssignmentOrder::RowMajor => {
+                let num_full_cols = len / col_size;
+                let mut plan = vec![];
+                if num_full_cols > 0 {
+                    plan.push(
+                        (0..col_size)
+                            .flat_map(|y| (0..num_full_cols).map(move |x| x * col_size + y))
+                            .collect(),
+                    );
+                }
+                if len % col_size != 0 {
+                    plan.push((num_full_cols * col_size..len).collect());
+                }
+                plan
+            }
+        }
+    }
+
     /// Assigns values to the constraints generated when calling `configure`.
     pub fn layout(
         &mut self,
         layouter: &mut impl Layouter<F>,
         preassigned_input: bool,
-    ) -> Result<(), Box<dyn Error>> {
+        order: AssignmentOrder,
+    ) -> Result<(), TableError> {
         if self.is_assigned {
-            return Err(Box::new(CircuitError::TableAlreadyAssigned));
+            return Err(TableError::AlreadyAssigned);
+        }
+
+        if self.sub_ranges.is_some() {
+            return self.layout_nonuniform(layouter, preassigned_input);
         }
 
         let smallest = self.range.0;
         let largest = self.range.1;
 
-        let inputs = Tensor::from(smallest..=largest).map(|x| i128_to_felt(x));
-        let evals = Op::<F>::f(&self.nonlinearity, &[inputs.clone()])?;
-        let chunked_inputs = inputs.chunks(self.col_size);
+        if largest < smallest {
+            return Err(TableError::RangeInvalid(self.range));
+        }
 
+        let inputs = Tensor::from(smallest..=largest).map(|x| i128_to_felt(x));
+        let evals = self
+            .evaluate(&inputs, self.range)
+            .map_err(|e| TableError::Evaluation(e.to_string()))?;
         self.is_assigned = true;
+        if let Some(warning) = self.clamp_warning {
+            self.warn_if_clamped(warning.count_clamped(&evals), evals.len());
+        }
 
-        let col_multipliers: Vec<F> = (0..chunked_inputs.len())
-            .map(|x| self.selector_constructor.get_selector_val_at_idx(x))
-            .collect();
+        let name = self.name();
 
-        let _ = chunked_inputs
-            .enumerate()
-            .map(|(chunk_idx, inputs)| {
+        let _ = self
+            .assignment_plan(order)
+            .into_iter()
+            .map(|offsets| {
                 layouter.assign_table(
-                    || "nl table",
+                    || name.clone(),
                     |mut table| {
-                        let _ = inputs
-                            .iter()
-                            .enumerate()
-                            .map(|(mut row_offset, input)| {
-                                let col_multiplier = col_multipliers[chunk_idx];
-
-                                row_offset += chunk_idx * self.col_size;
-                                let (x, y) = self.cartesian_coord(row_offset);
-                                if !preassigned_input {
-                                    table.assign_cell(
-                                        || format!("nl_i_col row {}", row_offset),
-                                        self.table_inputs[x],
-                                        y,
-                                        || Value::known(*input * col_multiplier),
-                                    )?;
-                                }
-
-                                let output = evals.output[row_offset];
+                        for row_offset in offsets.iter() {
+                            self.assign_row(
+                                &mut table,
+                                &name,
+                                *row_offset,
+                                inputs[*row_offset],
+                                evals[*row_offset],
+                                preassigned_input,
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            })
+            .collect::<Result<Vec<()>, halo2_proofs::plonk::Error>>()?;
+        Ok(())
+    }
+
+    /// Same as [Table::layout], in column major order, but evaluates and assigns the op over windows of
+    /// `chunk_rows` rows, dropping each window's evaluations before moving on to the next, so that the
+    /// memory held by the table's inputs and evaluations is bounded by `chunk_rows` rather than by the
+    /// range. Each column is still assigned by a single `assign_table` call, as halo2 requires. Table
+    /// evaluation caches are bypassed, see [Table::eval_cache] and [Table::evals_file]. Non-uniform
+    /// tables, whose columns are already evaluated one at a time, are laid out as by [Table::layout].
+    pub fn layout_streaming(
+        &mut self,
+        layouter: &mut impl Layouter<F>,
+        preassigned_input: bool,
+        chunk_rows: usize,
+    ) -> Result<(), TableError> {
+        if self.is_assigned {
+            return Err(TableError::AlreadyAssigned);
+        }
+
+        if self.sub_ranges.is_some() {
+            return self.layout_nonuniform(layouter, preassigned_input);
+        }
+
+        let smallest = self.range.0;
+        let largest = self.range.1;
+
+        if largest < smallest {
+            return Err(TableError::RangeInvalid(self.range));
+        }
+        if chunk_rows == 0 {
+            return Err(TableError::Evaluation(
+                "tables must be streamed in windows of at least one row".to_string(),
+            ));
+        }
+
+        self.is_assigned = true;
+
+        let name = self.name();
+        let len = (largest - smallest + 1) as usize;
+        let mut num_clamped = 0;
+        for (x, col_start) in (0..len).step_by(self.col_size).enumerate() {
+            let col_end = (col_start + self.col_size).min(len);
+            let mut col_clamped = 0;
+            layouter.assign_table(
+                || format!("{} col {}", name, x),
+                |mut table| {
+                    col_clamped = 0;
+                    for window_start in (col_start..col_end).step_by(chunk_rows) {
+                        let window_end = (window_start + chunk_rows).min(col_end);
+                        let inputs = Tensor::from(
+                            (smallest + window_start as i128)..(smallest + window_end as i128),
+                        )
+                        .map(|v| i128_to_felt(v));
+                        let evals = self.eval(&inputs).map_err(|e| {
+                            log::error!("failed to evaluate {}: {}", name, e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?;
+                        if let Some(warning) = self.clamp_warning {
+                            col_clamped += warning.count_clamped(&evals);
+                        }
+
+                        for (i, (input, output)) in inputs.iter().zip(evals.iter()).enumerate() {
+                            self.assign_row(
+                                &mut table,
+                                &name,
+                                window_start + i,
+                                *input,
+                                *output,
+                                preassigned_input,
+                            )?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            num_clamped += col_clamped;
+        }
+        self.warn_if_clamped(num_clamped, len);
+        Ok(())
+    }
+
+    /// Assigns the row at the linear offset `row_offset` of the table: its `input`, unless the input
+    /// columns are `preassigned_input`, and its `output`, both scaled by the column's multiplier.
+    fn assign_row(
+        &self,
+        table: &mut halo2_proofs::circuit::Table<F>,
+        name: &str,
+        row_offset: usize,
+        input: F,
+        output: F,
+        preassigned_input: bool,
+    ) -> Result<(), halo2_proofs::plonk::Error> {
+        let (x, y) = self.cartesian_coord(row_offset);
+        let col_multiplier = self.col_multipliers[x];
+        if !preassigned_input {
+            table.assign_cell(
+                || format!("{} i_col row {}", name, row_offset),
+                self.table_inputs[x],
+                y,
+                || Value::known(input * col_multiplier),
+            )?;
+        }
+        table.assign_cell(
+            || format!("{} o_col row {}", name, row_offset),
+            self.table_outputs[x],
+            y,
+            || Value::known(output * col_multiplier),
+        )
+    }
+
+    /// Evaluates the table's op over `inputs`, spanning `range`. Evaluations over the table's full range
+    /// are reloaded from [Table::evals_file] when it holds them, and written to it otherwise. Precomputed
+    /// outputs are already in memory, so they bypass the file.
+    fn evaluate(&self, inputs: &Tensor<F>, range: Range) -> Result<Arc<Tensor<F>>, Box<dyn Error>> {
+        let evals_file = self
+            .evals_file
+            .as_ref()
+            .filter(|_| range == self.range && self.precomputed.is_none());
+        if let Some(path) = evals_file {
+            if let Some(evals) = Self::try_load_evals(range, &self.nonlinearity, path) {
+                debug!("loaded table evaluations from {:?}", path);
+                return Ok(Arc::new(evals));
+            }
+        }
+
+        let evals = self.compute(inputs, range)?;
+
+        if let Some(path) = evals_file {
+            // failing to write the cache only costs a recomputation next time
+            if let Err(e) = write_evals(path, range, &self.nonlinearity, &evals) {
+                warn!("failed to cache table evaluations to {:?}: {}", path, e);
+            }
+        }
+        Ok(evals)
+    }
+
+    /// Writes the evaluations of the table's op over its range to `path`, keyed on the range and op so that
+    /// [Table::try_load_evals] can tell whether they still apply.
+    pub fn serialize_evals(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let inputs = Tensor::from(self.range.0..=self.range.1).map(|x| i128_to_felt(x));
+        let evals = self.compute(&inputs, self.range)?;
+        write_evals(path, self.range, &self.nonlinearity, &evals)
+    }
+
+    /// Every `(input, output)` pair of the table as integers, in the order the rows are laid out.
+    pub fn rows(&self) -> Result<Vec<(i128, i128)>, Box<dyn Error>> {
+        let ranges = self.sub_ranges.clone().unwrap_or_else(|| vec![self.range]);
+        let mut rows = vec![];
+        for (lo, hi) in ranges {
+            let inputs = Tensor::from(lo..=hi).map(|x| i128_to_felt(x));
+            let evals = self.compute(&inputs, (lo, hi))?;
+            rows.extend((lo..=hi).zip(evals.iter().map(|y| felt_to_i128(*y))));
+        }
+        Ok(rows)
+    }
+
+    /// Loads evaluations written by [Table::serialize_evals], if `path` holds the evaluations of `nl`
+    /// over `range`. Returns `None` if the file is missing, unreadable or was written for another table,
+    /// in which case the caller should recompute them.
+    pub fn try_load_evals(range: Range, nl: &LookupOp, path: &Path) -> Option<Tensor<F>> {
+        let bytes = std::fs::read(path).ok()?;
+        let stored: SerializedEvals = match bincode::deserialize(&bytes) {
+            Ok(stored) => stored,
+            Err(e) => {
+                warn!("ignoring unreadable table evaluations in {:?}: {}", path, e);
+                return None;
+            }
+        };
+        if stored.range != range || &stored.nonlinearity != nl {
+            debug!(
+                "table evaluations in {:?} are for {} over {:?}, not {} over {:?}",
+                path,
+                Op::<F>::as_string(&stored.nonlinearity),
+                stored.range,
+                Op::<F>::as_string(nl),
+                range
+            );
+            return None;
+        }
+        if stored.evals.len() as i128 != range.1 - range.0 + 1 {
+            warn!("ignoring truncated table evaluations in {:?}", path);
+            return None;
+        }
+
+        let evals = stored
+            .evals
+            .iter()
+            .map(|bytes| {
+                let mut repr = F::Repr::default();
+                if repr.as_ref().len() != bytes.len() {
+                    return None;
+                }
+                repr.as_mut().copy_from_slice(bytes);
+                Option::from(F::from_repr(repr))
+            })
+            .collect::<Option<Vec<F>>>();
+        if evals.is_none() {
+            warn!("ignoring malformed table evaluations in {:?}", path);
+        }
+        evals.map(|evals| Tensor::from(evals.into_iter()))
+    }
+
+    /// Evaluates the table's op over `inputs`, spanning `range`, through the shared cache if there is one.
+    fn compute(&self, inputs: &Tensor<F>, range: Range) -> Result<Arc<Tensor<F>>, Box<dyn Error>> {
+        match &self.eval_cache {
+            Some(cache) if self.precomputed.is_none() => {
+                cache.get_or_eval(&self.nonlinearity, range)
+            }
+            _ => Ok(Arc::new(self.eval(inputs)?)),
+        }
+    }
+
+    /// Evaluates the table's op at each of `inputs`, or reads them off the [Table::precomputed]
+    /// outputs, in which case inputs the mapping doesn't cover evaluate to 0.
+    pub(crate) fn eval(&self, inputs: &Tensor<F>) -> Result<Tensor<F>, Box<dyn Error>> {
+        match &self.precomputed {
+            Some(precomputed) => {
+                let outputs: &[F] = &precomputed.outputs;
+                Ok(inputs.map(|x| {
+                    usize::try_from(felt_to_i128(x) - precomputed.range.0)
+                        .ok()
+                        .and_then(|i| outputs.get(i))
+                        .copied()
+                        .unwrap_or(F::ZERO)
+                }))
+            }
+            None => Ok(Op::<F>::f(&self.nonlinearity, &[inputs.clone()])?.output),
+        }
+    }
+
+    /// Assigns each sub-range of a non-uniform table to its own column.
+    fn layout_nonuniform(
+        &mut self,
+        layouter: &mut impl Layouter<F>,
+        preassigned_input: bool,
+    ) -> Result<(), TableError> {
+        let sub_ranges = self.sub_ranges.clone().unwrap_or_default();
+        let name = self.name();
+
+        self.is_assigned = true;
+
+        // clamped outputs are tallied over every column and reported once
+        let mut num_clamped = 0;
+        let mut num_evals = 0;
+        for (x, (lo, hi)) in sub_ranges.into_iter().enumerate() {
+            let col_multiplier = self.col_multipliers[x];
+            let inputs = Tensor::from(lo..=hi).map(|v| i128_to_felt(v));
+            let evals = self
+                .evaluate(&inputs, (lo, hi))
+                .map_err(|e| TableError::Evaluation(e.to_string()))?;
+            if let Some(warning) = self.clamp_warning {
+                num_clamped += warning.count_clamped(&evals);
+                num_evals += evals.len();
+            }
+
+            layouter.assign_table(
+                || format!("{} col {}", name, x),
+                |mut table| {
+                    for (y, input) in inputs.iter().enumerate() {
+                        if !preassigned_input {
+                            table.assign_cell(
+                                || format!("{} i_col {} row {}", name, x, y),
+                                self.table_inputs[x],
+                                y,
+                                || Value::known(*input * col_multiplier),
+                            )?;
+                        }
+                        table.assign_cell(
+                            || format!("{} o_col {} row {}", name, x, y),
+                            self.table_outputs[x],
+                            y,
+                            || Value::known(evals[y] * col_multiplier),
+                        )?;
+                    }
+                    Ok(())
+                },
+            )?;
+        }
+        if self.clamp_warning.is_some() {
+            self.warn_if_clamped(num_clamped, num_evals);
+        }
+        Ok(())
+    }
+
+    /// Logs a single warning if more than the [ClampWarning::fraction] of the table's `num_evals`
+    /// outputs were clamped.
+    fn warn_if_clamped(&mut self, num_clamped: usize, num_evals: usize) {
+        let warning = match self.clamp_warning {
+            Some(warning) if num_evals > 0 => warning,
+            _ => return,
+        };
+        let fraction = num_clamped as f64 / num_evals as f64;
+        if fraction > warning.fraction {
+            warn!(
+                "{} clamped {} of its {} table outputs ({:.1}%), its scale may be too large for its range",
+                Op::<F>::as_string(&self.nonlinearity),
+                num_clamped,
+                num_evals,
+                100.0 * fraction
+            );
+            self.clamped = Some((num_clamped, fraction));
+        }
+    }
+}
+
+/// An elementwise function of two integer inputs, looked up with a [Table2].
+pub type BinaryFn = fn(i128, i128) -> i128;
+
+/// Halo2 lookup table for elementwise functions of two inputs, e.g. a learned gating function
+/// `f(a, b)`. Rows enumerate the cartesian product of the two input ranges with `a` major, split into
+/// columns of `col_size` rows as for [Table].
+#[derive(Clone, Debug)]
+pub struct Table2<F: PrimeField> {
+    /// Function to be used in table.
+    pub f: BinaryFn,
+    /// First input to table.
+    pub table_inputs_a: Vec<TableColumn>,
+    /// Second input to table.
+    pub table_inputs_b: Vec<TableColumn>,
+    /// Output of table
+    pub table_outputs: Vec<TableColumn>,
+    /// col size
+    pub col_size: usize,
+    /// selector cn
+    pub selector_constructor: SelectorConstructor<F>,
+    /// Multiplier of each column, computed from `selector_constructor` at configure time.
+    pub col_multipliers: Vec<F>,
+    /// Flags if table has been previously assigned to.
+    pub is_assigned: bool,
+    /// Ranges of the two inputs.
+    pub ranges: [Range; 2],
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> Table2<F> {
+    /// Configures the table. Errors with [TableError::RangeInvalid] if either range is inverted.
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        ranges: [Range; 2],
+        logrows: usize,
+        f: BinaryFn,
+    ) -> Result<Table2<F>, TableError> {
+        if let Some(range) = ranges.iter().find(|r| r.1 < r.0) {
+            return Err(TableError::RangeInvalid(*range));
+        }
+        let factors = cs.blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        let col_size = Table::<F>::cal_col_size(logrows, factors);
+        let num_rows = ranges.iter().map(|r| r.1 - r.0 + 1).product::<i128>();
+        // number of cols needed to store the cartesian product of the ranges
+        let num_cols = num_cols_required(num_rows - 1, col_size);
+
+        debug!("table ranges: {:?}", ranges);
+
+        if num_cols > 1 {
+            warn!("Using {} columns for binary table.", num_cols);
+        }
+
+        let mut table_columns = || {
+            (0..num_cols)
+                .map(|_| cs.lookup_table_column())
+                .collect::<Vec<_>>()
+        };
+        let table_inputs_a = table_columns();
+        let table_inputs_b = table_columns();
+        let table_outputs = table_columns();
+
+        let selector_constructor = SelectorConstructor::new(num_cols);
+
+        Ok(Table2 {
+            f,
+            table_inputs_a,
+            table_inputs_b,
+            table_outputs,
+            col_size,
+            col_multipliers: selector_constructor.get_selector_vals(),
+            selector_constructor,
+            is_assigned: false,
+            ranges,
+        })
+    }
 
+    /// Number of rows of the cartesian product of the input ranges.
+    pub fn num_rows(&self) -> usize {
+        self.ranges
+            .iter()
+            .map(|r| (r.1 - r.0 + 1) as usize)
+            .product()
+    }
+
+    /// The inputs `(a, b)` at `linear_coord` in the cartesian product.
+    pub fn input_at(&self, linear_coord: usize) -> (i128, i128) {
+        let len_b = (self.ranges[1].1 - self.ranges[1].0 + 1) as usize;
+        (
+            self.ranges[0].0 + (linear_coord / len_b) as i128,
+            self.ranges[1].0 + (linear_coord % len_b) as i128,
+        )
+    }
+
+    /// Inverse of [Table2::input_at].
+    pub fn linear_coord(&self, a: i128, b: i128) -> usize {
+        let len_b = self.ranges[1].1 - self.ranges[1].0 + 1;
+        ((a - self.ranges[0].0) * len_b + (b - self.ranges[1].0)) as usize
+    }
+
+    /// Column and row of `linear_coord`.
+    pub fn cartesian_coord(&self, linear_coord: usize) -> (usize, usize) {
+        let x = linear_coord / self.col_size;
+        let y = linear_coord % self.col_size;
+        (x, y)
+    }
+
+    /// The integers `a` and `b` encode, erroring if either doesn't encode an integer within its range.
+    fn int_inputs(&self, a: F, b: F) -> Result<(i128, i128), CircuitError> {
+        let [a, b] = [(a, self.ranges[0]), (b, self.ranges[1])].map(|(input, range)| {
+            felt_to_int_in_range(input, range)
+                .ok_or_else(|| CircuitError::NonIntegerFieldElement(format!("{:?}", input), range))
+        });
+        Ok((a?, b?))
+    }
+
+    /// get column index given the two inputs. Errors if either input is outside of its range, as the
+    /// cartesian product has no row for it.
+    pub fn get_col_index(&self, a: F, b: F) -> Result<F, CircuitError> {
+        let (a, b) = self.int_inputs(a, b)?;
+        let linear_coord = self.linear_coord(a, b);
+        Ok(i128_to_felt(self.cartesian_coord(linear_coord).0 as i128))
+    }
+
+    /// The table's output for the inputs `a` and `b`. Errors if either input is outside of its range.
+    pub fn eval(&self, a: F, b: F) -> Result<F, CircuitError> {
+        let (a, b) = self.int_inputs(a, b)?;
+        Ok(i128_to_felt((self.f)(a, b)))
+    }
+
+    /// get first_element of column, as `(a, b, f(a, b))`
+    pub fn get_first_element(&self, chunk: usize) -> (F, F, F) {
+        let (a, b) = self.input_at(chunk * self.col_size);
+        (
+            i128_to_felt(a),
+            i128_to_felt(b),
+            i128_to_felt((self.f)(a, b)),
+        )
+    }
+
+    /// Name used for the table's regions and cells, identifying the ranges it covers.
+    pub fn name(&self) -> String {
+        format!(
+            "table2[{},{}]x[{},{}]",
+            self.ranges[0].0, self.ranges[0].1, self.ranges[1].0, self.ranges[1].1
+        )
+    }
+
+    /// Assigns values to the constraints generated when calling `configure`.
+    pub fn layout(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
+        if self.is_assigned {
+            return Err(TableError::AlreadyAssigned);
+        }
+        self.is_assigned = true;
+
+        let name = self.name();
+        let num_rows = self.num_rows();
+
+        // each column has to be assigned in one go, so cells are prepared per column
+        let prepared = (0..self.table_outputs.len())
+            .into_par_iter()
+            .map(|chunk_idx| {
+                let col_multiplier = self.col_multipliers[chunk_idx];
+                let start = chunk_idx * self.col_size;
+                let end = (start + self.col_size).min(num_rows);
+                (start..end)
+                    .map(|row_offset| {
+                        let (a, b) = self.input_at(row_offset);
+                        let values =
+                            [a, b, (self.f)(a, b)].map(|v| i128_to_felt::<F>(v) * col_multiplier);
+                        (row_offset, self.cartesian_coord(row_offset), values)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let _ = prepared
+            .into_iter()
+            .map(|cells| {
+                layouter.assign_table(
+                    || name.clone(),
+                    |mut table| {
+                        for (row_offset, (x, y), values) in cells.iter() {
+                            let columns = [
+                                ("a_col", self.table_inputs_a[*x]),
+                                ("b_col", self.table_inputs_b[*x]),
+                                ("o_col", self.table_outputs[*x]),
+                            ];
+                            for ((label, column), value) in columns.into_iter().zip(values) {
                                 table.assign_cell(
-                                    || format!("nl_o_col row {}", row_offset),
-                                    self.table_outputs[x],
-                                    y,
-                                    || Value::known(output * col_multiplier),
+                                    || format!("{} {} row {}", name, label, row_offset),
+                                    column,
+                                    *y,
+                                    || Value::known(*value),
                                 )?;
-
-                                Ok(())
-                            })
-                            .collect::<Result<Vec<()>, halo2_proofs::plonk::Error>>()?;
+                            }
+                        }
                         Ok(())
                     },
                 )
@@ -268,21 +1422,63 @@ pub struct RangeCheck<F: PrimeField> {
     pub col_size: usize,
     /// selector cn
     pub selector_constructor: SelectorConstructor<F>,
+    /// Multiplier of each column, computed from `selector_constructor` at configure time.
+    pub col_multipliers: Vec<F>,
     /// Flags if table has been previously assigned to.
     pub is_assigned: bool,
     /// Number of bits used in lookup table.
     pub range: Range,
+    /// Column the checked input is echoed into, if any.
+    pub output: Option<VarTensor>,
+    /// Chunks touched by assigned inputs, when the table is assigned incrementally.
+    /// `None` means the full table is assigned in one go.
+    pub touched_chunks: Option<Arc<Mutex<BTreeSet<usize>>>>,
+    /// Chunks whose table rows have already been assigned.
+    pub assigned_chunks: BTreeSet<usize>,
+    /// Values unselected rows look up in each column instead of its first element, see
+    /// [RangeCheck::set_default].
+    pub defaults: BTreeMap<usize, F>,
     _marker: PhantomData<F>,
 }
 
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
-    /// get first_element of column
+    /// get first_element of column, or the default set with [RangeCheck::set_default]
     pub fn get_first_element(&self, chunk: usize) -> F {
+        if let Some(default) = self.defaults.get(&chunk) {
+            return *default;
+        }
         let chunk = chunk as i128;
         // we index from 1 to prevent soundness issues
         i128_to_felt(chunk * (self.col_size as i128) + self.range.0)
     }
 
+    /// Sets the value the unselected rows of the lookups against column `col_idx` are filled with,
+    /// rather than the column's first element, e.g. when the lower end of the range is a sensitive
+    /// value. Unselected rows look up this constant instead of an input, so any value of the column
+    /// keeps the range check sound: selected rows still look up their own input. It must however lie
+    /// within the column, or every unselected row would fail its lookup. Only honored by lookups
+    /// configured after it is set, see [crate::circuit::BaseConfig::configure_range_check].
+    pub fn set_default(&mut self, col_idx: usize, value: F) -> Result<(), CircuitError> {
+        if col_idx >= self.num_chunks() {
+            return Err(CircuitError::DimMismatch(format!(
+                "{} has {} columns, no column {}",
+                self.name(),
+                self.num_chunks(),
+                col_idx
+            )));
+        }
+        let start = self.range.0 + (col_idx * self.col_size) as i128;
+        let col_range = (start, (start + self.col_size as i128 - 1).min(self.range.1));
+        if felt_to_int_in_range(value, col_range).is_none() {
+            return Err(CircuitError::NonIntegerFieldElement(
+                format!("{:?}", value),
+                col_range,
+            ));
+        }
+        self.defaults.insert(col_idx, value);
+        Ok(())
+    }
+
     ///
     pub fn cal_col_size(logrows: usize, reserved_blinding_rows: usize) -> usize {
         2usize.pow(logrows as u32) - reserved_blinding_rows
@@ -293,6 +1489,18 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
         2usize.pow(bits as u32) - reserved_blinding_rows
     }
 
+    /// The columns a range check over `range` configured at `logrows` takes, see [TableFootprint] and
+    /// [Table::layout_footprint].
+    pub fn layout_footprint(range: Range, logrows: usize) -> TableFootprint {
+        let factors =
+            ConstraintSystem::<F>::default().blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        let col_size = Self::cal_col_size(logrows, factors);
+        TableFootprint::new::<F>(
+            num_cols_required((range.1 - range.0).abs(), col_size),
+            col_size,
+        )
+    }
+
     /// get column index given input
     pub fn get_col_index(&self, input: F) -> F {
         //    range is split up into chunks of size col_size, find the chunk that input is in
@@ -301,14 +1509,90 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
 
         i128_to_felt(chunk)
     }
+
+    /// Like [RangeCheck::get_col_index], but errors rather than returning a meaningless index when
+    /// `input` doesn't encode an integer within the range.
+    pub fn try_get_col_index(&self, input: F) -> Result<F, CircuitError> {
+        if felt_to_int_in_range(input, self.range).is_none() {
+            return Err(CircuitError::NonIntegerFieldElement(
+                format!("{:?}", input),
+                self.range,
+            ));
+        }
+        Ok(self.get_col_index(input))
+    }
+
+    /// Number of chunks (one per table column) the range is split into.
+    pub fn num_chunks(&self) -> usize {
+        self.inputs.len()
+    }
+
+    /// Switches the table to incremental assignment: [RangeCheck::layout] then only assigns the
+    /// chunks touched by inputs laid out so far, deferring the rest to later calls.
+    pub fn set_incremental(&mut self) {
+        if self.touched_chunks.is_none() {
+            self.touched_chunks = Some(Arc::new(Mutex::new(BTreeSet::new())));
+        }
+    }
+
+    /// Records the chunk `input` falls in, if the table is assigned incrementally.
+    pub fn mark_touched(&self, input: F) {
+        if let Some(touched) = &self.touched_chunks {
+            let chunk =
+                (felt_to_i128(input) - self.range.0).unsigned_abs() / (self.col_size as u128);
+            if chunk < self.num_chunks() as u128 {
+                touched.lock().unwrap().insert(chunk as usize);
+            }
+        }
+    }
+
+    /// Marks every chunk as touched, e.g. when an input's value is unknown as during keygen, so that
+    /// the fixed columns committed to in the verifying key hold the full table.
+    pub fn mark_all_touched(&self) {
+        if let Some(touched) = &self.touched_chunks {
+            touched.lock().unwrap().extend(0..self.num_chunks());
+        }
+    }
+
+    /// Chunks that still need to be assigned on the next [RangeCheck::layout].
+    fn pending_chunks(&self) -> BTreeSet<usize> {
+        match &self.touched_chunks {
+            Some(touched) => touched
+                .lock()
+                .unwrap()
+                .difference(&self.assigned_chunks)
+                .cloned()
+                .collect(),
+            None => (0..self.num_chunks()).collect(),
+        }
+    }
 }
 
 impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
-    /// Configures the table.
-    pub fn configure(cs: &mut ConstraintSystem<F>, range: Range, logrows: usize) -> RangeCheck<F> {
+    /// Configures the table. Panics if `range.0 > range.1`.
+    /// If `output` is given, each checked input is also echoed into it, copy-constrained to equal the input.
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        range: Range,
+        logrows: usize,
+        output: Option<VarTensor>,
+    ) -> RangeCheck<F> {
+        Self::configure_with_pad(cs, range, logrows, output, RESERVED_BLINDING_ROWS_PAD)
+    }
+
+    /// Same as [RangeCheck::configure] but reserves `pad` rows on top of the blinding factors rather than
+    /// [RESERVED_BLINDING_ROWS_PAD], see [BlindingRowsPad].
+    pub fn configure_with_pad(
+        cs: &mut ConstraintSystem<F>,
+        range: Range,
+        logrows: usize,
+        output: Option<VarTensor>,
+        pad: usize,
+    ) -> RangeCheck<F> {
+        assert!(range.0 <= range.1, "{}", TableError::RangeInvalid(range));
         log::debug!("range check range: {:?}", range);
 
-        let factors = cs.blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        let factors = cs.blinding_factors() + pad.max(1);
         let col_size = Self::cal_col_size(logrows, factors);
         // number of cols needed to store the range
         let num_cols = num_cols_required((range.1 - range.0).abs(), col_size);
@@ -327,12 +1611,19 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
             warn!("Using {} columns for range-check.", num_cols);
         }
 
+        let selector_constructor = SelectorConstructor::new(num_cols);
+
         RangeCheck {
             inputs,
             col_size,
             is_assigned: false,
-            selector_constructor: SelectorConstructor::new(num_cols),
+            col_multipliers: selector_constructor.get_selector_vals(),
+            selector_constructor,
             range,
+            output,
+            touched_chunks: None,
+            assigned_chunks: BTreeSet::new(),
+            defaults: BTreeMap::new(),
             _marker: PhantomData,
         }
     }
@@ -344,43 +1635,87 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
         (x, y)
     }
 
+    /// Name used for the range check's regions and cells, e.g. `range_check[0,255]`.
+    pub fn name(&self) -> String {
+        format!("range_check[{},{}]", self.range.0, self.range.1)
+    }
+
+    /// The cells of the given chunks as `(row offset, column, row, multiplied value)`, one vector per
+    /// chunk, computed serially. See [RangeCheck::prepare_chunks_par].
+    pub fn prepare_chunks_serial(&self, chunks: &[usize]) -> Vec<Vec<(usize, usize, usize, F)>> {
+        chunks
+            .iter()
+            .map(|chunk_idx| {
+                self.chunk_rows(*chunk_idx)
+                    .map(|row_offset| self.prepare_cell(*chunk_idx, row_offset))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Same as [RangeCheck::prepare_chunks_serial] but the chunks, and the rows of each chunk, are
+    /// computed in parallel.
+    pub fn prepare_chunks_par(&self, chunks: &[usize]) -> Vec<Vec<(usize, usize, usize, F)>> {
+        chunks
+            .to_vec()
+            .into_par_iter()
+            .map(|chunk_idx| {
+                self.chunk_rows(chunk_idx)
+                    .into_par_iter()
+                    .map(|row_offset| self.prepare_cell(chunk_idx, row_offset))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Row offsets into the range covered by `chunk_idx`.
+    fn chunk_rows(&self, chunk_idx: usize) -> std::ops::Range<usize> {
+        let num_rows = (self.range.1 - self.range.0 + 1) as usize;
+        let start = chunk_idx * self.col_size;
+        start..(start + self.col_size).min(num_rows)
+    }
+
+    /// The cell at `row_offset` of chunk `chunk_idx`, see [RangeCheck::prepare_chunks_serial].
+    fn prepare_cell(&self, chunk_idx: usize, row_offset: usize) -> (usize, usize, usize, F) {
+        let (x, y) = self.cartesian_coord(row_offset);
+        let input = i128_to_felt::<F>(self.range.0 + row_offset as i128);
+        (row_offset, x, y, input * self.col_multipliers[chunk_idx])
+    }
+
     /// Assigns values to the constraints generated when calling `configure`.
-    pub fn layout(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), Box<dyn Error>> {
+    pub fn layout(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
         if self.is_assigned {
-            return Err(Box::new(CircuitError::TableAlreadyAssigned));
+            return Err(TableError::AlreadyAssigned);
+        }
+        if self.range.1 < self.range.0 {
+            return Err(TableError::RangeInvalid(self.range));
         }
 
-        let smallest = self.range.0;
-        let largest = self.range.1;
-
-        let inputs: Tensor<F> = Tensor::from(smallest..=largest).map(|x| i128_to_felt(x));
-        let chunked_inputs = inputs.chunks(self.col_size);
+        let pending = self.pending_chunks();
+        self.assigned_chunks.extend(pending.iter().cloned());
+        // an incrementally assigned table is only done once every chunk has been assigned
+        self.is_assigned = self.assigned_chunks.len() == self.num_chunks();
 
-        self.is_assigned = true;
+        let name = self.name();
 
-        let col_multipliers: Vec<F> = (0..chunked_inputs.len())
-            .map(|x| self.selector_constructor.get_selector_val_at_idx(x))
-            .collect();
+        // the layouter can only assign one table at a time, so the cell values of every pending chunk
+        // are prepared in parallel up front and only the assignment itself is sequential
+        let prepared = self.prepare_chunks_par(&pending.into_iter().collect::<Vec<_>>());
 
-        let _ = chunked_inputs
-            .enumerate()
-            .map(|(chunk_idx, inputs)| {
+        let _ = prepared
+            .into_iter()
+            .map(|cells| {
                 layouter.assign_table(
-                    || "range check table",
+                    || name.clone(),
                     |mut table| {
-                        let _ = inputs
+                        let _ = cells
                             .iter()
-                            .enumerate()
-                            .map(|(mut row_offset, input)| {
-                                let col_multiplier = col_multipliers[chunk_idx];
-
-                                row_offset += chunk_idx * self.col_size;
-                                let (x, y) = self.cartesian_coord(row_offset);
+                            .map(|(row_offset, x, y, value)| {
                                 table.assign_cell(
-                                    || format!("rc_i_col row {}", row_offset),
-                                    self.inputs[x],
-                                    y,
-                                    || Value::known(*input * col_multiplier),
+                                    || format!("{} i_col row {}", name, row_offset),
+                                    self.inputs[*x],
+                                    *y,
+                                    || Value::known(*value),
                                 )?;
 
                                 Ok(())
@@ -393,4 +1728,212 @@ impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> RangeCheck<F> {
             .collect::<Result<Vec<()>, halo2_proofs::plonk::Error>>()?;
         Ok(())
     }
+
+    /// Completes an incrementally assigned table: every chunk not yet assigned by
+    /// [RangeCheck::layout] gets only its default row, see [RangeCheck::get_first_element], which the
+    /// layouter repeats down the column. Every selected row looks up into every column, the
+    /// unselected ones looking up their multiplied default, so a column left empty would make the
+    /// circuit unsatisfiable. The skipped chunks can no longer be assigned afterwards.
+    pub fn layout_defaults(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
+        if self.is_assigned {
+            return Ok(());
+        }
+        let name = self.name();
+        let remaining = (0..self.num_chunks())
+            .filter(|chunk| !self.assigned_chunks.contains(chunk))
+            .collect::<Vec<_>>();
+        for chunk_idx in remaining {
+            let value = self.get_first_element(chunk_idx) * self.col_multipliers[chunk_idx];
+            layouter.assign_table(
+                || name.clone(),
+                |mut table| {
+                    table.assign_cell(
+                        || format!("{} default col {}", name, chunk_idx),
+                        self.inputs[chunk_idx],
+                        0,
+                        || Value::known(value),
+                    )?;
+                    Ok(())
+                },
+            )?;
+            self.assigned_chunks.insert(chunk_idx);
+        }
+        self.is_assigned = true;
+        Ok(())
+    }
+}
+
+/// Sorts `ranges` and merges overlapping or adjacent ones, so that every integer they cover is
+/// covered exactly once.
+pub fn merge_ranges(ranges: &[Range]) -> Vec<Range> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort();
+    let mut merged: Vec<Range> = vec![];
+    for (lo, hi) in sorted {
+        match merged.last_mut() {
+            Some(last) if lo <= last.1 + 1 => last.1 = last.1.max(hi),
+            _ => merged.push((lo, hi)),
+        }
+    }
+    merged
+}
+
+/// Halo2 range check columns covering a union of disjoint ranges, e.g. `{-1, 0, 1}` plus a high
+/// region, without laying out the span between them. The integers of the union are laid out in
+/// increasing order and split into columns of `col_size` rows as for [RangeCheck].
+#[derive(Clone, Debug)]
+pub struct MultiRangeCheck<F: PrimeField> {
+    /// Input to table.
+    pub inputs: Vec<TableColumn>,
+    /// col size
+    pub col_size: usize,
+    /// selector cn
+    pub selector_constructor: SelectorConstructor<F>,
+    /// Multiplier of each column, computed from `selector_constructor` at configure time.
+    pub col_multipliers: Vec<F>,
+    /// Flags if table has been previously assigned to.
+    pub is_assigned: bool,
+    /// The merged ranges of the union, sorted, see [merge_ranges].
+    pub ranges: Vec<Range>,
+}
+
+impl<F: PrimeField + TensorType + PartialOrd + std::hash::Hash> MultiRangeCheck<F> {
+    /// Configures the table over the union of `ranges`, which may overlap.
+    pub fn configure(
+        cs: &mut ConstraintSystem<F>,
+        ranges: &[Range],
+        logrows: usize,
+    ) -> Result<MultiRangeCheck<F>, Box<dyn Error>> {
+        if ranges.is_empty() {
+            return Err("at least one range is required".into());
+        }
+        if let Some(range) = ranges.iter().find(|(lo, hi)| lo > hi) {
+            return Err(format!("invalid range {:?}", range).into());
+        }
+        let ranges = merge_ranges(ranges);
+
+        let factors = cs.blinding_factors() + RESERVED_BLINDING_ROWS_PAD;
+        let col_size = Table::<F>::cal_col_size(logrows, factors);
+        let num_rows: i128 = ranges.iter().map(|r| r.1 - r.0 + 1).sum();
+        let num_cols = num_cols_required(num_rows - 1, col_size);
+
+        debug!("multi range check ranges: {:?}", ranges);
+
+        let inputs = (0..num_cols)
+            .map(|_| cs.lookup_table_column())
+            .collect::<Vec<_>>();
+
+        let selector_constructor = SelectorConstructor::new(num_cols);
+
+        Ok(MultiRangeCheck {
+            inputs,
+            col_size,
+            col_multipliers: selector_constructor.get_selector_vals(),
+            selector_constructor,
+            is_assigned: false,
+            ranges,
+        })
+    }
+
+    /// Number of integers in the union.
+    pub fn num_rows(&self) -> usize {
+        self.ranges.iter().map(|r| (r.1 - r.0 + 1) as usize).sum()
+    }
+
+    /// Position of `input` among the integers of the union, if it is covered.
+    pub fn linear_coord(&self, input: i128) -> Option<usize> {
+        let mut offset = 0;
+        for (lo, hi) in &self.ranges {
+            if *lo <= input && input <= *hi {
+                return Some(offset + (input - lo) as usize);
+            }
+            offset += (hi - lo + 1) as usize;
+        }
+        None
+    }
+
+    /// The integer at `linear_coord` in the union, inverse of [MultiRangeCheck::linear_coord].
+    pub fn value_at(&self, linear_coord: usize) -> i128 {
+        let mut offset = linear_coord as i128;
+        for (lo, hi) in &self.ranges {
+            let len = hi - lo + 1;
+            if offset < len {
+                return lo + offset;
+            }
+            offset -= len;
+        }
+        panic!("{} is past the end of the union", linear_coord);
+    }
+
+    /// Column and row of `linear_coord`.
+    pub fn cartesian_coord(&self, linear_coord: usize) -> (usize, usize) {
+        let x = linear_coord / self.col_size;
+        let y = linear_coord % self.col_size;
+        (x, y)
+    }
+
+    /// get column index given input. Errors if `input` doesn't encode an integer covered by the union.
+    pub fn get_col_index(&self, input: F) -> Result<F, CircuitError> {
+        let linear_coord = self
+            .ranges
+            .iter()
+            .find_map(|r| felt_to_int_in_range(input, *r))
+            .and_then(|input| self.linear_coord(input))
+            .ok_or_else(|| {
+                let span = (self.ranges[0].0, self.ranges[self.ranges.len() - 1].1);
+                CircuitError::NonIntegerFieldElement(format!("{:?}", input), span)
+            })?;
+        Ok(i128_to_felt(self.cartesian_coord(linear_coord).0 as i128))
+    }
+
+    /// get first_element of column
+    pub fn get_first_element(&self, chunk: usize) -> F {
+        i128_to_felt(self.value_at(chunk * self.col_size))
+    }
+
+    /// Name used for the table's regions and cells, e.g. `multi_range_check[-1,1][40,60]`.
+    pub fn name(&self) -> String {
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|(lo, hi)| format!("[{},{}]", lo, hi))
+            .collect::<String>();
+        format!("multi_range_check{}", ranges)
+    }
+
+    /// Assigns values to the constraints generated when calling `configure`.
+    pub fn layout(&mut self, layouter: &mut impl Layouter<F>) -> Result<(), TableError> {
+        if self.is_assigned {
+            return Err(TableError::AlreadyAssigned);
+        }
+        self.is_assigned = true;
+
+        let name = self.name();
+        let num_rows = self.num_rows();
+
+        let _ = (0..self.inputs.len())
+            .map(|chunk_idx| {
+                let col_multiplier = self.col_multipliers[chunk_idx];
+                let start = chunk_idx * self.col_size;
+                let end = (start + self.col_size).min(num_rows);
+                layouter.assign_table(
+                    || name.clone(),
+                    |mut table| {
+                        for row_offset in start..end {
+                            let (x, y) = self.cartesian_coord(row_offset);
+                            let input = i128_to_felt::<F>(self.value_at(row_offset));
+                            table.assign_cell(
+                                || format!("{} i_col row {}", name, row_offset),
+                                self.inputs[x],
+                                y,
+                                || Value::known(input * col_multiplier),
+                            )?;
+                        }
+                        Ok(())
+                    },
+                )
+            })
+            .collect::<Result<Vec<()>, halo2_proofs::plonk::Error>>()?;
+        Ok(())
+    }
 }
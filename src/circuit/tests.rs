@@ -1617,9 +1617,10 @@ mod dynamic_lookup {
 
         fn synthesize(
             &self,
-            config: Self::Config,
+            mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
+            config.layout_dynamic_lookups().unwrap();
             layouter
                 .assign_region(
                     || "",
@@ -1759,9 +1760,10 @@ mod shuffle {
 
         fn synthesize(
             &self,
-            config: Self::Config,
+            mut config: Self::Config,
             mut layouter: impl Layouter<F>,
         ) -> Result<(), Error> {
+            config.layout_shuffles().unwrap();
             layouter
                 .assign_region(
                     || "",
@@ -2372,7 +2374,12 @@ mod relu {
 
             let nl = LookupOp::ReLU;
 
-            let mut config = BaseConfig::default();
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
 
             config
                 .configure_lookup(cs, &advices[0], &advices[1], &advices[2], (-6, 6), 4, &nl)
@@ -2452,7 +2459,12 @@ mod lookup_ultra_overflow {
 
             let nl = LookupOp::ReLU;
 
-            let mut config = BaseConfig::default();
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
 
             config
                 .configure_lookup(
@@ -2537,3 +2549,6897 @@ mod lookup_ultra_overflow {
         assert!(prover.is_ok());
     }
 }
+
+#[cfg(test)]
+mod configure_after_layout {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Records the warnings logged while the tests run, so they can check one was emitted.
+    struct WarningCapture(Mutex<Vec<String>>);
+
+    impl log::Log for WarningCapture {
+        fn enabled(&self, metadata: &log::Metadata) -> bool {
+            metadata.level() <= log::Level::Warn
+        }
+
+        fn log(&self, record: &log::Record) {
+            if self.enabled(record.metadata()) {
+                self.0.lock().unwrap().push(record.args().to_string());
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static WARNINGS: WarningCapture = WarningCapture(Mutex::new(Vec::new()));
+
+    fn lookup_vars(cs: &mut ConstraintSystem<F>) -> Vec<VarTensor> {
+        (0..3)
+            .map(|_| VarTensor::new_advice(cs, 4, 1, 3))
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn warns_when_configured_after_layout() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = lookup_vars(&mut cs);
+
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config.tables_laid_out = true;
+
+        log::set_logger(&WARNINGS).expect("no other logger is installed by the tests");
+        log::set_max_level(log::LevelFilter::Warn);
+
+        // without the strict flag we only warn
+        assert!(config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .is_ok());
+        assert!(WARNINGS
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|w| w.starts_with("configure_lookup called after layout_tables")));
+    }
+
+    #[test]
+    fn errors_when_configured_after_layout_in_strict_mode() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = lookup_vars(&mut cs);
+
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config.tables_laid_out = true;
+        config.strict = true;
+
+        assert!(config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .is_err());
+        assert!(config
+            .configure_range_check(&mut cs, &advices[0], &advices[2], (0, 8), 4)
+            .is_err());
+    }
+}
+
+#[cfg(test)]
+mod piecewise {
+    use super::*;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    fn eval(op: &LookupOp, xs: &[i128]) -> Vec<i128> {
+        let inputs = Tensor::from(xs.iter().map(|x| i128_to_felt::<F>(*x)));
+        Op::<F>::f(op, &[inputs])
+            .unwrap()
+            .output
+            .iter()
+            .map(|x| felt_to_i128(*x))
+            .collect()
+    }
+
+    #[test]
+    fn piecewise_reproduces_relu() {
+        let xs = (-16..=16).collect::<Vec<_>>();
+        let piecewise = LookupOp::Piecewise {
+            breakpoints: vec![0],
+            slopes: vec![0, 1],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        };
+        assert_eq!(eval(&piecewise, &xs), eval(&LookupOp::ReLU, &xs));
+    }
+
+    #[test]
+    fn piecewise_reproduces_hard_sigmoid() {
+        // hard_sigmoid(x) = clamp(0.2 * x + 0.5, 0, 1) at a fixed point scale of 10
+        let scale = 10_i128;
+        let piecewise = LookupOp::Piecewise {
+            breakpoints: vec![-25, 25],
+            slopes: vec![0, 2, 0],
+            intercept: 0,
+            scale: utils::F32(10.0),
+        };
+        let xs = (-40..=40).collect::<Vec<_>>();
+        let expected = xs
+            .iter()
+            .map(|x| {
+                let real = *x as f64 / scale as f64;
+                let y = (0.2 * real + 0.5).clamp(0.0, 1.0);
+                (y * scale as f64).round() as i128
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(eval(&piecewise, &xs), expected);
+    }
+
+    #[test]
+    fn piecewise_rejects_mismatched_slopes() {
+        let piecewise = LookupOp::Piecewise {
+            breakpoints: vec![0],
+            slopes: vec![1],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        };
+        let inputs = Tensor::from([i128_to_felt::<F>(1)].into_iter());
+        assert!(Op::<F>::f(&piecewise, &[inputs]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod with_geometry {
+    use super::*;
+
+    #[test]
+    fn inner_cols_trade_width_for_height() {
+        const COL_SIZE: usize = 32;
+        const NUM_ELEMENTS: usize = 40;
+
+        let mut narrow_cs = ConstraintSystem::<F>::default();
+        let narrow =
+            BaseConfig::<F>::with_geometry(&mut narrow_cs, COL_SIZE, 2, 1, CheckMode::SAFE);
+
+        let mut wide_cs = ConstraintSystem::<F>::default();
+        let wide = BaseConfig::<F>::with_geometry(&mut wide_cs, COL_SIZE, 2, 2, CheckMode::SAFE);
+
+        for config in [&narrow, &wide] {
+            for var in config.custom_gates.inputs.iter() {
+                assert_eq!(var.num_blocks(), 2);
+                assert_eq!(var.col_size(), COL_SIZE);
+            }
+        }
+
+        assert_eq!(narrow.custom_gates.output.rows_for(NUM_ELEMENTS), COL_SIZE);
+        assert_eq!(wide.custom_gates.output.rows_for(NUM_ELEMENTS), 20);
+        assert_eq!(
+            wide_cs.num_advice_columns(),
+            2 * narrow_cs.num_advice_columns()
+        );
+    }
+}
+
+#[cfg(test)]
+mod rational_scale {
+    use super::*;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    fn eval(op: &LookupOp, xs: &[i128]) -> Vec<i128> {
+        let inputs = Tensor::from(xs.iter().map(|x| i128_to_felt::<F>(*x)));
+        Op::<F>::f(op, &[inputs])
+            .unwrap()
+            .output
+            .iter()
+            .map(|x| felt_to_i128(*x))
+            .collect()
+    }
+
+    #[test]
+    fn rational_scale_reduces_rounding_error() {
+        let (num, den) = (10, 3);
+        let exact_scale = num as f64 / den as f64;
+        let xs = (-50..=50).collect::<Vec<_>>();
+
+        let expected = xs
+            .iter()
+            .map(|x| {
+                let real = *x as f64 / exact_scale;
+                (exact_scale / (1.0 + (-real).exp())).round() as i128
+            })
+            .collect::<Vec<_>>();
+
+        let integer_approx = LookupOp::Sigmoid {
+            scale: utils::F32(3.0),
+        };
+        let rational = LookupOp::RationalScale {
+            op: Box::new(integer_approx.clone()),
+            num,
+            den,
+        };
+
+        let error = |res: Vec<i128>| {
+            res.iter()
+                .zip(expected.iter())
+                .map(|(a, b)| (a - b).abs())
+                .sum::<i128>()
+        };
+
+        let rational_error = error(eval(&rational, &xs));
+        let integer_error = error(eval(&integer_approx, &xs));
+
+        assert_eq!(rational_error, 0);
+        assert!(integer_error > rational_error);
+    }
+}
+
+#[cfg(test)]
+mod table_assignment_order {
+    use super::*;
+    use crate::circuit::table::{AssignmentOrder, Table};
+
+    #[test]
+    fn row_and_column_major_cover_the_same_cells() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = Table::<F>::configure(&mut cs, (-20, 20), 4, &LookupOp::ReLU, None);
+        // the range must spill over several columns with a partial final column
+        assert!(table.table_inputs.len() > 1);
+        assert_ne!(41 % table.col_size, 0);
+
+        let column_major = table.assignment_plan(AssignmentOrder::ColumnMajor);
+        let row_major = table.assignment_plan(AssignmentOrder::RowMajor);
+        assert_ne!(column_major, row_major);
+
+        let flatten = |plan: Vec<Vec<usize>>| {
+            let mut cells = plan
+                .into_iter()
+                .flatten()
+                .map(|offset| table.cartesian_coord(offset))
+                .collect::<Vec<_>>();
+            cells.sort();
+            cells
+        };
+
+        let column_major = flatten(column_major);
+        assert_eq!(column_major.len(), 41);
+        assert_eq!(column_major, flatten(row_major));
+    }
+}
+
+#[cfg(test)]
+mod shared_lookup_index {
+    use super::*;
+
+    #[test]
+    fn lookups_of_different_degree_share_an_index() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..4)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-2, 2),
+                4,
+                &LookupOp::ReLU,
+            )
+            .unwrap();
+        let num_advice_columns = cs.num_advice_columns();
+
+        // a different index is rejected rather than silently replaced by the shared one
+        let err = config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[3],
+                (-8, 8),
+                4,
+                &LookupOp::Abs,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::InconsistentLookupIndex)
+        ));
+        assert!(!config.static_lookups.tables.contains_key(&LookupOp::Abs));
+
+        config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                4,
+                &LookupOp::Abs,
+            )
+            .unwrap();
+
+        let degree = |nl: &LookupOp| config.static_lookups.tables[nl].selector_constructor.degree;
+        assert_eq!(degree(&LookupOp::ReLU), 1);
+        assert_eq!(degree(&LookupOp::Abs), 3);
+        assert_eq!(config.static_lookups.index, advices[2]);
+        assert_eq!(cs.num_advice_columns(), num_advice_columns);
+    }
+}
+
+#[cfg(test)]
+mod dry_run {
+    use super::*;
+
+    const K: usize = 5;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl MyCircuit<F> {
+        fn ops(&self) -> Vec<(Box<dyn Op<F>>, Vec<ValTensor<F>>)> {
+            vec![
+                (Box::new(PolyOp::Add), self.inputs.to_vec()),
+                (Box::new(PolyOp::Mult), self.inputs.to_vec()),
+                (
+                    Box::new(PolyOp::Sum { axes: vec![0] }),
+                    vec![self.inputs[0].clone()],
+                ),
+            ]
+        }
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, 3 * LEN);
+            let b = VarTensor::new_advice(cs, K, 1, 3 * LEN);
+            let output = VarTensor::new_advice(cs, K, 1, 3 * LEN);
+
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let plan = config.clone().dry_run(&self.ops()).unwrap();
+
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1);
+                        for ((op, values), rows) in self.ops().into_iter().zip(plan.ops.iter()) {
+                            assert_eq!(region.row(), rows.start_row);
+                            config
+                                .layout(&mut region, &values, op)
+                                .map_err(|_| Error::Synthesis)?;
+                            assert_eq!(region.row(), rows.end_row);
+                        }
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn dry_run_matches_assignment() {
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+
+        let circuit = MyCircuit::<F> {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            _marker: PhantomData,
+        };
+
+        let plan = BaseConfig::<F>::dummy(K, 1)
+            .dry_run(&circuit.ops())
+            .unwrap();
+        assert_eq!(plan.ops.len(), 3);
+        assert!(plan.total_rows() > 0);
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod nonuniform_table {
+    use super::*;
+    use crate::circuit::table::Table;
+
+    #[test]
+    fn sub_ranges_of_different_widths() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table =
+            Table::<F>::configure_nonuniform(&mut cs, &[(-4, 4), (10, 50)], 6, &LookupOp::ReLU)
+                .unwrap();
+
+        assert_eq!(table.table_inputs.len(), 2);
+        assert_eq!(table.selector_constructor.degree, 2);
+        assert_eq!(table.range, (-4, 50));
+
+        assert_eq!(table.get_col_index(F::from(3)).unwrap(), F::from(0));
+        assert_eq!(table.get_col_index(-F::from(4)).unwrap(), F::from(0));
+        assert_eq!(table.get_col_index(F::from(10)).unwrap(), F::from(1));
+        assert_eq!(table.get_col_index(F::from(50)).unwrap(), F::from(1));
+        // inputs between or beyond the sub-ranges have no column
+        assert!(table.get_col_index(F::from(7)).is_err());
+        assert!(table.get_col_index(F::from(51)).is_err());
+        assert_eq!(table.get_first_element(1), (F::from(10), F::from(10)));
+
+        // a sub-range wider than a column is rejected
+        assert!(
+            Table::<F>::configure_nonuniform(&mut cs, &[(0, 1000)], 6, &LookupOp::ReLU).is_err()
+        );
+    }
+
+    const K: usize = 6;
+    const SUB_RANGES: [crate::circuit::table::Range; 2] = [(-4, 4), (10, 50)];
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 4));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup_nonuniform(cs, &a, &output, &b, &SUB_RANGES, K, &LookupOp::ReLU)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    config
+                        .layout(&mut region, &[self.input.clone()], Box::new(LookupOp::ReLU))
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit(values: &[i128]) -> MyCircuit {
+        let input = Tensor::from(
+            values
+                .iter()
+                .map(|v| Value::known(crate::fieldutils::i128_to_felt::<F>(*v))),
+        );
+        MyCircuit {
+            input: ValTensor::from(input),
+        }
+    }
+
+    #[test]
+    fn configured_through_base_config() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = MyCircuit::configure(&mut cs);
+        let table = &config.static_lookups.tables[&LookupOp::ReLU];
+        assert_eq!(table.sub_ranges, Some(SUB_RANGES.to_vec()));
+        assert_eq!(table.table_inputs.len(), 2);
+
+        // configuring again over the same sub-ranges is a no-op, over others an error
+        let a = config.custom_gates.inputs[0].clone();
+        let b = config.custom_gates.inputs[1].clone();
+        let output = config.custom_gates.output.clone();
+        config
+            .configure_lookup_nonuniform(&mut cs, &a, &output, &b, &SUB_RANGES, K, &LookupOp::ReLU)
+            .unwrap();
+        assert!(config
+            .configure_lookup_nonuniform(&mut cs, &a, &output, &b, &[(0, 4)], K, &LookupOp::ReLU)
+            .is_err());
+    }
+
+    #[test]
+    fn lookups_within_the_sub_ranges_are_satisfied() {
+        let prover = MockProver::run(K as u32, &circuit(&[-4, 0, 4, 10, 33, 50]), vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn inputs_between_the_sub_ranges_are_rejected() {
+        assert!(MockProver::run(K as u32, &circuit(&[7]), vec![]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod check_mode_structure {
+    use super::*;
+
+    fn configure(check_mode: CheckMode) -> (ConstraintSystem<F>, BaseConfig<F>) {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            check_mode,
+        );
+        config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[2],
+                &advices[1],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .unwrap();
+        (cs, config)
+    }
+
+    #[test]
+    fn safe_mode_does_not_change_constraints() {
+        let (safe_cs, safe) = configure(CheckMode::SAFE);
+        let (unsafe_cs, _) = configure(CheckMode::UNSAFE);
+
+        for check_mode in [
+            CheckMode::SAFE,
+            CheckMode::UNSAFE,
+            CheckMode::WARN,
+            CheckMode::OUTPUTS_ONLY,
+        ] {
+            assert!(!check_mode.affects_constraint_system());
+        }
+        assert_eq!(safe.safe_mode_extra_constraints(), 0);
+
+        let num_constraints = |cs: &ConstraintSystem<F>| {
+            cs.gates()
+                .iter()
+                .map(|g| g.polynomials().len())
+                .sum::<usize>()
+        };
+        assert_eq!(num_constraints(&safe_cs), num_constraints(&unsafe_cs));
+        assert_eq!(safe_cs.num_selectors(), unsafe_cs.num_selectors());
+        assert_eq!(safe_cs.degree(), unsafe_cs.degree());
+    }
+}
+
+#[cfg(test)]
+mod scaled_lookup {
+    use super::*;
+
+    // relu(x) * 2
+    fn doubled() -> LookupOp {
+        LookupOp::Piecewise {
+            breakpoints: vec![0],
+            slopes: vec![0, 2],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        }
+    }
+
+    fn configure(share_scaled_tables: bool) -> BaseConfig<F> {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config.share_scaled_tables = share_scaled_tables;
+
+        for nl in [LookupOp::ReLU, doubled()] {
+            config
+                .configure_lookup(
+                    &mut cs,
+                    &advices[0],
+                    &advices[2],
+                    &advices[1],
+                    (-8, 8),
+                    4,
+                    &nl,
+                )
+                .unwrap();
+        }
+        config
+    }
+
+    #[test]
+    fn power_of_two_scaled_op_shares_table() {
+        let config = configure(true);
+        let doubled = doubled();
+
+        assert_eq!(config.static_lookups.tables.len(), 1);
+        assert_eq!(
+            config.static_lookups.scaled.get(&doubled),
+            Some(&(LookupOp::ReLU, 1))
+        );
+
+        let relu_table = &config.static_lookups.tables[&LookupOp::ReLU];
+        let doubled_table = config.static_lookups.table_for(&doubled).unwrap();
+        assert_eq!(relu_table.table_inputs, doubled_table.table_inputs);
+        assert_eq!(relu_table.table_outputs, doubled_table.table_outputs);
+
+        assert!(config
+            .static_lookups
+            .selectors
+            .keys()
+            .any(|(op, _, _)| *op == doubled));
+    }
+
+    #[test]
+    fn scaled_tables_are_not_shared_by_default() {
+        let config = configure(false);
+        assert_eq!(config.static_lookups.tables.len(), 2);
+        assert!(config.static_lookups.scaled.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tolerance_scale {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+
+    fn values(scale: crate::Scale) -> Vec<ValTensor<F>> {
+        let mut a: ValTensor<F> = Tensor::from((0..4).map(|i| Value::known(F::from(i + 1)))).into();
+        a.set_scale(scale);
+        vec![a.clone(), a]
+    }
+
+    #[test]
+    fn matching_scale_passes() {
+        let mut config = BaseConfig::<F>::default();
+        config.strict = true;
+        let op = HybridOp::RangeCheck(Tolerance {
+            val: 1.0,
+            scale: utils::F32(128.0),
+            ..Default::default()
+        });
+        assert!(config.check_tolerance_scale(&op, &values(7)).is_ok());
+    }
+
+    #[test]
+    fn mismatched_scale_errors_in_strict_mode() {
+        let mut config = BaseConfig::<F>::default();
+        // quantized at scale 7 (multiplier 128) but the tolerance is measured at scale 8
+        let op = HybridOp::RangeCheck(Tolerance {
+            val: 1.0,
+            scale: utils::F32(256.0),
+            ..Default::default()
+        });
+        assert!(config.check_tolerance_scale(&op, &values(7)).is_ok());
+
+        config.strict = true;
+        assert!(config.check_tolerance_scale(&op, &values(7)).is_err());
+
+        // a zero tolerance is an equality check, so the scale doesn't matter
+        let op = HybridOp::RangeCheck(Tolerance {
+            val: 0.0,
+            scale: utils::F32(256.0),
+            ..Default::default()
+        });
+        assert!(config.check_tolerance_scale(&op, &values(7)).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod table_names {
+    use super::*;
+    use crate::circuit::table::{RangeCheck, Table};
+
+    #[test]
+    fn table_name_contains_op_and_range() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = Table::<F>::configure(&mut cs, (-128, 128), 10, &LookupOp::ReLU, None);
+        let name = table.name();
+        assert!(name.contains("RELU"));
+        assert_eq!(name, "table:RELU[-128,128]");
+    }
+
+    #[test]
+    fn range_check_name_contains_range() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let range_check = RangeCheck::<F>::configure(&mut cs, (0, 255), 10, None);
+        assert_eq!(range_check.name(), "range_check[0,255]");
+    }
+}
+
+#[cfg(test)]
+mod dynamic_range_check {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use std::cell::RefCell;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+    const RANGE: crate::circuit::table::Range = (0, 15);
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN * 3);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * 3);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * 3);
+            let index = VarTensor::new_advice(cs, K, 1, LEN * 3);
+            let diff = VarTensor::new_advice(cs, K, 1, LEN * 3);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::UNSAFE);
+            config
+                .configure_dynamic_range_check(cs, &a, &b, &diff)
+                .unwrap();
+            config
+                .configure_range_check(cs, &a, &index, RANGE, K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    // skip the witness-side range assertions, and the column index checks of safe mode,
+                    // so that the constraints alone are tested
+                    let mut region = RegionCtx::from_wrapped_region(
+                        Some(RefCell::new(region)),
+                        0,
+                        1,
+                        Default::default(),
+                        Default::default(),
+                    );
+                    config
+                        .layout(
+                            &mut region,
+                            &self.inputs,
+                            Box::new(HybridOp::DynamicRangeCheck { range: RANGE }),
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(x: [u64; LEN], bound: [u64; LEN]) -> MockProver<F> {
+        let circuit = MyCircuit::<F> {
+            inputs: [
+                ValTensor::from(Tensor::from(
+                    x.into_iter().map(|v| Value::known(F::from(v))),
+                )),
+                ValTensor::from(Tensor::from(
+                    bound.into_iter().map(|v| Value::known(F::from(v))),
+                )),
+            ],
+            _marker: PhantomData,
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn accepts_inputs_below_witnessed_bound() {
+        run([0, 4, 7, 15], [1, 5, 16, 16]).assert_satisfied();
+    }
+
+    #[test]
+    fn rejects_inputs_at_or_above_witnessed_bound() {
+        assert!(run([0, 5, 7, 3], [1, 5, 16, 16]).verify().is_err());
+        assert!(run([0, 4, 9, 3], [1, 5, 8, 16]).verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod sorted_range_check {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use std::cell::RefCell;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+    const RANGE: crate::circuit::table::Range = (2, 17);
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN * 4);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * 4);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * 4);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b], &output, CheckMode::UNSAFE);
+            config
+                .configure_sorted_range_check(cs, &a, RANGE, K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    // skip the witness-side range assertions so that the constraints alone are tested
+                    let mut region = RegionCtx::from_wrapped_region(
+                        Some(RefCell::new(region)),
+                        0,
+                        1,
+                        Default::default(),
+                        Default::default(),
+                    );
+                    config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(HybridOp::SortedRangeCheck { range: RANGE }),
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(x: [u64; LEN]) -> MockProver<F> {
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(Tensor::from(
+                x.into_iter().map(|v| Value::known(F::from(v))),
+            )),
+            _marker: PhantomData,
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn accepts_sorted_column() {
+        run([2, 5, 5, 17]).assert_satisfied();
+    }
+
+    #[test]
+    fn rejects_out_of_order_column() {
+        assert!(run([2, 6, 5, 17]).verify().is_err());
+    }
+
+    #[test]
+    fn rejects_sorted_column_out_of_range() {
+        assert!(run([2, 5, 5, 18]).verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod tight_table {
+    use super::*;
+    use crate::circuit::table::{Range, RANGE_MULTIPLIER};
+    use std::cell::RefCell;
+
+    const K: usize = 6;
+    const DATA_RANGE: Range = (0, 7);
+
+    /// the range calibration configures for [DATA_RANGE] with a lookup safety margin of `margin`
+    fn range_for(margin: i128) -> Range {
+        (DATA_RANGE.0 * margin, DATA_RANGE.1 * margin)
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        margin: i128,
+    }
+
+    #[derive(Clone, Default)]
+    struct Params {
+        margin: i128,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = Params;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn params(&self) -> Self::Params {
+            Params {
+                margin: self.margin,
+            }
+        }
+
+        fn configure_with_params(
+            cs: &mut ConstraintSystem<F>,
+            params: Self::Params,
+        ) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, 4);
+            let b = VarTensor::new_advice(cs, K, 1, 4);
+            let output = VarTensor::new_advice(cs, K, 1, 4);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::UNSAFE);
+            config
+                .configure_range_check(cs, &a, &b, range_for(params.margin), K)
+                .unwrap();
+            config
+        }
+
+        fn configure(_: &mut ConstraintSystem<F>) -> Self::Config {
+            unimplemented!("call configure_with_params instead")
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    // skip the witness-side range assertions, and the column index checks of safe mode,
+                    // so that the lookup alone is tested
+                    let mut region = RegionCtx::from_wrapped_region(
+                        Some(RefCell::new(region)),
+                        0,
+                        1,
+                        Default::default(),
+                        Default::default(),
+                    );
+                    layouts::range_check(
+                        &config,
+                        &mut region,
+                        &[self.input.clone()],
+                        &range_for(self.margin),
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(values: [u64; 2], margin: i128) -> MockProver<F> {
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(Tensor::from(
+                values.into_iter().map(|v| Value::known(F::from(v))),
+            )),
+            margin,
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn tight_range_is_half_the_size() {
+        let safe = range_for(RANGE_MULTIPLIER);
+        let tight = range_for(1);
+        assert_eq!(tight, DATA_RANGE);
+        assert_eq!(2 * (tight.1 - tight.0), safe.1 - safe.0);
+    }
+
+    #[test]
+    fn tight_range_rejects_values_outside_the_data_range() {
+        run([3, 7], 1).assert_satisfied();
+        // within the safety margin, but outside of the data range
+        run([3, 12], RANGE_MULTIPLIER).assert_satisfied();
+        assert!(run([3, 12], 1).verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod configured_blocks {
+    use super::*;
+
+    #[test]
+    fn reports_every_block_and_inner_col() {
+        let mut cs = ConstraintSystem::<F>::default();
+        // capacity beyond a single column forces several blocks
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 2, 40))
+            .collect::<Vec<_>>();
+        assert!(advices[0].num_blocks() > 1);
+
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[2],
+                &advices[1],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .unwrap();
+
+        let expected = (0..advices[0].num_blocks())
+            .flat_map(|x| (0..2).map(move |y| (x, y)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            config.static_lookups.configured_blocks(&LookupOp::ReLU),
+            expected
+        );
+        assert!(config
+            .static_lookups
+            .configured_blocks(&LookupOp::Sigmoid {
+                scale: utils::F32(1.0)
+            })
+            .is_empty());
+    }
+}
+
+#[cfg(test)]
+mod non_integer_field_element {
+    use super::*;
+    use crate::circuit::table::{RangeCheck, Table};
+
+    #[test]
+    fn rejects_elements_outside_the_table_range() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = Table::<F>::configure(&mut cs, (-8, 8), 4, &LookupOp::ReLU, None);
+        let range_check = RangeCheck::<F>::configure(&mut cs, (0, 8), 4, None);
+
+        // not the encoding of any small signed integer
+        let large = F::from(2).pow([200]) + F::from(3);
+
+        assert!(matches!(
+            table.try_get_col_index(large),
+            Err(CircuitError::NonIntegerFieldElement(..))
+        ));
+        assert!(matches!(
+            range_check.try_get_col_index(large),
+            Err(CircuitError::NonIntegerFieldElement(..))
+        ));
+        assert!(table.try_get_col_index(F::from(9)).is_err());
+
+        assert_eq!(
+            table.try_get_col_index(-F::from(8)).unwrap(),
+            table.get_col_index(-F::from(8)).unwrap()
+        );
+        assert_eq!(
+            range_check.try_get_col_index(F::from(8)).unwrap(),
+            range_check.get_col_index(F::from(8))
+        );
+    }
+}
+
+#[cfg(test)]
+mod shared_table_evals {
+    use super::*;
+    use crate::circuit::table::TableEvalCache;
+
+    #[derive(Clone)]
+    struct ReLUCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        cache: TableEvalCache<F>,
+    }
+
+    impl Circuit<F> for ReLUCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, 4, 1, 3))
+                .collect::<Vec<_>>();
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    (-6, 6),
+                    4,
+                    &LookupOp::ReLU,
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.share_table_evals(&self.cache);
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    config
+                        .layout(&mut region, &[self.input.clone()], Box::new(LookupOp::ReLU))
+                        .map_err(|_| Error::Synthesis)
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn circuits_proven_in_parallel_evaluate_tables_once() {
+        let cache = TableEvalCache::<F>::new();
+
+        std::thread::scope(|s| {
+            let handles = [1_u64, 5].map(|v| {
+                let circuit = ReLUCircuit::<F> {
+                    input: ValTensor::from(Tensor::from((0..3).map(|_| Value::known(F::from(v))))),
+                    cache: cache.clone(),
+                };
+                s.spawn(move || {
+                    MockProver::run(4_u32, &circuit, vec![])
+                        .unwrap()
+                        .verify()
+                        .is_ok()
+                })
+            });
+            for handle in handles {
+                assert!(handle.join().unwrap());
+            }
+        });
+
+        assert_eq!(cache.num_evaluations(), 1);
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod vk_structure_digest {
+    use super::*;
+
+    fn configure(cs: &mut ConstraintSystem<F>) -> (BaseConfig<F>, Vec<VarTensor>) {
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config
+            .configure_lookup(
+                cs,
+                &advices[0],
+                &advices[2],
+                &advices[1],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .unwrap();
+        (config, advices)
+    }
+
+    #[test]
+    fn identical_structure_gives_identical_digest() {
+        let (a, _) = configure(&mut ConstraintSystem::<F>::default());
+        let (b, _) = configure(&mut ConstraintSystem::<F>::default());
+        assert_eq!(a.vk_structure_digest(), b.vk_structure_digest());
+
+        // check mode doesn't affect the constraint system
+        let mut c = b.clone();
+        c.check_mode = CheckMode::UNSAFE;
+        assert_eq!(a.vk_structure_digest(), c.vk_structure_digest());
+    }
+
+    #[test]
+    fn changed_gate_set_changes_digest() {
+        let (a, _) = configure(&mut ConstraintSystem::<F>::default());
+
+        let mut cs = ConstraintSystem::<F>::default();
+        let (mut b, advices) = configure(&mut cs);
+        b.configure_dynamic_range_check(&mut cs, &advices[0], &advices[1], &advices[2])
+            .unwrap();
+
+        assert_ne!(a.vk_structure_digest(), b.vk_structure_digest());
+    }
+
+    #[test]
+    fn proofs_same_circuit_compares_all_digests() {
+        let (a, _) = configure(&mut ConstraintSystem::<F>::default());
+        let (b, _) = configure(&mut ConstraintSystem::<F>::default());
+
+        let mut cs = ConstraintSystem::<F>::default();
+        let (mut c, advices) = configure(&mut cs);
+        c.configure_dynamic_range_check(&mut cs, &advices[0], &advices[1], &advices[2])
+            .unwrap();
+
+        let [a, b, c] = [a, b, c].map(|config| config.vk_structure_digest());
+        assert!(proofs_same_circuit(&[a, b]));
+        assert!(!proofs_same_circuit(&[a, b, c]));
+        assert!(proofs_same_circuit(&[c]));
+    }
+}
+
+#[cfg(test)]
+mod repeated_configuration {
+    use super::*;
+
+    const NUM_REPEATS: usize = 10_000;
+
+    #[test]
+    fn repeated_calls_configure_once() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        let num_selectors = cs.num_selectors();
+
+        let start = instant::Instant::now();
+        for _ in 0..NUM_REPEATS {
+            config
+                .configure_lookup(
+                    &mut cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    (-8, 8),
+                    4,
+                    &LookupOp::ReLU,
+                )
+                .unwrap();
+            config
+                .configure_range_check(&mut cs, &advices[0], &advices[2], (0, 8), 4)
+                .unwrap();
+        }
+        log::debug!(
+            "{} repeated configurations took {:?}",
+            NUM_REPEATS,
+            start.elapsed()
+        );
+
+        assert_eq!(config.static_lookups.tables.len(), 1);
+        assert_eq!(config.static_lookups.selectors.len(), 1);
+        assert_eq!(config.range_checks.ranges.len(), 1);
+        assert_eq!(config.range_checks.selectors.len(), 1);
+        assert_eq!(cs.num_selectors(), num_selectors + 2);
+    }
+}
+
+#[cfg(test)]
+mod range_check_output {
+    use super::*;
+
+    const K: usize = 6;
+    const RANGE: crate::circuit::table::Range = (0, 15);
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        expected_output: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output, index, echo] = [0; 5].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_range_check_with_output(cs, &a, &index, Some(&echo), RANGE, K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let echoed =
+                        layouts::range_check(&config, &mut region, &[self.input.clone()], &RANGE)
+                            .map_err(|_| Error::Synthesis)?;
+                    // downstream use of the echoed values
+                    layouts::enforce_equality(
+                        &config,
+                        &mut region,
+                        &[echoed, self.expected_output.clone()],
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(input: [u64; 3], expected_output: [u64; 3]) -> MockProver<F> {
+        let to_val_tensor = |v: [u64; 3]| {
+            ValTensor::from(Tensor::from(
+                v.into_iter().map(|v| Value::known(F::from(v))),
+            ))
+        };
+        let circuit = MyCircuit::<F> {
+            input: to_val_tensor(input),
+            expected_output: to_val_tensor(expected_output),
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn output_column_echoes_the_input() {
+        run([1, 7, 15], [1, 7, 15]).assert_satisfied();
+    }
+
+    #[test]
+    fn output_column_differing_from_the_input_fails() {
+        assert!(run([1, 7, 15], [1, 8, 15]).verify().is_err());
+    }
+
+    #[test]
+    fn output_column_is_configured_on_the_range_check() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = MyCircuit::<F>::configure(&mut cs);
+        let range_check = &config.range_checks.ranges[&RANGE];
+        assert!(range_check.output.is_some());
+    }
+}
+
+#[cfg(test)]
+mod num_table_columns {
+    use super::*;
+
+    #[test]
+    fn counts_every_allocated_table_column_once() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        // two tables over the same range share their input columns
+        for nl in [LookupOp::ReLU, LookupOp::Abs] {
+            config
+                .configure_lookup(
+                    &mut cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    (-20, 20),
+                    4,
+                    &nl,
+                )
+                .unwrap();
+        }
+        config
+            .configure_range_check(&mut cs, &advices[0], &advices[2], (0, 30), 4)
+            .unwrap();
+
+        let relu = &config.static_lookups.tables[&LookupOp::ReLU];
+        assert_eq!(
+            relu.table_inputs,
+            config.static_lookups.tables[&LookupOp::Abs].table_inputs
+        );
+
+        // every lookup_table_column call allocates a fixed column, and nothing else here does
+        assert_eq!(config.num_table_columns(), cs.num_fixed_columns());
+        assert_eq!(
+            config.num_table_columns(),
+            3 * relu.table_inputs.len() + config.range_checks.ranges[&(0, 30)].inputs.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod is_zero {
+    use super::*;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 5;
+
+    #[derive(Clone)]
+    struct IsZeroCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for IsZeroCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, 8))
+                .collect::<Vec<_>>();
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config
+                .configure_lookup(
+                    cs,
+                    &advices[0],
+                    &advices[2],
+                    &advices[1],
+                    (-8, 8),
+                    K,
+                    &LookupOp::IS_ZERO,
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let output = config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(LookupOp::IS_ZERO),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    let evals = output.get_int_evals().map_err(|_| Error::Synthesis)?;
+                    assert_eq!(evals.iter().cloned().collect::<Vec<_>>(), vec![1, 0, 0, 0]);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn indicator_is_one_only_at_zero() {
+        let x = Tensor::from([0, 1, -3, 8].into_iter().map(i128_to_felt::<F>));
+        let res = Op::<F>::f(&LookupOp::IS_ZERO, &[x]).unwrap().output;
+        assert_eq!(res, Tensor::from([1u64, 0, 0, 0].into_iter().map(F::from)));
+    }
+
+    #[test]
+    fn iszerocircuit() {
+        let input = Tensor::from(
+            [0, 1, -3, 8]
+                .into_iter()
+                .map(|v: i128| Value::known(i128_to_felt::<F>(v))),
+        );
+        let circuit = IsZeroCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod incremental_range_check {
+    use super::*;
+    use halo2_proofs::{
+        plonk::keygen_vk,
+        poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    };
+    use halo2curves::bn256::Bn256;
+    use std::collections::BTreeSet;
+    use std::sync::{Arc, Mutex};
+
+    const K: usize = 4;
+    // wide enough to span several table chunks at this K
+    const RANGE: crate::circuit::table::Range = (0, 127);
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        batches: Vec<ValTensor<F>>,
+        // chunks assigned after each batch, plus the total number of chunks
+        assigned: Arc<Mutex<Vec<BTreeSet<usize>>>>,
+        num_chunks: Arc<Mutex<usize>>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output, index] = [0; 4].map(|_| VarTensor::new_advice(cs, K, 1, 4));
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_range_check(cs, &a, &index, RANGE, K)
+                .unwrap();
+            config.set_incremental_range_checks();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            for batch in &self.batches {
+                layouter.assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1);
+                        layouts::range_check(&config, &mut region, &[batch.clone()], &RANGE)
+                            .map_err(|_| Error::Synthesis)?;
+                        Ok(())
+                    },
+                )?;
+                config.layout_range_checks(&mut layouter).unwrap();
+                let range_check = &config.range_checks.ranges[&RANGE];
+                self.assigned
+                    .lock()
+                    .unwrap()
+                    .push(range_check.assigned_chunks.clone());
+                *self.num_chunks.lock().unwrap() = range_check.num_chunks();
+            }
+            config
+                .finish_incremental_range_checks(&mut layouter)
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    fn to_val_tensor(v: &[u64]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(v.iter().map(|v| Value::known(F::from(*v)))))
+    }
+
+    #[test]
+    fn only_exercised_chunks_are_assigned() {
+        let circuit = MyCircuit::<F> {
+            batches: vec![to_val_tensor(&[0, 1]), to_val_tensor(&[127])],
+            assigned: Arc::new(Mutex::new(vec![])),
+            num_chunks: Arc::new(Mutex::new(0)),
+        };
+        // the chunks no batch exercised only get their default row, which keeps every lookup satisfied
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let num_chunks = *circuit.num_chunks.lock().unwrap();
+        assert!(num_chunks > 2);
+        let assigned = circuit.assigned.lock().unwrap();
+        // the first batch only touches the first chunk, so the remaining chunks are deferred
+        assert_eq!(assigned[0], BTreeSet::from([0]));
+        // the second batch exercises the last chunk, and the chunks in between are still skipped
+        assert_eq!(assigned[1], BTreeSet::from([0, num_chunks - 1]));
+    }
+
+    #[test]
+    fn unknown_inputs_assign_every_chunk() {
+        let circuit = MyCircuit::<F> {
+            batches: vec![ValTensor::from(Tensor::from(
+                [Value::<F>::unknown(), Value::unknown()].into_iter(),
+            ))],
+            assigned: Arc::new(Mutex::new(vec![])),
+            num_chunks: Arc::new(Mutex::new(0)),
+        };
+        // the mock prover rejects unknown values, keygen synthesizes the circuit without them
+        let params = ParamsKZG::<Bn256>::setup(K as u32, OsRng);
+        keygen_vk(&params, &circuit).unwrap();
+
+        // the values aren't known, so the full table is assigned and committed to in the keys
+        let num_chunks = *circuit.num_chunks.lock().unwrap();
+        let assigned = circuit.assigned.lock().unwrap();
+        assert_eq!(assigned[0], (0..num_chunks).collect::<BTreeSet<_>>());
+    }
+}
+
+#[cfg(test)]
+mod config_not_initialized {
+    use super::*;
+
+    #[test]
+    fn configure_lookup_on_default_config_errors() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+
+        let mut config = BaseConfig::<F>::default();
+        let err = config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::ConfigNotInitialized(_))
+        ));
+        // nothing was configured
+        assert!(config.static_lookups.tables.is_empty());
+        assert_eq!(cs.num_selectors(), 0);
+    }
+
+    #[test]
+    fn configure_lookup_after_configure_succeeds() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        assert!(config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                4,
+                &LookupOp::ReLU,
+            )
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+mod quotient_poly_degree {
+    use super::*;
+    use halo2_proofs::{
+        plonk::keygen_vk_custom,
+        poly::{commitment::ParamsProver, kzg::commitment::ParamsKZG},
+    };
+    use halo2curves::bn256::Bn256;
+
+    const K: usize = 6;
+
+    #[derive(Clone, Default)]
+    struct MyCircuit;
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(cs, &a, &output, &b, (-8, 8), K, &LookupOp::ReLU)
+                .unwrap();
+            config
+                .configure_range_check(cs, &a, &b, (0, 15), K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            config.layout_range_checks(&mut layouter).unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn matches_the_verifying_key_domain() {
+        let mut cs = ConstraintSystem::<F>::default();
+        MyCircuit::configure(&mut cs);
+
+        let params = ParamsKZG::<Bn256>::setup(K as u32, OsRng);
+        // selector compression can change the degree, so compare against an uncompressed key
+        let vk = keygen_vk_custom(&params, &MyCircuit, false).unwrap();
+
+        let expected = vk.get_domain().get_quotient_poly_degree();
+        assert!(expected > 1);
+        assert_eq!(BaseConfig::<F>::quotient_poly_degree(&cs), expected);
+    }
+}
+
+#[cfg(test)]
+mod lookup_default_fill {
+    use super::*;
+
+    fn configure(default_fill: Option<(i64, i64)>) -> (ConstraintSystem<F>, Result<(), String>) {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 6, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        let res = config
+            .configure_lookup_with_default_fill(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                6,
+                &LookupOp::ReLU,
+                default_fill,
+            )
+            .map_err(|e| e.to_string());
+        (cs, res)
+    }
+
+    #[test]
+    fn chosen_default_is_used_for_unselected_rows() {
+        let first_element = format!("{:?}", -F::from(8));
+        let chosen = format!("{:?}", F::from(3));
+
+        let (cs, res) = configure(None);
+        assert!(res.is_ok());
+        let lookups = format!("{:?}", cs.lookups());
+        assert!(lookups.contains(&first_element));
+        assert!(!lookups.contains(&chosen));
+
+        // relu(3) = 3
+        let (cs, res) = configure(Some((3, 3)));
+        assert!(res.is_ok());
+        let lookups = format!("{:?}", cs.lookups());
+        assert!(lookups.contains(&chosen));
+        assert!(!lookups.contains(&first_element));
+    }
+
+    #[test]
+    fn invalid_defaults_are_rejected() {
+        // out of range
+        assert!(configure(Some((9, 9))).1.is_err());
+        // not a row of the table
+        assert!(configure(Some((-3, -3))).1.is_err());
+    }
+
+    #[test]
+    fn scaled_ops_are_checked_against_their_own_outputs() {
+        // relu(x) * 2, which shares the relu table
+        let doubled = LookupOp::Piecewise {
+            breakpoints: vec![0],
+            slopes: vec![0, 2],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        };
+        let configure = |default_fill: Option<(i64, i64)>| {
+            let mut cs = ConstraintSystem::<F>::default();
+            let advices = (0..3)
+                .map(|_| VarTensor::new_advice(&mut cs, 6, 1, 3))
+                .collect::<Vec<_>>();
+            let mut config = BaseConfig::<F>::configure(
+                &mut cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            config.share_scaled_tables = true;
+            config
+                .configure_lookup(
+                    &mut cs,
+                    &advices[0],
+                    &advices[1],
+                    &advices[2],
+                    (-8, 8),
+                    6,
+                    &LookupOp::ReLU,
+                )
+                .unwrap();
+            let res = config.configure_lookup_with_default_fill(
+                &mut cs,
+                &advices[0],
+                &advices[1],
+                &advices[2],
+                (-8, 8),
+                6,
+                &doubled,
+                default_fill,
+            );
+            if res.is_ok() {
+                assert!(config.static_lookups.scaled.contains_key(&doubled));
+            }
+            res
+        };
+
+        assert!(configure(Some((3, 6))).is_ok());
+        // the output of the shared relu table, not of the doubled op
+        assert!(configure(Some((3, 3))).is_err());
+    }
+}
+
+#[cfg(test)]
+mod selector_already_configured {
+    use super::*;
+
+    fn vars(cs: &mut ConstraintSystem<F>) -> Vec<VarTensor> {
+        (0..3)
+            .map(|_| VarTensor::new_advice(cs, 4, 1, 3))
+            .collect::<Vec<_>>()
+    }
+
+    #[test]
+    fn configuring_twice_errors() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = vars(&mut cs);
+        let inputs = [advices[0].clone(), advices[1].clone()];
+
+        let mut config = BaseConfig::<F>::configure(&mut cs, &inputs, &advices[2], CheckMode::SAFE);
+        let num_gates = cs.gates().len();
+
+        assert!(matches!(
+            config.configure_custom_gates(&mut cs, &inputs, &advices[2]),
+            Err(CircuitError::SelectorAlreadyConfigured { .. })
+        ));
+        // no gates were added on top of the original ones
+        assert_eq!(cs.gates().len(), num_gates);
+    }
+
+    #[test]
+    fn configuring_a_dynamic_range_check_twice_errors() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = vars(&mut cs);
+
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config
+            .configure_dynamic_range_check(&mut cs, &advices[0], &advices[1], &advices[2])
+            .unwrap();
+        let err = config
+            .configure_dynamic_range_check(&mut cs, &advices[0], &advices[1], &advices[2])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::SelectorAlreadyConfigured { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod quantize_dequantize {
+    use super::*;
+    use crate::circuit::table::Table;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    const RANGE: crate::circuit::table::Range = (-512, 512);
+
+    fn apply(op: &LookupOp, x: &[i128]) -> Vec<i128> {
+        let x = Tensor::from(x.iter().map(|v| i128_to_felt::<F>(*v)));
+        Op::<F>::f(op, &[x])
+            .unwrap()
+            .output
+            .iter()
+            .map(|v| felt_to_i128(*v))
+            .collect()
+    }
+
+    #[test]
+    fn dequantize_recovers_quantized_input() {
+        for scale in [2.0, 4.0, 16.0, 0.5] {
+            let quantize = LookupOp::Quantize {
+                scale: utils::F32(scale),
+            };
+            let dequantize = LookupOp::Dequantize {
+                scale: utils::F32(scale),
+            };
+
+            let xs = (RANGE.0..=RANGE.1).collect::<Vec<_>>();
+            let round_trip = apply(&dequantize, &apply(&quantize, &xs));
+            for (x, y) in xs.iter().zip(round_trip) {
+                // recovered to within half a quantization step, or exactly when the step is below 1
+                let tolerance = (scale as f64 / 2.0).floor() as i128;
+                assert!(
+                    (x - y).abs() <= tolerance,
+                    "scale {}: {} round tripped to {}",
+                    scale,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn quantize_inverts_dequantize() {
+        let xs = (-64..=64).collect::<Vec<_>>();
+        let quantize = LookupOp::Quantize {
+            scale: utils::F32(8.0),
+        };
+        let dequantize = LookupOp::Dequantize {
+            scale: utils::F32(8.0),
+        };
+        assert_eq!(apply(&quantize, &apply(&dequantize, &xs)), xs);
+    }
+
+    #[test]
+    fn tables_evaluate_quantization() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let op = LookupOp::Quantize {
+            scale: utils::F32(4.0),
+        };
+        let table = Table::<F>::configure(&mut cs, RANGE, 12, &op, None);
+        let (x, y) = table.get_first_element(0);
+        assert_eq!(felt_to_i128(x), RANGE.0);
+        assert_eq!(felt_to_i128(y), RANGE.0 / 4);
+        assert_eq!(
+            Op::<F>::out_scale(&op, vec![7]).unwrap(),
+            7 - 2,
+            "quantizing by 4 removes two bits of scale"
+        );
+    }
+
+    #[test]
+    fn quantize_is_distinct_from_cast() {
+        let op = LookupOp::Quantize {
+            scale: utils::F32(4.0),
+        };
+        assert!(Op::<F>::as_string(&op).starts_with("QUANTIZE"));
+        let cast = LookupOp::Cast {
+            scale: utils::F32(4.0),
+        };
+        // the same evaluation, but a table of its own
+        assert_ne!(op, cast);
+        assert_eq!(apply(&op, &[-9, 3, 10]), apply(&cast, &[-9, 3, 10]));
+
+        let serialized = serde_json::to_string(&op).unwrap();
+        assert!(serialized.contains("Quantize"));
+        assert_eq!(serde_json::from_str::<LookupOp>(&serialized).unwrap(), op);
+    }
+}
+
+#[cfg(test)]
+mod outputs_only_check_mode {
+    use super::*;
+    use crate::circuit::ops::ForwardResult;
+    use crate::tensor::TensorError;
+    use std::any::Any;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Identity op counting how often its safe mode check runs.
+    #[derive(Clone, Debug, Default)]
+    struct CountingOp {
+        checks: Arc<AtomicUsize>,
+    }
+
+    impl Op<F> for CountingOp {
+        fn f(&self, x: &[Tensor<F>]) -> Result<ForwardResult<F>, TensorError> {
+            Ok(ForwardResult {
+                output: x[0].clone(),
+            })
+        }
+
+        fn as_string(&self) -> String {
+            "COUNTING".into()
+        }
+
+        fn layout(
+            &self,
+            _: &mut BaseConfig<F>,
+            _: &mut RegionCtx<F>,
+            values: &[ValTensor<F>],
+        ) -> Result<Option<ValTensor<F>>, Box<dyn std::error::Error>> {
+            Ok(Some(values[0].clone()))
+        }
+
+        fn out_scale(
+            &self,
+            scales: Vec<crate::Scale>,
+        ) -> Result<crate::Scale, Box<dyn std::error::Error>> {
+            Ok(scales[0])
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn safe_mode_check(&self, _: &ValTensor<F>, _: &[ValTensor<F>]) -> Result<(), TensorError> {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        check_mode: CheckMode,
+        op: CountingOp,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, 4, 1, 3));
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.check_mode = self.check_mode;
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let x = ValTensor::from(Tensor::from(
+                        [1u64, 2, 3].into_iter().map(|v| Value::known(F::from(v))),
+                    ));
+                    // two interior ops followed by the final output op
+                    for _ in 0..2 {
+                        config
+                            .layout(&mut region, &[x.clone()], Box::new(self.op.clone()))
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                    config
+                        .layout_output(&mut region, &[x], Box::new(self.op.clone()))
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Number of safe mode checks run while proving the circuit.
+    fn run(check_mode: CheckMode) -> usize {
+        let circuit = MyCircuit {
+            check_mode,
+            op: CountingOp::default(),
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap();
+        circuit.op.checks.load(Ordering::SeqCst)
+    }
+
+    #[test]
+    fn only_the_final_op_is_checked() {
+        // the floor planner may synthesize a region more than once, so compare relative counts
+        let outputs_only = run(CheckMode::OUTPUTS_ONLY);
+        assert!(outputs_only > 0);
+        assert_eq!(run(CheckMode::SAFE), 3 * outputs_only);
+        assert_eq!(run(CheckMode::UNSAFE), 0);
+    }
+
+    #[test]
+    fn round_trips_through_strings() {
+        for mode in [CheckMode::SAFE, CheckMode::UNSAFE, CheckMode::OUTPUTS_ONLY] {
+            assert_eq!(CheckMode::from(mode.to_string()), mode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod nested_table_ranges {
+    use super::*;
+
+    const K: usize = 6;
+    const WIDE: crate::circuit::table::Range = (-16, 16);
+    const NARROW: crate::circuit::table::Range = (-8, 8);
+
+    /// Checks the witness in safe mode unless `UNSAFE`, in which case only the constraints hold
+    /// inputs to their ranges.
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd, const UNSAFE: bool = false> {
+        input: ValTensor<F>,
+    }
+
+    impl<const UNSAFE: bool> Circuit<F> for MyCircuit<F, UNSAFE> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let check_mode = if UNSAFE {
+                CheckMode::UNSAFE
+            } else {
+                CheckMode::SAFE
+            };
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, check_mode);
+            config
+                .configure_lookup(cs, &a, &output, &b, WIDE, K, &LookupOp::ReLU)
+                .unwrap();
+            config
+                .configure_lookup(cs, &a, &output, &b, NARROW, K, &LookupOp::Abs)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    for op in [LookupOp::ReLU, LookupOp::Abs] {
+                        config
+                            .layout(&mut region, &[self.input.clone()], Box::new(op))
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn narrower_op_shares_the_wider_input_columns() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = MyCircuit::<F>::configure(&mut cs);
+
+        let relu = &config.static_lookups.tables[&LookupOp::ReLU];
+        let abs = &config.static_lookups.tables[&LookupOp::Abs];
+        assert_eq!(abs.table_inputs, relu.table_inputs);
+        assert_eq!(abs.range, WIDE);
+        assert_eq!(config.static_lookups.nested[&LookupOp::Abs], NARROW);
+        // one shared input column plus an output column per table, and the narrower range's check
+        let range_check = &config.range_checks.ranges[&NARROW];
+        assert_eq!(
+            config.num_table_columns(),
+            relu.table_inputs.len()
+                + relu.table_outputs.len()
+                + abs.table_outputs.len()
+                + range_check.inputs.len()
+        );
+    }
+
+    #[test]
+    fn nested_lookups_are_satisfied() {
+        let input = Tensor::from(
+            [-8i128, -3, 0, 5, 8]
+                .into_iter()
+                .map(|v| Value::known(crate::fieldutils::i128_to_felt::<F>(v))),
+        );
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn inputs_outside_the_narrower_range_are_rejected() {
+        // within the shared table but outside the range abs was configured for
+        let input = Tensor::from(
+            [12i128]
+                .into_iter()
+                .map(|v| Value::known(crate::fieldutils::i128_to_felt::<F>(v))),
+        );
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn narrower_range_is_constrained() {
+        // without the safe mode check, the range check alone rejects the input
+        let input = Tensor::from(
+            [12i128]
+                .into_iter()
+                .map(|v| Value::known(crate::fieldutils::i128_to_felt::<F>(v))),
+        );
+        let circuit = MyCircuit::<F, true> {
+            input: ValTensor::from(input),
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod verbose_safe_mode_check {
+    use super::*;
+    use crate::circuit::ops::{ElementDiff, ForwardResult};
+    use crate::tensor::TensorError;
+    use std::any::Any;
+
+    fn known(v: &[u64]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(v.iter().map(|v| Value::known(F::from(*v)))))
+    }
+
+    /// Identity op whose layout claims twice the true output, as an off-by-scale bug would.
+    #[derive(Clone, Debug)]
+    struct OffByScaleOp;
+
+    impl Op<F> for OffByScaleOp {
+        fn f(&self, x: &[Tensor<F>]) -> Result<ForwardResult<F>, TensorError> {
+            Ok(ForwardResult {
+                output: x[0].clone(),
+            })
+        }
+
+        fn as_string(&self) -> String {
+            "OFF_BY_SCALE".into()
+        }
+
+        fn layout(
+            &self,
+            _: &mut BaseConfig<F>,
+            _: &mut RegionCtx<F>,
+            values: &[ValTensor<F>],
+        ) -> Result<Option<ValTensor<F>>, Box<dyn std::error::Error>> {
+            let doubled = values[0]
+                .get_int_evals()?
+                .iter()
+                .map(|v| 2 * *v as u64)
+                .collect::<Vec<_>>();
+            Ok(Some(known(&doubled)))
+        }
+
+        fn out_scale(
+            &self,
+            scales: Vec<crate::Scale>,
+        ) -> Result<crate::Scale, Box<dyn std::error::Error>> {
+            Ok(scales[0])
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn expected_diffs() -> Vec<ElementDiff> {
+        [1, 2, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(index, v)| ElementDiff {
+                index,
+                claimed: 2 * v,
+                expected: v,
+                diff: v,
+            })
+            .collect()
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit;
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, 4, 1, 3));
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.verbose_checks = true;
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let err = config
+                        .layout(&mut region, &[known(&[1, 2, 3])], Box::new(OffByScaleOp))
+                        .unwrap_err();
+                    match err.downcast_ref::<CircuitError>() {
+                        Some(CircuitError::SafeModeCheck { op, diffs }) => {
+                            assert_eq!(op, "OFF_BY_SCALE");
+                            assert_eq!(diffs, &expected_diffs());
+                        }
+                        _ => panic!("unexpected error: {}", err),
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn reports_per_element_diffs() {
+        let claimed = known(&[2, 4, 6]);
+        let values = [known(&[1, 2, 3])];
+        assert_eq!(
+            OffByScaleOp.safe_mode_check_verbose(&claimed, &values),
+            Err(expected_diffs())
+        );
+        assert!(OffByScaleOp
+            .safe_mode_check_verbose(&values[0], &values)
+            .is_ok());
+    }
+
+    #[test]
+    fn layout_surfaces_diffs_when_verbose() {
+        MockProver::run(4, &MyCircuit, vec![]).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod blinding_rows_pad {
+    use super::*;
+    use crate::circuit::table::{BlindingRowsPad, RESERVED_BLINDING_ROWS_PAD};
+
+    const K: usize = 4;
+    const REDUCED_PAD: usize = 1;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 4));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config.blinding_rows_pad = BlindingRowsPad {
+                range_checks: REDUCED_PAD,
+                ..Default::default()
+            };
+            let range = (0, usable_rows(cs, REDUCED_PAD) as i128 - 1);
+            config.configure_range_check(cs, &a, &b, range, K).unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            let range = *config.range_checks.ranges.keys().next().unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    layouts::range_check(&config, &mut region, &[self.input.clone()], &range)
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn usable_rows(cs: &ConstraintSystem<F>, pad: usize) -> usize {
+        (1 << K) - cs.blinding_factors() - pad
+    }
+
+    #[test]
+    fn reduced_range_check_pad_yields_more_rows() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = MyCircuit::<F>::configure(&mut cs);
+        let range_check = config.range_checks.ranges.values().next().unwrap();
+
+        assert_eq!(range_check.col_size, usable_rows(&cs, REDUCED_PAD));
+        assert_eq!(
+            range_check.col_size,
+            usable_rows(&cs, RESERVED_BLINDING_ROWS_PAD) + RESERVED_BLINDING_ROWS_PAD - REDUCED_PAD
+        );
+        // the range fills exactly one column with the reduced pad, it would need two otherwise
+        assert_eq!(range_check.inputs.len(), 1);
+    }
+
+    #[test]
+    fn reduced_range_check_pad_is_satisfied() {
+        let cs = {
+            let mut cs = ConstraintSystem::<F>::default();
+            MyCircuit::<F>::configure(&mut cs);
+            cs
+        };
+        // the last value sits on the last reclaimed row of the table
+        let max = usable_rows(&cs, REDUCED_PAD) as u64 - 1;
+        let input = Tensor::from([0, 1, max].into_iter().map(|v| Value::known(F::from(v))));
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(input),
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod lookup_op_validation {
+    use super::*;
+
+    fn configure(nl: &LookupOp) -> Result<(), Box<dyn std::error::Error>> {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::<F>::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        config.configure_lookup(
+            &mut cs,
+            &advices[0],
+            &advices[1],
+            &advices[2],
+            (-8, 8),
+            4,
+            nl,
+        )
+    }
+
+    fn assert_invalid(nl: &LookupOp) {
+        assert!(matches!(
+            nl.validate(),
+            Err(CircuitError::InvalidLookupOp(_))
+        ));
+        let err = configure(nl).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::InvalidLookupOp(_))
+        ));
+    }
+
+    #[test]
+    fn zero_scale_is_rejected() {
+        assert_invalid(&LookupOp::Sigmoid {
+            scale: utils::F32(0.0),
+        });
+        assert_invalid(&LookupOp::Div {
+            denom: utils::F32(0.0),
+        });
+        assert_invalid(&LookupOp::RationalScale {
+            op: Box::new(LookupOp::Exp {
+                scale: utils::F32(1.0),
+            }),
+            num: 1,
+            den: 0,
+        });
+    }
+
+    #[test]
+    fn mismatched_piecewise_is_rejected() {
+        // two breakpoints need three slopes
+        assert_invalid(&LookupOp::Piecewise {
+            breakpoints: vec![-2, 2],
+            slopes: vec![0, 1],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        });
+        assert_invalid(&LookupOp::Piecewise {
+            breakpoints: vec![2, -2],
+            slopes: vec![0, 1, 0],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        });
+    }
+
+    #[test]
+    fn valid_ops_are_accepted() {
+        let nl = LookupOp::Piecewise {
+            breakpoints: vec![-2, 2],
+            slopes: vec![0, 1, 0],
+            intercept: 0,
+            scale: utils::F32(1.0),
+        };
+        assert!(nl.validate().is_ok());
+        assert!(configure(&nl).is_ok());
+        assert!(LookupOp::ReLU.validate().is_ok());
+    }
+}
+
+#[cfg(test)]
+mod linear_lookup {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use crate::circuit::table::Range;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    const K: usize = 7;
+    const RANGE: Range = (-32, 32);
+
+    #[derive(Clone)]
+    struct LinearLookupCircuit<F: PrimeField + TensorType + PartialOrd> {
+        a: ValTensor<F>,
+        b: ValTensor<F>,
+        weights: Vec<i64>,
+    }
+
+    impl Circuit<F> for LinearLookupCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let advices = (0..4)
+                .map(|_| VarTensor::new_advice(cs, K, 1, 4))
+                .collect::<Vec<_>>();
+
+            let mut config = BaseConfig::configure(
+                cs,
+                &[advices[0].clone(), advices[1].clone()],
+                &advices[2],
+                CheckMode::SAFE,
+            );
+            // looks up relu(2a + b)
+            config
+                .configure_lookup_linear(
+                    cs,
+                    &[(advices[0].clone(), 2), (advices[1].clone(), 1)],
+                    &advices[2],
+                    &advices[3],
+                    &LookupOp::ReLU,
+                    RANGE,
+                    K,
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    config
+                        .layout(
+                            &mut region,
+                            &[self.a.clone(), self.b.clone()],
+                            Box::new(HybridOp::LinearLookup {
+                                nl: LookupOp::ReLU,
+                                weights: self.weights.clone(),
+                            }),
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit(a: &[i128], b: &[i128]) -> LinearLookupCircuit<F> {
+        let to_valtensor = |v: &[i128]| {
+            ValTensor::from(Tensor::from(
+                v.iter().map(|x| Value::known(i128_to_felt::<F>(*x))),
+            ))
+        };
+        LinearLookupCircuit {
+            a: to_valtensor(a),
+            b: to_valtensor(b),
+            weights: vec![2, 1],
+        }
+    }
+
+    #[test]
+    fn weighted_sum_is_looked_up() {
+        // 2a + b = [4, -5, -1, 5]
+        let circuit = circuit(&[1, -3, 4, 0], &[2, 1, -9, 5]);
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        let op = HybridOp::LinearLookup {
+            nl: LookupOp::ReLU,
+            weights: vec![2, 1],
+        };
+        let inputs = [[1, -3, 4, 0], [2, 1, -9, 5]]
+            .map(|v| Tensor::from(v.into_iter().map(i128_to_felt::<F>)));
+        let output = Op::<F>::f(&op, &inputs).unwrap().output;
+        assert_eq!(output.map(felt_to_i128).to_vec(), vec![4, 0, 0, 5]);
+    }
+
+    #[test]
+    fn weights_must_match_the_configured_ones() {
+        let mut circuit = circuit(&[1, -3, 4, 0], &[2, 1, -9, 5]);
+        circuit.weights = vec![1, 2];
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn weighted_sum_outside_range_is_rejected() {
+        // 2 * 20 + 0 is outside the table
+        let circuit = circuit(&[20, 0, 0, 0], &[0, 0, 0, 0]);
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+
+    #[test]
+    fn only_one_combination_per_op() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advices = (0..4)
+            .map(|_| VarTensor::new_advice(&mut cs, K, 1, 4))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+        let mut configure = |weights: [i64; 2]| {
+            config.configure_lookup_linear(
+                &mut cs,
+                &[
+                    (advices[0].clone(), weights[0]),
+                    (advices[1].clone(), weights[1]),
+                ],
+                &advices[2],
+                &advices[3],
+                &LookupOp::ReLU,
+                RANGE,
+                K,
+            )
+        };
+        assert!(configure([2, 1]).is_ok());
+        assert!(configure([2, 1]).is_ok());
+        assert!(configure([1, 2]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod table_evals_file {
+    use super::*;
+    use crate::circuit::table::Table;
+    use crate::fieldutils::i128_to_felt;
+
+    const RANGE: crate::circuit::table::Range = (-16, 16);
+
+    fn table(nl: &LookupOp) -> Table<F> {
+        let mut cs = ConstraintSystem::<F>::default();
+        Table::<F>::configure(&mut cs, RANGE, 8, nl, None)
+    }
+
+    fn expected(nl: &LookupOp) -> Tensor<F> {
+        let inputs = Tensor::from(RANGE.0..=RANGE.1).map(|x| i128_to_felt(x));
+        Op::<F>::f(nl, &[inputs]).unwrap().output
+    }
+
+    #[test]
+    fn evals_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relu.evals");
+
+        table(&LookupOp::ReLU).serialize_evals(&path).unwrap();
+        let loaded = Table::<F>::try_load_evals(RANGE, &LookupOp::ReLU, &path);
+        assert_eq!(loaded, Some(expected(&LookupOp::ReLU)));
+    }
+
+    #[test]
+    fn mismatched_key_is_not_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relu.evals");
+
+        table(&LookupOp::ReLU).serialize_evals(&path).unwrap();
+        assert!(Table::<F>::try_load_evals((-8, 8), &LookupOp::ReLU, &path).is_none());
+        assert!(Table::<F>::try_load_evals(RANGE, &LookupOp::Abs, &path).is_none());
+    }
+
+    #[test]
+    fn missing_or_corrupt_file_is_not_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("relu.evals");
+        assert!(Table::<F>::try_load_evals(RANGE, &LookupOp::ReLU, &path).is_none());
+
+        std::fs::write(&path, b"not a table").unwrap();
+        assert!(Table::<F>::try_load_evals(RANGE, &LookupOp::ReLU, &path).is_none());
+    }
+}
+
+#[cfg(test)]
+mod warn_check_mode {
+    use super::*;
+    use crate::circuit::ops::ForwardResult;
+    use crate::tensor::TensorError;
+    use std::any::Any;
+
+    fn known(v: &[u64]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(v.iter().map(|v| Value::known(F::from(*v)))))
+    }
+
+    /// Identity op whose layout claims the wrong output for its last element.
+    #[derive(Clone, Debug)]
+    struct OffByOneOp;
+
+    impl Op<F> for OffByOneOp {
+        fn f(&self, x: &[Tensor<F>]) -> Result<ForwardResult<F>, TensorError> {
+            Ok(ForwardResult {
+                output: x[0].clone(),
+            })
+        }
+
+        fn as_string(&self) -> String {
+            "OFF_BY_ONE".into()
+        }
+
+        fn layout(
+            &self,
+            _: &mut BaseConfig<F>,
+            _: &mut RegionCtx<F>,
+            values: &[ValTensor<F>],
+        ) -> Result<Option<ValTensor<F>>, Box<dyn std::error::Error>> {
+            let mut claimed = values[0]
+                .get_int_evals()?
+                .iter()
+                .map(|v| *v as u64)
+                .collect::<Vec<_>>();
+            if let Some(last) = claimed.last_mut() {
+                *last += 1;
+            }
+            Ok(Some(known(&claimed)))
+        }
+
+        fn out_scale(
+            &self,
+            scales: Vec<crate::Scale>,
+        ) -> Result<crate::Scale, Box<dyn std::error::Error>> {
+            Ok(scales[0])
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        check_mode: CheckMode,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, 4, 1, 3));
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.check_mode = self.check_mode;
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let res =
+                        config.layout(&mut region, &[known(&[1, 2, 3])], Box::new(OffByOneOp));
+                    match self.check_mode {
+                        // the mismatch is only logged, the claimed output goes through
+                        CheckMode::WARN => {
+                            let output = res.unwrap().unwrap();
+                            assert_eq!(output.get_int_evals().unwrap().to_vec(), vec![1, 2, 4]);
+                        }
+                        _ => assert!(res.is_err()),
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn warn_does_not_abort_layout() {
+        let circuit = MyCircuit {
+            check_mode: CheckMode::WARN,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap();
+    }
+
+    #[test]
+    fn safe_still_aborts_layout() {
+        let circuit = MyCircuit {
+            check_mode: CheckMode::SAFE,
+        };
+        MockProver::run(4, &circuit, vec![]).unwrap();
+    }
+
+    #[test]
+    fn warn_round_trips_through_strings() {
+        assert_eq!(CheckMode::WARN.to_string(), "warn");
+        assert_eq!(CheckMode::from("WARN".to_string()), CheckMode::WARN);
+    }
+}
+
+#[cfg(test)]
+mod num_lookup_arguments {
+    use super::*;
+
+    const K: usize = 6;
+
+    #[test]
+    fn prediction_matches_configured_lookups() {
+        let mut cs = ConstraintSystem::<F>::default();
+        // two blocks of two inner columns each
+        let advices = (0..3)
+            .map(|_| VarTensor::new_advice(&mut cs, K, 2, 200))
+            .collect::<Vec<_>>();
+        let mut config = BaseConfig::configure(
+            &mut cs,
+            &[advices[0].clone(), advices[1].clone()],
+            &advices[2],
+            CheckMode::SAFE,
+        );
+
+        // a range too wide for a single table column
+        let before = cs.lookups().len();
+        config
+            .configure_lookup(
+                &mut cs,
+                &advices[0],
+                &advices[2],
+                &advices[1],
+                (-100, 100),
+                K,
+                &LookupOp::ReLU,
+            )
+            .unwrap();
+        let added = cs.lookups().len() - before;
+
+        let table = &config.static_lookups.tables[&LookupOp::ReLU];
+        assert!(advices[0].num_blocks() > 1);
+        assert!(table.table_inputs.len() > 1);
+        assert_eq!(table.num_lookup_arguments_for(&advices[0]), added);
+    }
+}
+
+#[cfg(test)]
+mod verifier_config {
+    use super::*;
+    use crate::fieldutils::i128_to_felt;
+    use halo2_proofs::{
+        plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, VerifyingKey},
+        poly::{
+            commitment::{Params, ParamsProver},
+            kzg::{
+                commitment::{KZGCommitmentScheme, ParamsKZG},
+                multiopen::{ProverSHPLONK, VerifierSHPLONK},
+                strategy::SingleStrategy,
+            },
+        },
+        transcript::{
+            Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+        },
+        SerdeFormat,
+    };
+    use halo2curves::bn256::{Bn256, G1Affine};
+
+    const K: usize = 6;
+
+    #[derive(Clone, Default)]
+    struct FullCircuit;
+
+    impl Circuit<F> for FullCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(cs, &a, &output, &b, (-8, 8), K, &LookupOp::ReLU)
+                .unwrap();
+            config
+                .configure_range_check(cs, &a, &b, (0, 15), K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let input = ValTensor::from(Tensor::from(
+                        [1, -2, 3]
+                            .into_iter()
+                            .map(|v: i128| Value::known(i128_to_felt::<F>(v))),
+                    ));
+                    config
+                        .layout(&mut region, &[input], Box::new(LookupOp::ReLU))
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    /// Only rebuilds the constraint system, as a verifier reading a verifying key does.
+    #[derive(Clone, Default)]
+    struct VerifierCircuit;
+
+    impl Circuit<F> for VerifierCircuit {
+        type Config = VerifierConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            FullCircuit::configure(cs).to_verifier_config()
+        }
+
+        fn synthesize(&self, _: Self::Config, _: impl Layouter<F>) -> Result<(), Error> {
+            Err(Error::Synthesis)
+        }
+    }
+
+    #[test]
+    fn verifies_proof_of_full_config() {
+        let params = ParamsKZG::<Bn256>::setup(K as u32, OsRng);
+        let vk = keygen_vk(&params, &FullCircuit).unwrap();
+        let pk = keygen_pk(&params, vk, &FullCircuit).unwrap();
+
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[FullCircuit],
+            &[&[]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.finalize();
+
+        // the verifier only ever sees the serialized key
+        let mut vk_bytes = vec![];
+        pk.get_vk()
+            .write(&mut vk_bytes, SerdeFormat::RawBytes)
+            .unwrap();
+        let vk = VerifyingKey::<G1Affine>::read::<_, VerifierCircuit>(
+            &mut &vk_bytes[..],
+            SerdeFormat::RawBytes,
+            TestParams,
+        )
+        .unwrap();
+
+        let strategy = SingleStrategy::new(params.verifier_params());
+        let mut transcript = Blake2bRead::<_, G1Affine, Challenge255<_>>::init(&proof[..]);
+        verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<_>, _, _, _>(
+            params.verifier_params(),
+            &vk,
+            strategy,
+            &[&[]],
+            &mut transcript,
+            params.n(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn keeps_the_structure() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = FullCircuit::configure(&mut cs);
+        let verifier_config = config.to_verifier_config();
+
+        assert_eq!(
+            verifier_config.lookup_selectors,
+            config.static_lookups.selectors
+        );
+        let relu = &verifier_config.lookup_tables[&LookupOp::ReLU];
+        let table = &config.static_lookups.tables[&LookupOp::ReLU];
+        assert_eq!(relu.range, table.range);
+        assert_eq!(relu.inputs, table.table_inputs);
+        assert_eq!(relu.outputs, table.table_outputs);
+        assert_eq!(
+            verifier_config.range_check_tables[&(0, 15)],
+            config.range_checks.ranges[&(0, 15)].inputs
+        );
+    }
+}
+
+#[cfg(test)]
+mod tolerance_mode {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use crate::fieldutils::i128_to_felt;
+    use std::str::FromStr;
+
+    const K: usize = 6;
+
+    #[test]
+    fn parses_modes() {
+        let tol = Tolerance::from_str("abs:3.0").unwrap();
+        assert_eq!(tol.mode, ToleranceMode::Absolute);
+        assert_eq!(tol.val, 3.0);
+
+        let tol = Tolerance::from_str("pct:1.5").unwrap();
+        assert_eq!(tol.mode, ToleranceMode::Percentage);
+        assert_eq!(tol.val, 1.5);
+
+        // plain numbers stay percentages
+        let tol = Tolerance::from_str("1.5").unwrap();
+        assert_eq!(tol.mode, ToleranceMode::Percentage);
+
+        assert!(Tolerance::from_str("rel:1.5").is_err());
+        assert!(Tolerance::from_str("abs:x").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for s in ["abs:3.00", "1.50"] {
+            assert_eq!(Tolerance::from_str(s).unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn absolute_bound_is_expressed_at_scale() {
+        let tol = Tolerance {
+            val: 0.5,
+            scale: utils::F32(128.0),
+            mode: ToleranceMode::Absolute,
+        };
+        assert_eq!(tol.absolute_bound(), 64);
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        output: ValTensor<F>,
+        comparator: ValTensor<F>,
+        tol: Tolerance,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 16));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::UNSAFE);
+            config
+                .configure_range_check(cs, &a, &b, (-4, 4), K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    config
+                        .layout(
+                            &mut region,
+                            &[self.output.clone(), self.comparator.clone()],
+                            Box::new(HybridOp::RangeCheck(self.tol)),
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit(output: &[i128], comparator: &[i128]) -> MyCircuit {
+        let to_valtensor = |v: &[i128]| {
+            ValTensor::from(Tensor::from(
+                v.iter().map(|x| Value::known(i128_to_felt::<F>(*x))),
+            ))
+        };
+        MyCircuit {
+            output: to_valtensor(output),
+            comparator: to_valtensor(comparator),
+            // 0.5 at a multiplier of 8 accepts differences of up to 4
+            tol: Tolerance {
+                val: 0.5,
+                scale: utils::F32(8.0),
+                mode: ToleranceMode::Absolute,
+            },
+        }
+    }
+
+    #[test]
+    fn absolute_tolerance_accepts_small_differences() {
+        let circuit = circuit(&[10, 20, 30], &[14, 17, 30]);
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn absolute_tolerance_rejects_large_differences() {
+        let circuit = circuit(&[10, 20, 30], &[15, 20, 30]);
+        if let Ok(prover) = MockProver::run(K as u32, &circuit, vec![]) {
+            assert!(prover.verify().is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod col_multipliers {
+    use super::*;
+    use crate::circuit::table::{RangeCheck, Table};
+
+    const K: usize = 5;
+
+    #[test]
+    fn range_check_multipliers_match_layout() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let range_check = RangeCheck::<F>::configure(&mut cs, (-40, 40), K, None);
+        assert!(range_check.inputs.len() > 1);
+
+        // what layout used to compute for every chunk of the range
+        let num_chunks = 81_usize.div_ceil(range_check.col_size);
+        let expected = (0..num_chunks)
+            .map(|x| range_check.selector_constructor.get_selector_val_at_idx(x))
+            .collect::<Vec<_>>();
+        assert_eq!(range_check.col_multipliers, expected);
+    }
+
+    #[test]
+    fn table_multipliers_match_layout() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = Table::<F>::configure(&mut cs, (-40, 40), K, &LookupOp::ReLU, None);
+        assert!(table.table_inputs.len() > 1);
+
+        let num_chunks = 81_usize.div_ceil(table.col_size);
+        let expected = (0..num_chunks)
+            .map(|x| table.selector_constructor.get_selector_val_at_idx(x))
+            .collect::<Vec<_>>();
+        assert_eq!(table.col_multipliers, expected);
+    }
+}
+
+#[cfg(test)]
+mod selector_expr_degree {
+    use super::*;
+    use crate::circuit::table::SelectorConstructor;
+    use halo2_proofs::poly::Rotation;
+
+    #[test]
+    fn reported_degree_matches_expression() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let advice = cs.advice_column();
+        cs.create_gate("synthetic selectors", |meta| {
+            let query = meta.query_advice(advice, Rotation::cur());
+            for (degree, expected) in [(1, 0), (2, 1), (3, 2), (5, 4)] {
+                let constructor = SelectorConstructor::<F>::new(degree);
+                assert_eq!(constructor.expr_degree(), expected);
+                for i in 0..degree {
+                    let expr = constructor.get_expr_at_idx(i, query.clone());
+                    assert_eq!(expr.degree(), expected);
+                }
+            }
+            vec![query]
+        });
+    }
+
+    #[test]
+    fn serial_and_parallel_paths_agree() {
+        for degree in [1, 2, 4, 5, 16] {
+            let constructor = SelectorConstructor::<F>::new(degree);
+            for i in 0..degree {
+                assert_eq!(
+                    constructor.get_selector_val_at_idx_serial(i),
+                    constructor.get_selector_val_at_idx_par(i)
+                );
+            }
+        }
+
+        let mut cs = ConstraintSystem::<F>::default();
+        let advice = cs.advice_column();
+        cs.create_gate("synthetic selectors", |meta| {
+            let query = meta.query_advice(advice, Rotation::cur());
+            let constructor = SelectorConstructor::<F>::new(16);
+            let serial = constructor.get_expr_at_idx_serial(3, query.clone());
+            let par = constructor.get_expr_at_idx_par(3, query.clone());
+            assert_eq!(serial.degree(), par.degree());
+            vec![query]
+        });
+    }
+
+    #[test]
+    fn config_reports_widest_table() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, 4, 1, 3));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        assert_eq!(config.synthetic_selector_degree(), 0);
+
+        // fits in a single column at k = 4
+        config
+            .configure_range_check(&mut cs, &a, &b, (0, 3), 4)
+            .unwrap();
+        assert_eq!(config.synthetic_selector_degree(), 0);
+
+        let range = (-40, 40);
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, range, 4, &LookupOp::ReLU)
+            .unwrap();
+        let table = &config.static_lookups.tables[&LookupOp::ReLU];
+        assert!(table.table_inputs.len() > 1);
+        assert_eq!(
+            config.synthetic_selector_degree(),
+            table.table_inputs.len() - 1
+        );
+    }
+}
+
+#[cfg(test)]
+mod table2 {
+    use super::*;
+    use crate::circuit::ops::layouts;
+    use crate::circuit::table::{Range, Table2, TableError};
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 5;
+    const RANGES: [Range; 2] = [(-3, 3), (0, 9)];
+    const NAME: &str = "gate";
+
+    fn gate(a: i128, b: i128) -> i128 {
+        if a > 0 {
+            b
+        } else {
+            0
+        }
+    }
+
+    #[test]
+    fn col_index_matches_layout() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = Table2::<F>::configure(&mut cs, RANGES, K, gate).unwrap();
+        assert_eq!(table.num_rows(), 70);
+        assert!(table.table_outputs.len() > 1);
+
+        for linear_coord in 0..table.num_rows() {
+            let (a, b) = table.input_at(linear_coord);
+            assert_eq!(table.linear_coord(a, b), linear_coord);
+            let (x, _) = table.cartesian_coord(linear_coord);
+            assert_eq!(
+                table
+                    .get_col_index(i128_to_felt(a), i128_to_felt(b))
+                    .unwrap(),
+                F::from(x as u64)
+            );
+        }
+
+        // inputs outside of their range would otherwise wrap to another row of the product
+        assert!(table
+            .get_col_index(i128_to_felt(4), i128_to_felt(0))
+            .is_err());
+        assert!(table
+            .get_col_index(i128_to_felt(0), i128_to_felt(-1))
+            .is_err());
+        assert!(table.eval(i128_to_felt(0), i128_to_felt(10)).is_err());
+    }
+
+    #[test]
+    fn inverted_ranges_are_rejected() {
+        let mut cs = ConstraintSystem::<F>::default();
+        assert!(matches!(
+            Table2::<F>::configure(&mut cs, [(-3, 3), (9, 0)], K, gate),
+            Err(TableError::RangeInvalid((9, 0)))
+        ));
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        inputs: [ValTensor<F>; 2],
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output, index] = [0; 4].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_binary_lookup(cs, &[a, b], &output, &index, NAME, RANGES, K, gate)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let output = layouts::binary_lookup(&config, &mut region, &self.inputs, NAME)
+                        .map_err(|_| Error::Synthesis)?;
+                    let expected = self.inputs[0]
+                        .get_int_evals()
+                        .unwrap()
+                        .iter()
+                        .zip(self.inputs[1].get_int_evals().unwrap().iter())
+                        .map(|(a, b)| gate(*a, *b))
+                        .collect::<Vec<_>>();
+                    assert_eq!(output.get_int_evals().unwrap().to_vec(), expected);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit(rows: &[(i128, i128)]) -> MyCircuit {
+        let column = |f: fn(&(i128, i128)) -> i128| {
+            ValTensor::from(Tensor::from(
+                rows.iter()
+                    .map(|row| Value::known(i128_to_felt::<F>(f(row)))),
+            ))
+        };
+        MyCircuit {
+            inputs: [column(|row| row.0), column(|row| row.1)],
+        }
+    }
+
+    #[test]
+    fn configured_through_base_config() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = MyCircuit::configure(&mut cs);
+        let table = &config.binary_lookups.tables[NAME];
+        assert_eq!(config.num_table_columns(), 3 * table.table_outputs.len());
+
+        let inputs = config.binary_lookups.inputs.clone();
+        let output = config.binary_lookups.output.clone();
+        let index = config.binary_lookups.index.clone();
+        // configuring the name again over the same ranges is a no-op, over others an error
+        config
+            .configure_binary_lookup(
+                &mut cs,
+                &[inputs[0].clone(), inputs[1].clone()],
+                &output,
+                &index,
+                NAME,
+                RANGES,
+                K,
+                gate,
+            )
+            .unwrap();
+        assert!(config
+            .configure_binary_lookup(
+                &mut cs,
+                &[inputs[0].clone(), inputs[1].clone()],
+                &output,
+                &index,
+                NAME,
+                [(0, 3), (0, 9)],
+                K,
+                gate,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn lookup_accepts_table_rows() {
+        let circuit = circuit(&[(-3, 0), (1, 7), (3, 9), (0, 5), (2, 2)]);
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn inputs_outside_the_ranges_are_rejected() {
+        let circuit = circuit(&[(1, 7), (4, 2)]);
+        assert!(MockProver::run(K as u32, &circuit, vec![]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod multi_range_check {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use crate::circuit::table::{merge_ranges, MultiRangeCheck, Range};
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 5;
+    const RANGES: [Range; 5] = [(100, 130), (-1, 1), (0, 0), (40, 55), (50, 60)];
+
+    #[test]
+    fn ranges_are_merged() {
+        assert_eq!(merge_ranges(&RANGES), vec![(-1, 1), (40, 60), (100, 130)]);
+        // adjacent ranges are merged too
+        assert_eq!(merge_ranges(&[(3, 5), (0, 2)]), vec![(0, 5)]);
+    }
+
+    #[test]
+    fn col_index_matches_layout() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = MultiRangeCheck::<F>::configure(&mut cs, &RANGES, K).unwrap();
+        assert_eq!(table.num_rows(), 3 + 21 + 31);
+        assert!(table.inputs.len() > 1);
+
+        for linear_coord in 0..table.num_rows() {
+            let input = table.value_at(linear_coord);
+            assert_eq!(table.linear_coord(input), Some(linear_coord));
+            let (x, _) = table.cartesian_coord(linear_coord);
+            assert_eq!(
+                table.get_col_index(i128_to_felt(input)).unwrap(),
+                F::from(x as u64)
+            );
+        }
+
+        for uncovered in [-2, 2, 39, 99, 131] {
+            assert_eq!(table.linear_coord(uncovered), None);
+            assert!(table.get_col_index(i128_to_felt(uncovered)).is_err());
+        }
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_multi_range_check(cs, &a, &b, &RANGES, K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(HybridOp::MultiRangeCheck {
+                                ranges: RANGES.to_vec(),
+                            }),
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(inputs: Vec<i128>) -> Result<MockProver<F>, Error> {
+        let input = Tensor::from(
+            inputs
+                .into_iter()
+                .map(|v| Value::known(i128_to_felt::<F>(v))),
+        );
+        let circuit = MyCircuit {
+            input: ValTensor::from(input),
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+    }
+
+    #[test]
+    fn configured_through_base_config() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = MyCircuit::configure(&mut cs);
+        let merged = merge_ranges(&RANGES);
+        let table = &config.range_checks.multi[&merged];
+        assert_eq!(config.num_table_columns(), table.inputs.len());
+
+        // the same union, however it is split, is only configured once
+        let a = config.custom_gates.inputs[0].clone();
+        let b = config.custom_gates.inputs[1].clone();
+        config
+            .configure_multi_range_check(&mut cs, &a, &b, &merged, K)
+            .unwrap();
+        assert_eq!(config.range_checks.multi.len(), 1);
+        assert!(config
+            .configure_multi_range_check(&mut cs, &a, &b, &[(3, 0)], K)
+            .is_err());
+    }
+
+    #[test]
+    fn accepts_inputs_in_union() {
+        run(vec![-1, 0, 1, 40, 57, 60, 100, 130])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn rejects_inputs_outside_union() {
+        for uncovered in [2, 39, 99, 131] {
+            assert!(run(vec![0, uncovered]).is_err());
+        }
+    }
+}
+
+#[cfg(test)]
+mod precomputed_lookup {
+    use super::*;
+    use crate::circuit::table::Range;
+
+    const K: usize = 5;
+    const RANGE: Range = (0, 7);
+    // a permutation of the range, as a ROM might hold
+    const MAPPING: [u64; 8] = [5, 3, 7, 1, 0, 2, 6, 4];
+
+    fn mapping() -> Tensor<F> {
+        Tensor::from(MAPPING.iter().map(|y| F::from(*y)))
+    }
+
+    fn op() -> LookupOp {
+        LookupOp::precomputed(RANGE, &mapping())
+    }
+
+    fn known(v: &[u64]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(v.iter().map(|v| Value::known(F::from(*v)))))
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        input: Vec<u64>,
+        // lays the lookup out by hand with these outputs rather than the mapping's
+        claimed: Option<Vec<u64>>,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output, index] = [0; 4].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let mut config = Self::Config::configure(cs, &[a.clone(), b], &output, CheckMode::SAFE);
+            let nl = config
+                .configure_lookup_precomputed(cs, &a, &output, &index, RANGE, K, mapping())
+                .unwrap();
+            assert_eq!(nl, op());
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let input = known(&self.input);
+                    match &self.claimed {
+                        None => {
+                            config
+                                .layout(&mut region, &[input], Box::new(op()))
+                                .map_err(|_| Error::Synthesis)?;
+                        }
+                        Some(claimed) => {
+                            let lookups = &config.static_lookups;
+                            let index = known(&vec![0; claimed.len()]);
+                            region.assign(&lookups.input, &input)?;
+                            region.assign(&lookups.output, &known(claimed))?;
+                            region.assign(&lookups.index, &index)?;
+                            for row in 0..claimed.len() {
+                                region.enable(lookups.selectors.get(&(op(), 0, 0)), row)?;
+                            }
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn mapping_is_looked_up() {
+        let circuit = MyCircuit {
+            input: vec![0, 3, 4, 7],
+            claimed: None,
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        // the same pairs laid out by hand
+        let circuit = MyCircuit {
+            input: vec![0, 3, 4, 7],
+            claimed: Some(vec![5, 1, 0, 4]),
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn pairs_outside_mapping_are_rejected() {
+        // 3 maps to 1, not 2
+        let circuit = MyCircuit {
+            input: vec![0, 3],
+            claimed: Some(vec![5, 2]),
+        };
+        assert!(MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+
+        // 8 is outside the range
+        let circuit = MyCircuit {
+            input: vec![0, 8],
+            claimed: Some(vec![5, 0]),
+        };
+        assert!(MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn mapping_must_cover_range() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output, index] = [0; 4].map(|_| VarTensor::new_advice(&mut cs, K, 1, 8));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b], &output, CheckMode::SAFE);
+        let precomputed = Tensor::from(MAPPING[..7].iter().map(|y| F::from(*y)));
+        assert!(config
+            .configure_lookup_precomputed(&mut cs, &a, &output, &index, RANGE, K, precomputed)
+            .is_err());
+    }
+
+    #[test]
+    fn mappings_are_keyed_on_their_outputs() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output, index] = [0; 4].map(|_| VarTensor::new_advice(&mut cs, K, 1, 8));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b], &output, CheckMode::SAFE);
+
+        // outputs need not be small integers
+        let wide = Tensor::from(MAPPING.iter().map(|y| F::from(*y) * F::from(u64::MAX)));
+        let ops = [mapping(), wide.clone()].map(|precomputed| {
+            config
+                .configure_lookup_precomputed(&mut cs, &a, &output, &index, RANGE, K, precomputed)
+                .unwrap()
+        });
+        assert_ne!(ops[0], ops[1]);
+        assert_ne!(Op::<F>::as_string(&ops[0]), Op::<F>::as_string(&ops[1]));
+        // the op only carries the key, the outputs are held by the table
+        assert_eq!(ops[0], op());
+        let table = &config.static_lookups.tables[&ops[1]];
+        assert_eq!(*table.precomputed.as_ref().unwrap().outputs, wide);
+        assert_eq!(
+            config.static_lookups.eval(&ops[1], F::from(2)).unwrap(),
+            F::from(7) * F::from(u64::MAX)
+        );
+    }
+}
+
+#[cfg(test)]
+mod column_budget {
+    use super::*;
+    use crate::circuit::table::{estimate_columns, Range};
+
+    const K: usize = 8;
+    const LOOKUP_RANGE: Range = (-300, 300);
+    const RANGES: [Range; 2] = [(0, 15), (0, 600)];
+
+    #[test]
+    fn estimate_matches_configuration() {
+        let ops = [LookupOp::ReLU, LookupOp::Abs, LookupOp::ReLU];
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        let budget = estimate_columns(&ops, LOOKUP_RANGE, &RANGES, K, cs.blinding_factors());
+
+        // duplicates are only counted once
+        assert_eq!(budget.lookups.len(), 2);
+        assert!(budget.lookups.iter().all(|(_, n)| *n == 6));
+        assert_eq!(budget.range_checks, vec![((0, 15), 1), ((0, 600), 3)]);
+        assert_eq!(budget.total, 3 * 3 + 1 + 3);
+
+        for op in ops.iter() {
+            config
+                .configure_lookup(&mut cs, &a, &output, &b, LOOKUP_RANGE, K, op)
+                .unwrap();
+        }
+        for range in RANGES {
+            config
+                .configure_range_check(&mut cs, &a, &b, range, K)
+                .unwrap();
+        }
+        assert_eq!(config.num_table_columns(), budget.total);
+    }
+
+    #[test]
+    fn empty_budget() {
+        let cs = ConstraintSystem::<F>::default();
+        let budget = estimate_columns(&[], LOOKUP_RANGE, &[], K, cs.blinding_factors());
+        assert_eq!(budget.total, 0);
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+mod provable_with {
+    use super::*;
+    use crate::Commitments;
+
+    #[test]
+    fn kzg_requires_a_public_srs() {
+        assert!(BaseConfig::<F>::provable_with(Commitments::KZG, 17).is_ok());
+
+        let max = crate::srs_sha::max_supported_k();
+        match BaseConfig::<F>::provable_with(Commitments::KZG, max + 1) {
+            Err(CircuitError::NoPublicSrs { k, max: m, .. }) => {
+                assert_eq!(k, max + 1);
+                assert_eq!(m, max);
+            }
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn ipa_needs_no_public_srs() {
+        let max = crate::srs_sha::max_supported_k();
+        assert!(BaseConfig::<F>::provable_with(Commitments::IPA, max + 1).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod subset_range_check {
+    use super::*;
+    use crate::circuit::table::Range;
+    use crate::fieldutils::i128_to_felt;
+    use std::cell::RefCell;
+
+    const K: usize = 9;
+    const LEN: usize = 4;
+    const ENCLOSING: Range = (-128, 127);
+    const SUBSETS: [Range; 2] = [(-64, 63), (0, 15)];
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        range: Range,
+    }
+
+    #[derive(Clone, Default)]
+    struct Params {
+        range: Range,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = Params;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn params(&self) -> Self::Params {
+            Params { range: self.range }
+        }
+
+        fn configure_with_params(
+            cs: &mut ConstraintSystem<F>,
+            params: Self::Params,
+        ) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN);
+            let b = VarTensor::new_advice(cs, K, 1, LEN);
+            let output = VarTensor::new_advice(cs, K, 1, LEN);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::UNSAFE);
+            config
+                .configure_range_check(cs, &a, &b, ENCLOSING, K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &a, &b, params.range, K)
+                .unwrap();
+            config
+        }
+
+        fn configure(_cs: &mut ConstraintSystem<F>) -> Self::Config {
+            unimplemented!("call configure_with_params instead")
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    // skip the witness-side range assertions so that the constraints alone are tested
+                    let mut region = RegionCtx::from_wrapped_region(
+                        Some(RefCell::new(region)),
+                        0,
+                        1,
+                        Default::default(),
+                        Default::default(),
+                    );
+                    layouts::range_check(&config, &mut region, &[self.input.clone()], &self.range)
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn run(x: [i64; LEN], range: Range) -> MockProver<F> {
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(Tensor::from(
+                x.into_iter()
+                    .map(|v| Value::known(i128_to_felt::<F>(v as i128))),
+            )),
+            range,
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn subset_ranges_add_no_table_columns() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, LEN));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        config
+            .configure_range_check(&mut cs, &a, &b, ENCLOSING, K)
+            .unwrap();
+        let num_cols = config.num_table_columns();
+        assert_eq!(num_cols, 1);
+
+        for range in SUBSETS {
+            config
+                .configure_range_check(&mut cs, &a, &b, range, K)
+                .unwrap();
+            assert_eq!(config.num_table_columns(), num_cols);
+            assert_eq!(config.range_checks.subsets.get(&range), Some(&ENCLOSING));
+        }
+        assert_eq!(config.range_checks.ranges.len(), 1);
+    }
+
+    #[test]
+    fn accepts_values_within_subset() {
+        run([-64, 0, 5, 63], SUBSETS[0]).assert_satisfied();
+        run([0, 1, 7, 15], SUBSETS[1]).assert_satisfied();
+    }
+
+    #[test]
+    fn rejects_values_outside_subset_but_within_enclosing_range() {
+        assert!(run([-64, 0, 5, 100], SUBSETS[0]).verify().is_err());
+        assert!(run([-65, 0, 5, 63], SUBSETS[0]).verify().is_err());
+        assert!(run([0, 1, 7, 16], SUBSETS[1]).verify().is_err());
+        assert!(run([-1, 1, 7, 15], SUBSETS[1]).verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod column_reuse {
+    use super::*;
+
+    const K: usize = 8;
+
+    #[test]
+    fn reports_reused_input_columns() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        let mut builder = BaseConfigBuilder::new(config);
+
+        let lookups = [
+            (LookupOp::ReLU, (-100, 100)),
+            (LookupOp::Abs, (-100, 100)),
+            (LookupOp::Sign, (-10, 300)),
+            // configuring an op again keeps its first record
+            (LookupOp::ReLU, (-100, 100)),
+        ];
+        for (op, range) in lookups {
+            builder
+                .configure_lookup(&mut cs, &a, &output, &b, range, K, &op)
+                .unwrap();
+        }
+
+        let report = builder.column_reuse_report();
+        assert_eq!(report.len(), 3);
+        assert!(!report[&LookupOp::ReLU]);
+        assert!(report[&LookupOp::Abs]);
+        assert!(!report[&LookupOp::Sign]);
+
+        let config = builder.build();
+        assert_eq!(config.static_lookups.tables.len(), 3);
+    }
+}
+
+#[cfg(test)]
+mod table_sharing_ratio {
+    use super::*;
+    use crate::circuit::table::Range;
+
+    const K: usize = 8;
+
+    fn ratio(lookups: &[(LookupOp, Range)]) -> f64 {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        for (op, range) in lookups {
+            config
+                .configure_lookup(&mut cs, &a, &output, &b, *range, K, op)
+                .unwrap();
+        }
+        config.table_sharing_ratio()
+    }
+
+    #[test]
+    fn fully_shared_columns() {
+        let lookups = [
+            (LookupOp::ReLU, (-100, 100)),
+            (LookupOp::Abs, (-100, 100)),
+            (LookupOp::Sign, (-100, 100)),
+        ];
+        assert!((ratio(&lookups) - 3.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn unshared_columns() {
+        let lookups = [(LookupOp::ReLU, (-100, 100)), (LookupOp::Abs, (200, 300))];
+        assert!((ratio(&lookups) - 1.0).abs() < f64::EPSILON);
+        assert_eq!(ratio(&[]), 0.0);
+    }
+}
+
+#[cfg(test)]
+mod configured_lookups {
+    use super::*;
+
+    const K: usize = 8;
+
+    #[test]
+    fn lists_configured_ops_and_ranges() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        assert!(config.configured_lookup_ops().is_empty());
+        assert!(config.configured_ranges().is_empty());
+
+        for op in [LookupOp::Sign, LookupOp::ReLU, LookupOp::Abs] {
+            config
+                .configure_lookup(&mut cs, &a, &output, &b, (-100, 100), K, &op)
+                .unwrap();
+        }
+        for range in [(0, 15), (-8, 7)] {
+            config
+                .configure_range_check(&mut cs, &a, &b, range, K)
+                .unwrap();
+        }
+
+        let mut expected = vec![LookupOp::Sign, LookupOp::ReLU, LookupOp::Abs];
+        expected.sort();
+        assert_eq!(config.configured_lookup_ops(), expected);
+        assert_eq!(config.configured_ranges(), vec![(-8, 7), (0, 15)]);
+    }
+}
+
+#[cfg(test)]
+mod lookup_at_positions {
+    use super::*;
+
+    const K: usize = 8;
+    const POSITION: (usize, usize) = (1, 2);
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: i64,
+        output: i64,
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let col_size = VarTensor::max_rows(cs, K);
+            let [a, b, output] =
+                [0; 3].map(|_| VarTensor::new_advice_with_geometry(cs, col_size, 2, 3));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            // out of range positions are rejected
+            assert!(config
+                .configure_lookup_at_positions(
+                    cs,
+                    &a,
+                    &output,
+                    &b,
+                    (-16, 16),
+                    K,
+                    &LookupOp::ReLU,
+                    vec![(2, 0)],
+                )
+                .is_err());
+            config
+                .configure_lookup_at_positions(
+                    cs,
+                    &a,
+                    &output,
+                    &b,
+                    (-16, 16),
+                    K,
+                    &LookupOp::ReLU,
+                    vec![POSITION, (0, 0)],
+                )
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            let column = |var: &VarTensor| match var {
+                VarTensor::Advice { inner, .. } => inner[POSITION.0][POSITION.1],
+                _ => unreachable!(),
+            };
+            let input_col = column(&config.static_lookups.input);
+            let output_col = column(&config.static_lookups.output);
+            let selector =
+                config.static_lookups.selectors[&(LookupOp::ReLU, POSITION.0, POSITION.1)];
+            let to_felt = |x: i64| Value::known(crate::fieldutils::i128_to_felt::<F>(x as i128));
+            layouter.assign_region(
+                || "",
+                |mut region| {
+                    region.assign_advice(|| "", input_col, 0, || to_felt(self.input))?;
+                    region.assign_advice(|| "", output_col, 0, || to_felt(self.output))?;
+                    selector.enable(&mut region, 0)
+                },
+            )
+        }
+    }
+
+    fn run(input: i64, output: i64) -> MockProver<F> {
+        let circuit = MyCircuit::<F> {
+            input,
+            output,
+            _marker: PhantomData,
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap()
+    }
+
+    #[test]
+    fn lookups_only_at_given_positions() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = MyCircuit::<F>::configure(&mut cs);
+        let mut positions = config
+            .static_lookups
+            .selectors
+            .keys()
+            .map(|(_, x, y)| (*x, *y))
+            .collect::<Vec<_>>();
+        positions.sort();
+        assert_eq!(positions, vec![(0, 0), POSITION]);
+
+        run(-3, 0).assert_satisfied();
+        run(5, 5).assert_satisfied();
+        assert!(run(5, 4).verify().is_err());
+    }
+
+    /// Configures ReLU at a single position, then over all of them if `COMPLETE`, and lays it out
+    /// over the whole input.
+    #[derive(Clone, Default)]
+    struct LayoutCircuit<const COMPLETE: bool>;
+
+    impl<const COMPLETE: bool> Circuit<F> for LayoutCircuit<COMPLETE> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let col_size = VarTensor::max_rows(cs, K);
+            let [a, b, output] =
+                [0; 3].map(|_| VarTensor::new_advice_with_geometry(cs, col_size, 1, 3));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup_at_positions(
+                    cs,
+                    &a,
+                    &output,
+                    &b,
+                    (-16, 16),
+                    K,
+                    &LookupOp::ReLU,
+                    vec![(0, 0)],
+                )
+                .unwrap();
+            if COMPLETE {
+                config
+                    .configure_lookup(cs, &a, &output, &b, (-16, 16), K, &LookupOp::ReLU)
+                    .unwrap();
+            }
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            let input = ValTensor::from(Tensor::from(
+                (-3..3).map(|x| Value::known(crate::fieldutils::i128_to_felt::<F>(x))),
+            ));
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 3);
+                    config
+                        .layout(&mut region, &[input.clone()], Box::new(LookupOp::ReLU))
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn configure_lookup_fills_in_missing_positions() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = LayoutCircuit::<true>::configure(&mut cs);
+        assert_eq!(config.static_lookups.selectors.len(), 3);
+        MockProver::run(K as u32, &LayoutCircuit::<true>, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn layout_without_selector_is_an_error() {
+        assert!(MockProver::run(K as u32, &LayoutCircuit::<false>, vec![]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod logrows_consistency {
+    use super::*;
+
+    const K: usize = 8;
+
+    fn config(cs: &mut ConstraintSystem<F>) -> BaseConfig<F> {
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        config
+            .configure_lookup(cs, &a, &output, &b, (-100, 100), K, &LookupOp::ReLU)
+            .unwrap();
+        config
+            .configure_range_check(cs, &a, &b, (0, 15), K)
+            .unwrap();
+        config
+    }
+
+    #[test]
+    fn tables_configured_at_k_fit() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = config(&mut cs);
+        config.validate_logrows_consistency(&cs, K as u32).unwrap();
+        config
+            .validate_logrows_consistency(&cs, K as u32 + 1)
+            .unwrap();
+    }
+
+    #[test]
+    fn table_configured_above_k_is_reported() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = config(&mut cs);
+        let a = config.custom_gates.inputs[0].clone();
+        let b = config.custom_gates.inputs[1].clone();
+        let output = config.custom_gates.output.clone();
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (200, 300), K + 2, &LookupOp::Abs)
+            .unwrap();
+
+        match config.validate_logrows_consistency(&cs, K as u32) {
+            Err(CircuitError::InconsistentLogrows { component, k, .. }) => {
+                assert!(component.contains(&Op::<F>::as_string(&LookupOp::Abs)));
+                assert_eq!(k, K as u32);
+            }
+            other => panic!("expected an inconsistent logrows error, got {:?}", other),
+        }
+        config
+            .validate_logrows_consistency(&cs, K as u32 + 2)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod clamp_warning {
+    use super::*;
+    use crate::circuit::table::{ClampWarning, Range};
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 8;
+    const RANGE: Range = (-8, 120);
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        op: LookupOp,
+        // number of clamped outputs the table should report, if any
+        expected: Option<usize>,
+    }
+
+    #[derive(Clone, Default)]
+    struct Params {
+        op: Option<LookupOp>,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = Params;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn params(&self) -> Self::Params {
+            Params {
+                op: Some(self.op.clone()),
+            }
+        }
+
+        fn configure_with_params(
+            cs: &mut ConstraintSystem<F>,
+            params: Self::Params,
+        ) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 4));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(cs, &a, &output, &b, RANGE, K, &params.op.unwrap())
+                .unwrap();
+            config.warn_on_clamped_outputs(ClampWarning::default());
+            config
+        }
+
+        fn configure(_: &mut ConstraintSystem<F>) -> Self::Config {
+            unimplemented!("call configure_with_params instead")
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_tables(&mut layouter).unwrap();
+            let clamped = config.static_lookups.tables[&self.op].clamped;
+            assert_eq!(clamped.map(|(n, _)| n), self.expected);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn over_scaled_op_warns_once() {
+        // e^x overflows i128 for x >= 89, so 32 of the 129 outputs saturate
+        let circuit = MyCircuit {
+            op: LookupOp::Exp {
+                scale: utils::F32(1.0),
+            },
+            expected: Some(32),
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap();
+    }
+
+    #[test]
+    fn well_scaled_op_does_not_warn() {
+        let circuit = MyCircuit {
+            op: LookupOp::ReLU,
+            expected: None,
+        };
+        MockProver::run(K as u32, &circuit, vec![]).unwrap();
+    }
+
+    #[test]
+    fn counts_outputs_reaching_bound() {
+        let warning = ClampWarning {
+            max_output: 10,
+            fraction: 0.5,
+        };
+        let evals = Tensor::from([-12i128, -3, 0, 10, 9].into_iter().map(i128_to_felt::<F>));
+        assert_eq!(warning.count_clamped(&evals), 2);
+    }
+}
+
+#[cfg(test)]
+mod argmax_indicator {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use crate::circuit::table::Range;
+    use crate::fieldutils::felt_to_i128;
+
+    const K: usize = 8;
+    const LEN: usize = 4;
+    const RANGE: Range = (0, 100);
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        expected: [i128; LEN],
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 1, LEN * 4);
+            let b = VarTensor::new_advice(cs, K, 1, LEN * 4);
+            let output = VarTensor::new_advice(cs, K, 1, LEN * 4);
+            let indicator = VarTensor::new_advice(cs, K, 1, LEN * 4);
+
+            let mut config = Self::Config::configure(cs, &[a.clone(), b], &output, CheckMode::SAFE);
+            config
+                .configure_argmax(cs, &a, &indicator, RANGE, K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let indicator = config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(HybridOp::ArgmaxIndicator { range: RANGE }),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    assert_eq!(
+                        indicator.get_int_evals().unwrap().to_vec(),
+                        self.expected.to_vec()
+                    );
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit(x: [u64; LEN], expected: [i128; LEN]) -> MyCircuit<F> {
+        MyCircuit::<F> {
+            input: ValTensor::from(Tensor::from(
+                x.into_iter().map(|v| Value::known(F::from(v))),
+            )),
+            expected,
+        }
+    }
+
+    fn run(x: [u64; LEN], expected: [i128; LEN]) {
+        let op = HybridOp::ArgmaxIndicator { range: RANGE };
+        let x_felt = Tensor::from(x.into_iter().map(F::from));
+        let forward = Op::<F>::f(&op, &[x_felt]).unwrap().output;
+        assert_eq!(forward.map(felt_to_i128).to_vec(), expected.to_vec());
+
+        MockProver::run(K as u32, &circuit(x, expected), vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn unique_max() {
+        run([3, 97, 42, 0], [0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn tie_goes_to_first_index() {
+        run([3, 42, 7, 42], [0, 1, 0, 0]);
+        run([100, 100, 100, 100], [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn inputs_outside_the_range_are_rejected() {
+        let prover =
+            MockProver::run(K as u32, &circuit([3, 142, 42, 0], [0, 1, 0, 0]), vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod table_dumps {
+    use super::*;
+    use crate::circuit::table::{diff_table_dumps, Range, TableDiff};
+
+    const K: usize = 8;
+
+    fn relu_dump() -> String {
+        format!("lookup_RELU_{:016x}.csv", LookupOp::ReLU.stable_hash())
+    }
+
+    fn config(relu_range: Range, check_range: Range) -> BaseConfig<F> {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (-8, 8), K, &LookupOp::Abs)
+            .unwrap();
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, relu_range, K, &LookupOp::ReLU)
+            .unwrap();
+        config
+            .configure_range_check(&mut cs, &a, &b, check_range, K)
+            .unwrap();
+        config
+    }
+
+    #[test]
+    fn dumps_every_table() {
+        let dir = tempfile::tempdir().unwrap();
+        config((-8, 8), (0, 15))
+            .dump_all_tables(dir.path())
+            .unwrap();
+
+        let relu = std::fs::read_to_string(dir.path().join(relu_dump())).unwrap();
+        let lines = relu.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 18);
+        assert_eq!(lines[0], "input,output");
+        assert_eq!(lines[1], "-8,0");
+        assert_eq!(lines[17], "8,8");
+
+        let check = std::fs::read_to_string(dir.path().join("range_check_0_15.csv")).unwrap();
+        assert_eq!(check.lines().count(), 17);
+
+        // one file per table, named after the op and its hash
+        let mut lookups = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .filter(|name| name.starts_with("lookup_"))
+            .collect::<Vec<_>>();
+        lookups.sort();
+        assert_eq!(
+            lookups,
+            vec![
+                format!("lookup_ABS_{:016x}.csv", LookupOp::Abs.stable_hash()),
+                relu_dump(),
+            ]
+        );
+    }
+
+    #[test]
+    fn identical_configs_do_not_differ() {
+        let [dir_a, dir_b] = [0; 2].map(|_| tempfile::tempdir().unwrap());
+        config((-8, 8), (0, 15))
+            .dump_all_tables(dir_a.path())
+            .unwrap();
+        config((-8, 8), (0, 15))
+            .dump_all_tables(dir_b.path())
+            .unwrap();
+
+        assert!(diff_table_dumps(dir_a.path(), dir_b.path())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn modified_config_differs() {
+        let [dir_a, dir_b] = [0; 2].map(|_| tempfile::tempdir().unwrap());
+        config((-8, 8), (0, 15))
+            .dump_all_tables(dir_a.path())
+            .unwrap();
+        config((-8, 9), (0, 16))
+            .dump_all_tables(dir_b.path())
+            .unwrap();
+
+        assert_eq!(
+            diff_table_dumps(dir_a.path(), dir_b.path()).unwrap(),
+            vec![
+                TableDiff::Rows {
+                    name: relu_dump(),
+                    rows: vec![17],
+                },
+                TableDiff::OnlyInFirst("range_check_0_15.csv".into()),
+                TableDiff::OnlyInSecond("range_check_0_16.csv".into()),
+            ]
+        );
+    }
+}
+
+#[cfg(test)]
+mod table_error {
+    use super::*;
+    use crate::circuit::table::TableError;
+
+    const K: usize = 6;
+    const RANGE: crate::circuit::table::Range = (0, 15);
+
+    #[derive(Clone, Default)]
+    struct MyCircuit;
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 4));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config.configure_range_check(cs, &a, &b, RANGE, K).unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let mut inverted = config.range_checks.ranges[&RANGE].clone();
+            inverted.range = (RANGE.1, RANGE.0);
+            assert!(matches!(
+                inverted.layout(&mut layouter),
+                Err(TableError::RangeInvalid(_))
+            ));
+
+            let mut range_check = config.range_checks.ranges[&RANGE].clone();
+            range_check.layout(&mut layouter).unwrap();
+            assert!(matches!(
+                range_check.layout(&mut layouter),
+                Err(TableError::AlreadyAssigned)
+            ));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn layout_errors_are_typed() {
+        MockProver::run(K as u32, &MyCircuit, vec![]).unwrap();
+    }
+
+    #[test]
+    fn converts_into_circuit_error() {
+        assert!(matches!(
+            CircuitError::from(TableError::AlreadyAssigned),
+            CircuitError::Table(TableError::AlreadyAssigned)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod covered_ranges {
+    use super::*;
+
+    const K: usize = 8;
+
+    #[test]
+    fn merges_lookup_and_range_check_ranges() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        assert!(config.covered_ranges().is_empty());
+
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (-20, 10), K, &LookupOp::ReLU)
+            .unwrap();
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (200, 210), K, &LookupOp::Abs)
+            .unwrap();
+        for range in [(5, 30), (31, 40), (100, 120)] {
+            config
+                .configure_range_check(&mut cs, &a, &b, range, K)
+                .unwrap();
+        }
+
+        assert_eq!(
+            config.covered_ranges(),
+            vec![(-20, 40), (100, 120), (200, 210)]
+        );
+    }
+}
+
+#[cfg(test)]
+mod inverted_range {
+    use super::*;
+    use crate::circuit::table::{Range, RangeCheck, Table, TableError};
+
+    const K: usize = 6;
+    const INVERTED: Range = (10, -5);
+
+    #[test]
+    #[should_panic(expected = "invalid table range")]
+    fn range_check_rejects_inverted_range() {
+        let mut cs = ConstraintSystem::<F>::default();
+        RangeCheck::<F>::configure(&mut cs, INVERTED, K, None);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid table range")]
+    fn table_rejects_inverted_range() {
+        let mut cs = ConstraintSystem::<F>::default();
+        Table::<F>::configure(&mut cs, INVERTED, K, &LookupOp::ReLU, None);
+    }
+
+    #[test]
+    fn config_returns_range_invalid() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let mut config =
+            BaseConfig::<F>::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+
+        let err = config
+            .configure_range_check(&mut cs, &a, &b, INVERTED, K)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TableError>(),
+            Some(TableError::RangeInvalid(INVERTED))
+        ));
+
+        let err = config
+            .configure_lookup(&mut cs, &a, &output, &b, INVERTED, K, &LookupOp::ReLU)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<TableError>(),
+            Some(TableError::RangeInvalid(INVERTED))
+        ));
+        assert_eq!(config.num_table_columns(), 0);
+    }
+}
+
+#[cfg(test)]
+mod selector_strategy {
+    use super::*;
+    use crate::circuit::ops::base::BaseOp;
+
+    const K: usize = 5;
+    // not a multiple of the inner columns, so shared selectors pad the last row of an op
+    const LEN: usize = 7;
+    const NUM_INNER_COLS: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        strategy: SelectorStrategy,
+    }
+
+    impl MyCircuit<F> {
+        fn ops(&self) -> Vec<(Box<dyn Op<F>>, Vec<ValTensor<F>>)> {
+            vec![
+                (Box::new(PolyOp::Add), self.inputs.to_vec()),
+                (
+                    Box::new(PolyOp::Sum { axes: vec![0] }),
+                    vec![self.inputs[0].clone()],
+                ),
+            ]
+        }
+    }
+
+    fn configure(cs: &mut ConstraintSystem<F>, strategy: &SelectorStrategy) -> BaseConfig<F> {
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, NUM_INNER_COLS, 3 * LEN));
+        BaseConfig::configure_with_strategy(cs, &[a, b], &output, CheckMode::SAFE, strategy)
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = SelectorStrategy;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn params(&self) -> Self::Params {
+            self.strategy.clone()
+        }
+
+        fn configure_with_params(
+            cs: &mut ConstraintSystem<F>,
+            params: Self::Params,
+        ) -> Self::Config {
+            configure(cs, &params)
+        }
+
+        fn configure(_: &mut ConstraintSystem<F>) -> Self::Config {
+            unimplemented!("call configure_with_params instead")
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, NUM_INNER_COLS);
+                    for (op, values) in self.ops() {
+                        config
+                            .layout(&mut region, &values, op)
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit(strategy: SelectorStrategy) -> MyCircuit<F> {
+        let a = Tensor::from((0..LEN).map(|i| Value::known(F::from(i as u64 + 1))));
+        let b = Tensor::from((0..LEN).map(|i| Value::known(F::from(2 * i as u64))));
+        MyCircuit {
+            inputs: [ValTensor::from(a), ValTensor::from(b)],
+            strategy,
+        }
+    }
+
+    #[test]
+    fn lazy_strategy_allocates_fewer_selectors() {
+        let plan = BaseConfig::<F>::dummy(K, NUM_INNER_COLS)
+            .dry_run(&circuit(SelectorStrategy::Eager).ops())
+            .unwrap();
+        assert_eq!(
+            plan.base_ops,
+            [BaseOp::Add, BaseOp::SumInit, BaseOp::Sum]
+                .into_iter()
+                .collect()
+        );
+        let lazy = SelectorStrategy::Lazy(plan.base_ops);
+
+        let mut cs = ConstraintSystem::<F>::default();
+        let eager = configure(&mut cs, &SelectorStrategy::Eager);
+        let num_blocks = eager.custom_gates.output.num_blocks();
+        let eager_count = eager.custom_gates.selectors.len();
+        let mut cs = ConstraintSystem::<F>::default();
+        let lazy_count = configure(&mut cs, &lazy).custom_gates.selectors.len();
+
+        // three of the four element-wise ops are never laid out, at each of the inner columns, and
+        // neither are the dot product and cumulative product accumulators of each block
+        assert_eq!(
+            eager_count - lazy_count,
+            3 * NUM_INNER_COLS * num_blocks + 4 * num_blocks
+        );
+
+        MockProver::run(K as u32, &circuit(lazy), vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn laying_out_an_op_without_selectors_errors() {
+        let lazy = SelectorStrategy::Lazy(std::collections::BTreeSet::new());
+        assert!(MockProver::run(K as u32, &circuit(lazy), vec![]).is_err());
+    }
+
+    #[test]
+    fn shared_strategy_allocates_a_selector_per_block() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let eager = configure(&mut cs, &SelectorStrategy::Eager);
+        let num_blocks = eager.custom_gates.output.num_blocks();
+        let mut cs = ConstraintSystem::<F>::default();
+        let shared = configure(&mut cs, &SelectorStrategy::Shared);
+
+        // the four element-wise ops share one selector across the inner columns of each block
+        assert_eq!(
+            eager.custom_gates.selectors.len() - shared.custom_gates.selectors.len(),
+            4 * (NUM_INNER_COLS - 1) * num_blocks
+        );
+        assert!(shared.custom_gates.is_shared(&BaseOp::Add));
+        assert!(!shared.custom_gates.is_shared(&BaseOp::Sum));
+
+        MockProver::run(K as u32, &circuit(SelectorStrategy::Shared), vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod lagged_diff {
+    use super::*;
+
+    const K: u32 = 6;
+    const LEN: usize = 10;
+    // columns of 4 rows, so adjacent elements cross into a new column every 4 elements
+    const COL_SIZE: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let mut config = Self::Config::with_geometry(cs, COL_SIZE, 4, 1, CheckMode::SAFE);
+            config.configure_rotated_sub(cs, 1).unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let output = config
+                        .layout(
+                            &mut region,
+                            &[self.input.clone()],
+                            Box::new(PolyOp::LaggedDiff { lag: 1 }),
+                        )
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    let expected = (1..LEN as i128).map(|i| 2 * i + 1).collect::<Vec<_>>();
+                    assert_eq!(output.get_int_evals().unwrap().to_vec(), expected);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn squares() -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            (0..LEN as u64).map(|i| Value::known(F::from(i * i))),
+        ))
+    }
+
+    #[test]
+    fn adjacent_difference_is_satisfied() {
+        let circuit = MyCircuit::<F> { input: squares() };
+        MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+
+        let squares = Tensor::from((0..LEN as u64).map(|i| F::from(i * i)));
+        let output = Op::<F>::f(&PolyOp::LaggedDiff { lag: 1 }, &[squares.clone()])
+            .unwrap()
+            .output;
+        let expected = (1..LEN as u64)
+            .map(|i| F::from(2 * i + 1))
+            .collect::<Vec<_>>();
+        assert_eq!(output.to_vec(), expected);
+        assert!(Op::<F>::f(&PolyOp::LaggedDiff { lag: LEN }, &[squares]).is_err());
+    }
+
+    #[test]
+    fn unconfigured_rotation_is_an_error() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = BaseConfig::with_geometry(&mut cs, COL_SIZE, 4, 1, CheckMode::SAFE);
+        config.configure_rotated_sub(&mut cs, 1).unwrap();
+
+        // only a rotation of one row is configured
+        let mut region = RegionCtx::new_dummy(0, 1, false);
+        assert!(layouts::lagged_diff(&config, &mut region, &[squares()], 2).is_err());
+        assert!(layouts::lagged_diff(&config, &mut region, &[squares()], 1).is_ok());
+
+        // the gate can only be configured once per rotation
+        assert!(matches!(
+            config.configure_rotated_sub(&mut cs, 1),
+            Err(CircuitError::SelectorAlreadyConfigured { .. })
+        ));
+    }
+}
+
+#[cfg(test)]
+mod layout_footprint {
+    use super::*;
+    use crate::circuit::table::{RangeCheck, Table};
+
+    const K: usize = 8;
+
+    #[test]
+    fn matches_configured_tables() {
+        for range in [(-100, 100), (0, 1000), (-5000, 5000)] {
+            let mut cs = ConstraintSystem::<F>::default();
+            let table = Table::<F>::configure(&mut cs, range, K, &LookupOp::ReLU, None);
+            let footprint = Table::<F>::layout_footprint(range, K);
+            assert_eq!(footprint.col_size, table.col_size);
+            assert_eq!(
+                footprint.num_cols,
+                table.table_inputs.len() + table.table_outputs.len()
+            );
+            assert_eq!(
+                footprint.total_cells,
+                footprint.num_cols * footprint.col_size
+            );
+            assert_eq!(footprint.total_bytes, footprint.total_cells * 32);
+
+            let range_check = RangeCheck::<F>::configure(&mut cs, range, K, None);
+            let footprint = RangeCheck::<F>::layout_footprint(range, K);
+            assert_eq!(footprint.col_size, range_check.col_size);
+            assert_eq!(footprint.num_cols, range_check.inputs.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod min_k_for_rows {
+    use crate::circuit::table::{min_k_for_rows, RESERVED_BLINDING_ROWS_PAD};
+
+    const BLINDING: usize = 5;
+    const RESERVED: usize = BLINDING + RESERVED_BLINDING_ROWS_PAD;
+
+    #[test]
+    fn exact_fit_at_power_of_two() {
+        for k in 4..20 {
+            assert_eq!(min_k_for_rows((1 << k) - RESERVED, BLINDING), k);
+        }
+    }
+
+    #[test]
+    fn one_row_over_needs_the_next_k() {
+        for k in 4..20 {
+            assert_eq!(min_k_for_rows((1 << k) - RESERVED + 1, BLINDING), k + 1);
+            assert_eq!(min_k_for_rows((1 << k) - RESERVED - 1, BLINDING), k);
+        }
+    }
+
+    #[test]
+    fn blinding_factors_count_towards_the_rows() {
+        assert_eq!(min_k_for_rows(0, 0), 2);
+        assert_eq!(min_k_for_rows(8, 0), 4);
+        assert_eq!(min_k_for_rows(8, 6), 5);
+    }
+}
+
+#[cfg(test)]
+mod skip_check_ops {
+    use super::*;
+    use crate::circuit::ops::ForwardResult;
+    use crate::tensor::TensorError;
+    use std::any::Any;
+
+    fn known(v: &[u64]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(v.iter().map(|v| Value::known(F::from(*v)))))
+    }
+
+    /// Identity op, named by its field, whose layout claims twice the true output.
+    #[derive(Clone, Debug)]
+    struct WrongOutputOp(&'static str);
+
+    impl Op<F> for WrongOutputOp {
+        fn f(&self, x: &[Tensor<F>]) -> Result<ForwardResult<F>, TensorError> {
+            Ok(ForwardResult {
+                output: x[0].clone(),
+            })
+        }
+
+        fn as_string(&self) -> String {
+            self.0.into()
+        }
+
+        fn layout(
+            &self,
+            _: &mut BaseConfig<F>,
+            _: &mut RegionCtx<F>,
+            values: &[ValTensor<F>],
+        ) -> Result<Option<ValTensor<F>>, Box<dyn std::error::Error>> {
+            let doubled = values[0]
+                .get_int_evals()?
+                .iter()
+                .map(|v| 2 * *v as u64)
+                .collect::<Vec<_>>();
+            Ok(Some(known(&doubled)))
+        }
+
+        fn out_scale(
+            &self,
+            scales: Vec<crate::Scale>,
+        ) -> Result<crate::Scale, Box<dyn std::error::Error>> {
+            Ok(scales[0])
+        }
+
+        fn clone_dyn(&self) -> Box<dyn Op<F>> {
+            Box::new(self.clone())
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit;
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, 4, 1, 3));
+            Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            // report failed checks as errors rather than panics
+            config.verbose_checks = true;
+            config.skip_check_ops.insert("TRUSTED".to_string());
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let values = [known(&[1, 2, 3])];
+
+                    // the exempted op's wrong output goes unnoticed
+                    let res =
+                        config.layout(&mut region, &values, Box::new(WrongOutputOp("TRUSTED")));
+                    assert!(res.is_ok());
+
+                    // while every other op is still checked
+                    let err = config
+                        .layout(&mut region, &values, Box::new(WrongOutputOp("CHECKED")))
+                        .unwrap_err();
+                    match err.downcast_ref::<CircuitError>() {
+                        Some(CircuitError::SafeModeCheck { op, .. }) => assert_eq!(op, "CHECKED"),
+                        _ => panic!("unexpected error: {}", err),
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn exempted_op_is_not_checked() {
+        MockProver::run(4, &MyCircuit, vec![]).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod is_zero_gate {
+    use super::*;
+    use crate::circuit::ops::base::BaseOp;
+    use crate::fieldutils::i128_to_felt;
+
+    const K: u32 = 6;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        expected: Vec<i128>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K as usize, 1, 8));
+            let mut config = Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE);
+            config.configure_is_zero(cs).unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let output = config
+                        .layout(&mut region, &[self.input.clone()], Box::new(PolyOp::IsZero))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    assert_eq!(output.get_int_evals().unwrap().to_vec(), self.expected);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn indicates_zeros() {
+        let input = [0i128, 3, -2, 0, 1];
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(Tensor::from(
+                input.iter().map(|v| Value::known(i128_to_felt::<F>(*v))),
+            )),
+            expected: vec![1, 0, 0, 1, 0],
+        };
+        MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn gate_is_configured_on_demand() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K as usize, 1, 8));
+        let mut config = BaseConfig::configure(&mut cs, &[a, b], &output, CheckMode::SAFE);
+        let is_zero_gate = |cs: &ConstraintSystem<F>| {
+            cs.gates()
+                .iter()
+                .any(|gate| gate.name() == BaseOp::IsZero.as_str())
+        };
+        assert!(!is_zero_gate(&cs));
+        assert!(matches!(
+            config.custom_gates.required_selector(&BaseOp::IsZero, 0, 0),
+            Err(CircuitError::MissingSelector { .. })
+        ));
+
+        config.configure_is_zero(&mut cs).unwrap();
+        assert!(is_zero_gate(&cs));
+        assert!(matches!(
+            config.configure_is_zero(&mut cs),
+            Err(CircuitError::SelectorAlreadyConfigured { .. })
+        ));
+    }
+
+    #[test]
+    fn gate_has_three_constraints() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K as usize, 1, 8));
+        let mut config = BaseConfig::configure(&mut cs, &[a, b], &output, CheckMode::SAFE);
+        config.configure_is_zero(&mut cs).unwrap();
+
+        let gate = cs
+            .gates()
+            .iter()
+            .find(|gate| gate.name() == BaseOp::IsZero.as_str())
+            .unwrap();
+        assert_eq!(gate.polynomials().len(), 3);
+    }
+}
+
+#[cfg(test)]
+mod lookup_stable_hash {
+    use super::*;
+    use crate::circuit::table::evals_cache_file_name;
+    use crate::circuit::utils::F32;
+
+    #[test]
+    fn equal_ops_hash_equal() {
+        let div = |denom: f32| LookupOp::Div { denom: F32(denom) };
+        assert_eq!(div(2.0).stable_hash(), div(2.0).stable_hash());
+        assert_eq!(div(0.0).stable_hash(), div(-0.0).stable_hash());
+        assert_eq!(div(f32::NAN).stable_hash(), div(-f32::NAN).stable_hash());
+
+        let nested = || LookupOp::RationalScale {
+            op: Box::new(LookupOp::Sigmoid { scale: F32(128.0) }),
+            num: 3,
+            den: 4,
+        };
+        assert_eq!(nested().stable_hash(), nested().stable_hash());
+    }
+
+    #[test]
+    fn differing_params_hash_differently() {
+        let div = |denom: f32| LookupOp::Div { denom: F32(denom) };
+        assert_ne!(div(2.0).stable_hash(), div(2.5).stable_hash());
+        // the smallest possible change to the float
+        assert_ne!(
+            div(2.0).stable_hash(),
+            div(f32::from_bits(2.0f32.to_bits() + 1)).stable_hash()
+        );
+        // same parameter, different variant
+        assert_ne!(
+            div(2.0).stable_hash(),
+            LookupOp::Cast { scale: F32(2.0) }.stable_hash()
+        );
+        assert_ne!(LookupOp::ReLU.stable_hash(), LookupOp::Abs.stable_hash());
+    }
+
+    #[test]
+    fn tables_cache_evals_under_their_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, 6, 1, 4));
+        let mut config =
+            BaseConfig::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (-8, 8), 6, &LookupOp::ReLU)
+            .unwrap();
+        config.cache_table_evals_in(dir.path());
+
+        let table = &config.static_lookups.tables[&LookupOp::ReLU];
+        assert_eq!(
+            table.evals_file,
+            Some(
+                dir.path()
+                    .join(format!("{:016x}_-8_8.evals", LookupOp::ReLU.stable_hash()))
+            )
+        );
+        assert_eq!(
+            table
+                .evals_file
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            evals_cache_file_name(&LookupOp::ReLU, (-8, 8))
+        );
+    }
+}
+
+#[cfg(test)]
+mod inconsistent_lookup_input {
+    use super::*;
+
+    const K: usize = 6;
+
+    #[test]
+    fn lookups_over_different_inputs_are_rejected() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, 4));
+        let other = VarTensor::new_advice(&mut cs, K, 1, 4);
+        let mut config =
+            BaseConfig::configure(&mut cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (-8, 8), K, &LookupOp::ReLU)
+            .unwrap();
+        // the same input is fine
+        config
+            .configure_lookup(&mut cs, &a, &output, &b, (-8, 8), K, &LookupOp::Abs)
+            .unwrap();
+
+        let err = config
+            .configure_lookup(&mut cs, &other, &output, &b, (-8, 8), K, &LookupOp::Sign)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::InconsistentLookupInput)
+        ));
+        assert!(!config.static_lookups.tables.contains_key(&LookupOp::Sign));
+        assert_eq!(config.static_lookups.input, a);
+    }
+}
+
+#[cfg(test)]
+mod ternary_inputs {
+    use super::*;
+
+    const K: u32 = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 3],
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, c, output] = [0; 4].map(|_| VarTensor::new_advice(cs, K as usize, 1, LEN));
+            Self::Config::configure(cs, &[a, b, c], &output, CheckMode::SAFE)
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let output = config
+                        .layout(&mut region, &self.inputs, Box::new(PolyOp::MultAdd))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    assert_eq!(
+                        output.get_int_evals().unwrap().to_vec(),
+                        vec![7, 14, 23, 34]
+                    );
+                    // the two input ops still work alongside
+                    layouts::pairwise(
+                        &config,
+                        &mut region,
+                        &[self.inputs[0].clone(), self.inputs[1].clone()],
+                        BaseOp::Add,
+                    )
+                    .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn known(v: [u64; LEN]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            v.into_iter().map(|v| Value::known(F::from(v))),
+        ))
+    }
+
+    #[test]
+    fn mult_add_over_three_columns() {
+        let circuit = MyCircuit::<F> {
+            inputs: [
+                known([1, 2, 3, 4]),
+                known([2, 3, 4, 5]),
+                known([5, 8, 11, 14]),
+            ],
+        };
+        MockProver::run(K, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn two_inputs_leave_out_ternary_ops() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K as usize, 1, LEN));
+        let config = BaseConfig::configure(&mut cs, &[a.clone(), b], &output, CheckMode::SAFE);
+        assert!(config
+            .custom_gates
+            .selectors
+            .contains_key(&(BaseOp::Add, 0, 0)));
+        assert!(!config
+            .custom_gates
+            .selectors
+            .contains_key(&(BaseOp::MultAdd, 0, 0)));
+        assert!(matches!(
+            config
+                .custom_gates
+                .required_selector(&BaseOp::MultAdd, 0, 0),
+            Err(CircuitError::MissingSelector { .. })
+        ));
+
+        let mut region = RegionCtx::new_dummy(0, 1, false);
+        let inputs = [known([1; LEN]), known([2; LEN]), known([3; LEN])];
+        assert!(layouts::mult_add(&config, &mut region, &inputs).is_err());
+
+        // a single input can't configure the binary ops
+        let mut config = BaseConfig::<F>::default();
+        assert!(matches!(
+            config.configure_custom_gates(&mut cs, &[a], &output),
+            Err(CircuitError::DimMismatch(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod layout_dynamic_lookups_and_shuffles {
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let a = VarTensor::new_advice(cs, K, 2, LEN);
+            let b = VarTensor::new_advice(cs, K, 2, LEN);
+            let c = VarTensor::new_advice(cs, K, 2, LEN);
+
+            let d = VarTensor::new_advice(cs, K, 1, LEN);
+            let e = VarTensor::new_advice(cs, K, 1, LEN);
+            let f = VarTensor::new_advice(cs, K, 1, LEN);
+
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b.clone()], &c, CheckMode::SAFE);
+            config
+                .configure_dynamic_lookup(
+                    cs,
+                    &[a.clone(), b.clone(), c.clone()],
+                    &[d.clone(), e.clone(), f.clone()],
+                )
+                .unwrap();
+            config
+                .configure_shuffles(cs, &[a.clone(), b.clone()], &[d.clone(), e.clone()])
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            // mismatched tables are rejected
+            let mut broken = config.clone();
+            broken.dynamic_lookups.tables.pop();
+            broken.shuffles.references.pop();
+            assert!(broken.layout_dynamic_lookups().is_err());
+            assert!(broken.layout_shuffles().is_err());
+            assert!(!broken.dynamic_lookups.is_assigned);
+            assert!(!broken.shuffles.is_assigned);
+
+            assert!(!config.dynamic_lookups.is_assigned);
+            assert!(!config.shuffles.is_assigned);
+
+            // neither can be laid out before being marked as laid out
+            let lookups = [0; 2]
+                .map(|_| ValTensor::from(Tensor::from([Value::known(F::ONE); LEN].into_iter())));
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    assert!(
+                        layouts::dynamic_lookup(&config, &mut region, &lookups, &lookups).is_err()
+                    );
+                    assert!(
+                        layouts::shuffles(&config, &mut region, &lookups[..1], &lookups[..1])
+                            .is_err()
+                    );
+                    Ok(())
+                },
+            )?;
+
+            for _ in 0..2 {
+                config.layout_dynamic_lookups().unwrap();
+                config.layout_shuffles().unwrap();
+                assert!(config.dynamic_lookups.is_assigned);
+                assert!(config.shuffles.is_assigned);
+                // dynamic tables are witnessed inline, so the config stays open to configuration
+                assert!(!config.tables_laid_out);
+            }
+
+            // once laid out, the configuration is no longer re-checked
+            config.dynamic_lookups.tables.pop();
+            config.shuffles.references.pop();
+            config.layout_dynamic_lookups().unwrap();
+            config.layout_shuffles().unwrap();
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn layout_is_guarded() {
+        let circuit = MyCircuit::<F> {
+            _marker: PhantomData,
+        };
+
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+}
+
+#[cfg(test)]
+mod mock_structure_check {
+    use super::*;
+    use crate::circuit::ops::base::BaseOp;
+
+    const K: u32 = 6;
+    const COL_SIZE: usize = 32;
+
+    fn full_config(cs: &mut ConstraintSystem<F>) -> BaseConfig<F> {
+        let mut config = BaseConfig::<F>::with_geometry(cs, COL_SIZE, 1, 2, CheckMode::SAFE);
+        let [a, b] = [0, 1].map(|i| config.custom_gates.inputs[i].clone());
+        let output = config.custom_gates.output.clone();
+        config
+            .configure_lookup(cs, &a, &output, &b, (-8, 8), K as usize, &LookupOp::ReLU)
+            .unwrap();
+        config
+            .configure_range_check(cs, &a, &b, (0, 15), K as usize)
+            .unwrap();
+        let indicator = VarTensor::new_advice_with_geometry(cs, COL_SIZE, 1, 2);
+        config
+            .configure_argmax(cs, &a, &indicator, (-4, 4), K as usize)
+            .unwrap();
+
+        let mut var = |inner_cols| VarTensor::new_advice_with_geometry(cs, COL_SIZE, 1, inner_cols);
+        let lookups = [var(2), var(2), var(2)];
+        let tables = [var(1), var(1), var(1)];
+        config
+            .configure_dynamic_lookup(cs, &lookups, &tables)
+            .unwrap();
+        config
+    }
+
+    #[test]
+    fn valid_config_passes() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = full_config(&mut cs);
+        config.mock_structure_check(K).unwrap();
+    }
+
+    #[test]
+    fn missing_selector_fails() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = full_config(&mut cs);
+        config
+            .custom_gates
+            .selectors
+            .remove(&(BaseOp::Add, 0, 1))
+            .unwrap();
+        assert!(matches!(
+            config.mock_structure_check(K),
+            Err(CircuitError::MissingSelector {
+                block: 0,
+                column: 1,
+                ..
+            })
+        ));
+
+        let mut config = full_config(&mut cs);
+        config
+            .range_checks
+            .selectors
+            .remove(&((0, 15), 0, 0))
+            .unwrap();
+        assert!(matches!(
+            config.mock_structure_check(K),
+            Err(CircuitError::MissingSelector {
+                block: 0,
+                column: 0,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn missing_table_fails() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = full_config(&mut cs);
+        config
+            .static_lookups
+            .tables
+            .remove(&LookupOp::ReLU)
+            .unwrap();
+        assert!(matches!(
+            config.mock_structure_check(K),
+            Err(CircuitError::StructureCheck(_))
+        ));
+
+        let mut config = full_config(&mut cs);
+        config.range_checks.ranges.remove(&(0, 15)).unwrap();
+        assert!(matches!(
+            config.mock_structure_check(K),
+            Err(CircuitError::StructureCheck(_))
+        ));
+    }
+
+    #[test]
+    fn mismatched_columns_fail() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = full_config(&mut cs);
+        config.custom_gates.output = VarTensor::new_advice_with_geometry(&mut cs, COL_SIZE, 1, 1);
+        assert!(matches!(
+            config.mock_structure_check(K),
+            Err(CircuitError::StructureCheck(_))
+        ));
+
+        let mut config = full_config(&mut cs);
+        config.dynamic_lookups.tables.pop();
+        assert!(matches!(
+            config.mock_structure_check(K),
+            Err(CircuitError::StructureCheck(_))
+        ));
+    }
+
+    #[test]
+    fn columns_too_long_for_k_fail() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let config = full_config(&mut cs);
+        assert!(matches!(
+            config.mock_structure_check(4),
+            Err(CircuitError::StructureCheck(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod dynamic_lookup_width {
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        tables: [ValTensor<F>; 3],
+        lookups: [ValTensor<F>; 3],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let lookups = (0..4)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let tables = (0..4)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let _constant = VarTensor::constant_cols(cs, K, 2 * LEN, false);
+
+            let mut config =
+                Self::Config::configure(cs, &lookups[0..2], &lookups[2], CheckMode::SAFE);
+            // lookups and tables must be as wide as one another
+            assert!(config
+                .configure_dynamic_lookup(cs, &lookups, &tables[0..3])
+                .is_err());
+            config
+                .configure_dynamic_lookup(cs, &lookups, &tables)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_dynamic_lookups().unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1);
+                        let assigned = layouts::dynamic_lookup(
+                            &config,
+                            &mut region,
+                            &self.lookups,
+                            &self.tables,
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                        assert_eq!(assigned.len(), 3);
+                        // the lookups must cover the index column too
+                        assert!(layouts::dynamic_lookup(
+                            &config,
+                            &mut region,
+                            &self.lookups[0..2],
+                            &self.tables[0..2]
+                        )
+                        .is_err());
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    fn column(values: impl Iterator<Item = u64>) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(values.map(|v| Value::known(F::from(v)))))
+    }
+
+    #[test]
+    fn four_column_lookup() {
+        let tables = [
+            column(0..LEN as u64),
+            column((0..LEN as u64).map(|i| 2 * i)),
+            column((0..LEN as u64).map(|i| i * i)),
+        ];
+
+        let circuit = MyCircuit::<F> {
+            tables: tables.clone(),
+            lookups: [
+                column([1, 3].into_iter()),
+                column([2, 6].into_iter()),
+                column([1, 9].into_iter()),
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        // the first two columns match a row of the table but the third doesn't
+        let circuit = MyCircuit::<F> {
+            tables,
+            lookups: [
+                column([1, 3].into_iter()),
+                column([2, 6].into_iter()),
+                column([1, 4].into_iter()),
+            ],
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod dynamic_lookup_arity {
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[test]
+    fn over_wide_lookup_is_rejected() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let a = VarTensor::new_advice(&mut cs, K, 1, LEN);
+        let b = VarTensor::new_advice(&mut cs, K, 1, LEN);
+        let c = VarTensor::new_advice(&mut cs, K, 1, LEN);
+        let mut config = BaseConfig::<F>::configure(&mut cs, &[a, b], &c, CheckMode::SAFE);
+        config.max_dynamic_lookup_arity = 4;
+
+        let wide = config.max_dynamic_lookup_arity + 1;
+        let lookups = (0..wide)
+            .map(|_| VarTensor::new_advice(&mut cs, K, 1, LEN))
+            .collect::<Vec<_>>();
+        let tables = (0..wide)
+            .map(|_| VarTensor::new_advice(&mut cs, K, 1, LEN))
+            .collect::<Vec<_>>();
+
+        let err = config
+            .configure_dynamic_lookup(&mut cs, &lookups, &tables)
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::DynamicLookupArityTooLarge { arity: 5, max: 4 })
+        ));
+        assert!(config.dynamic_lookups.lookup_selectors.is_empty());
+
+        config
+            .configure_dynamic_lookup(&mut cs, &lookups[0..4], &tables[0..4])
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod shuffle_width {
+    use super::*;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        references: [ValTensor<F>; 2],
+        _marker: PhantomData<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let inputs = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+            let references = (0..3)
+                .map(|_| VarTensor::new_advice(cs, K, 1, LEN))
+                .collect::<Vec<_>>();
+
+            let _constant = VarTensor::constant_cols(cs, K, LEN, false);
+
+            let mut config =
+                Self::Config::configure(cs, &inputs[0..2], &inputs[2], CheckMode::SAFE);
+            // inputs and references must be as wide as one another
+            assert!(config
+                .configure_shuffles(cs, &inputs, &references[0..2])
+                .is_err());
+            config.configure_shuffles(cs, &inputs, &references).unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_shuffles().unwrap();
+            layouter
+                .assign_region(
+                    || "",
+                    |region| {
+                        let mut region = RegionCtx::new(region, 0, 1);
+                        let assigned =
+                            layouts::shuffles(&config, &mut region, &self.inputs, &self.references)
+                                .map_err(|_| Error::Synthesis)?;
+                        assert_eq!(assigned.len(), 2);
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            Ok(())
+        }
+    }
+
+    fn column(values: [u64; LEN]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            values.into_iter().map(|v| Value::known(F::from(v))),
+        ))
+    }
+
+    #[test]
+    fn three_wide_shuffle() {
+        let references = [column([1, 2, 3, 4]), column([10, 20, 30, 40])];
+
+        // the rows of the references, reordered
+        let circuit = MyCircuit::<F> {
+            inputs: [column([3, 1, 4, 2]), column([30, 10, 40, 20])],
+            references: references.clone(),
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+
+        // each column is a permutation of its reference column, but the rows are not
+        let circuit = MyCircuit::<F> {
+            inputs: [column([3, 1, 4, 2]), column([10, 30, 40, 20])],
+            references,
+            _marker: PhantomData,
+        };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+#[cfg(test)]
+mod reciprocal_epsilon {
+    use super::*;
+    use crate::circuit::ops::hybrid::HybridOp;
+    use crate::circuit::utils::F32;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    fn eval(op: &LookupOp, xs: &[i128]) -> Vec<i128> {
+        let x = Tensor::from(xs.iter().map(|x| i128_to_felt::<F>(*x)));
+        Op::<F>::f(op, &[x])
+            .unwrap()
+            .output
+            .iter()
+            .map(|y| felt_to_i128(*y))
+            .collect()
+    }
+
+    #[test]
+    fn recip_inputs_within_epsilon_are_floored() {
+        let op = LookupOp::Recip {
+            input_scale: F32(1.0),
+            output_scale: F32(100.0),
+            epsilon: 4,
+        };
+        // inside the band every input evaluates as epsilon with its sign, outside it is left alone
+        assert_eq!(
+            eval(&op, &[0, 1, 3, -1, -3, 4, 5, -5, 100]),
+            vec![25, 25, 25, -25, -25, 25, 20, -20, 1]
+        );
+
+        let unbounded = LookupOp::Recip {
+            input_scale: F32(1.0),
+            output_scale: F32(100.0),
+            epsilon: 0,
+        };
+        assert_eq!(eval(&unbounded, &[1, 3, 5]), vec![100, 33, 20]);
+        assert_eq!(
+            Op::<F>::as_string(&unbounded),
+            "RECIP(input_scale=1, output_scale=100)"
+        );
+    }
+
+    #[test]
+    fn hybrid_recip_carries_epsilon() {
+        let op = HybridOp::Recip {
+            input_scale: F32(1.0),
+            output_scale: F32(100.0),
+            use_range_check_for_int: true,
+            epsilon: 4,
+        };
+        let x = Tensor::from([0, -2, 5].into_iter().map(i128_to_felt::<F>));
+        let y = Op::<F>::f(&op, &[x]).unwrap().output;
+        assert_eq!(
+            y.iter().map(|y| felt_to_i128(*y)).collect::<Vec<_>>(),
+            vec![25, -25, 20]
+        );
+    }
+
+    #[test]
+    fn rsqrt_inputs_within_epsilon_are_floored() {
+        let op = LookupOp::Rsqrt {
+            scale: F32(16.0),
+            epsilon: 16,
+        };
+        assert_eq!(eval(&op, &[0, 1, 15, 16, 64]), vec![16, 16, 16, 16, 8]);
+    }
+
+    #[test]
+    fn negative_epsilon_is_invalid() {
+        let op = LookupOp::Rsqrt {
+            scale: F32(16.0),
+            epsilon: -1,
+        };
+        assert!(matches!(
+            op.validate(),
+            Err(CircuitError::InvalidLookupOp(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod streamed_table_layout {
+    use super::*;
+
+    const K: usize = 6;
+    const RANGE: (i128, i128) = (-40, 40);
+
+    /// Outputs of a lookup over [RANGE] whose table is precomputed rather than evaluated.
+    fn mapping() -> Tensor<F> {
+        Tensor::from((RANGE.0..=RANGE.1).map(|x| crate::fieldutils::i128_to_felt::<F>(x * x - 3)))
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+        chunk_rows: Option<usize>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let len = (RANGE.1 - RANGE.0 + 1) as usize;
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 2 * len));
+            let mut config =
+                BaseConfig::configure(cs, &[a.clone(), b.clone()], &output, CheckMode::SAFE);
+            config
+                .configure_lookup(cs, &b, &output, &a, RANGE, K, &LookupOp::ReLU)
+                .unwrap();
+            config
+                .configure_lookup_precomputed(cs, &b, &output, &a, RANGE, K, mapping())
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            match self.chunk_rows {
+                Some(chunk_rows) => {
+                    // both tables share their input columns
+                    let mut preassigned_input = false;
+                    for table in config.static_lookups.tables.values_mut() {
+                        assert!(table.table_inputs.len() > 1);
+                        table
+                            .layout_streaming(&mut layouter, preassigned_input, chunk_rows)
+                            .unwrap();
+                        assert!(table.is_assigned);
+                        preassigned_input = true;
+                    }
+                }
+                None => config.layout_tables(&mut layouter).unwrap(),
+            }
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let precomputed = LookupOp::precomputed(RANGE, &mapping());
+                    for nl in [LookupOp::ReLU, precomputed] {
+                        config
+                            .layout(&mut region, &[self.input.clone()], Box::new(nl))
+                            .map_err(|_| Error::Synthesis)?;
+                    }
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    #[test]
+    fn streamed_layout_matches_full_layout() {
+        let input = ValTensor::from(Tensor::from(
+            (RANGE.0..=RANGE.1).map(|x| Value::known(crate::fieldutils::i128_to_felt::<F>(x))),
+        ));
+        // windows both dividing and straddling the table columns
+        for chunk_rows in [None, Some(1), Some(7), Some(1 << K)] {
+            let circuit = MyCircuit::<F> {
+                input: input.clone(),
+                chunk_rows,
+            };
+            let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+    }
+}
+
+#[cfg(test)]
+mod table_from_precomputed {
+    use super::*;
+    use crate::circuit::table::{AssignmentOrder, Range, Table, TableError};
+    use crate::fieldutils::i128_to_felt;
+    use halo2_proofs::plonk::{Advice, Column, Expression, Selector};
+    use halo2_proofs::poly::Rotation;
+
+    const K: usize = 5;
+    const RANGE: Range = (-4, 4);
+
+    /// Stands in for a mapping computed by an external tool, which no [LookupOp] implements.
+    fn cubic(x: i128) -> i128 {
+        x * x * x - 2 * x + 1
+    }
+
+    fn outputs() -> Tensor<F> {
+        Tensor::from((RANGE.0..=RANGE.1).map(|x| i128_to_felt(cubic(x))))
+    }
+
+    #[derive(Clone)]
+    struct MyConfig {
+        table: Table<F>,
+        advices: [Column<Advice>; 2],
+        selector: Selector,
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        rows: Vec<(i128, i128)>,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = MyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let table =
+                Table::<F>::from_precomputed(cs, RANGE, K, "cubic".to_string(), outputs()).unwrap();
+            assert_eq!(table.table_inputs.len(), 1);
+            let advices = [0; 2].map(|_| cs.advice_column());
+            let selector = cs.complex_selector();
+
+            cs.lookup("", |meta| {
+                let sel = meta.query_selector(selector);
+                let not_sel = Expression::Constant(F::from(1)) - sel.clone();
+                let [x, y] = advices.map(|col| meta.query_advice(col, Rotation::cur()));
+                let (default_x, default_y) = table.get_first_element(0);
+
+                vec![
+                    (
+                        sel.clone() * x + not_sel.clone() * Expression::Constant(default_x),
+                        table.table_inputs[0],
+                    ),
+                    (
+                        sel * y + not_sel * Expression::Constant(default_y),
+                        table.table_outputs[0],
+                    ),
+                ]
+            });
+
+            MyConfig {
+                table,
+                advices,
+                selector,
+            }
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config
+                .table
+                .layout(&mut layouter, false, AssignmentOrder::ColumnMajor)
+                .unwrap();
+            layouter.assign_region(
+                || "",
+                |mut region| {
+                    for (row, (x, y)) in self.rows.iter().enumerate() {
+                        config.selector.enable(&mut region, row)?;
+                        let values = [i128_to_felt::<F>(*x), i128_to_felt(*y)];
+                        for (col, value) in config.advices.iter().zip(values) {
+                            region.assign_advice(|| "", *col, row, || Value::known(value))?;
+                        }
+                    }
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn rows_are_the_precomputed_outputs() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let table = Table::<F>::from_precomputed(&mut cs, RANGE, K, "cubic".to_string(), outputs())
+            .unwrap();
+        let expected = (RANGE.0..=RANGE.1)
+            .map(|x| (x, cubic(x)))
+            .collect::<Vec<_>>();
+        assert_eq!(table.rows().unwrap(), expected);
+        assert_eq!(table.name(), "table:cubic[-4,4]");
+    }
+
+    #[test]
+    fn lookup_accepts_precomputed_rows() {
+        let rows = vec![(-4, cubic(-4)), (0, cubic(0)), (3, cubic(3)), (4, cubic(4))];
+        let circuit = MyCircuit { rows };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn lookup_rejects_wrong_output() {
+        let rows = vec![(1, cubic(1)), (2, cubic(2) + 1)];
+        let circuit = MyCircuit { rows };
+        let prover = MockProver::run(K as u32, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn mismatched_outputs_are_rejected() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let too_short = Tensor::from((RANGE.0..RANGE.1).map(|x| i128_to_felt::<F>(cubic(x))));
+        assert!(matches!(
+            Table::<F>::from_precomputed(&mut cs, RANGE, K, "cubic".to_string(), too_short),
+            Err(CircuitError::DimMismatch(_))
+        ));
+        assert!(matches!(
+            Table::<F>::from_precomputed(&mut cs, (4, -4), K, "cubic".to_string(), outputs()),
+            Err(CircuitError::Table(TableError::RangeInvalid(_)))
+        ));
+    }
+
+    #[test]
+    fn tables_are_keyed_on_their_outputs() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let shifted = outputs().map(|y| y + F::from(1));
+        let tables = [outputs(), shifted].map(|outputs| {
+            Table::<F>::from_precomputed(&mut cs, RANGE, K, "cubic".to_string(), outputs).unwrap()
+        });
+        assert_ne!(tables[0].nonlinearity, tables[1].nonlinearity);
+        for table in tables.iter() {
+            table.nonlinearity.validate().unwrap();
+        }
+
+        // evaluations written for one table are never reloaded for the other
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cubic.evals");
+        tables[0].serialize_evals(&path).unwrap();
+        assert!(Table::<F>::try_load_evals(RANGE, &tables[0].nonlinearity, &path).is_some());
+        assert!(Table::<F>::try_load_evals(RANGE, &tables[1].nonlinearity, &path).is_none());
+    }
+}
+
+#[cfg(test)]
+mod degree_report {
+    use super::*;
+    use crate::circuit::ops::base::BaseOp;
+
+    const K: usize = 6;
+
+    fn columns(cs: &mut ConstraintSystem<F>) -> [VarTensor; 3] {
+        [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 8))
+    }
+
+    #[test]
+    fn increases_add_up_to_the_gate_degree() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = columns(&mut cs);
+        let before = cs.degree();
+        let (_, report) =
+            BaseConfig::configure_with_degree_report(&mut cs, &[a, b], &output, CheckMode::SAFE);
+
+        let total = report.iter().map(|(_, increase)| increase).sum::<usize>();
+        assert_eq!(before + total, cs.degree());
+
+        // one entry per op
+        let mut names = report
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), report.len());
+        for op in [BaseOp::Mult, BaseOp::Dot, BaseOp::Sum] {
+            assert!(names.iter().any(|name| name == op.as_str()));
+        }
+    }
+
+    #[test]
+    fn report_does_not_change_the_constraint_system() {
+        let mut reported = ConstraintSystem::<F>::default();
+        let [a, b, output] = columns(&mut reported);
+        BaseConfig::configure_with_degree_report(&mut reported, &[a, b], &output, CheckMode::SAFE);
+
+        let mut plain = ConstraintSystem::<F>::default();
+        let [a, b, output] = columns(&mut plain);
+        BaseConfig::configure(&mut plain, &[a, b], &output, CheckMode::SAFE);
+
+        assert_eq!(reported.gates().len(), plain.gates().len());
+        assert_eq!(reported.degree(), plain.degree());
+    }
+
+    #[test]
+    fn lookups_are_reported_through_the_builder() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = columns(&mut cs);
+        let before = cs.degree();
+        let mut builder = BaseConfigBuilder::configure_with_degree_report(
+            &mut cs,
+            &[a.clone(), b.clone()],
+            &output,
+            CheckMode::SAFE,
+        );
+        let gates = builder.degree_report().len();
+        builder
+            .configure_lookup(&mut cs, &a, &output, &b, (-8, 8), K, &LookupOp::ReLU)
+            .unwrap();
+
+        let report = builder.degree_report();
+        assert_eq!(report.len(), gates + 1);
+        assert_eq!(report[gates].0, Op::<F>::as_string(&LookupOp::ReLU));
+
+        let total = report.iter().map(|(_, increase)| increase).sum::<usize>();
+        assert_eq!(before + total, cs.degree());
+    }
+}
+
+#[cfg(test)]
+mod saturating_ops {
+    use super::*;
+    use crate::circuit::ops::base::BaseOp;
+    use crate::fieldutils::{felt_to_i128, i128_to_felt};
+
+    const K: usize = 8;
+    const LEN: usize = 6;
+    const LO: i64 = -8;
+    const HI: i64 = 7;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        inputs: [ValTensor<F>; 2],
+        op: PolyOp,
+        expected: Vec<i128>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, c, output, index] = [0; 5].map(|_| VarTensor::new_advice(cs, K, 1, LEN * 8));
+            let mut config =
+                Self::Config::configure(cs, &[a.clone(), b, c], &output, CheckMode::SAFE);
+            config.configure_saturating(cs, LO, HI).unwrap();
+            let range = (LO as i128, HI as i128);
+            config
+                .configure_range_check(cs, &a, &index, range, K)
+                .unwrap();
+            config
+                .configure_range_check(cs, &a, &index, (0, range.1 - range.0), K)
+                .unwrap();
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    let output = config
+                        .layout(&mut region, &self.inputs, Box::new(self.op.clone()))
+                        .map_err(|_| Error::Synthesis)?
+                        .unwrap();
+                    assert_eq!(output.get_int_evals().unwrap().to_vec(), self.expected);
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn known(v: [i128; LEN]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(
+            v.into_iter().map(|v| Value::known(i128_to_felt::<F>(v))),
+        ))
+    }
+
+    fn run(op: PolyOp, a: [i128; LEN], b: [i128; LEN], expected: [i128; LEN]) {
+        let evals = Op::<F>::f(
+            &op,
+            &[a, b].map(|x| x.into_iter().map(i128_to_felt).collect()),
+        )
+        .unwrap()
+        .output;
+        assert_eq!(
+            evals.iter().map(|x| felt_to_i128(*x)).collect::<Vec<_>>(),
+            expected
+        );
+
+        let circuit = MyCircuit::<F> {
+            inputs: [known(a), known(b)],
+            op,
+            expected: expected.to_vec(),
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn add_saturates_at_both_bounds() {
+        run(
+            PolyOp::SatAdd { lo: LO, hi: HI },
+            [5, -6, 3, 7, -8, 7],
+            [4, -5, -2, 7, -8, 0],
+            [7, -8, 1, 7, -8, 7],
+        );
+    }
+
+    #[test]
+    fn sub_saturates_at_both_bounds() {
+        run(
+            PolyOp::SatSub { lo: LO, hi: HI },
+            [5, -6, 3, 0, -8, -1],
+            [-4, 5, 7, -8, 0, 7],
+            [7, -8, -4, 7, -8, -8],
+        );
+    }
+
+    #[test]
+    fn saturating_gates_need_a_third_input() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(&mut cs, K, 1, LEN));
+        let mut config = BaseConfig::configure(&mut cs, &[a, b], &output, CheckMode::SAFE);
+        assert!(matches!(
+            config.configure_saturating(&mut cs, LO, HI),
+            Err(CircuitError::DimMismatch(_))
+        ));
+
+        let mut region = RegionCtx::new_dummy(0, 1, false);
+        let inputs = [known([1; LEN]), known([2; LEN])];
+        let op = BaseOp::SatAdd { lo: LO, hi: HI };
+        assert!(layouts::saturating(&config, &mut region, &inputs, op).is_err());
+        assert!(layouts::saturating(&config, &mut region, &inputs, BaseOp::Add).is_err());
+    }
+
+    #[test]
+    fn inverted_bounds_are_an_error() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let [a, b, c, output] = [0; 4].map(|_| VarTensor::new_advice(&mut cs, K, 1, LEN));
+        let mut config = BaseConfig::configure(&mut cs, &[a, b, c], &output, CheckMode::SAFE);
+        assert!(matches!(
+            config.configure_saturating(&mut cs, HI, LO),
+            Err(CircuitError::InvertedRange(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod range_check_defaults {
+    use super::*;
+    use crate::circuit::table::{Range, RangeCheck};
+    use crate::fieldutils::i128_to_felt;
+
+    const K: usize = 6;
+    const LEN: usize = 4;
+    const RANGE: Range = (0, 15);
+    const DEFAULT: i128 = 5;
+
+    #[derive(Clone)]
+    struct MyCircuit<F: PrimeField + TensorType + PartialOrd> {
+        input: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit<F> {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output, index] = [0; 4].map(|_| VarTensor::new_advice(cs, K, 1, LEN));
+            let mut config = Self::Config::configure(cs, &[a.clone(), b], &output, CheckMode::SAFE);
+
+            let mut range_check = RangeCheck::<F>::configure(cs, RANGE, K, None);
+            range_check.set_default(0, i128_to_felt(DEFAULT)).unwrap();
+            config.range_checks.ranges.insert(RANGE, range_check);
+            config
+                .configure_range_check(cs, &a, &index, RANGE, K)
+                .unwrap();
+            assert_eq!(
+                config.range_checks.ranges[&RANGE].get_first_element(0),
+                i128_to_felt(DEFAULT)
+            );
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            config.layout_range_checks(&mut layouter).unwrap();
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    layouts::range_check(&config, &mut region, &[self.input.clone()], &RANGE)
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn overridden_default_is_honored() {
+        let circuit = MyCircuit::<F> {
+            input: ValTensor::from(Tensor::from(
+                [0, 3, 9, 15].into_iter().map(|v| Value::known(F::from(v))),
+            )),
+        };
+        MockProver::run(K as u32, &circuit, vec![])
+            .unwrap()
+            .assert_satisfied();
+    }
+
+    #[test]
+    fn default_must_lie_within_its_column() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut range_check = RangeCheck::<F>::configure(&mut cs, RANGE, 4, None);
+        assert!(range_check.num_chunks() > 1);
+        let col_size = range_check.col_size as i128;
+
+        // outside the range
+        for value in [-1, RANGE.1 + 1] {
+            assert!(matches!(
+                range_check.set_default(0, i128_to_felt(value)),
+                Err(CircuitError::NonIntegerFieldElement(..))
+            ));
+        }
+        // within the range but in another column
+        assert!(range_check.set_default(0, i128_to_felt(col_size)).is_err());
+        assert!(matches!(
+            range_check.set_default(range_check.num_chunks(), i128_to_felt(0)),
+            Err(CircuitError::DimMismatch(_))
+        ));
+        assert!(range_check.defaults.is_empty());
+
+        range_check.set_default(1, i128_to_felt(col_size)).unwrap();
+        assert_eq!(range_check.get_first_element(1), i128_to_felt(col_size));
+        assert_eq!(range_check.get_first_element(0), i128_to_felt(RANGE.0));
+    }
+}
+
+#[cfg(test)]
+mod public_outputs {
+    use super::*;
+
+    const K: usize = 5;
+
+    fn known(v: &[u64]) -> ValTensor<F> {
+        ValTensor::from(Tensor::from(v.iter().map(|v| Value::known(F::from(*v)))))
+    }
+
+    #[derive(Clone)]
+    struct MyCircuit {
+        score: ValTensor<F>,
+        bbox: ValTensor<F>,
+    }
+
+    impl Circuit<F> for MyCircuit {
+        type Config = BaseConfig<F>;
+        type FloorPlanner = SimpleFloorPlanner;
+        type Params = TestParams;
+
+        fn without_witnesses(&self) -> Self {
+            self.clone()
+        }
+
+        fn configure(cs: &mut ConstraintSystem<F>) -> Self::Config {
+            let [a, b, output] = [0; 3].map(|_| VarTensor::new_advice(cs, K, 1, 8));
+            let mut config = Self::Config::configure(cs, &[a, b], &output, CheckMode::SAFE);
+            config.configure_instance_columns(cs, 2);
+            config
+        }
+
+        fn synthesize(
+            &self,
+            mut config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            layouter.assign_region(
+                || "",
+                |region| {
+                    let mut region = RegionCtx::new(region, 0, 1);
+                    config
+                        .layout_public_outputs(
+                            &mut region,
+                            &[(self.score.clone(), 0), (self.bbox.clone(), 1)],
+                        )
+                        .map_err(|_| Error::Synthesis)?;
+                    Ok(())
+                },
+            )?;
+            Ok(())
+        }
+    }
+
+    fn circuit() -> MyCircuit {
+        MyCircuit {
+            score: known(&[7]),
+            bbox: known(&[1, 2, 3, 4]),
+        }
+    }
+
+    #[test]
+    fn outputs_land_in_their_columns() {
+        let public_inputs = vec![vec![F::from(7u64)], [1u64, 2, 3, 4].map(F::from).to_vec()];
+        let prover = MockProver::run(K as u32, &circuit(), public_inputs).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn swapped_columns_fail() {
+        let public_inputs = vec![[1u64, 2, 3, 4].map(F::from).to_vec(), vec![F::from(7u64)]];
+        let prover = MockProver::run(K as u32, &circuit(), public_inputs).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn unknown_column_errors() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let mut config = MyCircuit::configure(&mut cs);
+        let mut region = RegionCtx::new_dummy(0, 1, true);
+        let err = config
+            .layout_public_outputs(&mut region, &[(known(&[7]), 2)])
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CircuitError>(),
+            Some(CircuitError::MissingInstanceColumn {
+                index: 2,
+                num_columns: 2
+            })
+        ));
+    }
+}
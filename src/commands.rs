@@ -471,7 +471,7 @@ pub enum Commands {
         /// logrows used for aggregation circuit
         #[arg(long, default_value = DEFAULT_AGGREGATED_LOGROWS)]
         logrows: u32,
-        /// run sanity checks during calculations (safe or unsafe)
+        /// run sanity checks during calculations (safe, unsafe, warn or outputs-only)
         #[arg(long, default_value = DEFAULT_CHECKMODE)]
         check_mode: CheckMode,
         /// whether the accumulated proofs are segments of a larger circuit
@@ -590,7 +590,7 @@ pub enum Commands {
             value_enum
         )]
         proof_type: ProofType,
-        /// run sanity checks during calculations (safe or unsafe)
+        /// run sanity checks during calculations (safe, unsafe, warn or outputs-only)
         #[arg(long, default_value = DEFAULT_CHECKMODE)]
         check_mode: CheckMode,
     },
@@ -549,25 +549,6 @@ async fn fetch_srs(uri: &str) -> Result<Vec<u8>, Box<dyn Error>> {
     Ok(std::mem::take(&mut buf))
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-pub(crate) fn get_file_hash(path: &PathBuf) -> Result<String, Box<dyn Error>> {
-    use std::io::Read;
-    let file = std::fs::File::open(path)?;
-    let mut reader = std::io::BufReader::new(file);
-    let mut buffer = vec![];
-    let bytes_read = reader.read_to_end(&mut buffer)?;
-    info!(
-        "read {} bytes from file (vector of len = {})",
-        bytes_read,
-        buffer.len()
-    );
-
-    let hash = sha256::digest(buffer);
-    info!("file hash: {}", hash);
-
-    Ok(hash)
-}
-
 #[cfg(not(target_arch = "wasm32"))]
 fn check_srs_hash(
     logrows: u32,
@@ -575,23 +556,17 @@ fn check_srs_hash(
     commitment: Commitments,
 ) -> Result<String, Box<dyn Error>> {
     let path = get_srs_path(logrows, srs_path, commitment);
-    let hash = get_file_hash(&path)?;
-
-    let predefined_hash = match crate::srs_sha::PUBLIC_SRS_SHA256_HASHES.get(&logrows) {
-        Some(h) => h,
-        None => return Err(format!("SRS (k={}) hash not found in public set", logrows).into()),
-    };
 
-    if hash != *predefined_hash {
-        // delete file
-        warn!("removing SRS file at {}", path.display());
-        std::fs::remove_file(path)?;
-        return Err(
-            "SRS hash does not match the expected hash. Remote SRS may have been tampered with."
-                .into(),
-        );
+    match crate::srs_sha::verify_srs(&path, logrows) {
+        Ok(()) => Ok(crate::srs_sha::PUBLIC_SRS_SHA256_HASHES[&logrows].to_string()),
+        Err(e @ crate::srs_sha::SrsError::HashMismatch { .. }) => {
+            // delete file
+            warn!("removing SRS file at {}", path.display());
+            std::fs::remove_file(path)?;
+            Err(e.into())
+        }
+        Err(e) => Err(e.into()),
     }
-    Ok(hash)
 }
 
 #[cfg(not(target_arch = "wasm32"))]
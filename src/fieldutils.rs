@@ -2,6 +2,11 @@ use halo2_proofs::arithmetic::Field;
 /// Utilities for converting from Halo2 PrimeField types to integers (and vice-versa).
 use halo2curves::ff::PrimeField;
 
+/// Serialized byte length of a field element, derived from its [PrimeField::Repr].
+pub fn field_byte_size<F: PrimeField>() -> usize {
+    F::Repr::default().as_ref().len()
+}
+
 /// Converts an i32 to a PrimeField element.
 pub fn i32_to_felt<F: PrimeField>(x: i32) -> F {
     if x >= 0 {
@@ -71,6 +76,12 @@ mod test {
     use super::*;
     use halo2curves::pasta::Fp as F;
 
+    #[test]
+    fn test_field_byte_size() {
+        assert_eq!(field_byte_size::<F>(), 32);
+        assert_eq!(field_byte_size::<halo2curves::bn256::Fr>(), 32);
+    }
+
     #[test]
     fn test_conv() {
         let res: F = i32_to_felt(-15i32);
@@ -619,6 +619,13 @@ pub struct GraphConfig {
     circuit_size: CircuitSize,
 }
 
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+impl crate::circuit::VkStructure for GraphConfig {
+    fn vk_structure_digest(&self) -> crate::circuit::VkStructureDigest {
+        self.model_config.base.vk_structure_digest()
+    }
+}
+
 /// Defines the circuit for a computational graph / model loaded from a `.onnx` file.
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct CoreCircuit {
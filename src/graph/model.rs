@@ -906,6 +906,7 @@ impl Model {
                         symbol_values,
                         run_args.div_rebasing,
                         run_args.rebase_frac_zero_constants,
+                        run_args.recip_epsilon,
                     )?;
                     if let Some(ref scales) = override_input_scales {
                         if let Some(inp) = n.opkind.get_input() {
@@ -1057,19 +1058,11 @@ impl Model {
         }
 
         if settings.requires_dynamic_lookup() {
-            base_gate.configure_dynamic_lookup(
-                meta,
-                vars.advices[0..3].try_into()?,
-                vars.advices[3..6].try_into()?,
-            )?;
+            base_gate.configure_dynamic_lookup(meta, &vars.advices[0..3], &vars.advices[3..6])?;
         }
 
         if settings.requires_shuffle() {
-            base_gate.configure_shuffles(
-                meta,
-                vars.advices[0..2].try_into()?,
-                vars.advices[3..5].try_into()?,
-            )?;
+            base_gate.configure_shuffles(meta, &vars.advices[0..2], &vars.advices[3..5])?;
         }
 
         Ok(base_gate)
@@ -1116,6 +1109,8 @@ impl Model {
 
         config.base.layout_tables(layouter)?;
         config.base.layout_range_checks(layouter)?;
+        config.base.layout_dynamic_lookups()?;
+        config.base.layout_shuffles()?;
 
         let original_constants = constants.clone();
 
@@ -1144,6 +1139,8 @@ impl Model {
                         .map(|(i, output)| {
                             let mut tolerance = run_args.tolerance;
                             tolerance.scale = scale_to_multiplier(output_scales[i]).into();
+                            let mut output = output.clone();
+                            output.set_scale(output_scales[i]);
 
                             let comparators = if run_args.output_visibility == Visibility::Public {
                                 let res = vars.instance.as_ref().ok_or("no instance")?.clone();
@@ -1239,13 +1236,18 @@ impl Model {
                         let c = node.opkind.get_mutable_constant().ok_or("no constant")?;
                         Some(c.quantized_values.clone().try_into()?)
                     } else {
-                        config
-                            .base
-                            .layout(region, &values, n.opkind.clone_dyn())
-                            .map_err(|e| {
-                                error!("{}", e);
-                                halo2_proofs::plonk::Error::Synthesis
-                            })?
+                        // graph outputs are the only nodes checked in outputs-only check mode
+                        let is_output = self.graph.outputs.iter().any(|(o, _)| o == idx);
+                        let op = n.opkind.clone_dyn();
+                        let res = if is_output {
+                            config.base.layout_output(region, &values, op)
+                        } else {
+                            config.base.layout(region, &values, op)
+                        };
+                        res.map_err(|e| {
+                            error!("{}", e);
+                            halo2_proofs::plonk::Error::Synthesis
+                        })?
                     };
 
                     if let Some(mut vt) = res {
@@ -507,6 +507,7 @@ impl Node {
         symbol_values: &SymbolValues,
         div_rebasing: bool,
         rebase_frac_zero_constants: bool,
+        recip_epsilon: i64,
     ) -> Result<Self, Box<dyn Error>> {
         trace!("Create {:?}", node);
         trace!("Create op {:?}", node.op);
@@ -546,6 +547,7 @@ impl Node {
             &mut inputs,
             symbol_values,
             rebase_frac_zero_constants,
+            recip_epsilon,
         )?; // parses the op name
 
         // we can only take the inputs as mutable once -- so we need to collect them first
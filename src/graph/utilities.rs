@@ -247,6 +247,7 @@ pub fn new_op_from_onnx(
     inputs: &mut [super::NodeType],
     symbol_values: &SymbolValues,
     rebase_frac_zero_constants: bool,
+    recip_epsilon: i64,
 ) -> Result<(SupportedOp, Vec<usize>), Box<dyn std::error::Error>> {
     use tract_onnx::tract_core::ops::array::Trilu;
 
@@ -845,6 +846,7 @@ pub fn new_op_from_onnx(
                 input_scale: (scale_to_multiplier(in_scale) as f32).into(),
                 output_scale: (scale_to_multiplier(max_scale) as f32).into(),
                 use_range_check_for_int: true,
+                epsilon: recip_epsilon,
             })
         }
 
@@ -885,6 +887,7 @@ pub fn new_op_from_onnx(
         }),
         "Rsqrt" => SupportedOp::Nonlinear(LookupOp::Rsqrt {
             scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
+            epsilon: recip_epsilon,
         }),
         "Exp" => SupportedOp::Nonlinear(LookupOp::Exp {
             scale: scale_to_multiplier(inputs[0].out_scales()[0]).into(),
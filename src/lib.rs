@@ -94,6 +94,9 @@ lazy_static! {
     /// The serialization format for the keys
     pub static ref EZKL_KEY_FORMAT: String = std::env::var("EZKL_KEY_FORMAT")
         .unwrap_or("raw-bytes".to_string());
+
+    /// If set, every proof writes a structured log of its phases and metrics to this path
+    pub static ref EZKL_PROOF_RUN_LOG: Option<String> = std::env::var("EZKL_PROOF_RUN_LOG").ok();
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -215,6 +218,10 @@ pub struct RunArgs {
     /// Should constants with 0.0 fraction be rebased to scale 0
     #[arg(long, default_value = "false")]
     pub rebase_frac_zero_constants: bool,
+    /// Inputs of reciprocals and reciprocal square roots within this (quantized) distance of zero are treated as this distance, bounding their outputs
+    #[arg(long, default_value = "0")]
+    #[serde(default)]
+    pub recip_epsilon: i64,
     /// check mode (safe, unsafe, etc)
     #[arg(long, default_value = "unsafe")]
     pub check_mode: CheckMode,
@@ -239,6 +246,7 @@ impl Default for RunArgs {
             param_visibility: Visibility::Private,
             div_rebasing: false,
             rebase_frac_zero_constants: false,
+            recip_epsilon: 0,
             check_mode: CheckMode::UNSAFE,
             commitment: None,
         }
@@ -257,6 +265,9 @@ impl RunArgs {
         if self.logrows < 1 {
             return Err("logrows must be >= 1".into());
         }
+        if self.recip_epsilon < 0 {
+            return Err("recip_epsilon must be >= 0".into());
+        }
         if self.num_inner_cols < 1 {
             return Err("num_inner_cols must be >= 1".into());
         }
@@ -1,22 +1,28 @@
 use colored::*;
 use env_logger::Builder;
 use log::{Level, LevelFilter, Record};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::env;
 use std::fmt::Formatter;
 use std::io::Write;
 use csv::Writer;
-use std::path::Path;
-use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::fs::{File, OpenOptions};
+use std::sync::Mutex;
+use halo2curves::bn256::Fr;
+use crate::fieldutils::field_byte_size;
 
 
-#[derive(Serialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
 pub struct ProverPerformanceMetrics {
     // pub num_rows: usize,
     // pub log_rows: u32,
     // pub n: usize,
     // pub params_k: u32,
+    #[serde(alias = "circuit_size(n)")]
     pub n: u64, /// Size of the circuit
+    #[serde(alias = "log_circuit_size (k)")]
     pub k: u32,  /// Logaritmic size of the circuit
     pub extended_k: u32, /// size of the extended domain
     pub quotient_poly_degree: usize, 
@@ -27,6 +33,7 @@ pub struct ProverPerformanceMetrics {
     pub num_instance_columns: usize,
     pub num_selectors: usize,
     pub num_challenges: usize,
+    pub num_permutation_columns: usize, // number of columns participating in the permutation argument
     pub minimum_rows: usize, // minimum necessary rows that need to exist in order to account for e.g. blinding factors.
     pub blinding_factors: usize, //number of blinding factors necessary to perfectly blind each of the prover's witness polynomials.
     // pub num_ffts: usize,
@@ -41,46 +48,28 @@ pub struct ProverPerformanceMetrics {
     pub verify_time: f64,
 }
 
-pub fn write_perf_metrics_to_csv(file_path: &str, metrics: &ProverPerformanceMetrics) -> Result<(), Box<dyn std::error::Error>> {
-    let path = Path::new(file_path);
+const CSV_HEADER: [&str; 17] = [
+    "circuit_size(n)", 
+    "log_circuit_size (k)", 
+    "extended_k", 
+    "quotient_poly_degree", 
+    "max_gate_degree",
+    "cs_degree", 
+    "num_fixed_columns", 
+    "num_advice_columns", 
+    "num_instance_columns",
+    "num_selectors", 
+    "num_challenges", 
+    "minimum_rows", 
+    "blinding_factors",
+    "setup_time", 
+    "proof_time", 
+    "verify_time",
+    "num_permutation_columns"
+];
 
-    // Open the file in append mode, create it if it does not exist
-    let file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open(path)?;
-
-    // Create a CSV writer
-    let mut wtr = Writer::from_writer(file);
-    
-    // Check if the file is empty to determine if we need to write a header
-    let file_is_empty = path.metadata()?.len() == 0;
-
-    if file_is_empty {
-        // Write the header if the file is empty
-        wtr.write_record(&[
-            "circuit_size(n)", 
-            "log_circuit_size (k)", 
-            "extended_k", 
-            "quotient_poly_degree", 
-            "max_gate_degree",
-            "cs_degree", 
-            "num_fixed_columns", 
-            "num_advice_columns", 
-            "num_instance_columns",
-            "num_selectors", 
-            "num_challenges", 
-            "minimum_rows", 
-            "blinding_factors",
-            "setup_time", 
-            "proof_time", 
-            "verify_time"
-        ])?;
-    }
-
-    // Write the metric record
-    wtr.write_record(&[
+fn csv_record(metrics: &ProverPerformanceMetrics) -> [String; 17] {
+    [
         metrics.n.to_string(),
         metrics.k.to_string(),
         metrics.extended_k.to_string(),
@@ -97,16 +86,822 @@ pub fn write_perf_metrics_to_csv(file_path: &str, metrics: &ProverPerformanceMet
         metrics.setup_time.to_string(),
         metrics.proof_time.to_string(),
         metrics.verify_time.to_string(),
-    ])?;
+        metrics.num_permutation_columns.to_string(),
+    ]
+}
+
+/// When a [MetricsSink] moves on to a new file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rotation {
+    /// never, every record goes to the same file
+    Never,
+    /// once the current file has reached the given size in bytes, files are numbered `metrics.1.csv`, `metrics.2.csv`, ...
+    Size(u64),
+    /// when the (UTC) date changes, files are named `metrics.2024-01-31.csv`
+    Daily,
+}
+
+/// Appends [ProverPerformanceMetrics] to a CSV file it keeps open across records, rotating to a new
+/// file according to its [Rotation]. Every file starts with a header.
+#[derive(Debug)]
+pub struct MetricsSink {
+    path: PathBuf,
+    rotation: Rotation,
+    writer: Option<Writer<File>>,
+    current: PathBuf,
+    date: String,
+    index: usize,
+}
+
+impl MetricsSink {
+    /// A sink writing to `path`, which is also the base name of rotated files. No file is opened until
+    /// the first record.
+    pub fn new(path: impl AsRef<Path>, rotation: Rotation) -> Self {
+        MetricsSink {
+            path: path.as_ref().to_path_buf(),
+            rotation,
+            writer: None,
+            current: PathBuf::new(),
+            date: String::new(),
+            index: 0,
+        }
+    }
+
+    /// The file the last record was written to.
+    pub fn current_path(&self) -> &Path {
+        &self.current
+    }
+
+    /// Appends `metrics` to the current file, first rotating to a new one if the policy calls for it.
+    pub fn record(&mut self, metrics: &ProverPerformanceMetrics) -> Result<(), Box<dyn std::error::Error>> {
+        let date = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        let rotate = match (&self.writer, self.rotation) {
+            (None, _) => true,
+            (Some(_), Rotation::Never) => false,
+            (Some(_), Rotation::Daily) => date != self.date,
+            (Some(_), Rotation::Size(max)) => self.current.metadata()?.len() >= max,
+        };
+        if rotate {
+            if self.writer.is_some() {
+                self.index += 1;
+            }
+            self.open(&date)?;
+        }
+
+        let wtr = self.writer.as_mut().ok_or("metrics sink has no open file")?;
+        wtr.write_record(&csv_record(metrics))?;
+        // flush so the size based rotation sees the record
+        wtr.flush()?;
+        Ok(())
+    }
+
+    fn path_for(&self, index: usize, date: &str) -> PathBuf {
+        let stem = self.path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let ext = self.path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        let name = match self.rotation {
+            Rotation::Daily => format!("{}.{}{}", stem, date, ext),
+            Rotation::Size(_) if index > 0 => format!("{}.{}{}", stem, index, ext),
+            _ => return self.path.clone(),
+        };
+        self.path.with_file_name(name)
+    }
+
+    fn open(&mut self, date: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut path = self.path_for(self.index, date);
+        // skip over files a previous run already filled up
+        if let Rotation::Size(max) = self.rotation {
+            while path.metadata().map(|m| m.len() >= max).unwrap_or(false) {
+                self.index += 1;
+                path = self.path_for(self.index, date);
+            }
+        }
+
+        // Open the file in append mode, create it if it does not exist
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(true)
+            .open(&path)?;
+        // Check if the file is empty to determine if we need to write a header
+        let file_is_empty = file.metadata()?.len() == 0;
+
+        let mut wtr = Writer::from_writer(file);
+        if file_is_empty {
+            wtr.write_record(CSV_HEADER)?;
+            wtr.flush()?;
+        }
+
+        self.writer = Some(wtr);
+        self.current = path;
+        self.date = date.to_string();
+        Ok(())
+    }
+}
 
-    // Flush the writer to ensure all data is written
-    wtr.flush()?;
+pub fn write_perf_metrics_to_csv(file_path: &str, metrics: &ProverPerformanceMetrics) -> Result<(), Box<dyn std::error::Error>> {
+    MetricsSink::new(file_path, Rotation::Never).record(metrics)?;
 
-    println!("Data written to {}", file_path);
+    log::info!("Data written to {}", file_path);
 
     Ok(())
 }
 
+/// Reads back the records of a CSV file written by [write_perf_metrics_to_csv] or a [MetricsSink].
+/// Columns are matched by header name, so files written before a column was added still read, with
+/// the missing metrics left at their default.
+pub fn read_perf_metrics_from_csv(file_path: &str) -> Result<Vec<ProverPerformanceMetrics>, Box<dyn std::error::Error>> {
+    let mut rdr = csv::ReaderBuilder::new().trim(csv::Trim::All).from_path(file_path)?;
+    let records = rdr.deserialize().collect::<Result<Vec<ProverPerformanceMetrics>, _>>()?;
+    Ok(records)
+}
+
+#[test]
+fn csv_metrics_round_trip_every_column() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.csv");
+    let file_path = path.to_str().unwrap();
+
+    let metrics = ProverPerformanceMetrics {
+        n: 1 << 17,
+        k: 17,
+        extended_k: 19,
+        quotient_poly_degree: 3,
+        max_gate_degree: 4,
+        cs_degree: 5,
+        num_fixed_columns: 6,
+        num_advice_columns: 7,
+        num_instance_columns: 8,
+        num_selectors: 9,
+        num_challenges: 10,
+        num_permutation_columns: 11,
+        minimum_rows: 12,
+        blinding_factors: 13,
+        setup_time: 1.5,
+        proof_time: 2.5,
+        verify_time: 0.5,
+    };
+    write_perf_metrics_to_csv(file_path, &metrics).unwrap();
+    assert_eq!(read_perf_metrics_from_csv(file_path).unwrap(), vec![metrics]);
+
+    // columns added after the original layout go at the end, so older readers keep their positions
+    let header = std::fs::read_to_string(file_path).unwrap().lines().next().unwrap().to_string();
+    assert!(header.starts_with("circuit_size(n),log_circuit_size (k),extended_k,quotient_poly_degree,max_gate_degree,cs_degree,num_fixed_columns,num_advice_columns,num_instance_columns,num_selectors,num_challenges,minimum_rows,blinding_factors,setup_time,proof_time,verify_time"));
+    assert!(header.ends_with(",num_permutation_columns"));
+}
+
+#[test]
+fn csv_metrics_read_by_header_name() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.csv");
+
+    // an older file, without num_permutation_columns and with its columns in another order
+    std::fs::write(&path, "proof_time,log_circuit_size (k),circuit_size(n),num_advice_columns\n2.5, 17,131072,7\n").unwrap();
+
+    let records = read_perf_metrics_from_csv(path.to_str().unwrap()).unwrap();
+    assert_eq!(
+        records,
+        vec![ProverPerformanceMetrics { n: 131072, k: 17, num_advice_columns: 7, proof_time: 2.5, ..Default::default() }]
+    );
+}
+
+/// Mean, extremes and (population) standard deviation of a timing across runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimingStats {
+    pub mean: f64,
+    pub min: f64,
+    pub max: f64,
+    pub stddev: f64,
+}
+
+impl TimingStats {
+    fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return TimingStats::default();
+        }
+        let n = samples.len() as f64;
+        let mean = samples.iter().sum::<f64>() / n;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        TimingStats {
+            mean,
+            min: samples.iter().copied().fold(f64::INFINITY, f64::min),
+            max: samples.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            stddev: variance.sqrt(),
+        }
+    }
+}
+
+/// Aggregates of the timings of a set of runs, see [summarize].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MetricsSummary {
+    /// number of runs summarized
+    pub runs: usize,
+    pub setup_time: TimingStats,
+    pub proof_time: TimingStats,
+    pub verify_time: TimingStats,
+}
+
+/// Summarizes the setup, proof and verify times of `metrics`. An empty slice gives an all zero summary.
+pub fn summarize(metrics: &[ProverPerformanceMetrics]) -> MetricsSummary {
+    let timing = |f: fn(&ProverPerformanceMetrics) -> f64| TimingStats::from_samples(&metrics.iter().map(f).collect::<Vec<_>>());
+    MetricsSummary {
+        runs: metrics.len(),
+        setup_time: timing(|m| m.setup_time),
+        proof_time: timing(|m| m.proof_time),
+        verify_time: timing(|m| m.verify_time),
+    }
+}
+
+#[test]
+fn csv_metrics_round_trip_and_summarize() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.csv");
+    let file_path = path.to_str().unwrap();
+
+    for (i, proof_time) in [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].iter().enumerate() {
+        let metrics = ProverPerformanceMetrics {
+            k: 17,
+            num_advice_columns: i,
+            setup_time: 1.0,
+            proof_time: *proof_time,
+            verify_time: 0.25,
+            ..Default::default()
+        };
+        write_perf_metrics_to_csv(file_path, &metrics).unwrap();
+    }
+
+    let records = read_perf_metrics_from_csv(file_path).unwrap();
+    assert_eq!(records.len(), 8);
+    assert!(records.iter().all(|m| m.k == 17));
+    assert_eq!(records.iter().map(|m| m.num_advice_columns).collect::<Vec<_>>(), (0..8).collect::<Vec<_>>());
+
+    let summary = summarize(&records);
+    assert_eq!(summary.runs, 8);
+    assert_eq!(summary.proof_time, TimingStats { mean: 5.0, min: 2.0, max: 9.0, stddev: 2.0 });
+    assert_eq!(summary.setup_time, TimingStats { mean: 1.0, min: 1.0, max: 1.0, stddev: 0.0 });
+    assert_eq!(summary.verify_time.mean, 0.25);
+
+    assert_eq!(summarize(&[]), MetricsSummary::default());
+}
+
+/// Appends `metrics` to the JSON array held in `file_path`, creating the file if it does not exist.
+/// An empty file, or one that doesn't hold a JSON array, is replaced by a fresh array.
+pub fn write_perf_metrics_to_json(file_path: &str, metrics: &ProverPerformanceMetrics) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Path::new(file_path);
+
+    let mut entries = match std::fs::read_to_string(path) {
+        Ok(contents) if contents.trim().is_empty() => {
+            log::warn!("{} is empty, starting a new array of metrics", file_path);
+            vec![]
+        }
+        Ok(contents) => match serde_json::from_str::<Vec<serde_json::Value>>(&contents) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log::warn!("{} does not hold a JSON array ({}), starting a new array of metrics", file_path, e);
+                vec![]
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => vec![],
+        Err(e) => return Err(e.into()),
+    };
+    entries.push(serde_json::to_value(metrics)?);
+
+    // rewrite the whole array, JSON can't be appended to in place
+    let file = File::create(path)?;
+    serde_json::to_writer_pretty(std::io::BufWriter::new(file), &entries)?;
+
+    log::info!("Data written to {}", file_path);
+
+    Ok(())
+}
+
+#[test]
+fn json_metrics_are_appended_to_an_array() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("metrics.json");
+    let file_path = path.to_str().unwrap();
+    let num_entries = || {
+        let contents = std::fs::read_to_string(&path).unwrap();
+        serde_json::from_str::<Vec<serde_json::Value>>(&contents).unwrap().len()
+    };
+
+    let metrics = ProverPerformanceMetrics { k: 17, ..Default::default() };
+    write_perf_metrics_to_json(file_path, &metrics).unwrap();
+    write_perf_metrics_to_json(file_path, &metrics).unwrap();
+    assert_eq!(num_entries(), 2);
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let entries = serde_json::from_str::<Vec<serde_json::Value>>(&contents).unwrap();
+    assert_eq!(entries[1]["k"], 17);
+
+    // empty and malformed files are started over
+    std::fs::write(&path, "").unwrap();
+    write_perf_metrics_to_json(file_path, &metrics).unwrap();
+    assert_eq!(num_entries(), 1);
+
+    std::fs::write(&path, "{\"not\": \"an array\"}").unwrap();
+    write_perf_metrics_to_json(file_path, &metrics).unwrap();
+    assert_eq!(num_entries(), 1);
+}
+
+/// Escapes a Prometheus label value: backslashes, double quotes and newlines.
+fn escape_prometheus_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Formats a gauge value the way the Prometheus text format spells infinities and NaN.
+fn prometheus_float(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf".to_string() } else { "-Inf".to_string() }
+    } else {
+        value.to_string()
+    }
+}
+
+/// Formats `metrics` in the Prometheus text exposition format, one gauge per numeric field, each
+/// carrying `labels`. Timings are `_seconds` gauges, structural counts are integer gauges. Label names
+/// are expected to be valid Prometheus label names, label values are escaped.
+pub fn format_perf_metrics_prometheus(metrics: &ProverPerformanceMetrics, labels: &[(&str, &str)]) -> String {
+    let gauges: [(&str, &str, String); 17] = [
+        ("ezkl_circuit_rows", "Number of rows of the circuit (n)", metrics.n.to_string()),
+        ("ezkl_circuit_log_rows", "Logarithmic size of the circuit (k)", metrics.k.to_string()),
+        ("ezkl_extended_log_rows", "Logarithmic size of the extended domain", metrics.extended_k.to_string()),
+        ("ezkl_quotient_poly_degree", "Degree of the quotient polynomial", metrics.quotient_poly_degree.to_string()),
+        ("ezkl_max_gate_degree", "Maximum degree of the gates", metrics.max_gate_degree.to_string()),
+        ("ezkl_cs_degree", "Degree of the constraint system", metrics.cs_degree.to_string()),
+        ("ezkl_fixed_columns", "Number of fixed columns", metrics.num_fixed_columns.to_string()),
+        ("ezkl_advice_columns", "Number of advice columns", metrics.num_advice_columns.to_string()),
+        ("ezkl_instance_columns", "Number of instance columns", metrics.num_instance_columns.to_string()),
+        ("ezkl_selectors", "Number of selectors", metrics.num_selectors.to_string()),
+        ("ezkl_challenges", "Number of challenges", metrics.num_challenges.to_string()),
+        ("ezkl_permutation_columns", "Number of columns in the permutation argument", metrics.num_permutation_columns.to_string()),
+        ("ezkl_minimum_rows", "Minimum number of rows needed, e.g. for blinding factors", metrics.minimum_rows.to_string()),
+        ("ezkl_blinding_factors", "Number of blinding factors", metrics.blinding_factors.to_string()),
+        ("ezkl_setup_time_seconds", "Time taken by the setup, in seconds", prometheus_float(metrics.setup_time)),
+        ("ezkl_proof_time_seconds", "Time taken to prove, in seconds", prometheus_float(metrics.proof_time)),
+        ("ezkl_verify_time_seconds", "Time taken to verify, in seconds", prometheus_float(metrics.verify_time)),
+    ];
+
+    let labels = if labels.is_empty() {
+        String::new()
+    } else {
+        let pairs = labels
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name, escape_prometheus_label(value)))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", pairs.join(","))
+    };
+
+    let mut out = String::new();
+    for (name, help, value) in gauges.iter() {
+        out.push_str(&format!("# HELP {} {}\n", name, help));
+        out.push_str(&format!("# TYPE {} gauge\n", name));
+        out.push_str(&format!("{}{} {}\n", name, labels, value));
+    }
+    out
+}
+
+#[test]
+fn prometheus_metrics_are_valid_exposition_text() {
+    let metrics = ProverPerformanceMetrics { k: 17, num_advice_columns: 12, proof_time: 1.5, ..Default::default() };
+    let out = format_perf_metrics_prometheus(&metrics, &[("model", "mnist"), ("host", "a \"b\"\\c")]);
+
+    let valid_name = |name: &str| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':')
+    };
+    // checks a `{name="value",...}` label set, honouring escapes inside the values
+    let valid_labels = |labels: &str| {
+        let mut rest = match labels.strip_prefix('{').and_then(|l| l.strip_suffix('}')) {
+            Some(rest) => rest,
+            None => return false,
+        };
+        loop {
+            let (name, value) = match rest.split_once("=\"") {
+                Some(split) => split,
+                None => return false,
+            };
+            if !valid_name(name) || name.contains(':') {
+                return false;
+            }
+            let mut chars = value.char_indices();
+            let end = loop {
+                match chars.next() {
+                    Some((_, '\\')) => {
+                        if !matches!(chars.next(), Some((_, '\\' | '"' | 'n'))) {
+                            return false;
+                        }
+                    }
+                    Some((i, '"')) => break i,
+                    Some(_) => {}
+                    None => return false,
+                }
+            };
+            rest = &value[end + 1..];
+            if rest.is_empty() {
+                return true;
+            }
+            rest = match rest.strip_prefix(',') {
+                Some(rest) => rest,
+                None => return false,
+            };
+        }
+    };
+
+    let mut typed = std::collections::HashSet::new();
+    let mut samples = std::collections::HashMap::new();
+    for line in out.lines() {
+        if let Some(rest) = line.strip_prefix("# TYPE ") {
+            let (name, kind) = rest.split_once(' ').unwrap();
+            assert_eq!(kind, "gauge");
+            typed.insert(name.to_string());
+        } else if line.starts_with("# HELP ") {
+            continue;
+        } else {
+            let (series, value) = line.rsplit_once(' ').unwrap();
+            let (name, labels) = match series.find('{') {
+                Some(i) => series.split_at(i),
+                None => (series, ""),
+            };
+            assert!(valid_name(name), "invalid metric name: {}", line);
+            assert!(labels.is_empty() || valid_labels(labels), "invalid labels: {}", line);
+            // every sample is preceded by its type
+            assert!(typed.contains(name));
+            samples.insert(name.to_string(), value.parse::<f64>().unwrap());
+        }
+    }
+
+    assert_eq!(samples.len(), 17);
+    assert_eq!(samples["ezkl_circuit_log_rows"], 17.0);
+    assert_eq!(samples["ezkl_advice_columns"], 12.0);
+    assert_eq!(samples["ezkl_proof_time_seconds"], 1.5);
+    assert!(out.contains("ezkl_proof_time_seconds{model=\"mnist\",host=\"a \\\"b\\\"\\\\c\"} 1.5\n"));
+
+    // without labels the samples are bare
+    let out = format_perf_metrics_prometheus(&metrics, &[]);
+    assert!(out.contains("\nezkl_circuit_log_rows 17\n"));
+}
+
+/// An event of a [ProofRunLog].
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProofRunEvent {
+    /// A phase of the run (e.g. `prove`, `verify`) started
+    PhaseStart { phase: String },
+    /// A phase of the run ended, `elapsed` seconds after it started
+    PhaseEnd { phase: String, elapsed: f64 },
+    /// A warning raised during the run, tagged with where it was raised
+    Warning { context: String, message: String },
+    /// The final performance metrics of the run
+    Metrics { metrics: serde_json::Value },
+}
+
+/// A [ProofRunEvent] and the time it was recorded at, in RFC 3339.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TimestampedProofRunEvent {
+    pub timestamp: String,
+    #[serde(flatten)]
+    pub event: ProofRunEvent,
+}
+
+/// Target of the lines [ProofRunLog::warn] logs, which [RunLogCapture] doesn't capture a second time.
+const RUN_LOG_TARGET: &str = "ezkl::run_log";
+
+/// Events of the [ProofRunLog] capturing the warnings logged process wide, if any, see
+/// [ProofRunLog::capture_warnings].
+static CAPTURED_EVENTS: Mutex<Option<Vec<TimestampedProofRunEvent>>> = Mutex::new(None);
+
+/// Events of the last key generation in this process, see [ProofRunLog::keep_as_setup].
+static SETUP_EVENTS: Mutex<Vec<TimestampedProofRunEvent>> = Mutex::new(Vec::new());
+
+/// A structured log of a single proof: phase starts and ends, warnings and the final metrics, in the
+/// order they happened. Written out as one JSON artifact per proof with [ProofRunLog::save].
+#[derive(Serialize, Debug, Default)]
+pub struct ProofRunLog {
+    pub events: Vec<TimestampedProofRunEvent>,
+    #[serde(skip)]
+    open_phases: std::collections::HashMap<String, instant::Instant>,
+    #[serde(skip)]
+    capturing: bool,
+}
+
+impl ProofRunLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A log starting with the events of the last key generation in this process, see
+    /// [ProofRunLog::keep_as_setup], so that the log of a proof covers the setup of its keys. Starts
+    /// empty if the keys were generated by another process, e.g. an earlier `ezkl setup`.
+    pub fn with_setup() -> Self {
+        let mut run_log = Self::default();
+        run_log.events = SETUP_EVENTS.lock().unwrap().clone();
+        run_log
+    }
+
+    /// Keeps the events of this log, that of a key generation, for the next [ProofRunLog::with_setup].
+    pub fn keep_as_setup(mut self) {
+        self.stop_capture();
+        *SETUP_EVENTS.lock().unwrap() = std::mem::take(&mut self.events);
+    }
+
+    /// Records the warnings logged anywhere in the process until [ProofRunLog::stop_capture], e.g.
+    /// those raised while the circuit is synthesized, as [ProofRunEvent::Warning]s tagged with their
+    /// log target. The warnings reach the log through the logger installed by [init_logger] and its
+    /// variants, see [RunLogCapture]. Only one log captures at a time: returns false, capturing
+    /// nothing, if another one already is.
+    pub fn capture_warnings(&mut self) -> bool {
+        let mut captured = CAPTURED_EVENTS.lock().unwrap();
+        if captured.is_some() {
+            return false;
+        }
+        // while capturing, the log's own events go to the shared buffer too, keeping them in order
+        *captured = Some(std::mem::take(&mut self.events));
+        self.capturing = true;
+        true
+    }
+
+    /// Stops capturing the warnings logged process wide, see [ProofRunLog::capture_warnings].
+    pub fn stop_capture(&mut self) {
+        if self.capturing {
+            self.events = CAPTURED_EVENTS.lock().unwrap().take().unwrap_or_default();
+            self.capturing = false;
+        }
+    }
+
+    fn push(&mut self, event: ProofRunEvent) {
+        let event = TimestampedProofRunEvent { timestamp: chrono::Utc::now().to_rfc3339(), event };
+        if self.capturing {
+            if let Some(events) = CAPTURED_EVENTS.lock().unwrap().as_mut() {
+                events.push(event);
+                return;
+            }
+        }
+        self.events.push(event);
+    }
+
+    /// Records the start of `phase`.
+    pub fn start_phase(&mut self, phase: &str) {
+        self.open_phases.insert(phase.to_string(), instant::Instant::now());
+        self.push(ProofRunEvent::PhaseStart { phase: phase.to_string() });
+    }
+
+    /// Records the end of `phase` and returns the seconds since it started, zero if it never did.
+    pub fn end_phase(&mut self, phase: &str) -> f64 {
+        let elapsed = match self.open_phases.remove(phase) {
+            Some(start) => start.elapsed().as_secs_f64(),
+            None => {
+                log::warn!("phase {} ended without having started", phase);
+                0.0
+            }
+        };
+        self.push(ProofRunEvent::PhaseEnd { phase: phase.to_string(), elapsed });
+        elapsed
+    }
+
+    /// Records a warning raised in `context`, and logs it as usual.
+    pub fn warn(&mut self, context: &str, message: &str) {
+        log::warn!(target: RUN_LOG_TARGET, "{}: {}", context, message);
+        self.push(ProofRunEvent::Warning { context: context.to_string(), message: message.to_string() });
+    }
+
+    /// Records the final metrics of the run.
+    pub fn record_metrics(&mut self, metrics: &ProverPerformanceMetrics) -> Result<(), Box<dyn std::error::Error>> {
+        let metrics = serde_json::to_value(metrics)?;
+        self.push(ProofRunEvent::Metrics { metrics });
+        Ok(())
+    }
+
+    /// Writes the log to `path` as JSON. Captured warnings are only included once
+    /// [ProofRunLog::stop_capture] is called.
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::create(path)?;
+        serde_json::to_writer_pretty(std::io::BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+impl Drop for ProofRunLog {
+    // a run erroring out mustn't leave its log capturing the warnings of the runs after it
+    fn drop(&mut self) {
+        self.stop_capture();
+    }
+}
+
+/// A logger forwarding every record to the wrapped logger, and the warnings to the [ProofRunLog]
+/// capturing them, if any, see [ProofRunLog::capture_warnings]. Installed by [init_logger] and its
+/// variants.
+pub struct RunLogCapture<L: log::Log>(pub L);
+
+impl<L: log::Log> log::Log for RunLogCapture<L> {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= Level::Warn || self.0.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() == Level::Warn && record.target() != RUN_LOG_TARGET {
+            if let Some(events) = CAPTURED_EVENTS.lock().unwrap().as_mut() {
+                events.push(TimestampedProofRunEvent {
+                    timestamp: chrono::Utc::now().to_rfc3339(),
+                    event: ProofRunEvent::Warning {
+                        context: record.target().to_string(),
+                        message: record.args().to_string(),
+                    },
+                });
+            }
+        }
+        self.0.log(record);
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+#[test]
+fn proof_run_log_records_events_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("run.json");
+
+    let mut run_log = ProofRunLog::new();
+    run_log.start_phase("prove");
+    run_log.warn("prove", "check mode is unsafe");
+    let elapsed = run_log.end_phase("prove");
+    run_log.start_phase("verify");
+    run_log.end_phase("verify");
+    run_log.record_metrics(&ProverPerformanceMetrics { k: 17, ..Default::default() }).unwrap();
+    run_log.save(&path).unwrap();
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    let saved = serde_json::from_str::<serde_json::Value>(&contents).unwrap();
+    let events = saved["events"].as_array().unwrap();
+    let kinds = events.iter().map(|e| e["event"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(kinds, ["phase_start", "warning", "phase_end", "phase_start", "phase_end", "metrics"]);
+
+    assert_eq!(events[0]["phase"], "prove");
+    assert_eq!(events[1]["context"], "prove");
+    assert_eq!(events[2]["elapsed"], elapsed);
+    assert_eq!(events[3]["phase"], "verify");
+    assert_eq!(events[5]["metrics"]["k"], 17);
+    assert!(events.iter().all(|e| chrono::DateTime::parse_from_rfc3339(e["timestamp"].as_str().unwrap()).is_ok()));
+}
+
+#[test]
+fn proof_run_log_captures_warnings_logged_mid_run() {
+    struct Discard;
+
+    impl log::Log for Discard {
+        fn enabled(&self, _: &log::Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, _: &Record) {}
+
+        fn flush(&self) {}
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("run.json");
+    let logger = RunLogCapture(Discard);
+    let record = |level, msg| {
+        log::Log::log(&logger, &Record::builder().args(format_args!("{}", msg)).level(level).target("ezkl::logger::test").build());
+    };
+
+    let mut run_log = ProofRunLog::new();
+    assert!(run_log.capture_warnings());
+    run_log.start_phase("prove");
+    record(Level::Warn, "lookup input out of range");
+    // only warnings are captured
+    record(Level::Info, "proof started...");
+    run_log.end_phase("prove");
+    run_log.stop_capture();
+    run_log.save(&path).unwrap();
+
+    let saved = serde_json::from_str::<serde_json::Value>(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    // other tests may log warnings of their own while this one captures
+    let events = saved["events"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|e| e["event"] != "warning" || e["context"] == "ezkl::logger::test")
+        .collect::<Vec<_>>();
+    let kinds = events.iter().map(|e| e["event"].as_str().unwrap()).collect::<Vec<_>>();
+    assert_eq!(kinds, ["phase_start", "warning", "phase_end"]);
+    assert_eq!(events[1]["message"], "lookup input out of range");
+
+    // once the capture stopped, warnings are no longer recorded
+    record(Level::Warn, "after the run");
+    assert_eq!(run_log.events.len(), saved["events"].as_array().unwrap().len());
+}
+
+#[test]
+fn metrics_sink_rotates_by_size() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path().join("metrics.csv");
+    let metrics = ProverPerformanceMetrics::default();
+
+    // the first record fits under the threshold, so a second one is appended to the same file
+    let mut sink = MetricsSink::new(&base, Rotation::Size(u64::MAX));
+    sink.record(&metrics).unwrap();
+    let one_record = base.metadata().unwrap().len();
+    drop(sink);
+    std::fs::remove_file(&base).unwrap();
+
+    let mut sink = MetricsSink::new(&base, Rotation::Size(one_record + 1));
+    sink.record(&metrics).unwrap();
+    sink.record(&metrics).unwrap();
+    assert_eq!(sink.current_path(), base.as_path());
+
+    // the file is now past the threshold, so the next record starts a new one
+    sink.record(&metrics).unwrap();
+    let rotated = dir.path().join("metrics.1.csv");
+    assert_eq!(sink.current_path(), rotated.as_path());
+
+    let header = CSV_HEADER.join(",");
+    for (path, num_records) in [(&base, 2), (&rotated, 1)] {
+        let contents = std::fs::read_to_string(path).unwrap();
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], header);
+        assert_eq!(lines.len(), 1 + num_records);
+    }
+}
+
+/// Estimates the memory taken by the extended-domain polynomials while proving, which dominates
+/// the prover's peak memory on large circuits: one `2^extended_k` evaluation per advice, fixed and
+/// permutation column.
+pub fn estimate_fft_memory_bytes(metrics: &ProverPerformanceMetrics) -> usize {
+    let num_polys = metrics.num_advice_columns + metrics.num_fixed_columns + metrics.num_permutation_columns;
+    (1usize << metrics.extended_k) * num_polys * field_byte_size::<Fr>()
+}
+
+/// Keygen throughput of the target hardware, in column rows processed per second. Measure it once with
+/// [KeygenThroughput::calibrate] and reuse it for [estimate_setup_time_secs].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeygenThroughput {
+    pub rows_per_sec: f64,
+}
+
+impl KeygenThroughput {
+    /// Derives the throughput from a keygen of the circuit described by `metrics` that took `setup_time_secs`.
+    pub fn calibrate(metrics: &ProverPerformanceMetrics, setup_time_secs: f64) -> Self {
+        KeygenThroughput {
+            rows_per_sec: keygen_rows(metrics) / setup_time_secs,
+        }
+    }
+}
+
+/// Rows keygen works through: committing to every fixed, selector and permutation column over `2^k`
+/// rows, and extending the fixed and permutation polynomials to `2^extended_k` rows for the proving key.
+fn keygen_rows(metrics: &ProverPerformanceMetrics) -> f64 {
+    let committed = metrics.num_fixed_columns + metrics.num_selectors + metrics.num_permutation_columns;
+    let extended = metrics.num_fixed_columns + metrics.num_permutation_columns;
+    (committed as f64) * 2f64.powi(metrics.k as i32) + (extended as f64) * 2f64.powi(metrics.extended_k as i32)
+}
+
+/// Rough estimate of the keygen time, in seconds, of the circuit described by `metrics`. This is a
+/// planning aid for deciding whether to cache keys, not a precise model.
+pub fn estimate_setup_time_secs(metrics: &ProverPerformanceMetrics, throughput: KeygenThroughput) -> f64 {
+    keygen_rows(metrics) / throughput.rows_per_sec
+}
+
+#[test]
+fn setup_time_estimate_scales_with_k() {
+    let metrics = |k: u32| ProverPerformanceMetrics {
+        k,
+        extended_k: k + 2,
+        num_fixed_columns: 4,
+        num_selectors: 8,
+        num_permutation_columns: 6,
+        ..Default::default()
+    };
+    let throughput = KeygenThroughput { rows_per_sec: 1e6 };
+
+    // every term is linear in the number of rows, so each extra bit of k doubles the estimate
+    let base = estimate_setup_time_secs(&metrics(16), throughput);
+    assert!(base > 0.0);
+    assert_eq!(estimate_setup_time_secs(&metrics(17), throughput), 2.0 * base);
+    assert_eq!(estimate_setup_time_secs(&metrics(20), throughput), 16.0 * base);
+
+    // calibrating against a measured run reproduces that run
+    let calibrated = KeygenThroughput::calibrate(&metrics(16), 3.0);
+    assert!((estimate_setup_time_secs(&metrics(16), calibrated) - 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn fft_memory_estimate() {
+    let metrics = ProverPerformanceMetrics {
+        k: 17,
+        extended_k: 20,
+        num_advice_columns: 10,
+        num_fixed_columns: 4,
+        num_permutation_columns: 6,
+        ..Default::default()
+    };
+    // 2^20 rows * 20 polys * 32 bytes = 640 MiB
+    assert_eq!(estimate_fft_memory_bytes(&metrics), 640 * 1024 * 1024);
+}
 
 #[test]
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -194,29 +989,195 @@ pub fn format(buf: &mut Formatter, record: &Record<'_>) -> Result<(), std::fmt::
     )
 }
 
+/// Removes ANSI escape sequences, such as the colors set by [level_color], from `s`.
+pub fn strip_ansi(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        // a control sequence runs up to a final byte in `@`..=`~`, any other escape is a single character long
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// How log lines are timestamped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogTimeFormat {
+    /// UTC time, e.g. `2024-01-31 12:00:00`
+    #[default]
+    Utc,
+    /// local time, e.g. `2024-01-31 13:00:00`
+    Local,
+    /// UTC time in RFC 3339, e.g. `2024-01-31T12:00:00.000Z`
+    Rfc3339,
+}
+
+impl LogTimeFormat {
+    /// the current time, in this format
+    pub fn now(&self) -> String {
+        match self {
+            LogTimeFormat::Utc => chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            LogTimeFormat::Local => chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            LogTimeFormat::Rfc3339 => chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        }
+    }
+}
+
+/// formats a log line, timestamped in `time` and without color codes unless `colored`
+fn format_line(record: &Record<'_>, colored: bool, time: LogTimeFormat) -> String {
+    let line = format!(
+        "{} [{}, {}] - {}",
+        prefix_token(&record.level()),
+        time.now().bright_magenta(),
+        record.metadata().target(),
+        level_text_color(&record.level(), &format!("{}", record.args()))
+            .replace('\n', &format!("\n{} ", " | ".white().bold()))
+    );
+    if colored {
+        line
+    } else {
+        strip_ansi(&line)
+    }
+}
+
 /// initializes the logger
 pub fn init_logger() {
+    init_logger_with_target(env_logger::Target::Stdout);
+}
+
+/// initializes the logger, timestamping lines in `fmt` rather than in UTC
+pub fn init_logger_with_time(fmt: LogTimeFormat) {
+    init_logger_with(env_logger::Target::Stdout, fmt);
+}
+
+/// initializes the logger, writing to `target`. Colors are kept when the target is a terminal and
+/// stripped otherwise, so redirected and piped logs stay readable.
+pub fn init_logger_with_target(target: env_logger::Target) {
+    init_logger_with(target, LogTimeFormat::Utc);
+}
+
+fn init_logger_with(target: env_logger::Target, time: LogTimeFormat) {
+    use std::io::IsTerminal;
+
+    let colored = match target {
+        env_logger::Target::Stdout => std::io::stdout().is_terminal(),
+        env_logger::Target::Stderr => std::io::stderr().is_terminal(),
+        _ => false,
+    };
+
     let mut builder = Builder::new();
 
-    builder.format(move |buf, record| {
-        writeln!(
-            buf,
-            "{} [{}, {}] - {}",
-            prefix_token(&record.level()),
-            //    pretty print UTC time
-            chrono::Utc::now()
-                .format("%Y-%m-%d %H:%M:%S")
-                .to_string()
-                .bright_magenta(),
-            record.metadata().target(),
-            level_text_color(&record.level(), &format!("{}", record.args()))
-                .replace('\n', &format!("\n{} ", " | ".white().bold()))
-        )
-    });
+    builder.format(move |buf, record| writeln!(buf, "{}", format_line(record, colored, time)));
+    builder.target(target);
+    builder.filter(None, LevelFilter::Info);
+    if env::var("RUST_LOG").is_ok() {
+        builder.parse_filters(&env::var("RUST_LOG").unwrap());
+    }
+    install(builder);
+}
+
+/// installs the logger `builder` builds, wrapped in a [RunLogCapture] so that run logs see its warnings
+fn install(mut builder: Builder) {
+    let logger = builder.build();
+    // warnings are captured even when the logger itself filters them out
+    let max_level = logger.filter().max(LevelFilter::Warn);
+    log::set_boxed_logger(Box::new(RunLogCapture(logger))).expect("a logger is already installed");
+    log::set_max_level(max_level);
+}
+
+/// formats a log record as a single line JSON object, with no color codes
+fn json_line(record: &Record<'_>) -> String {
+    serde_json::json!({
+        "level": record.level().as_str(),
+        "ts": LogTimeFormat::Rfc3339.now(),
+        "target": record.metadata().target(),
+        "msg": format!("{}", record.args()),
+    })
+    .to_string()
+}
+
+/// initializes the logger to print one JSON object per record, with `level`, `ts` (RFC 3339), `target`
+/// and `msg` fields, for log aggregation.
+/// `RUST_LOG` filters records as with [init_logger].
+pub fn init_json_logger() {
+    let mut builder = Builder::new();
+
+    builder.format(|buf, record| writeln!(buf, "{}", json_line(record)));
     builder.target(env_logger::Target::Stdout);
     builder.filter(None, LevelFilter::Info);
     if env::var("RUST_LOG").is_ok() {
         builder.parse_filters(&env::var("RUST_LOG").unwrap());
     }
-    builder.init();
+    install(builder);
+}
+
+/// initializes the logger, appending to the file at `path` (created if need be) without color codes
+pub fn init_logger_to_file(path: &Path) -> Result<(), std::io::Error> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    init_logger_with_target(env_logger::Target::Pipe(Box::new(file)));
+    Ok(())
+}
+
+#[test]
+fn log_lines_are_stripped_of_colors() {
+    let colored = level_color(&Level::Warn, "careful\nnow");
+    assert_eq!(strip_ansi(&colored), "careful\nnow");
+    assert_eq!(strip_ansi("\x1b[1;31mE\x1b[0m plain \x1b[38;5;208mtext\x1b[0m"), "E plain text");
+
+    let line = format_line(
+        &Record::builder()
+            .args(format_args!("first\nsecond"))
+            .level(Level::Error)
+            .target("ezkl")
+            .build(),
+        false,
+        LogTimeFormat::Utc,
+    );
+    assert!(!line.contains('\x1b'));
+    assert!(line.starts_with("[E] ["));
+    assert!(line.ends_with(", ezkl] - first\n |  second"));
+}
+
+#[test]
+fn log_time_formats() {
+    let utc = chrono::NaiveDateTime::parse_from_str(&LogTimeFormat::Utc.now(), "%Y-%m-%d %H:%M:%S").unwrap();
+    let local = chrono::NaiveDateTime::parse_from_str(&LogTimeFormat::Local.now(), "%Y-%m-%d %H:%M:%S").unwrap();
+    // the two only differ by the local offset
+    let offset = chrono::Local::now().offset().local_minus_utc() as i64;
+    assert!(((local - utc).num_seconds() - offset).abs() <= 1);
+
+    let rfc3339 = LogTimeFormat::Rfc3339.now();
+    assert!(chrono::DateTime::parse_from_rfc3339(&rfc3339).is_ok());
+    assert!(rfc3339.ends_with('Z'));
+
+    assert_eq!(LogTimeFormat::default(), LogTimeFormat::Utc);
+}
+
+#[test]
+fn json_log_lines() {
+    let line = json_line(
+        &Record::builder()
+            .args(format_args!("first\n\"second\""))
+            .level(Level::Warn)
+            .target("ezkl::graph")
+            .build(),
+    );
+    assert!(!line.contains('\n'));
+    assert!(!line.contains('\x1b'));
+
+    let parsed = serde_json::from_str::<serde_json::Value>(&line).unwrap();
+    assert_eq!(parsed["level"], "WARN");
+    assert_eq!(parsed["target"], "ezkl::graph");
+    assert_eq!(parsed["msg"], "first\n\"second\"");
+    assert!(chrono::DateTime::parse_from_rfc3339(parsed["ts"].as_str().unwrap()).is_ok());
 }
@@ -7,7 +7,7 @@ pub mod srs;
 use crate::circuit::CheckMode;
 use crate::graph::GraphWitness;
 use crate::pfsys::evm::aggregation_kzg::PoseidonTranscript;
-use crate::{Commitments, EZKL_BUF_CAPACITY, EZKL_KEY_FORMAT};
+use crate::{Commitments, EZKL_BUF_CAPACITY, EZKL_KEY_FORMAT, EZKL_PROOF_RUN_LOG};
 use clap::ValueEnum;
 use halo2_proofs::circuit::Value;
 use halo2_proofs::plonk::{
@@ -42,6 +42,7 @@ use tosubcommand::ToFlags;
 
 use halo2curves::bn256::{Bn256, Fr, G1Affine};
 use crate::logger::ProverPerformanceMetrics;
+use crate::logger::ProofRunLog;
 use crate::logger::write_perf_metrics_to_csv;
 
 fn serde_format_from_str(s: &str) -> halo2_proofs::SerdeFormat {
@@ -506,6 +507,13 @@ where
     //	Real proof
     let empty_circuit = <C as Circuit<Scheme::Scalar>>::without_witnesses(circuit);
 
+    // the setup is recorded for the run logs of the proofs generated with these keys
+    let mut run_log = ProofRunLog::new();
+    if EZKL_PROOF_RUN_LOG.is_some() {
+        run_log.capture_warnings();
+    }
+    run_log.start_phase("setup");
+
     // Initialize verifying key
     let now = Instant::now();
     trace!("preparing VK");
@@ -518,6 +526,8 @@ where
     let pk = keygen_pk(params, vk, &empty_circuit)?;
     let elapsed = now.elapsed();
     info!("PK took {}.{}", elapsed.as_secs(), elapsed.subsec_millis());
+    run_log.end_phase("setup");
+    run_log.keep_as_setup();
     Ok(pk)
 }
 
@@ -556,6 +566,10 @@ where
 {
 
     let mut perf_metrics = ProverPerformanceMetrics::default();
+    let mut run_log = ProofRunLog::with_setup();
+    if EZKL_PROOF_RUN_LOG.is_some() {
+        run_log.capture_warnings();
+    }
 
     let strategy = Strategy::new(params.verifier_params());
     let mut transcript = TranscriptWriterBuffer::<_, Scheme::Curve, _>::init(vec![]);
@@ -584,6 +598,7 @@ where
      perf_metrics.num_instance_columns = pk.get_vk().cs().num_instance_columns();
      perf_metrics.num_selectors = pk.get_vk().cs().num_selectors();
      perf_metrics.num_challenges = pk.get_vk().cs().num_challenges();
+     perf_metrics.num_permutation_columns = pk.get_vk().cs().permutation().get_columns().len();
      perf_metrics.minimum_rows = pk.get_vk().cs().minimum_rows();
      perf_metrics.blinding_factors = pk.get_vk().cs().blinding_factors();
      perf_metrics.cs_degree = pk.get_vk().cs().degree();
@@ -596,6 +611,7 @@ where
     info!("proof started...");
     // not wasm32 unknown
     let now = Instant::now();
+    run_log.start_phase("prove");
 
     create_proof::<Scheme, P, _, _, TW, _>(
         params,
@@ -606,6 +622,7 @@ where
         &mut transcript,
     )?;
     let proof = transcript.finalize();
+    run_log.end_phase("prove");
     let hex_proof = format!("0x{}", hex::encode(&proof));
 
     let checkable_pf = Snark::new(
@@ -623,6 +640,7 @@ where
     if check_mode == CheckMode::SAFE {
         debug!("verifying generated proof");
         let verify_start  = Instant::now();
+        run_log.start_phase("verify");
         let verifier_params = params.verifier_params();
         verify_proof_circuit::<V, Scheme, Strategy, E, TR>(
             &checkable_pf,
@@ -633,6 +651,7 @@ where
         )?;
         let verify_elapsed = verify_start.elapsed();
         perf_metrics.verify_time  = verify_elapsed.as_secs_f64();
+        run_log.end_phase("verify");
     }
     let elapsed = now.elapsed();
     info!(
@@ -643,6 +662,11 @@ where
     // Update performance metrics if provided
     perf_metrics.proof_time = elapsed.as_secs_f64();
     let _ = write_perf_metrics_to_csv("halo2_circuit.csv", &perf_metrics)?;
+    run_log.stop_capture();
+    if let Some(path) = EZKL_PROOF_RUN_LOG.as_ref() {
+        run_log.record_metrics(&perf_metrics)?;
+        run_log.save(std::path::Path::new(path))?;
+    }
 
     Ok(checkable_pf)
 }
@@ -782,6 +806,34 @@ where
     Ok(vk)
 }
 
+/// Reads a serialized [VerifyingKey] of `C` and returns the digest of its structure, taken from the
+/// config of `C` the key deserializes against exactly as [crate::circuit::BaseConfig::vk_structure_digest]
+/// takes it, so the two can be compared. Proofs whose verifying keys share a digest were generated by
+/// structurally identical circuits, see [crate::circuit::proofs_same_circuit].
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn vk_structure_digest_from_bytes<Scheme: CommitmentScheme, C: Circuit<Scheme::Scalar>>(
+    bytes: &[u8],
+    params: <C as Circuit<Scheme::Scalar>>::Params,
+) -> Result<crate::circuit::VkStructureDigest, Box<dyn Error>>
+where
+    C::Config: crate::circuit::VkStructure,
+    C::Params: Clone,
+    Scheme::Curve: SerdeObject + CurveAffine,
+    Scheme::Scalar: PrimeField + SerdeObject + FromUniformBytes<64>,
+{
+    use crate::circuit::VkStructure;
+
+    // a key that doesn't deserialize against the constraint system of `C` is for another circuit
+    VerifyingKey::<Scheme::Curve>::read::<_, C>(
+        &mut Cursor::new(bytes),
+        serde_format_from_str(&EZKL_KEY_FORMAT),
+        params.clone(),
+    )?;
+    let mut cs = halo2_proofs::plonk::ConstraintSystem::default();
+    let config = C::configure_with_params(&mut cs, params);
+    Ok(config.vk_structure_digest())
+}
+
 /// Loads a [ProvingKey] at `path`.
 pub fn load_pk<Scheme: CommitmentScheme, C: Circuit<Scheme::Scalar>>(
     path: PathBuf,
@@ -187,6 +187,9 @@ struct PyRunArgs {
     /// bool: Should constants with 0.0 fraction be rebased to scale 0
     pub rebase_frac_zero_constants: bool,
     #[pyo3(get, set)]
+    /// int: Inputs of reciprocals within this (quantized) distance of zero are treated as this distance
+    pub recip_epsilon: i64,
+    #[pyo3(get, set)]
     /// str: check mode, accepts `safe`, `unsafe`
     pub check_mode: CheckMode,
     #[pyo3(get, set)]
@@ -220,6 +223,7 @@ impl From<PyRunArgs> for RunArgs {
             variables: py_run_args.variables,
             div_rebasing: py_run_args.div_rebasing,
             rebase_frac_zero_constants: py_run_args.rebase_frac_zero_constants,
+            recip_epsilon: py_run_args.recip_epsilon,
             check_mode: py_run_args.check_mode,
             commitment: Some(py_run_args.commitment.into()),
         }
@@ -242,6 +246,7 @@ impl Into<PyRunArgs> for RunArgs {
             variables: self.variables,
             div_rebasing: self.div_rebasing,
             rebase_frac_zero_constants: self.rebase_frac_zero_constants,
+            recip_epsilon: self.recip_epsilon,
             check_mode: self.check_mode,
             commitment: self.commitment.into(),
         }
@@ -1,8 +1,12 @@
 use lazy_static::lazy_static;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
 
 lazy_static! {
-    /// SRS SHA256 hashes
+    /// SRS SHA256 hashes, for k=1 to k=26.
+    // TODO: add the k=27 and k=28 hashes, computed from the published ceremony files. Until then
+    // `verify_srs` rejects those sizes with `SrsError::KTooLarge`.
     pub static ref PUBLIC_SRS_SHA256_HASHES: HashMap<u32, &'static str> = HashMap::from_iter([
         (
             1,
@@ -110,3 +114,177 @@ lazy_static! {
         ),
     ]);
 }
+
+/// Errors from checking an SRS file against [PUBLIC_SRS_SHA256_HASHES].
+#[derive(Debug, Error)]
+pub enum SrsError {
+    /// The SRS file couldn't be read
+    #[error("failed to read SRS file {path:?}: {source}")]
+    Io {
+        /// path of the SRS file
+        path: PathBuf,
+        /// underlying error
+        source: std::io::Error,
+    },
+    /// There is no public hash for this `k`
+    #[error("SRS (k={0}) hash not found in public set")]
+    UnknownK(u32),
+    /// `k` is larger than any SRS in the public set, so it can't be verified
+    #[error("SRS (k={k}) exceeds the largest public SRS (k={max}) and cannot be verified")]
+    KTooLarge {
+        /// log2 of the requested SRS size
+        k: u32,
+        /// see [max_supported_k]
+        max: u32,
+    },
+    /// The file's hash differs from the public one
+    #[error("SRS (k={k}) hash {actual} does not match the expected hash {expected}. Remote SRS may have been tampered with.")]
+    HashMismatch {
+        /// log2 of the SRS size
+        k: u32,
+        /// hash from [PUBLIC_SRS_SHA256_HASHES]
+        expected: String,
+        /// hash of the file
+        actual: String,
+    },
+}
+
+/// The largest `k` with a hash in [PUBLIC_SRS_SHA256_HASHES].
+pub fn max_supported_k() -> u32 {
+    // the table is never empty
+    *PUBLIC_SRS_SHA256_HASHES.keys().max().unwrap()
+}
+
+/// Whether [PUBLIC_SRS_SHA256_HASHES] has a hash for `k`.
+pub fn contains_k(k: u32) -> bool {
+    PUBLIC_SRS_SHA256_HASHES.contains_key(&k)
+}
+
+/// Checks that the SRS file at `path` hashes to the public SHA256 hash for `k`, so that an SRS
+/// downloaded from a mirror isn't silently corrupted or tampered with.
+pub fn verify_srs(path: &Path, k: u32) -> Result<(), SrsError> {
+    let max = max_supported_k();
+    if k > max {
+        return Err(SrsError::KTooLarge { k, max });
+    }
+    let expected = PUBLIC_SRS_SHA256_HASHES
+        .get(&k)
+        .ok_or(SrsError::UnknownK(k))?;
+
+    let actual = compute_srs_sha256(path).map_err(|source| SrsError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+
+    if actual != *expected {
+        return Err(SrsError::HashMismatch {
+            k,
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// The SHA256 hash of the SRS file at `path`, as a lowercase hex digest in the format of
+/// [PUBLIC_SRS_SHA256_HASHES], e.g. to register a locally generated SRS.
+pub fn compute_srs_sha256(path: &Path) -> Result<String, std::io::Error> {
+    sha256::try_digest(path)
+}
+
+/// Serializes [PUBLIC_SRS_SHA256_HASHES] to a JSON object of the form `{k: hash}`, sorted by `k`
+/// so that manifests from different versions can be diffed directly.
+pub fn export_srs_manifest() -> String {
+    let sorted: BTreeMap<u32, &str> = PUBLIC_SRS_SHA256_HASHES
+        .iter()
+        .map(|(k, v)| (*k, *v))
+        .collect();
+    // a map of u32 to str always serializes
+    serde_json::to_string_pretty(&sorted).unwrap()
+}
+
+/// Parses a manifest produced by [export_srs_manifest] back into a map of `k` to hash.
+pub fn parse_srs_manifest(s: &str) -> Result<HashMap<u32, String>, serde_json::Error> {
+    serde_json::from_str(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn srs_manifest_round_trip() {
+        let manifest = export_srs_manifest();
+        let parsed = parse_srs_manifest(&manifest).unwrap();
+
+        assert_eq!(parsed.len(), PUBLIC_SRS_SHA256_HASHES.len());
+        for (k, hash) in PUBLIC_SRS_SHA256_HASHES.iter() {
+            assert_eq!(parsed[k], *hash);
+        }
+        // exporting is stable
+        assert_eq!(manifest, export_srs_manifest());
+    }
+
+    #[test]
+    fn supported_k_bounds() {
+        let max = max_supported_k();
+        assert!((1..=max).all(contains_k));
+        assert!(!contains_k(0));
+        assert!(!contains_k(max + 1));
+    }
+
+    #[test]
+    fn verify_srs_rejects_unknown_k_and_bad_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kzg1.srs");
+        std::fs::write(&path, b"not an srs").unwrap();
+
+        assert!(matches!(verify_srs(&path, 0), Err(SrsError::UnknownK(0))));
+        let max = max_supported_k();
+        assert!(matches!(
+            verify_srs(&path, max + 1),
+            Err(SrsError::KTooLarge { k, max: m }) if k == max + 1 && m == max
+        ));
+
+        let err = verify_srs(&path, 1).unwrap_err();
+        let actual = sha256::digest(b"not an srs".to_vec());
+        match &err {
+            SrsError::HashMismatch {
+                k,
+                expected,
+                actual: hash,
+            } => {
+                assert_eq!(*k, 1);
+                assert_eq!(expected, PUBLIC_SRS_SHA256_HASHES[&1]);
+                assert_eq!(*hash, actual);
+            }
+            _ => panic!("unexpected error: {}", err),
+        }
+        // both hashes are reported
+        let msg = err.to_string();
+        assert!(msg.contains(&actual) && msg.contains(PUBLIC_SRS_SHA256_HASHES[&1]));
+
+        assert!(matches!(
+            verify_srs(&dir.path().join("missing.srs"), 1),
+            Err(SrsError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn computed_hash_matches_stored_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kzg1.srs");
+        let bytes = (0..20_000u32).map(|i| i as u8).collect::<Vec<_>>();
+        std::fs::write(&path, &bytes).unwrap();
+
+        let hash = compute_srs_sha256(&path).unwrap();
+        assert_eq!(hash, sha256::digest(bytes));
+        let stored = PUBLIC_SRS_SHA256_HASHES[&1];
+        assert_eq!(hash.len(), stored.len());
+        assert!(hash
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)));
+
+        assert!(compute_srs_sha256(&dir.path().join("missing.srs")).is_err());
+    }
+}
@@ -3609,6 +3609,33 @@ pub mod nonlinearities {
         res
     }
 
+    /// Applies range_check_abs, flagging the elements of `a` and `b` that differ by more than `bound`
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `b` - Tensor
+    /// * `bound` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::range_check_abs;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[100, 200, 300, 400, 500, 600]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let y = Tensor::<i128>::new(
+    ///    Some(&[103, 204, 297, 400, 505, 602]),
+    ///   &[2, 3],
+    /// ).unwrap();
+    /// let result = range_check_abs(&[x, y], 3);
+    /// let expected = Tensor::<i128>::new(Some(&[0, 1, 0, 0, 1, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn range_check_abs(t: &[Tensor<i128>], bound: i128) -> Tensor<i128> {
+        let diff: Tensor<i128> = sub(t).unwrap();
+        diff.map(|d| (d.abs() > bound) as i128)
+    }
+
     /// Applies range_check_percent
     /// # Arguments
     ///
@@ -4152,6 +4179,62 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Elementwise applies a piecewise-linear function to a tensor of integers.
+    /// `slopes[0]` applies left of the first breakpoint and `slopes[i]` right of `breakpoints[i - 1]`.
+    /// Slopes are expressed as multiples of `1 / scale` and `intercept` is where the leftmost segment crosses zero.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `breakpoints` - Sorted input values at which the slope changes
+    /// * `slopes` - One more slope than there are breakpoints
+    /// * `intercept` - Single value
+    /// * `scale` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::piecewise_linear;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 15, 2, 1, 1, -5]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// // relu
+    /// let result = piecewise_linear(&x, &[0], &[0, 1], 0, 1.0).unwrap();
+    /// let expected = Tensor::<i128>::new(Some(&[2, 15, 2, 1, 1, 0]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn piecewise_linear(
+        a: &Tensor<i128>,
+        breakpoints: &[i64],
+        slopes: &[i64],
+        intercept: i64,
+        scale: f64,
+    ) -> Result<Tensor<i128>, TensorError> {
+        if slopes.len() != breakpoints.len() + 1 {
+            return Err(TensorError::DimMismatch(format!(
+                "piecewise: expected {} slopes for {} breakpoints, got {}",
+                breakpoints.len() + 1,
+                breakpoints.len(),
+                slopes.len()
+            )));
+        }
+        if breakpoints.windows(2).any(|w| w[0] > w[1]) {
+            return Err(TensorError::DimError(
+                "piecewise: breakpoints must be sorted".to_string(),
+            ));
+        }
+
+        a.par_enum_map(|_, a_i| {
+            let x = a_i as f64;
+            // leftmost line plus a hinge at every breakpoint where the slope changes
+            let mut res = intercept as f64 + (slopes[0] as f64) * x / scale;
+            for (i, b) in breakpoints.iter().enumerate() {
+                let delta = (slopes[i + 1] - slopes[i]) as f64;
+                res += delta * (x - *b as f64).max(0.0) / scale;
+            }
+            Ok::<_, TensorError>(res.round() as i128)
+        })
+    }
+
     /// Elementwise applies max to a tensor of integers.
     /// # Arguments
     /// * `a` - Tensor
@@ -4238,6 +4321,32 @@ pub mod nonlinearities {
         .unwrap()
     }
 
+    /// Elementwise multiplies a tensor with a const element, rounding the result.
+    /// # Arguments
+    ///
+    /// * `a` - Tensor
+    /// * `b` - Single value
+    /// # Examples
+    /// ```
+    /// use ezkl::tensor::Tensor;
+    /// use ezkl::tensor::ops::nonlinearities::const_mult;
+    /// let x = Tensor::<i128>::new(
+    ///     Some(&[2, 1, 2, 7, 1, 1]),
+    ///     &[2, 3],
+    /// ).unwrap();
+    /// let k = 2.5;
+    /// let result = const_mult(&x, k);
+    /// let expected = Tensor::<i128>::new(Some(&[5, 3, 5, 18, 3, 3]), &[2, 3]).unwrap();
+    /// assert_eq!(result, expected);
+    /// ```
+    pub fn const_mult(a: &Tensor<i128>, scale: f64) -> Tensor<i128> {
+        a.par_enum_map(|_, a_i| {
+            let kx = (a_i as f64) * scale;
+            Ok::<_, TensorError>(kx.round() as i128)
+        })
+        .unwrap()
+    }
+
     /// Elementwise inverse.
     /// # Arguments
     ///
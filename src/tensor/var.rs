@@ -124,6 +124,36 @@ impl VarTensor {
         }
     }
 
+    /// Create a new VarTensor::Advice with an explicit geometry
+    /// Arguments
+    /// * `cs` - The constraint system
+    /// * `col_size` - Number of usable rows in each column.
+    /// * `num_blocks` - Number of blocks to allocate.
+    /// * `num_inner_cols` - Number of columns in each block.
+    pub fn new_advice_with_geometry<F: PrimeField>(
+        cs: &mut ConstraintSystem<F>,
+        col_size: usize,
+        num_blocks: usize,
+        num_inner_cols: usize,
+    ) -> Self {
+        let mut advices = vec![];
+        for _ in 0..num_blocks {
+            let mut inner = vec![];
+            for _ in 0..num_inner_cols {
+                let col = cs.advice_column();
+                cs.enable_equality(col);
+                inner.push(col);
+            }
+            advices.push(inner);
+        }
+
+        VarTensor::Advice {
+            inner: advices,
+            num_inner_cols,
+            col_size,
+        }
+    }
+
     /// Initializes fixed columns to support the VarTensor::Advice
     /// Arguments
     /// * `cs` - The constraint system
@@ -221,6 +251,18 @@ impl VarTensor {
         }
     }
 
+    /// Number of rows `num_elements` will occupy when laid out over the tensor's geometry.
+    /// Elements fill a block row by row across its inner columns and spill over into the next block once
+    /// `col_size` rows are used, so the height is capped at `col_size` for ops that span several blocks.
+    pub fn rows_for(&self, num_elements: usize) -> usize {
+        let num_inner_cols = self.num_inner_cols();
+        if num_inner_cols == 0 {
+            return 0;
+        }
+        let rows = num_elements.div_ceil(num_inner_cols);
+        rows.min(self.col_size())
+    }
+
     /// Take a linear coordinate and output the (column, row) position in the storage block.
     pub fn cartesian_coord(&self, linear_coord: usize) -> (usize, usize, usize) {
         // x indexes over blocks of size num_inner_cols
@@ -592,3 +634,31 @@ impl VarTensor {
         Ok(res)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2curves::bn256::Fr as F;
+
+    #[test]
+    fn rows_for_single_block() {
+        let var = VarTensor::dummy(4, 3);
+        assert_eq!(var.col_size(), 10);
+        assert_eq!(var.rows_for(0), 0);
+        assert_eq!(var.rows_for(3), 1);
+        assert_eq!(var.rows_for(7), 3);
+        assert_eq!(var.rows_for(30), 10);
+    }
+
+    #[test]
+    fn rows_for_multi_block() {
+        let mut cs = ConstraintSystem::<F>::default();
+        let var = VarTensor::new_advice(&mut cs, 4, 2, 64);
+        assert!(var.num_blocks() > 1);
+
+        let col_size = var.col_size();
+        assert_eq!(var.rows_for(2 * col_size - 1), col_size);
+        assert_eq!(var.rows_for(2 * col_size + 3), col_size);
+        assert_eq!(var.rows_for(5), 3);
+    }
+}